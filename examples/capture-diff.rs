@@ -0,0 +1,114 @@
+/*!
+Renders the demo window offscreen with glow and FNA3D and writes a per-pixel diff report, so that
+the backends can be checked against each other
+
+```sh
+$ cargo run --example capture-diff --features sdl2,glow,fna3d
+```
+*/
+
+use {
+    anyhow::*,
+    std::{rc::Rc, time::Duration},
+};
+
+use imgui_backends::{helper::diff, prelude::*};
+
+const W: u32 = 1280;
+const H: u32 = 720;
+
+fn main() -> Result<()> {
+    env_logger::init();
+
+    imgui_backends::platform::sdl2::configure_hints();
+    let sdl = sdl2::init().map_err(Error::msg)?;
+    let vid = sdl.video().map_err(Error::msg)?;
+
+    // FNA3D creates and binds its own GL context, so it goes first
+    let a = {
+        let flags = fna3d::prepare_window_attributes();
+        let win = vid
+            .window("capture-diff (FNA3D)", W, H)
+            .set_window_flags(flags.0)
+            .hidden()
+            .build()
+            .map_err(Error::msg)?;
+
+        let params = fna3d::utils::default_params_from_window_handle(win.raw() as *mut _);
+        let mut device = fna3d::Device::from_params(params, true);
+        device.apply_rasterizer_state(&fna3d::RasterizerState::default());
+        device.set_blend_state(&fna3d::BlendState::alpha_blend());
+
+        let mut imgui = self::create_context();
+        let mut renderer = ImGuiFna3d::init(&mut imgui, &device)?;
+        self::capture(&mut imgui, |draw_data| {
+            renderer.capture(&mut device, draw_data)
+        })?
+    };
+
+    let b = {
+        let attr = vid.gl_attr();
+        attr.set_context_profile(sdl2::video::GLProfile::Core);
+        attr.set_context_version(3, 3);
+
+        let win = vid
+            .window("capture-diff (glow)", W, H)
+            .opengl()
+            .hidden()
+            .build()
+            .map_err(Error::msg)?;
+        let _gl_context = win.gl_create_context().map_err(Error::msg)?;
+        let mut gl = Rc::new(unsafe {
+            glow::Context::from_loader_function(|s| vid.gl_get_proc_address(s) as *const _)
+        });
+
+        let mut imgui = self::create_context();
+        let mut renderer = ImGuiGlow::new(&mut imgui, &gl)?;
+        self::capture(&mut imgui, |draw_data| renderer.capture(&mut gl, draw_data))?
+    };
+
+    let report = diff::diff_images(&a, &b, 0).ok_or_else(|| anyhow!("size mismatch"))?;
+    println!(
+        "different pixels: {} / {} (max delta: {}, mean delta: {:.4})",
+        report.n_different, report.n_pixels, report.max_delta, report.mean_delta
+    );
+
+    std::fs::write("capture-diff.ppm", report.diff.to_ppm())?;
+    println!("wrote capture-diff.ppm");
+
+    Ok(())
+}
+
+fn create_context() -> imgui::Context {
+    let mut imgui = QuickStart {
+        display_size: [W as f32, H as f32],
+        fontsize: 13.0,
+        hidpi_factor: 1.0,
+        locale: Locale::Japanese,
+        input_timing: None,
+        fonts: None,
+        keyboard_nav: true,
+    }
+    .create_context();
+    imgui.set_ini_filename(None);
+    imgui
+}
+
+/// Renders a few frames so that windows settle and returns the last capture
+fn capture(
+    imgui: &mut imgui::Context,
+    mut render: impl FnMut(&imgui::DrawData) -> std::result::Result<diff::Image, String>,
+) -> Result<diff::Image> {
+    let mut image = None;
+    for _ in 0..3 {
+        imgui.io_mut().display_size = [W as f32, H as f32];
+        imgui
+            .io_mut()
+            .update_delta_time(Duration::from_nanos(1_000_000_000 / 60));
+        let ui = imgui.frame();
+        ui.show_demo_window(&mut true);
+        image = Some(render(ui.render()).map_err(Error::msg)?);
+    }
+
+    Ok(image.unwrap())
+}
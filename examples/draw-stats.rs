@@ -0,0 +1,64 @@
+/*!
+Runs the ImGUI demo window for N frames headlessly and prints per-frame draw statistics as JSON
+
+```sh
+$ cargo run --example draw-stats -- 120 > fixtures.json
+```
+*/
+
+use {anyhow::*, std::time::Duration};
+
+use imgui_backends::{
+    helper::{Locale, QuickStart},
+    platform::NullPlatform,
+    renderer::NullRenderer,
+    Backend,
+};
+
+const W: f32 = 1280.0;
+const H: f32 = 720.0;
+
+fn main() -> Result<()> {
+    let n_frames: usize = match std::env::args().nth(1) {
+        Some(arg) => arg.parse().map_err(Error::msg)?,
+        None => 60,
+    };
+
+    let mut backend = {
+        let mut imgui = QuickStart {
+            display_size: [W, H],
+            fontsize: 13.0,
+            hidpi_factor: 1.0,
+            locale: Locale::Japanese,
+            input_timing: None,
+            fonts: None,
+            keyboard_nav: true,
+        }
+        .create_context();
+
+        // don't load or save `imgui.ini` so that the output is reproducible
+        imgui.set_ini_filename(None);
+
+        let platform = NullPlatform::new(&mut imgui, [W, H]);
+        let renderer = NullRenderer::new(&mut imgui);
+
+        Backend::new(imgui, platform, renderer)
+    };
+
+    let dt = Duration::from_nanos(1_000_000_000 / 60);
+    let mut frames = Vec::with_capacity(n_frames);
+
+    for _ in 0..n_frames {
+        backend.update_delta_time(dt);
+
+        let ui = backend.begin_frame(&());
+        ui.show_demo_window(&mut true);
+        ui.end_frame(&mut (), &mut ())?;
+
+        frames.push(backend.renderer.stats().to_json());
+    }
+
+    println!("[\n  {}\n]", frames.join(",\n  "));
+
+    Ok(())
+}
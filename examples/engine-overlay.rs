@@ -0,0 +1,82 @@
+/*!
+Drives an [`Overlay`] from a fake engine loop, headlessly
+
+The "engine" owns the window and the device and runs its stages in order: events, update
+systems, scene rendering and then the overlay as a late render step.
+
+```sh
+$ cargo run --example engine-overlay --features overlay
+```
+*/
+
+use std::time::Duration;
+
+use imgui_backends::{
+    helper::{Locale, QuickStart},
+    overlay::Overlay,
+    platform::NullPlatform,
+    renderer::NullRenderer,
+    Backend,
+};
+
+const W: f32 = 1280.0;
+const H: f32 = 720.0;
+
+/// Stands in for engine resources
+struct Engine {
+    window: (),
+    device: (),
+    entities: usize,
+}
+
+fn main() {
+    env_logger::init();
+
+    let mut overlay = {
+        let mut imgui = QuickStart {
+            display_size: [W, H],
+            fontsize: 13.0,
+            hidpi_factor: 1.0,
+            locale: Locale::English,
+            input_timing: None,
+            fonts: None,
+            keyboard_nav: true,
+        }
+        .create_context();
+        imgui.set_ini_filename(None);
+
+        let platform = NullPlatform::new(&mut imgui, [W, H]);
+        let renderer = NullRenderer::new(&mut imgui);
+        Overlay::new(Backend::new(imgui, platform, renderer))
+    };
+
+    let mut engine = Engine {
+        window: (),
+        device: (),
+        entities: 0,
+    };
+
+    for frame in 0..3 {
+        // event stage: the overlay sees events before the game does
+        let captured = overlay.handle_event(&engine.window, &());
+        if !captured.is_captured() {
+            // game input systems would run here
+        }
+
+        // update stage: systems queue UI instead of touching the ImGUI context
+        engine.entities += 10;
+        let entities = engine.entities;
+        overlay.queue(move |ui| ui.text(format!("entities: {}", entities)));
+
+        // render stage: the scene is drawn first, then the overlay on top
+        overlay.tick(Duration::from_secs_f32(1.0 / 60.0));
+        overlay
+            .render(&mut engine.window, &mut engine.device, |ui| {
+                ui.text(format!("frame {}", frame));
+            })
+            .unwrap();
+
+        let stats = overlay.backend().renderer.stats();
+        println!("frame {}: {}", frame, stats.to_json());
+    }
+}
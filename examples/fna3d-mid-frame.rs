@@ -0,0 +1,142 @@
+/*!
+Rendering ImGUI between two passes of an existing FNA3D game
+
+Each frame is structured as:
+
+1. Events: hand them to the backend and to the game. No rendering here
+2. Update: build the UI (and update the game), then end the ImGUI frame with `prepare_render`
+3. World pass: the game draws the world
+4. UI pass: ImGUI is rendered with the game's render states saved by `RenderStateGuard`
+5. Post pass: the game draws on top with its own states restored
+6. Present
+
+```sh
+$ cargo run --example fna3d-mid-frame --features sdl2,fna3d
+```
+*/
+
+use {
+    anyhow::{Error, Result},
+    fna3d::Color,
+    imgui::Window,
+    imgui_backends::{
+        prelude::*,
+        renderer::fna3d::{GameRenderState, RenderStateGuard},
+    },
+    sdl2::event::Event,
+    std::time::Duration,
+};
+
+const W: u32 = 1280;
+const H: u32 = 720;
+
+pub fn main() -> Result<()> {
+    env_logger::init();
+
+    imgui_backends::platform::sdl2::configure_hints();
+    let sdl = sdl2::init().map_err(Error::msg)?;
+    let vid = sdl.video().map_err(Error::msg)?;
+    let mut window = vid
+        .window("FNA3D mid-frame", W, H)
+        .set_window_flags(fna3d::prepare_window_attributes().0)
+        .position_centered()
+        .build()
+        .map_err(|e| Error::msg(e.to_string()))?;
+
+    let params = fna3d::utils::default_params_from_window_handle(window.raw() as *mut _);
+    let mut device = fna3d::Device::from_params(params, true);
+
+    // the states the game renders with. The UI pass overwrites them
+    let game_state = GameRenderState {
+        blend: fna3d::BlendState::alpha_blend(),
+        rasterizer: fna3d::RasterizerState::default(),
+        viewport: fna3d::Viewport {
+            x: 0,
+            y: 0,
+            w: params.backBufferWidth as i32,
+            h: params.backBufferHeight as i32,
+            minDepth: 0.0,
+            maxDepth: 1.0,
+        },
+    };
+    device.set_blend_state(&game_state.blend);
+    device.apply_rasterizer_state(&game_state.rasterizer);
+    device.set_viewport(&game_state.viewport);
+
+    let mut backend: Sdl2Fna3dBackend = {
+        let mut imgui = QuickStart {
+            display_size: [W as f32, H as f32],
+            fontsize: 13.0,
+            hidpi_factor: 1.0,
+            locale: Locale::English,
+            input_timing: None,
+            fonts: None,
+            keyboard_nav: true,
+        }
+        .create_context();
+        let platform = ImGuiSdl2::new(&mut imgui, &window);
+        let renderer = ImGuiFna3d::init(&mut imgui, &device)?;
+        Backend::new(imgui, platform, renderer)
+    };
+
+    let mut pump = sdl.event_pump().map_err(Error::msg)?;
+    let mut clear_color = [0.47, 0.7, 0.55];
+
+    'running: loop {
+        // 1. events. Only collect input; never render from inside the poll loop
+        let mut quit = false;
+        backend.pump_events(&window, &mut pump, |ev| {
+            if let Event::Quit { .. } = ev {
+                quit = true;
+            }
+        });
+        if quit {
+            break 'running;
+        }
+
+        let dt = Duration::from_nanos(1_000_000_000 / 60);
+        backend.update_delta_time(dt);
+
+        // 2. update: build the UI before any rendering
+        let ui = backend.begin_frame(&window);
+        Window::new("World").build(&ui, || {
+            ui.color_edit3("clear color", &mut clear_color);
+        });
+        // ends the frame. The UI is rendered later with `frame.render`
+        let frame = ui.prepare_render(&mut window);
+
+        // 3. world pass
+        let to_u8 = |x: f32| (x * 255.0) as u8;
+        device.clear(
+            fna3d::ClearOptions::TARGET,
+            Color::rgb(
+                to_u8(clear_color[0]),
+                to_u8(clear_color[1]),
+                to_u8(clear_color[2]),
+            )
+            .to_vec4(),
+            0.0,
+            0,
+        );
+
+        // 4. UI pass. The guard restores the game's states when it goes out of scope
+        {
+            let _guard = RenderStateGuard::new(&device, &game_state);
+            frame.render(&mut device)?;
+        }
+
+        // 5. post pass: the game's blend/rasterizer/viewport states are active again
+        device.clear(
+            fna3d::ClearOptions::DEPTH_BUFFER,
+            Color::rgb(0, 0, 0).to_vec4(),
+            1.0,
+            0,
+        );
+
+        // 6. present
+        device.swap_buffers(None, None, window.raw() as *mut _);
+        std::thread::sleep(dt);
+    }
+
+    Ok(())
+}
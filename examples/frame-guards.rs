@@ -0,0 +1,78 @@
+/*!
+Misuses the frame protocol headlessly and checks that it's reported with typed errors instead of
+Dear ImGUI assertions
+
+* frames ended with `end_frame` or `discard` (or dropped) can be followed by another frame
+* `begin_frame` on an active frame returns `ReentrancyError::FrameActive`
+* handling events during an active frame returns `ReentrancyError::EventDuringFrame`
+
+The last frame is leaked on purpose, so the backend is leaked too.
+
+```sh
+$ cargo run --example frame-guards
+```
+*/
+
+use anyhow::*;
+
+use imgui_backends::{
+    helper::{self, Locale, QuickStart, ReentrancyError},
+    platform::NullPlatform,
+    renderer::NullRenderer,
+    Backend,
+};
+
+fn main() -> Result<()> {
+    let mut imgui = QuickStart {
+        display_size: [640.0, 480.0],
+        fontsize: 13.0,
+        hidpi_factor: 1.0,
+        locale: Locale::English,
+        input_timing: None,
+        fonts: None,
+        keyboard_nav: true,
+    }
+    .create_context();
+    imgui.set_ini_filename(None);
+
+    let platform = NullPlatform::new(&mut imgui, [640.0, 480.0]);
+    let renderer = NullRenderer::new(&mut imgui);
+    let mut backend = Backend::new(imgui, platform, renderer);
+
+    for _ in 0..3 {
+        ensure!(backend.try_handle_event(&(), &()).is_ok(), "event rejected");
+        let ui = backend.try_begin_frame(&())?;
+        ensure!(helper::is_frame_active(), "frame not marked active");
+        ui.text("frame");
+        ui.end_frame(&mut (), &mut ()).map_err(Error::msg)?;
+        ensure!(
+            !helper::is_frame_active(),
+            "frame still active after end_frame"
+        );
+    }
+
+    backend.try_begin_frame(&())?.discard();
+    ensure!(
+        !helper::is_frame_active(),
+        "frame still active after discard"
+    );
+
+    // leak the frame as if it was never ended
+    std::mem::forget(backend.try_begin_frame(&())?);
+
+    match backend.try_begin_frame(&()) {
+        Err(ReentrancyError::FrameActive) => {}
+        Err(err) => bail!("unexpected error: {}", err),
+        Ok(_) => bail!("nested frame was begun"),
+    }
+    ensure!(
+        backend.try_handle_event(&(), &()) == Err(ReentrancyError::EventDuringFrame),
+        "event handled during an active frame"
+    );
+
+    // ImGUI can't end the leaked frame
+    std::mem::forget(backend);
+
+    println!("OK");
+    Ok(())
+}
@@ -0,0 +1,134 @@
+/*!
+Draws plots and thick polylines headlessly and checks the vertex counts of the draw calls
+
+Renderers must not assume quads (4 vertices per 6 indices); this example reports how often that
+assumption would break.
+
+```sh
+$ cargo run --example line-stress -- 60
+```
+*/
+
+use {
+    anyhow::*,
+    imgui::{im_str, Condition, PlotLines, Ui, Window},
+};
+
+use imgui_backends::{
+    helper::{DrawParamsIterator, LineFidelity, Locale, QuickStart},
+    platform::NullPlatform,
+    Platform,
+};
+
+const W: f32 = 1280.0;
+const H: f32 = 720.0;
+
+fn main() -> Result<()> {
+    let n_frames: usize = match std::env::args().nth(1) {
+        Some(arg) => arg.parse().map_err(Error::msg)?,
+        None => 60,
+    };
+
+    let mut imgui = QuickStart {
+        display_size: [W, H],
+        fontsize: 13.0,
+        hidpi_factor: 1.0,
+        locale: Locale::Japanese,
+        input_timing: None,
+        fonts: None,
+        keyboard_nav: true,
+    }
+    .create_context();
+    imgui.set_ini_filename(None);
+
+    let mut platform = NullPlatform::new(&mut imgui, [W, H]);
+
+    let presets = [
+        ("default", LineFidelity::default()),
+        ("geometry", LineFidelity::geometry()),
+        ("aliased", LineFidelity::aliased()),
+    ];
+
+    for (name, fidelity) in presets.iter() {
+        fidelity.apply(imgui.style_mut());
+
+        let (mut n_draw_calls, mut n_non_quads) = (0, 0);
+        for frame in 0..n_frames {
+            imgui.io_mut().delta_time = 1.0 / 60.0;
+            platform.prepare_frame(imgui.io_mut(), &());
+
+            let ui = imgui.frame();
+            self::stress(&ui, frame);
+            let draw_data = ui.render();
+
+            for params in DrawParamsIterator::new(draw_data).draw_calls() {
+                let n_vertices = params.n_vertices();
+                if params.vtx_offset + n_vertices > params.vtx_buffer.len() {
+                    bail!("draw call out of vertex buffer: {}", name);
+                }
+
+                n_draw_calls += 1;
+                if n_vertices != params.n_elems * 2 / 3 {
+                    n_non_quads += 1;
+                }
+            }
+        }
+
+        println!(
+            "{}: {} draw calls, {} of them are not quad-only",
+            name, n_draw_calls, n_non_quads
+        );
+    }
+
+    Ok(())
+}
+
+fn stress(ui: &Ui, frame: usize) {
+    let t = frame as f32 / 60.0;
+
+    Window::new(im_str!("Plots"))
+        .position([20.0, 20.0], Condition::Always)
+        .size([600.0, 680.0], Condition::Always)
+        .build(ui, || {
+            for i in 0..8 {
+                let values = (0..256)
+                    .map(|x| (x as f32 * 0.05 + t + i as f32).sin())
+                    .collect::<Vec<_>>();
+                PlotLines::new(ui, &im_str!("sin {}", i), &values)
+                    .graph_size([560.0, 72.0])
+                    .build();
+            }
+        });
+
+    Window::new(im_str!("Polylines"))
+        .position([640.0, 20.0], Condition::Always)
+        .size([620.0, 680.0], Condition::Always)
+        .build(ui, || {
+            let draw_list = ui.get_window_draw_list();
+            let origin = ui.cursor_screen_pos();
+
+            for i in 0..16 {
+                let thickness = 1.0 + i as f32 * 2.0;
+                let y = origin[1] + 20.0 + i as f32 * 40.0;
+                let points = (0..64)
+                    .map(|x| {
+                        let x = x as f32 * 9.0;
+                        [origin[0] + x, y + (x * 0.05 + t).sin() * 12.0]
+                    })
+                    .collect::<Vec<_>>();
+                draw_list
+                    .add_polyline(points, [1.0, 1.0 - i as f32 / 16.0, 0.3, 1.0])
+                    .thickness(thickness)
+                    .build();
+            }
+
+            for i in 0..6 {
+                let center = [origin[0] + 80.0 + i as f32 * 90.0, origin[1] + 620.0];
+                draw_list
+                    .add_circle(center, 36.0, [0.3, 0.8, 1.0, 1.0])
+                    .thickness(1.0 + i as f32 * 4.0)
+                    .num_segments(12 + i * 8)
+                    .build();
+            }
+        });
+}
@@ -0,0 +1,20 @@
+/*!
+Runs the renderer conformance scenarios on the null renderer
+
+```sh
+$ cargo run --example renderer-conformance --features test-engine
+```
+*/
+
+use imgui_backends::{helper::conformance::run_conformance, renderer::NullRenderer};
+
+fn main() {
+    let reports = run_conformance::<NullRenderer>();
+    for report in &reports {
+        println!("{}", report);
+    }
+
+    if reports.iter().any(|r| !r.passed()) {
+        std::process::exit(1);
+    }
+}
@@ -5,14 +5,12 @@ SDL2 + Rust-FNA3D
 use {
     anyhow::{Error, Result},
     fna3d::Color,
-    imgui_backends::{
-        helper::QuickStart, platform::ImGuiSdl2, renderer::ImGuiFna3d, Platform, Renderer,
-    },
+    imgui_backends::prelude::*,
     sdl2::event::Event,
     std::time::Duration,
 };
 
-type Backend = imgui_backends::Backend<ImGuiSdl2, ImGuiFna3d>;
+type Backend = Sdl2Fna3dBackend;
 
 const W: u32 = 1280;
 const H: u32 = 720;
@@ -40,6 +38,7 @@ impl Init {
         let (sdl, vid, win) = {
             let flags = fna3d::prepare_window_attributes();
 
+            imgui_backends::platform::sdl2::configure_hints();
             let sdl = sdl2::init().map_err(Error::msg)?;
             let vid = sdl.video().map_err(Error::msg)?;
             let win = vid
@@ -101,11 +100,7 @@ impl Init {
         let platform = ImGuiSdl2::new(&mut imgui, &self.window);
         let renderer = ImGuiFna3d::init(&mut imgui, &self.device)?;
 
-        Ok(Backend {
-            imgui,
-            platform,
-            renderer,
-        })
+        Ok(Backend::new(imgui, platform, renderer))
     }
 }
 
@@ -121,6 +116,10 @@ pub fn main() -> Result<()> {
             display_size: [W as f32, H as f32],
             fontsize: 13.0,
             hidpi_factor: 1.0,
+            locale: Locale::Japanese,
+            input_timing: None,
+            fonts: None,
+            keyboard_nav: true,
         }
         .create_context();
         handles.create_imgui_backend(imgui)?
@@ -129,13 +128,14 @@ pub fn main() -> Result<()> {
     let mut pump = handles.sdl.event_pump().map_err(Error::msg)?;
 
     'running: loop {
-        for ev in pump.poll_iter() {
-            match ev {
-                Event::Quit { .. } => break 'running,
-                _ => {}
+        let mut quit = false;
+        backend.pump_events(&handles.window, &mut pump, |ev| {
+            if let Event::Quit { .. } = ev {
+                quit = true;
             }
-
-            backend.handle_event(&mut handles.window, &ev);
+        });
+        if quit {
+            break 'running;
         }
 
         // something like 30 FPS. do not use it for real applications
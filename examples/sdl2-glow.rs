@@ -2,11 +2,16 @@
 Rust-SDL2 + glow
 */
 
-use {anyhow::*, glow::HasContext, sdl2::event::Event, std::time::Duration};
+use {
+    anyhow::*,
+    glow::HasContext,
+    sdl2::event::Event,
+    std::{rc::Rc, time::Duration},
+};
 
-use imgui_backends::{helper::QuickStart, platform::ImGuiSdl2, renderer::ImGuiGlow};
+use imgui_backends::prelude::*;
 
-type Backend = imgui_backends::Backend<ImGuiSdl2, ImGuiGlow>;
+type Backend = Sdl2GlowBackend;
 
 const TITLE: &'static str = "SDL2 + glow";
 const W: u32 = 1280;
@@ -22,6 +27,7 @@ pub struct SdlHandles {
 
 impl SdlHandles {
     fn new() -> Result<Self> {
+        imgui_backends::platform::sdl2::configure_hints();
         let sdl = sdl2::init().map_err(Error::msg)?;
         let vid = sdl.video().map_err(Error::msg)?;
 
@@ -52,27 +58,28 @@ fn main() -> Result<()> {
     env_logger::init();
 
     let mut handles = SdlHandles::new()?;
-    let mut glow = unsafe {
+    let mut glow = Rc::new(unsafe {
         glow::Context::from_loader_function(|s| handles.vid.gl_get_proc_address(s) as *const _)
-    };
+    });
 
     let mut backend = {
         let mut imgui = QuickStart {
             display_size: [W as f32, H as f32],
             fontsize: 13.0,
             hidpi_factor: 1.0,
+            locale: Locale::Japanese,
+            input_timing: None,
+            fonts: None,
+            keyboard_nav: true,
         }
         .create_context();
 
         let platform = ImGuiSdl2::new(&mut imgui, &handles.win);
         let renderer = ImGuiGlow::new(&mut imgui, &glow)?;
 
-        Backend {
-            imgui,
-            platform,
-            renderer,
-        }
+        Backend::new(imgui, platform, renderer)
     };
+    log::info!("{}", backend.about());
 
     let mut pump = handles.sdl.event_pump().map_err(Error::msg)?;
     unsafe {
@@ -80,13 +87,14 @@ fn main() -> Result<()> {
     }
 
     'running: loop {
-        for ev in pump.poll_iter() {
-            match ev {
-                Event::Quit { .. } => break 'running,
-                _ => {}
+        let mut quit = false;
+        backend.pump_events(&handles.win, &mut pump, |ev| {
+            if let Event::Quit { .. } = ev {
+                quit = true;
             }
-
-            backend.handle_event(&handles.win, &ev);
+        });
+        if quit {
+            break 'running;
         }
 
         // something like 30 FPS. do not use it for real applications
@@ -112,5 +120,8 @@ fn main() -> Result<()> {
         std::thread::sleep(dt);
     }
 
+    // release GL objects while the context is alive
+    backend.shutdown(&handles.win, &mut glow)?;
+
     Ok(())
 }
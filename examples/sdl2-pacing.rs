@@ -0,0 +1,152 @@
+/*!
+Rust-SDL2 + glow, measuring present cadence against the requested swap interval
+
+Missed vblanks with a UI that renders well within the refresh period point to presentation
+issues (compositor, driver) rather than slow UI rendering. Add rectangles to stress the renderer.
+
+```sh
+$ cargo run --example sdl2-pacing --features sdl2,glow
+```
+*/
+
+use {anyhow::*, glow::HasContext, imgui::Window, sdl2::event::Event, std::rc::Rc};
+
+use imgui_backends::{
+    helper::{Present, SwapInterval},
+    prelude::*,
+};
+
+type Backend = Sdl2GlowBackend;
+
+const TITLE: &'static str = "SDL2 + glow: frame pacing";
+const W: u32 = 1280;
+const H: u32 = 720;
+
+const INTERVALS: &[SwapInterval] = &[
+    SwapInterval::VSync,
+    SwapInterval::Adaptive,
+    SwapInterval::Immediate,
+];
+
+fn main() -> Result<()> {
+    env_logger::init();
+
+    imgui_backends::platform::sdl2::configure_hints();
+    let sdl = sdl2::init().map_err(Error::msg)?;
+    let vid = sdl.video().map_err(Error::msg)?;
+
+    let attr = vid.gl_attr();
+    attr.set_context_profile(sdl2::video::GLProfile::Core);
+    attr.set_context_version(3, 3);
+
+    let mut win = vid
+        .window(TITLE, W, H)
+        .position_centered()
+        .opengl()
+        .build()
+        .map_err(Error::msg)?;
+    let _gl = win.gl_create_context().map_err(Error::msg)?;
+
+    let mut glow = Rc::new(unsafe {
+        glow::Context::from_loader_function(|s| vid.gl_get_proc_address(s) as *const _)
+    });
+
+    let mut backend = {
+        let mut imgui = QuickStart {
+            display_size: [W as f32, H as f32],
+            fontsize: 13.0,
+            hidpi_factor: 1.0,
+            locale: Locale::English,
+            input_timing: None,
+            fonts: None,
+            keyboard_nav: true,
+        }
+        .create_context();
+
+        let platform = ImGuiSdl2::new(&mut imgui, &win);
+        let renderer = ImGuiGlow::new(&mut imgui, &glow)?;
+
+        Backend::new(imgui, platform, renderer)
+    };
+    backend.pacing.set_enabled(true);
+
+    let mut interval = 0;
+    if !win.set_swap_interval(INTERVALS[interval]) {
+        log::warn!("vsync is not supported");
+    }
+    let mut n_rects = 0;
+
+    let mut pump = sdl.event_pump().map_err(Error::msg)?;
+    let mut last = std::time::Instant::now();
+
+    'running: loop {
+        let mut quit = false;
+        backend.pump_events(&win, &mut pump, |ev| {
+            if let Event::Quit { .. } = ev {
+                quit = true;
+            }
+        });
+        if quit {
+            break 'running;
+        }
+
+        let now = std::time::Instant::now();
+        backend.update_delta_time(now - last);
+        last = now;
+
+        unsafe {
+            glow.clear_color(0.1, 0.2, 0.3, 1.0);
+            glow.clear(glow::COLOR_BUFFER_BIT);
+        }
+
+        let report = backend.pacing.report();
+        let mut reset = false;
+        let ui = backend.begin_frame(&win);
+
+        Window::new("Frame pacing").build(&ui, || {
+            let names = INTERVALS
+                .iter()
+                .map(|i| format!("{:?}", i))
+                .collect::<Vec<_>>();
+            if ui.combo_simple_string("swap interval", &mut interval, &names) {
+                if !win.set_swap_interval(INTERVALS[interval]) {
+                    log::warn!("swap interval {:?} is not supported", INTERVALS[interval]);
+                }
+            }
+            imgui::Slider::new("stress rects", 0, 10_000).build(&ui, &mut n_rects);
+
+            ui.separator();
+            ui.text(format!("{}", report));
+            if report.is_presentation_bound() {
+                ui.text_colored(
+                    [1.0, 0.6, 0.2, 1.0],
+                    "missed vblanks while the UI renders in time: presentation issue",
+                );
+            }
+            reset = ui.button("Reset");
+        });
+
+        {
+            let draw_list = ui.get_background_draw_list();
+            for i in 0..n_rects {
+                let x = (i * 8 % 1280) as f32;
+                let y = (i * 8 / 1280 % 720) as f32;
+                draw_list
+                    .add_rect([x, y], [x + 8.0, y + 8.0], [1.0, 1.0, 1.0, 0.01])
+                    .filled(true)
+                    .build();
+            }
+        }
+
+        ui.end_frame(&mut win, &mut glow).map_err(Error::msg)?;
+
+        backend.present(&mut win);
+        if reset {
+            backend.pacing.clear();
+        }
+    }
+
+    backend.shutdown(&win, &mut glow)?;
+
+    Ok(())
+}
@@ -0,0 +1,164 @@
+/*!
+Feeds Unicode text input and clipboard round-trips headlessly and checks that they survive
+
+Covers characters outside of the BMP (emoji, ZWJ sequences, flags), combining marks and CJK. For
+each string it checks that:
+
+* text input typed into a focused `InputText` is read back as is (non-BMP characters become U+FFFD
+  if Dear ImGUI is built with 16-bit `ImWchar`)
+* text copied through ImGUI reaches the clipboard backend and is pasted back as is
+* pasted text is cut at NUL
+
+```sh
+$ cargo run --example text-roundtrip
+```
+*/
+
+use {
+    anyhow::*,
+    imgui::{Condition, FocusedWidget, Window},
+    std::{cell::RefCell, rc::Rc},
+};
+
+use imgui_backends::{
+    helper::{Locale, QuickStart},
+    platform::{self, ClipboardBackend, NullPlatform},
+    Platform,
+};
+
+const SAMPLES: &[&str] = &[
+    "ascii",
+    "日本語の入力",
+    "😀",
+    "a😀b𝄞c",
+    // ZWJ sequence
+    "👨‍👩‍👧",
+    // regional indicators
+    "🇯🇵",
+    // combining acute accent
+    "e\u{301}",
+    "\u{20BB7}野家",
+];
+
+/// Clipboard shared with the test
+#[derive(Debug, Clone, Default)]
+struct SharedClipboard(Rc<RefCell<Option<String>>>);
+
+impl ClipboardBackend for SharedClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.0.borrow().clone()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        *self.0.borrow_mut() = Some(text.to_string());
+    }
+}
+
+fn main() -> Result<()> {
+    let mut imgui = QuickStart {
+        display_size: [640.0, 480.0],
+        fontsize: 13.0,
+        hidpi_factor: 1.0,
+        locale: Locale::English,
+        input_timing: None,
+        fonts: None,
+        keyboard_nav: true,
+    }
+    .create_context();
+    imgui.set_ini_filename(None);
+
+    let mut platform = NullPlatform::new(&mut imgui, [640.0, 480.0]);
+    let clipboard = SharedClipboard::default();
+    platform::set_clipboard(&mut imgui, Box::new(clipboard.clone()));
+
+    let mut n_errors = 0;
+    for sample in SAMPLES {
+        let expected = sample
+            .chars()
+            .filter_map(platform::input_char)
+            .collect::<String>();
+
+        let typed = self::type_text(&mut imgui, &mut platform, sample);
+        if typed != expected {
+            println!("typed {:?}: read back {:?}", sample, typed);
+            n_errors += 1;
+        }
+
+        let pasted = self::copy_paste(&mut imgui, &mut platform, sample);
+        if clipboard.0.borrow().as_deref() != Some(*sample) || pasted.as_deref() != Some(*sample) {
+            println!(
+                "copied {:?}: clipboard {:?}, pasted {:?}",
+                sample,
+                clipboard.0.borrow(),
+                pasted
+            );
+            n_errors += 1;
+        }
+    }
+
+    *clipboard.0.borrow_mut() = Some("before\0after".to_string());
+    let pasted = self::paste(&mut imgui, &mut platform);
+    if pasted.as_deref() != Some("before") {
+        println!("pasted {:?} across NUL", pasted);
+        n_errors += 1;
+    }
+
+    ensure!(n_errors == 0, "{} errors", n_errors);
+    println!(
+        "{} samples (32-bit ImWchar: {}): OK",
+        SAMPLES.len(),
+        platform::WCHAR32
+    );
+    Ok(())
+}
+
+/// Types `text` into a focused text field and returns its content
+fn type_text(imgui: &mut imgui::Context, platform: &mut NullPlatform, text: &str) -> String {
+    let mut buf = String::new();
+
+    // focus, activate, type and then let the field apply the input
+    for frame in 0..4 {
+        if frame == 2 {
+            platform::add_input_text(imgui.io_mut(), text);
+        }
+
+        imgui.io_mut().delta_time = 1.0 / 60.0;
+        platform.prepare_frame(imgui.io_mut(), &());
+        let ui = imgui.frame();
+        Window::new("text")
+            .position([0.0, 0.0], Condition::Always)
+            .build(&ui, || {
+                if frame == 0 {
+                    ui.set_keyboard_focus_here(FocusedWidget::Next);
+                }
+                ui.input_text("##field", &mut buf).build();
+            });
+        ui.render();
+    }
+
+    buf
+}
+
+/// Copies `text` through ImGUI and pastes it back
+fn copy_paste(
+    imgui: &mut imgui::Context,
+    platform: &mut NullPlatform,
+    text: &str,
+) -> Option<String> {
+    imgui.io_mut().delta_time = 1.0 / 60.0;
+    platform.prepare_frame(imgui.io_mut(), &());
+    let ui = imgui.frame();
+    ui.set_clipboard_text(text);
+    ui.render();
+
+    self::paste(imgui, platform)
+}
+
+fn paste(imgui: &mut imgui::Context, platform: &mut NullPlatform) -> Option<String> {
+    imgui.io_mut().delta_time = 1.0 / 60.0;
+    platform.prepare_frame(imgui.io_mut(), &());
+    let ui = imgui.frame();
+    let text = ui.clipboard_text();
+    ui.render();
+    text
+}
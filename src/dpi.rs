@@ -0,0 +1,90 @@
+/*!
+Logical (display-space) and physical (framebuffer-space) coordinates
+
+ImGUI works in logical pixels; renderers work in physical pixels. Converting between them is only
+possible through the framebuffer scale, which is what these newtypes enforce.
+*/
+
+/// Position in logical pixels, e.g. window-relative mouse position
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LogicalPos(pub [f32; 2]);
+
+/// Size in logical pixels, e.g. window size (ImGUI's `display_size`)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct LogicalSize(pub [f32; 2]);
+
+/// Position in physical pixels, e.g. scissor rectangle origin
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PhysicalPos(pub [f32; 2]);
+
+/// Size in physical pixels, e.g. drawable size of a window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PhysicalSize(pub [u32; 2]);
+
+impl LogicalPos {
+    pub fn to_physical(self, scale: [f32; 2]) -> PhysicalPos {
+        PhysicalPos([self.0[0] * scale[0], self.0[1] * scale[1]])
+    }
+}
+
+impl PhysicalPos {
+    pub fn to_logical(self, scale: [f32; 2]) -> LogicalPos {
+        LogicalPos([self.0[0] / scale[0], self.0[1] / scale[1]])
+    }
+}
+
+impl LogicalSize {
+    pub fn to_physical(self, scale: [f32; 2]) -> PhysicalSize {
+        PhysicalSize([
+            (self.0[0] * scale[0]).round() as u32,
+            (self.0[1] * scale[1]).round() as u32,
+        ])
+    }
+}
+
+impl PhysicalSize {
+    pub fn to_logical(self, scale: [f32; 2]) -> LogicalSize {
+        LogicalSize([self.0[0] as f32 / scale[0], self.0[1] as f32 / scale[1]])
+    }
+
+    /// Framebuffer scale relative to a logical size
+    pub fn scale_from(self, logical: LogicalSize) -> [f32; 2] {
+        let scale = |p: u32, l: f32| if l > 0.0 { p as f32 / l } else { 1.0 };
+        [
+            scale(self.0[0], logical.0[0]),
+            scale(self.0[1], logical.0[1]),
+        ]
+    }
+}
+
+/// Feeds `display_size` and `display_framebuffer_scale`
+pub fn set_display(io: &mut imgui::Io, logical: LogicalSize, physical: PhysicalSize) {
+    io.display_size = logical.0;
+    io.display_framebuffer_scale = physical.scale_from(logical);
+}
+
+/// Feeds the mouse position, which ImGUI expects in logical pixels
+pub fn set_mouse_pos(io: &mut imgui::Io, pos: LogicalPos) {
+    io.mouse_pos = pos.0;
+}
+
+/// Maps OS mouse coordinates into UI space, e.g. when the UI is drawn into a letterboxed or
+/// rotated sub-viewport
+#[derive(Clone)]
+pub struct PointerTransform(std::rc::Rc<dyn Fn([f32; 2]) -> [f32; 2]>);
+
+impl std::fmt::Debug for PointerTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("PointerTransform(..)")
+    }
+}
+
+impl PointerTransform {
+    pub fn new(f: impl Fn([f32; 2]) -> [f32; 2] + 'static) -> Self {
+        Self(std::rc::Rc::new(f))
+    }
+
+    pub fn apply(&self, pos: LogicalPos) -> LogicalPos {
+        LogicalPos((self.0)(pos.0))
+    }
+}
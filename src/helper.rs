@@ -6,10 +6,122 @@ NOTE: Take care of the coordinate system: `Rect` thinks y axis goes up!
 
 use imgui::{FontConfig, FontSource};
 
-use {
-    imgui::{internal::RawWrapper, DrawCmd},
-    thiserror::Error,
+mod registry;
+pub use self::registry::*;
+
+mod timers;
+pub use self::timers::*;
+
+mod toasts;
+pub use self::toasts::*;
+
+mod context_menu;
+pub use self::context_menu::*;
+
+mod undo;
+pub use self::undo::*;
+
+mod sounds;
+pub use self::sounds::*;
+
+mod budget;
+pub use self::budget::*;
+
+mod lines;
+pub use self::lines::*;
+
+mod procedural;
+pub use self::procedural::*;
+
+mod localize;
+pub use self::localize::*;
+
+mod vertex;
+pub use self::vertex::*;
+
+mod bytes;
+pub(crate) use self::bytes::*;
+
+mod reload;
+pub use self::reload::*;
+
+mod color;
+pub use self::color::*;
+
+mod input_log;
+pub use self::input_log::*;
+
+mod input_timing;
+pub use self::input_timing::*;
+
+mod telemetry;
+pub use self::telemetry::*;
+
+mod motion;
+pub use self::motion::*;
+
+mod latency;
+pub use self::latency::*;
+
+mod pacing;
+pub use self::pacing::*;
+
+mod temp_textures;
+pub use self::temp_textures::*;
+
+mod modifiers;
+pub use self::modifiers::*;
+
+mod fonts;
+pub use self::fonts::*;
+
+mod plugins;
+pub use self::plugins::*;
+
+mod draw;
+pub use self::draw::*;
+
+mod controllers;
+pub use self::controllers::*;
+
+mod pause;
+pub use self::pause::*;
+
+mod merge;
+pub use self::merge::*;
+
+mod pointer;
+pub use self::pointer::*;
+
+mod placement;
+pub use self::placement::*;
+
+mod text_blend;
+pub use self::text_blend::*;
+
+mod reentrancy;
+pub(crate) use self::reentrancy::{
+    check_begin_frame, check_event, check_render, CallbackScope, FrameScope,
 };
+pub use self::reentrancy::{is_frame_active, is_in_draw_callback, ReentrancyError};
+
+pub mod diff;
+
+#[cfg(feature = "image")]
+mod images;
+#[cfg(feature = "image")]
+pub use self::images::*;
+
+#[cfg(feature = "file-dialog")]
+pub mod file_dialog;
+
+#[cfg(feature = "test-engine")]
+pub mod ui_test;
+
+#[cfg(feature = "test-engine")]
+pub mod conformance;
+
+use {std::time::Duration, thiserror::Error};
 
 /// `mplus-1p-regular.ttf`
 pub const JP_FONT: &[u8] = include_bytes!("../assets/mplus-1p-regular.ttf");
@@ -20,6 +132,18 @@ pub struct QuickStart {
     pub display_size: [f32; 2],
     pub fontsize: f32,
     pub hidpi_factor: f32,
+    /// Selects glyph ranges. Non-English locales merge the bundled M+ font (Japanese glyphs and
+    /// common CJK ideographs; it has no Hangul)
+    pub locale: Locale,
+    /// Overrides the double-click/drag/key repeat settings. Otherwise platforms scale the Dear
+    /// ImGUI defaults with the display DPI
+    pub input_timing: Option<InputTiming>,
+    /// Overrides the fonts selected by `locale`, e.g. with a fallback chain ending with an emoji
+    /// font
+    pub fonts: Option<FontSetup>,
+    /// Enables keyboard navigation (Tab, arrow keys, Space, Enter and Escape). Platforms fill the
+    /// key map with [`crate::platform::set_key_map`]
+    pub keyboard_nav: bool,
 }
 
 impl QuickStart {
@@ -33,282 +157,88 @@ impl QuickStart {
 
         // initial font settings
         let font_size = (self.fontsize * self.hidpi_factor) as f32;
-        imgui.fonts().add_font(&[
-            FontSource::DefaultFontData {
+        let fonts = match self.fonts.as_ref() {
+            Some(fonts) => fonts.clone(),
+            None => FontSetup::for_locale(self.locale),
+        };
+        if let Err(err) = fonts.add_to(&mut imgui.fonts(), font_size) {
+            log::error!("imgui-backends: falling back to the default font: {}", err);
+            imgui.fonts().add_font(&[FontSource::DefaultFontData {
                 config: Some(FontConfig {
                     size_pixels: font_size,
                     ..FontConfig::default()
                 }),
-            },
-            FontSource::TtfData {
-                data: JP_FONT,
-                size_pixels: font_size,
-                config: Some(FontConfig {
-                    rasterizer_multiply: 1.75,
-                    glyph_ranges: imgui::FontGlyphRanges::japanese(),
-                    ..FontConfig::default()
-                }),
-            },
-        ]);
+            }]);
+        }
         imgui.io_mut().font_global_scale = (1.0 / self.hidpi_factor) as f32;
 
-        imgui
-    }
-}
-
-/// Creates an orthographic projection matrix for OpenGL
-///
-/// ```
-/// // left, right, bottom, top, near, far
-/// let mat = ortho_mat_gl(0.0, 1280.0, 0.0, 720.0, 0.0, 1.0);
-/// ```
-///
-/// Note that they're in OpenGL coordinate system and the y axis goes up. Swap `bottom` and `top` if
-/// you want your y axis to go down:
-///
-/// ```
-/// // left, right, top, bottom, near, far
-/// let mat = ortho_mat_gl(0.0, 1280.0, 720.0, 0.0, 0.0, 1.0);
-/// ```
-pub fn ortho_mat_gl(
-    left: f32,
-    right: f32,
-    bottom: f32,
-    top: f32,
-    near: f32,
-    far: f32,
-) -> [f32; 16] {
-    [
-        (2.0 / (right as f64 - left as f64)) as f32,
-        0.0,
-        0.0,
-        0.0,
-        // ---
-        0.0,
-        (2.0 / (top as f64 - bottom as f64)) as f32,
-        0.0,
-        0.0,
-        // ---
-        0.0,
-        0.0,
-        -(2.0 / (far as f64 - near as f64)) as f32,
-        0.0,
-        // ---
-        -((right as f64 + left as f64) / (right as f64 - left as f64)) as f32,
-        -((top as f64 + bottom as f64) / (top as f64 - bottom as f64)) as f32,
-        (near as f64 / (near as f64 - far as f64)) as f32,
-        1.0,
-    ]
-}
-
-/// TODO: extend and use this error
-#[derive(Debug, Error)]
-pub enum ImGuiRendererError {
-    #[error("bad texture id")]
-    BadTexture(imgui::TextureId),
-}
-
-/// Rectangle. NOTE: Y axis goes up
-///
-/// # Coordinate system
-/// ```md
-///    (up)
-///     y
-///     ^
-///     |
-/// ----+---> x (right)
-///     |
-///     |
-/// ```
-#[derive(Debug, Clone)]
-pub struct Rect {
-    left: f32,
-    top: f32,
-    right: f32,
-    bottom: f32,
-}
-
-impl Rect {
-    pub fn left(&self) -> f32 {
-        self.left
-    }
-
-    /// NOTE: Y axies goes up
-    pub fn top(&self) -> f32 {
-        self.top
-    }
-
-    pub fn right(&self) -> f32 {
-        self.right
-    }
-
-    /// NOTE: Y axies goes up
-    pub fn bottom(&self) -> f32 {
-        self.bottom
-    }
+        if let Some(timing) = self.input_timing.as_ref() {
+            timing.apply(imgui.io_mut());
+        }
 
-    pub fn width(&self) -> f32 {
-        self.right - self.left
-    }
+        if self.keyboard_nav {
+            imgui
+                .io_mut()
+                .config_flags
+                .insert(imgui::ConfigFlags::NAV_ENABLE_KEYBOARD);
+        }
 
-    pub fn height(&self) -> f32 {
-        // FIXME: why
-        // (self.top - self.bottom).abs()
-        (self.top - self.bottom).abs()
+        imgui
     }
 }
 
-/// Context and parameters for making a draw call; more comfortable version of
-/// [`imgui::DrawCmdParams`]
-#[derive(Debug, Clone)]
-pub struct DrawParams<'a> {
-    /// Display [`Rect`]. Can be used for calculating orthographic projection matrix
-    pub display: Rect,
-    /// Vertex buffer for multiple draw calls, sliced with `vtx_offset` and `n_elems`
-    pub vtx_buffer: &'a [imgui::DrawVert],
-    /// Vertex offset for this draw call
-    pub vtx_offset: usize,
-    /// Index buffer for multiple draw calls, sliced with `vtx_offset` and `n_elems`
-    pub idx_buffer: &'a [imgui::DrawIdx],
-    /// Index offset for this draw call
-    pub idx_offset: usize,
-    /// Number of triangles for this draw call: `n_elems` = `vbuf_span.len` `4` = `ibuf.len` / `6`
-    pub n_elems: usize,
-    /// Texture ID
-    pub tex_id: imgui::TextureId,
-    /// Scissor rectangle
-    pub scissor: Rect,
+/// Draw statistics of a frame. Used by the null renderer and for generating fixtures
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    pub n_draw_lists: usize,
+    /// Number of `DrawCmd` s including culled ones
+    pub n_cmds: usize,
+    /// Number of [`DrawParams`] yielded by [`DrawParamsIterator`]
+    pub n_draw_calls: usize,
+    pub n_vertices: usize,
+    pub n_indices: usize,
+    /// GPU time of the UI pass, filled by the user from the renderer if it supports timing
+    pub gpu_time: Option<Duration>,
 }
 
-/// Iterator of [`DrawParams`]
-pub struct DrawParamsIterator<'a> {
-    // variables
-    fb_width: f32,
-    fb_height: f32,
-    clip_off: [f32; 2],
-    clip_scale: [f32; 2],
-    display_rect: Rect,
-    // data.iterator()
-    draw_lists: imgui::DrawListIterator<'a>,
-    // states to pull `DrawCmd` one by one
-    draw_list: Option<&'a imgui::DrawList>,
-    draw_cmds: Option<imgui::DrawCmdIterator<'a>>,
-}
-
-impl<'a> DrawParamsIterator<'a> {
-    pub fn new(data: &'a imgui::DrawData) -> Self {
-        Self {
-            fb_width: data.display_size[0] * data.framebuffer_scale[0],
-            fb_height: data.display_size[1] * data.framebuffer_scale[1],
-            clip_off: data.display_pos,
-            clip_scale: data.framebuffer_scale,
-            display_rect: Rect {
-                left: data.display_pos[0],
-                right: data.display_pos[0] + data.display_size[0],
-                top: data.display_pos[1] + data.display_size[1],
-                bottom: data.display_pos[1],
-            },
-            draw_lists: data.draw_lists(),
-            draw_list: None,
-            draw_cmds: None,
+impl DrawStats {
+    pub fn new(data: &imgui::DrawData) -> Self {
+        let mut stats = Self {
+            n_draw_lists: data.draw_lists_count(),
+            n_vertices: data.total_vtx_count as usize,
+            n_indices: data.total_idx_count as usize,
+            ..Default::default()
+        };
+
+        for draw_list in data.draw_lists() {
+            stats.n_cmds += draw_list.commands().count();
         }
-    }
+        stats.n_draw_calls = DrawParamsIterator::new(data).draw_calls().count();
 
-    /// Collects `DrawList` s into continuous vertex/index buffer
-    fn into_batched_buffer(
-        self,
-        _vbuf: &mut Vec<imgui::DrawVert>,
-        _ibuf: &mut Vec<imgui::DrawIdx>,
-    ) {
-        todo!()
+        stats
     }
 
-    /// One step of this loop:
-    /// ```no_run
-    /// for draw_list in draw_data.draw_lists() {
-    ///     for cmd in draw_list.commands() {
-    /// ```
-    fn next_draw_cmd(&mut self) -> Option<imgui::DrawCmd> {
-        loop {
-            if let Some(cmds) = self.draw_cmds.as_mut() {
-                if let Some(cmd) = cmds.next() {
-                    return Some(cmd);
-                } else {
-                    self.draw_cmds = None;
-                }
-            }
-
-            if let Some(draw_list) = self.draw_lists.next() {
-                self.draw_cmds = Some(draw_list.commands());
-                self.draw_list = Some(draw_list);
-            } else {
-                return None;
-            }
-        }
+    /// Single-line JSON object
+    pub fn to_json(&self) -> String {
+        let gpu_time_us = match self.gpu_time {
+            Some(t) => t.as_micros().to_string(),
+            None => "null".to_string(),
+        };
+        format!(
+            r#"{{"n_draw_lists":{},"n_cmds":{},"n_draw_calls":{},"n_vertices":{},"n_indices":{},"gpu_time_us":{}}}"#,
+            self.n_draw_lists,
+            self.n_cmds,
+            self.n_draw_calls,
+            self.n_vertices,
+            self.n_indices,
+            gpu_time_us
+        )
     }
 }
 
-impl<'a> Iterator for DrawParamsIterator<'a> {
-    type Item = DrawParams<'a>;
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.fb_width <= 0.0 || self.fb_height <= 0.0 {
-            return None;
-        }
-
-        let clip_off = self.clip_off;
-        let clip_scale = self.clip_scale;
-        let fb_width = self.fb_width;
-        let fb_height = self.fb_height;
-        let display_rect = self.display_rect.clone();
-
-        'next: loop {
-            return match self.next_draw_cmd()? {
-                DrawCmd::Elements { count, cmd_params } => {
-                    let clip_rect = &cmd_params.clip_rect;
-                    // [left, up, right, down]
-                    let clip_rect = [
-                        (clip_rect[0] - clip_off[0]) * clip_scale[0],
-                        (clip_rect[1] - clip_off[1]) * clip_scale[1],
-                        (clip_rect[2] - clip_off[0]) * clip_scale[0],
-                        (clip_rect[3] - clip_off[1]) * clip_scale[1],
-                    ];
-
-                    if clip_rect[0] >= fb_width
-                        || clip_rect[1] >= fb_height
-                        || clip_rect[2] < 0.0
-                        || clip_rect[3] < 0.0
-                    {
-                        continue 'next;
-                    }
-
-                    let scissor = Rect {
-                        left: f32::max(0.0, clip_rect[0]).floor(),
-                        bottom: f32::max(0.0, fb_height - clip_rect[3]).floor(),
-                        right: (clip_rect[2]).ceil(),
-                        top: (clip_rect[3]).ceil(),
-                    };
-
-                    Some(DrawParams {
-                        display: display_rect.clone(),
-                        vtx_buffer: self.draw_list.unwrap().vtx_buffer(),
-                        vtx_offset: cmd_params.vtx_offset,
-                        idx_buffer: self.draw_list.unwrap().idx_buffer(),
-                        idx_offset: cmd_params.idx_offset,
-                        n_elems: count,
-                        tex_id: cmd_params.texture_id,
-                        scissor,
-                    })
-                }
-                DrawCmd::ResetRenderState => {
-                    log::warn!("imgui-backends: `ResetRenderState` is not implemented");
-                    None
-                }
-                DrawCmd::RawCallback { callback, raw_cmd } => unsafe {
-                    log::warn!("imgui-backends: `RawCallback` is not implemented");
-                    callback(self.draw_list?.raw(), raw_cmd);
-                    None
-                },
-            };
-        }
-    }
+/// TODO: extend and use this error
+#[derive(Debug, Error)]
+pub enum ImGuiRendererError {
+    #[error("bad texture id")]
+    BadTexture(imgui::TextureId),
 }
@@ -0,0 +1,83 @@
+//! Experimental: splitting huge draw data into multiple GPU submissions
+
+use std::time::{Duration, Instant};
+
+/// Submission budget for a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubmitBudget {
+    /// Number of draw calls submitted together before flushing
+    pub chunk_draw_calls: usize,
+    /// CPU time allowed for the UI submission. Exceeding it is reported in [`BudgetReport`]
+    pub frame_budget: Duration,
+}
+
+impl Default for SubmitBudget {
+    fn default() -> Self {
+        Self {
+            chunk_draw_calls: 256,
+            frame_budget: Duration::from_millis(4),
+        }
+    }
+}
+
+/// Measurements of the last submission
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BudgetReport {
+    pub elapsed: Duration,
+    pub n_draw_calls: usize,
+    /// Number of flushes including the final one
+    pub n_submits: usize,
+    pub is_over_budget: bool,
+}
+
+/// Tracks a submission against a [`SubmitBudget`]
+#[derive(Debug, Clone)]
+pub struct BudgetTracker {
+    budget: SubmitBudget,
+    start: Instant,
+    n_in_chunk: usize,
+    report: BudgetReport,
+}
+
+impl BudgetTracker {
+    pub fn begin(budget: SubmitBudget) -> Self {
+        Self {
+            budget,
+            start: Instant::now(),
+            n_in_chunk: 0,
+            report: BudgetReport::default(),
+        }
+    }
+
+    /// Call after each draw call. Returns true when the renderer should flush the chunk
+    pub fn on_draw(&mut self) -> bool {
+        self.report.n_draw_calls += 1;
+        self.n_in_chunk += 1;
+
+        if self.n_in_chunk >= self.budget.chunk_draw_calls.max(1) {
+            self.n_in_chunk = 0;
+            self.report.n_submits += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn finish(mut self) -> BudgetReport {
+        if self.n_in_chunk > 0 {
+            self.report.n_submits += 1;
+        }
+
+        self.report.elapsed = self.start.elapsed();
+        self.report.is_over_budget = self.report.elapsed > self.budget.frame_budget;
+        if self.report.is_over_budget {
+            log::debug!(
+                "imgui-backends: UI submission took {:?} (budget: {:?})",
+                self.report.elapsed,
+                self.budget.frame_budget
+            );
+        }
+
+        self.report
+    }
+}
@@ -0,0 +1,29 @@
+//! Byte views of vertex, index and uniform data
+//!
+//! Renderers upload `imgui` buffers as `&[u8]`. This is the only place where they're reinterpreted,
+//! so that renderer modules don't need `unsafe` for it.
+
+use std::mem::size_of_val;
+
+/// Types without padding bytes or pointers, i.e. every byte of a value is initialized
+///
+/// # Safety
+///
+/// Implementors must have no padding (including trailing padding) and no interior mutability.
+pub(crate) unsafe trait Plain: Copy + 'static {}
+
+unsafe impl Plain for u8 {}
+unsafe impl Plain for u16 {}
+unsafe impl Plain for u32 {}
+unsafe impl Plain for f32 {}
+// `#[repr(C)]` of `[f32; 2]`, `[f32; 2]` and `[u8; 4]`: 20 bytes with 4 bytes alignment
+unsafe impl Plain for imgui::DrawVert {}
+
+/// Reinterprets a slice as bytes
+pub(crate) fn as_bytes<T: Plain>(xs: &[T]) -> &[u8] {
+    let len = size_of_val(xs);
+    debug_assert!(len == 0 || !xs.as_ptr().is_null());
+    // SAFETY: `T: Plain` has no uninitialized bytes and the slice covers `len` bytes of one
+    // allocation. `u8` has alignment 1 and the lifetime is tied to `xs`
+    unsafe { std::slice::from_raw_parts(xs.as_ptr() as *const u8, len) }
+}
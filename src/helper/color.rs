@@ -0,0 +1,113 @@
+//! Color modes for accessibility checks and alpha conversion
+
+/// Color transformation applied to the whole UI pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorMode {
+    Normal,
+    Grayscale,
+    /// Simulates protanopia (no red cones)
+    SimulateProtanopia,
+    /// Simulates deuteranopia (no green cones)
+    SimulateDeuteranopia,
+    /// Shifts colors lost by protanopes to distinguishable ones (daltonization)
+    CompensateProtanopia,
+    /// Shifts colors lost by deuteranopes to distinguishable ones (daltonization)
+    CompensateDeuteranopia,
+}
+
+impl Default for ColorMode {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+type Mat3 = [[f32; 3]; 3];
+
+const IDENTITY: Mat3 = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+
+/// Rec. 709 luma
+const GRAYSCALE: Mat3 = [
+    [0.2126, 0.7152, 0.0722],
+    [0.2126, 0.7152, 0.0722],
+    [0.2126, 0.7152, 0.0722],
+];
+
+/// Machado et al. 2009, severity 1.0
+const PROTANOPIA: Mat3 = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+
+/// Machado et al. 2009, severity 1.0
+const DEUTERANOPIA: Mat3 = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+
+/// Redistributes the error of the simulation to green and blue
+const ERROR_SHIFT: Mat3 = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+
+fn mul(a: &Mat3, b: &Mat3) -> Mat3 {
+    let mut m = [[0.0; 3]; 3];
+    for r in 0..3 {
+        for c in 0..3 {
+            m[r][c] = (0..3).map(|i| a[r][i] * b[i][c]).sum();
+        }
+    }
+    m
+}
+
+/// `I + shift * (I - sim)`
+fn daltonize(sim: &Mat3) -> Mat3 {
+    let mut err = IDENTITY;
+    for r in 0..3 {
+        for c in 0..3 {
+            err[r][c] -= sim[r][c];
+        }
+    }
+
+    let mut m = self::mul(&ERROR_SHIFT, &err);
+    for i in 0..3 {
+        m[i][i] += 1.0;
+    }
+    m
+}
+
+impl ColorMode {
+    /// Row-major RGB transformation
+    pub fn matrix(&self) -> Mat3 {
+        match self {
+            Self::Normal => IDENTITY,
+            Self::Grayscale => GRAYSCALE,
+            Self::SimulateProtanopia => PROTANOPIA,
+            Self::SimulateDeuteranopia => DEUTERANOPIA,
+            Self::CompensateProtanopia => self::daltonize(&PROTANOPIA),
+            Self::CompensateDeuteranopia => self::daltonize(&DEUTERANOPIA),
+        }
+    }
+
+    /// Column-major 4x4 matrix for shaders. Alpha is kept as-is
+    pub fn matrix4_gl(&self) -> [f32; 16] {
+        let m = self.matrix();
+        let mut out = [0.0; 16];
+        for c in 0..3 {
+            for r in 0..3 {
+                out[c * 4 + r] = m[r][c];
+            }
+        }
+        out[15] = 1.0;
+        out
+    }
+}
+
+/// Multiplies RGB by alpha in place, e.g. for renderers configured with premultiplied alpha
+pub fn premultiply_rgba8(pixels: &mut [u8]) {
+    for px in pixels.chunks_exact_mut(4) {
+        let a = px[3] as u32;
+        for c in &mut px[..3] {
+            *c = ((*c as u32 * a + 127) / 255) as u8;
+        }
+    }
+}
@@ -0,0 +1,275 @@
+/*!
+Behavioral contract of renderers, checked with canned scenarios
+
+Each renderer implements [`RendererConformance`] and runs [`run_conformance`]. The renderers of this
+crate run it from their unit tests; the ones that need a GPU are ignored by default:
+
+```sh
+$ cargo test --features test-engine,sdl2,glow -- --ignored conformance
+```
+
+```no_run
+for report in run_conformance::<NullRenderer>() {
+    println!("{}", report);
+}
+```
+*/
+
+use std::{fmt, time::Duration};
+
+use imgui::{ChildWindow, Condition, Image, TextureId, Ui, Window};
+
+use crate::{
+    helper::{ui_test::UiTestReport, DrawParamsIterator},
+    platform::NullPlatform,
+    Backend, RegisterTexture, Renderer,
+};
+
+const DISPLAY_SIZE: [f32; 2] = [1280.0, 720.0];
+const N_FRAMES: usize = 3;
+
+/// Canned UI of a conformance test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scenario {
+    /// No window; nothing has to be drawn
+    EmptyFrame,
+    SingleWindow,
+    /// Child window whose contents overflow it
+    ClippedChild,
+    /// Child window scrolled to the bottom of thousands of lines
+    LargeScrollRegion,
+    /// Image of a texture registered with [`RegisterTexture::register_rgba8`]
+    UserTexture,
+    /// Draw list of more than 64K vertices, split with `vtx_offset`
+    VtxOffset,
+}
+
+impl Scenario {
+    pub const ALL: [Scenario; 6] = [
+        Self::EmptyFrame,
+        Self::SingleWindow,
+        Self::ClippedChild,
+        Self::LargeScrollRegion,
+        Self::UserTexture,
+        Self::VtxOffset,
+    ];
+
+    fn build(self, ui: &Ui, user_texture: TextureId) {
+        let window = |name: &str| {
+            Window::new(name)
+                .position([20.0, 20.0], Condition::Always)
+                .size([400.0, 300.0], Condition::Always)
+        };
+
+        match self {
+            Self::EmptyFrame => {}
+            Self::SingleWindow => window("single").build(ui, || {
+                ui.text("Hello");
+                ui.button("OK");
+            }),
+            Self::ClippedChild => window("clipped").build(ui, || {
+                ChildWindow::new("child").size([100.0, 50.0]).build(ui, || {
+                    for i in 0..20 {
+                        ui.text(format!("overflowing line {} of the child window", i));
+                    }
+                });
+            }),
+            Self::LargeScrollRegion => window("scroll").build(ui, || {
+                ChildWindow::new("region").build(ui, || {
+                    for i in 0..5000 {
+                        ui.text(format!("line {}", i));
+                    }
+                    ui.set_scroll_y(ui.scroll_max_y());
+                });
+            }),
+            Self::UserTexture => window("texture").build(ui, || {
+                Image::new(user_texture, [64.0, 64.0]).build(ui);
+            }),
+            Self::VtxOffset => window("vtx_offset").build(ui, || {
+                let draw_list = ui.get_window_draw_list();
+                // 12 segments: 13 vertices per filled circle
+                for i in 0..6000 {
+                    let p = [40.0 + (i % 80) as f32 * 4.0, 60.0 + (i / 80) as f32 * 3.0];
+                    draw_list
+                        .add_circle(p, 2.0, [1.0, 1.0, 1.0, 1.0])
+                        .num_segments(12)
+                        .filled(true)
+                        .build();
+                }
+            }),
+        }
+    }
+
+    /// Checks the draw data built for the scenario
+    fn check(
+        self,
+        draw_data: &imgui::DrawData,
+        user_texture: TextureId,
+        has_vtx_offset: bool,
+    ) -> Vec<String> {
+        let mut errors = Vec::new();
+        let params = DrawParamsIterator::new(draw_data)
+            .draw_calls()
+            .collect::<Vec<_>>();
+
+        for p in &params {
+            if p.n_elems == 0 || p.n_elems % 3 != 0 {
+                errors.push(format!("draw call of {} elements", p.n_elems));
+            }
+            if p.idx_offset + p.n_elems > p.idx_buffer.len() {
+                errors.push("draw call out of the index buffer".to_string());
+            } else if p.vtx_offset + p.n_vertices() > p.vtx_buffer.len() {
+                errors.push("draw call out of the vertex buffer".to_string());
+            }
+        }
+
+        let mut expect = |cond: bool, msg: &str| {
+            if !cond {
+                errors.push(msg.to_string());
+            }
+        };
+        match self {
+            Self::EmptyFrame => expect(params.is_empty(), "empty frame has draw calls"),
+            Self::SingleWindow => expect(!params.is_empty(), "window is not drawn"),
+            Self::ClippedChild => {
+                let clip_rects = params.iter().map(|p| p.clip_rect).collect::<Vec<_>>();
+                let n_distinct = clip_rects
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, r)| !clip_rects[..*i].contains(r))
+                    .count();
+                expect(n_distinct >= 2, "child window is not clipped");
+            }
+            Self::LargeScrollRegion => expect(!params.is_empty(), "scroll region is not drawn"),
+            Self::UserTexture => expect(
+                params.iter().any(|p| p.tex_id == user_texture),
+                "user texture is not drawn",
+            ),
+            Self::VtxOffset => expect(
+                !has_vtx_offset || params.iter().any(|p| p.vtx_offset > 0),
+                "large draw list is not split with vtx_offset",
+            ),
+        }
+
+        errors
+    }
+}
+
+impl fmt::Display for Scenario {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Renderer that can be checked with [`run_conformance`]
+pub trait RendererConformance: RegisterTexture + Sized {
+    /// Creates the renderer and its device for the ImGUI context
+    fn create(imgui: &mut imgui::Context) -> Result<(Self, Self::Device), String>;
+
+    /// Called before each frame is rendered, e.g. to begin recording commands
+    fn begin_frame(&mut self, _device: &mut Self::Device) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called after each frame is rendered, e.g. to submit the commands and wait for them
+    fn end_frame(&mut self, _device: &mut Self::Device) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Extra checks after the scenario is rendered, e.g. reading back pixels
+    fn check(&mut self, _device: &mut Self::Device, _scenario: Scenario) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Runs every [`Scenario`] on a fresh ImGUI context for a few frames
+pub fn run_conformance<R>() -> Vec<UiTestReport>
+where
+    R: RendererConformance,
+    R::Error: fmt::Debug,
+{
+    let _lock = crate::lock_imgui_context();
+    Scenario::ALL
+        .iter()
+        .map(|scenario| UiTestReport {
+            name: format!("{}: {}", std::any::type_name::<R>(), scenario),
+            errors: self::run_scenario::<R>(*scenario),
+        })
+        .collect()
+}
+
+fn run_scenario<R>(scenario: Scenario) -> Vec<String>
+where
+    R: RendererConformance,
+    R::Error: fmt::Debug,
+{
+    let mut imgui = imgui::Context::create();
+    imgui.set_ini_filename(None);
+    let platform = NullPlatform::new(&mut imgui, DISPLAY_SIZE);
+    let (renderer, mut device) = match R::create(&mut imgui) {
+        Ok(x) => x,
+        Err(err) => return vec![format!("failed to create the renderer: {}", err)],
+    };
+    let mut backend = Backend::new(imgui, platform, renderer);
+
+    let pixels = [0xFF; 4 * 4 * 4];
+    let user_texture = match backend
+        .renderer
+        .register_rgba8(&mut device, &pixels, 4, 4, false)
+    {
+        Ok(id) => id,
+        Err(err) => return vec![format!("failed to register a texture: {:?}", err)],
+    };
+
+    let mut errors = Vec::new();
+    let listed = backend
+        .renderer
+        .texture_infos()
+        .iter()
+        .any(|info| info.id == user_texture && info.size == [4, 4]);
+    if !listed {
+        errors.push("the user texture is not listed in `texture_infos`".to_string());
+    }
+
+    for frame in 0..N_FRAMES {
+        backend.update_delta_time(Duration::from_nanos(1_000_000_000 / 60));
+        if let Err(err) = backend.renderer.begin_frame(&mut device) {
+            errors.push(format!("frame {}: {}", frame, err));
+            break;
+        }
+        let ui = backend.begin_frame(&());
+        scenario.build(&ui, user_texture);
+        let prepared = ui.prepare_render(&mut ());
+
+        // the layout settles in the first frames
+        if frame + 1 == N_FRAMES {
+            errors.extend(scenario.check(prepared.draw_data(), user_texture, R::HAS_VTX_OFFSET));
+        }
+        if let Err(err) = prepared.render(&mut device) {
+            errors.push(format!("frame {}: render failed: {:?}", frame, err));
+        }
+        if let Err(err) = backend.renderer.end_frame(&mut device) {
+            errors.push(format!("frame {}: {}", frame, err));
+        }
+    }
+
+    if let Err(err) = backend.renderer.check(&mut device, scenario) {
+        errors.push(err);
+    }
+    errors
+}
+
+/// Runs [`run_conformance`] and fails with the errors of every failed scenario
+#[cfg(test)]
+pub(crate) fn assert_conformance<R>()
+where
+    R: RendererConformance,
+    R::Error: fmt::Debug,
+{
+    let failed = self::run_conformance::<R>()
+        .into_iter()
+        .filter(|report| !report.passed())
+        .map(|report| report.to_string())
+        .collect::<Vec<_>>();
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+}
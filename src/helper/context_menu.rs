@@ -0,0 +1,60 @@
+//! Right-click context menu for the last item
+
+use {
+    imgui::{MouseButton, Ui},
+    std::{fmt::Display, str::FromStr},
+};
+
+use super::localize::{tr, UiText};
+
+/// Attaches a standard context menu to the last item: copy value, copy label, paste value and
+/// reset to default. Copy and paste go through the clipboard backend set by the platform
+///
+/// Returns true if `value` was changed from the menu.
+///
+/// ```no_run
+/// Slider::new("speed", 0.0, 10.0).build(&ui, &mut speed);
+/// item_context_menu(&ui, "speed", &mut speed, &1.0);
+/// ```
+pub fn item_context_menu<T>(ui: &Ui, label: &str, value: &mut T, default: &T) -> bool
+where
+    T: Display + FromStr + Clone,
+{
+    let popup_id = format!("##context-menu-{}", label);
+    if ui.is_item_hovered() && ui.is_mouse_released(MouseButton::Right) {
+        ui.open_popup(&popup_id);
+    }
+
+    let mut changed = false;
+    ui.popup(&popup_id, || {
+        if imgui::MenuItem::new(tr(UiText::CopyValue)).build(ui) {
+            ui.set_clipboard_text(&value.to_string());
+        }
+
+        if imgui::MenuItem::new(tr(UiText::CopyLabel)).build(ui) {
+            ui.set_clipboard_text(label);
+        }
+
+        let pasted = ui
+            .clipboard_text()
+            .and_then(|text| text.trim().parse::<T>().ok());
+        if imgui::MenuItem::new(tr(UiText::PasteValue))
+            .enabled(pasted.is_some())
+            .build(ui)
+        {
+            if let Some(pasted) = pasted {
+                *value = pasted;
+                changed = true;
+            }
+        }
+
+        ui.separator();
+
+        if imgui::MenuItem::new(tr(UiText::ResetToDefault)).build(ui) {
+            *value = default.clone();
+            changed = true;
+        }
+    });
+
+    changed
+}
@@ -0,0 +1,209 @@
+//! Arbitration between game controllers and the keyboard
+//!
+//! With several controllers connected, only the *active* one (the one with the most recent input)
+//! drives ImGUI navigation. Small stick tilts don't count as input, so an idle controller's stick
+//! drift can't take over from the keyboard or from another controller.
+
+use imgui::NavInput;
+
+/// Source of the most recent input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputSource {
+    KeyboardMouse,
+    /// Controller with a platform-defined ID (e.g. SDL joystick instance ID)
+    Controller(u32),
+}
+
+impl Default for InputSource {
+    fn default() -> Self {
+        Self::KeyboardMouse
+    }
+}
+
+/// Decides which controller's input is accepted
+#[derive(Debug, Clone, PartialEq)]
+pub struct ControllerArbiter {
+    active: InputSource,
+    lock: Option<u32>,
+    /// Stick tilt in `[0, 1]` below which axis motion doesn't count as input
+    pub dead_zone: f32,
+}
+
+impl Default for ControllerArbiter {
+    fn default() -> Self {
+        Self {
+            active: InputSource::KeyboardMouse,
+            lock: None,
+            dead_zone: 0.25,
+        }
+    }
+}
+
+impl ControllerArbiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn active(&self) -> InputSource {
+        self.active
+    }
+
+    pub fn locked(&self) -> Option<u32> {
+        self.lock
+    }
+
+    /// Accepts input from the controller only. `None` switches to the most recent input again
+    pub fn lock(&mut self, id: Option<u32>) {
+        self.lock = id;
+        if let Some(id) = id {
+            self.active = InputSource::Controller(id);
+        }
+    }
+
+    /// Records keyboard or mouse input
+    pub fn on_keyboard_mouse(&mut self) {
+        if self.lock.is_none() {
+            self.active = InputSource::KeyboardMouse;
+        }
+    }
+
+    /// Records controller input with `magnitude` in `[0, 1]` (`1.0` for buttons). Returns if the
+    /// input should be handled
+    pub fn on_controller(&mut self, id: u32, magnitude: f32) -> bool {
+        if self.lock.map_or(false, |lock| lock != id) {
+            return false;
+        }
+
+        if magnitude > self.dead_zone {
+            self.active = InputSource::Controller(id);
+        }
+
+        // releases and small tilts of the active controller still have to reach ImGUI
+        self.active == InputSource::Controller(id)
+    }
+
+    /// Call it when a controller is disconnected
+    pub fn on_removed(&mut self, id: u32) {
+        if self.lock == Some(id) {
+            log::debug!("imgui-backends: locked controller {} was removed", id);
+        } else if self.active == InputSource::Controller(id) {
+            self.active = InputSource::KeyboardMouse;
+        }
+    }
+}
+
+/// Gamepad navigation inputs of the active controller
+#[derive(Debug, Clone, PartialEq)]
+pub struct GamepadNav {
+    inputs: [f32; NavInput::COUNT],
+}
+
+impl Default for GamepadNav {
+    fn default() -> Self {
+        Self {
+            inputs: [0.0; NavInput::COUNT],
+        }
+    }
+}
+
+impl GamepadNav {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, input: NavInput, value: f32) {
+        self.inputs[input as usize] = value.max(0.0).min(1.0);
+    }
+
+    /// Sets a pair of opposite inputs from an axis value in `[-1, 1]`
+    pub fn set_axis(&mut self, neg: NavInput, pos: NavInput, value: f32, dead_zone: f32) {
+        let t = if value.abs() <= dead_zone {
+            0.0
+        } else {
+            (value.abs() - dead_zone) / (1.0 - dead_zone)
+        };
+        let (on, off) = if value < 0.0 { (neg, pos) } else { (pos, neg) };
+        self.set(on, t);
+        self.set(off, 0.0);
+    }
+
+    /// Releases every input, e.g. when the active controller changes
+    pub fn clear(&mut self) {
+        self.inputs = [0.0; NavInput::COUNT];
+    }
+
+    /// Writes the inputs to ImGUI IO if gamepad navigation is enabled
+    pub fn apply(&self, io: &mut imgui::Io) {
+        if !io
+            .config_flags
+            .contains(imgui::ConfigFlags::NAV_ENABLE_GAMEPAD)
+        {
+            return;
+        }
+
+        io.nav_inputs = self.inputs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stick_drift_does_not_take_over() {
+        let mut arbiter = ControllerArbiter::new();
+
+        assert!(!arbiter.on_controller(1, 0.1));
+        assert_eq!(arbiter.active(), InputSource::KeyboardMouse);
+
+        assert!(arbiter.on_controller(1, 1.0));
+        assert_eq!(arbiter.active(), InputSource::Controller(1));
+        // small tilts and releases of the active controller are still handled
+        assert!(arbiter.on_controller(1, 0.0));
+
+        // drift of another controller is ignored
+        assert!(!arbiter.on_controller(2, 0.2));
+        assert_eq!(arbiter.active(), InputSource::Controller(1));
+
+        arbiter.on_keyboard_mouse();
+        assert_eq!(arbiter.active(), InputSource::KeyboardMouse);
+    }
+
+    #[test]
+    fn lock_and_removal() {
+        let mut arbiter = ControllerArbiter::new();
+        arbiter.lock(Some(2));
+        assert_eq!(arbiter.active(), InputSource::Controller(2));
+
+        assert!(!arbiter.on_controller(1, 1.0));
+        arbiter.on_keyboard_mouse();
+        assert_eq!(arbiter.active(), InputSource::Controller(2));
+
+        // the locked controller stays active until unlocked
+        arbiter.on_removed(2);
+        assert_eq!(arbiter.active(), InputSource::Controller(2));
+
+        arbiter.lock(None);
+        assert!(arbiter.on_controller(1, 1.0));
+        arbiter.on_removed(1);
+        assert_eq!(arbiter.active(), InputSource::KeyboardMouse);
+    }
+
+    #[test]
+    fn axis_dead_zone() {
+        let mut nav = GamepadNav::new();
+        let (left, right) = (NavInput::DpadLeft, NavInput::DpadRight);
+
+        nav.set_axis(left, right, -0.1, 0.2);
+        assert_eq!(nav.inputs[left as usize], 0.0);
+        assert_eq!(nav.inputs[right as usize], 0.0);
+
+        nav.set_axis(left, right, -0.6, 0.2);
+        assert!((nav.inputs[left as usize] - 0.5).abs() < 1e-6);
+        assert_eq!(nav.inputs[right as usize], 0.0);
+
+        nav.set_axis(left, right, 1.0, 0.2);
+        assert_eq!(nav.inputs[left as usize], 0.0);
+        assert!((nav.inputs[right as usize] - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,99 @@
+//! Per-pixel image diffing for visual regression
+
+/// RGBA8 image, e.g. read back from a renderer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub pixels: Vec<u8>,
+    pub w: u32,
+    pub h: u32,
+}
+
+impl Image {
+    /// Flips rows, e.g. for images read back from OpenGL
+    pub fn flip_y(&mut self) {
+        let row = 4 * self.w as usize;
+        let h = self.h as usize;
+        for y in 0..h / 2 {
+            let (top, bottom) = self.pixels.split_at_mut((h - 1 - y) * row);
+            top[y * row..(y + 1) * row].swap_with_slice(&mut bottom[..row]);
+        }
+    }
+
+    /// Binary PPM (alpha is dropped). Handy for dumping diffs without an image crate
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.w, self.h).into_bytes();
+        for px in self.pixels.chunks_exact(4) {
+            out.extend_from_slice(&px[0..3]);
+        }
+        out
+    }
+}
+
+/// Result of [`diff_images`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffReport {
+    /// Number of pixels whose max channel delta exceeds the threshold
+    pub n_different: usize,
+    pub n_pixels: usize,
+    /// Max channel delta over all pixels
+    pub max_delta: u8,
+    /// Mean of per-pixel max channel deltas
+    pub mean_delta: f32,
+    /// Different pixels in red, others in dimmed gray
+    pub diff: Image,
+}
+
+impl DiffReport {
+    pub fn is_same(&self) -> bool {
+        self.n_different == 0
+    }
+}
+
+/// Compares two RGBA8 images of the same size. Returns `None` if sizes differ
+pub fn diff_images(a: &Image, b: &Image, threshold: u8) -> Option<DiffReport> {
+    if a.w != b.w || a.h != b.h || a.pixels.len() != b.pixels.len() {
+        return None;
+    }
+
+    let n_pixels = (a.w * a.h) as usize;
+    let mut diff = Vec::with_capacity(4 * n_pixels);
+    let mut n_different = 0;
+    let mut max_delta = 0;
+    let mut sum_delta = 0u64;
+
+    for (pa, pb) in a.pixels.chunks_exact(4).zip(b.pixels.chunks_exact(4)) {
+        let delta = pa
+            .iter()
+            .zip(pb)
+            .map(|(x, y)| (*x as i16 - *y as i16).abs() as u8)
+            .max()
+            .unwrap_or(0);
+
+        max_delta = max_delta.max(delta);
+        sum_delta += delta as u64;
+
+        if delta > threshold {
+            n_different += 1;
+            diff.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let gray = (pa[0] as u16 + pa[1] as u16 + pa[2] as u16) / 6;
+            diff.extend_from_slice(&[gray as u8, gray as u8, gray as u8, 255]);
+        }
+    }
+
+    Some(DiffReport {
+        n_different,
+        n_pixels,
+        max_delta,
+        mean_delta: if n_pixels == 0 {
+            0.0
+        } else {
+            sum_delta as f32 / n_pixels as f32
+        },
+        diff: Image {
+            pixels: diff,
+            w: a.w,
+            h: a.h,
+        },
+    })
+}
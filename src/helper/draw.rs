@@ -0,0 +1,672 @@
+//! Draw data traversal, projection and scissor math
+//!
+//! This module depends on `core` (and `imgui` types) only: no `std::time`, no `std` float
+//! functions and no `log` except through the `draw_warn!` shim. Engines on restricted-`std` targets
+//! can reuse it for custom renderers.
+
+use core::fmt;
+
+use imgui::{internal::RawWrapper, sys, DrawCmd};
+
+use crate::dpi::{LogicalSize, PhysicalSize};
+
+mod flip;
+pub use self::flip::*;
+
+#[cfg(test)]
+mod test_util;
+
+/// Logging shim. Replace the body to port the module to targets without `log`
+#[allow(unused_macros)]
+macro_rules! draw_warn {
+    ($($arg:tt)*) => {
+        log::warn!($($arg)*)
+    };
+}
+
+// `f32::floor` and friends are in `std`, not in `core`. Inputs are in the range of `i32` after
+// clamping to the framebuffer; larger values saturate
+
+fn floor(x: f32) -> f32 {
+    let t = x as i32 as f32;
+    if t > x {
+        t - 1.0
+    } else {
+        t
+    }
+}
+
+fn ceil(x: f32) -> f32 {
+    let t = x as i32 as f32;
+    if t < x {
+        t + 1.0
+    } else {
+        t
+    }
+}
+
+fn round(x: f32) -> f32 {
+    self::floor(x + 0.5)
+}
+
+/// Framebuffer size in physical pixels, rounded the same way as [`DrawParams::framebuffer_size`]
+pub(crate) fn framebuffer_extent(display_size: [f32; 2], scale: [f32; 2]) -> [f32; 2] {
+    [
+        self::round(display_size[0] * scale[0]),
+        self::round(display_size[1] * scale[1]),
+    ]
+}
+
+/// Creates an orthographic projection matrix for OpenGL
+///
+/// ```
+/// // left, right, bottom, top, near, far
+/// let mat = ortho_mat_gl(0.0, 1280.0, 0.0, 720.0, 0.0, 1.0);
+/// ```
+///
+/// Note that they're in OpenGL coordinate system and the y axis goes up. Swap `bottom` and `top` if
+/// you want your y axis to go down:
+///
+/// ```
+/// // left, right, top, bottom, near, far
+/// let mat = ortho_mat_gl(0.0, 1280.0, 720.0, 0.0, 0.0, 1.0);
+/// ```
+pub fn ortho_mat_gl(
+    left: f32,
+    right: f32,
+    bottom: f32,
+    top: f32,
+    near: f32,
+    far: f32,
+) -> [f32; 16] {
+    [
+        (2.0 / (right as f64 - left as f64)) as f32,
+        0.0,
+        0.0,
+        0.0,
+        // ---
+        0.0,
+        (2.0 / (top as f64 - bottom as f64)) as f32,
+        0.0,
+        0.0,
+        // ---
+        0.0,
+        0.0,
+        -(2.0 / (far as f64 - near as f64)) as f32,
+        0.0,
+        // ---
+        -((right as f64 + left as f64) / (right as f64 - left as f64)) as f32,
+        -((top as f64 + bottom as f64) / (top as f64 - bottom as f64)) as f32,
+        (near as f64 / (near as f64 - far as f64)) as f32,
+        1.0,
+    ]
+}
+
+/// Rectangle. NOTE: Y axis goes up
+///
+/// # Coordinate system
+/// ```md
+///    (up)
+///     y
+///     ^
+///     |
+/// ----+---> x (right)
+///     |
+///     |
+/// ```
+#[derive(Debug, Clone)]
+pub struct Rect {
+    left: f32,
+    top: f32,
+    right: f32,
+    bottom: f32,
+}
+
+impl Rect {
+    /// Display rectangle of draw data
+    pub(crate) fn from_display(pos: [f32; 2], size: [f32; 2]) -> Self {
+        Self {
+            left: pos[0],
+            right: pos[0] + size[0],
+            top: pos[1] + size[1],
+            bottom: pos[1],
+        }
+    }
+
+    pub fn left(&self) -> f32 {
+        self.left
+    }
+
+    /// NOTE: Y axies goes up
+    pub fn top(&self) -> f32 {
+        self.top
+    }
+
+    pub fn right(&self) -> f32 {
+        self.right
+    }
+
+    /// NOTE: Y axies goes up
+    pub fn bottom(&self) -> f32 {
+        self.bottom
+    }
+
+    pub fn width(&self) -> f32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> f32 {
+        self.top - self.bottom
+    }
+}
+
+/// Context and parameters for making a draw call; more comfortable version of
+/// [`imgui::DrawCmdParams`]
+#[derive(Debug, Clone)]
+pub struct DrawParams<'a> {
+    /// Display [`Rect`] in logical pixels. Can be used for calculating orthographic projection
+    /// matrix
+    pub display: Rect,
+    /// Scale from logical pixels to physical pixels
+    pub framebuffer_scale: [f32; 2],
+    /// Vertex buffer for multiple draw calls, sliced with `vtx_offset` and `n_elems`
+    pub vtx_buffer: &'a [imgui::DrawVert],
+    /// Vertex offset for this draw call
+    pub vtx_offset: usize,
+    /// Index buffer for multiple draw calls, sliced with `vtx_offset` and `n_elems`
+    pub idx_buffer: &'a [imgui::DrawIdx],
+    /// Index offset for this draw call
+    pub idx_offset: usize,
+    /// Number of triangles for this draw call: `n_elems` = `vbuf_span.len` `4` = `ibuf.len` / `6`
+    pub n_elems: usize,
+    /// Texture ID
+    pub tex_id: imgui::TextureId,
+    /// Scissor rectangle in physical pixels, clamped to the framebuffer. The origin is
+    /// bottom-left (OpenGL's `glScissor`). Same area as `clip_rect`
+    pub scissor: Rect,
+    /// `[x1, y1, x2, y2]` clip rectangle in physical pixels, clamped to the framebuffer. The
+    /// origin is top-left. See [`FlipY::scissor`]
+    pub clip_rect: [f32; 4],
+}
+
+impl<'a> DrawParams<'a> {
+    /// Size of the display in logical pixels
+    pub fn display_size(&self) -> LogicalSize {
+        LogicalSize([self.display.width(), self.display.height()])
+    }
+
+    /// Size of the framebuffer in physical pixels
+    pub fn framebuffer_size(&self) -> PhysicalSize {
+        PhysicalSize([
+            self::round(self.display.width() * self.framebuffer_scale[0]) as u32,
+            self::round(self.display.height() * self.framebuffer_scale[1]) as u32,
+        ])
+    }
+
+    /// If the clip rectangle covers the whole framebuffer, so that scissoring has no effect (e.g.
+    /// backgrounds of fullscreen windows)
+    pub fn covers_framebuffer(&self) -> bool {
+        let PhysicalSize([w, h]) = self.framebuffer_size();
+        let [x1, y1, x2, y2] = self.clip_rect;
+        // `framebuffer_size` is rounded
+        x1 <= 0.0 && y1 <= 0.0 && x2 >= w as f32 - 0.5 && y2 >= h as f32 - 0.5
+    }
+
+    /// Number of vertices referenced by this draw call, counted from `vtx_offset`
+    ///
+    /// Don't assume quads (4 vertices per 6 indices); polylines and circles have other ratios.
+    pub fn n_vertices(&self) -> usize {
+        self.idx_buffer[self.idx_offset..self.idx_offset + self.n_elems]
+            .iter()
+            .max()
+            .map_or(0, |i| *i as usize + 1)
+    }
+}
+
+/// Item yielded by [`DrawParamsIterator`]
+#[derive(Debug, Clone)]
+pub enum DrawItem<'a> {
+    /// Draw call
+    Draw(DrawParams<'a>),
+    /// `DrawCmd::ResetRenderState`: a callback may have changed the render states. Renderers set
+    /// up their pipeline, blend and scissor states again before the next draw call
+    ResetState,
+    /// `DrawCmd::RawCallback`. Renderers run it with [`crate::Renderer::run_callback`]
+    Callback(DrawCallback<'a>),
+}
+
+impl<'a> DrawItem<'a> {
+    pub fn params(&self) -> Option<&DrawParams<'a>> {
+        match self {
+            Self::Draw(params) => Some(params),
+            _ => None,
+        }
+    }
+
+    pub fn into_params(self) -> Option<DrawParams<'a>> {
+        match self {
+            Self::Draw(params) => Some(params),
+            _ => None,
+        }
+    }
+}
+
+/// User callback in a draw list, e.g. drawing a 3D viewport inside an ImGUI window
+#[derive(Clone)]
+pub struct DrawCallback<'a> {
+    /// Display [`Rect`] in logical pixels
+    pub display: Rect,
+    /// Scale from logical pixels to physical pixels
+    pub framebuffer_scale: [f32; 2],
+    /// `[x1, y1, x2, y2]` clip rectangle in physical pixels, clamped to the framebuffer. The
+    /// origin is top-left. `None` if the callback is clipped out (it's still run)
+    pub clip_rect: Option<[f32; 4]>,
+    draw_list: &'a imgui::DrawList,
+    callback: unsafe extern "C" fn(*const sys::ImDrawList, *const sys::ImDrawCmd),
+    raw_cmd: *const sys::ImDrawCmd,
+}
+
+impl<'a> fmt::Debug for DrawCallback<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DrawCallback")
+            .field("display", &self.display)
+            .field("framebuffer_scale", &self.framebuffer_scale)
+            .field("clip_rect", &self.clip_rect)
+            .field("user_callback_data", &self.user_callback_data())
+            .finish()
+    }
+}
+
+impl<'a> DrawCallback<'a> {
+    /// `UserCallbackData` given to `ImDrawList::AddCallback`
+    pub fn user_callback_data(&self) -> *mut core::ffi::c_void {
+        // SAFETY: `raw_cmd` points to a command of `draw_list`, which we borrow
+        unsafe { (*self.raw_cmd).UserCallbackData }
+    }
+
+    /// Calls the callback as Dear ImGUI expects. Beginning frames, handling events and rendering
+    /// through [`crate::Backend`] are rejected while it runs
+    pub fn invoke(&self) {
+        let _scope = super::CallbackScope::begin();
+        // SAFETY: the callback and both pointers come from the same live draw list of the draw
+        // data we're borrowing
+        unsafe {
+            (self.callback)(self.draw_list.raw(), self.raw_cmd);
+        }
+    }
+}
+
+/// Scissor and clamped clip rectangle of a draw command, or `None` if the command is culled
+pub(crate) fn clip_command(
+    clip_rect: [f32; 4],
+    clip_off: [f32; 2],
+    clip_scale: [f32; 2],
+    fb_width: f32,
+    fb_height: f32,
+) -> Option<(Rect, [f32; 4])> {
+    // [left, up, right, down]
+    let clip_rect = [
+        (clip_rect[0] - clip_off[0]) * clip_scale[0],
+        (clip_rect[1] - clip_off[1]) * clip_scale[1],
+        (clip_rect[2] - clip_off[0]) * clip_scale[0],
+        (clip_rect[3] - clip_off[1]) * clip_scale[1],
+    ];
+
+    if clip_rect[0] >= fb_width
+        || clip_rect[1] >= fb_height
+        || clip_rect[2] <= 0.0
+        || clip_rect[3] <= 0.0
+        || clip_rect[2] <= clip_rect[0]
+        || clip_rect[3] <= clip_rect[1]
+    {
+        return None;
+    }
+
+    // pixels touched by the clip rectangle, within the framebuffer
+    let clip_rect = [
+        self::floor(f32::max(0.0, clip_rect[0])),
+        self::floor(f32::max(0.0, clip_rect[1])),
+        f32::min(fb_width, self::ceil(clip_rect[2])),
+        f32::min(fb_height, self::ceil(clip_rect[3])),
+    ];
+
+    // the same area with bottom-left origin
+    let scissor = Rect {
+        left: clip_rect[0],
+        right: clip_rect[2],
+        top: fb_height - clip_rect[1],
+        bottom: fb_height - clip_rect[3],
+    };
+
+    Some((scissor, clip_rect))
+}
+
+/// Iterator of [`DrawItem`]s
+///
+/// Yields every `Elements` command of every draw list in order as [`DrawItem::Draw`], except:
+///
+/// * commands with no elements
+/// * commands clipped out of the framebuffer or with empty clip rectangles
+///
+/// `ResetRenderState` and `RawCallback` commands are yielded as [`DrawItem::ResetState`] and
+/// [`DrawItem::Callback`]. Callbacks are never culled. See the `fuzz_draw_params` test.
+pub struct DrawParamsIterator<'a> {
+    // variables
+    fb_width: f32,
+    fb_height: f32,
+    clip_off: [f32; 2],
+    clip_scale: [f32; 2],
+    display_rect: Rect,
+    // data.iterator()
+    draw_lists: imgui::DrawListIterator<'a>,
+    // states to pull `DrawCmd` one by one
+    draw_list: Option<&'a imgui::DrawList>,
+    draw_cmds: Option<imgui::DrawCmdIterator<'a>>,
+}
+
+impl<'a> DrawParamsIterator<'a> {
+    pub fn new(data: &'a imgui::DrawData) -> Self {
+        let [fb_width, fb_height] =
+            self::framebuffer_extent(data.display_size, data.framebuffer_scale);
+        Self {
+            fb_width,
+            fb_height,
+            clip_off: data.display_pos,
+            clip_scale: data.framebuffer_scale,
+            display_rect: Rect::from_display(data.display_pos, data.display_size),
+            draw_lists: data.draw_lists(),
+            draw_list: None,
+            draw_cmds: None,
+        }
+    }
+
+    /// Draw calls only, for consumers without render states to reset. Callbacks are dropped without
+    /// being run
+    pub fn draw_calls(self) -> impl Iterator<Item = DrawParams<'a>> {
+        self.filter_map(DrawItem::into_params)
+    }
+
+    /// One step of this loop:
+    /// ```no_run
+    /// for draw_list in draw_data.draw_lists() {
+    ///     for cmd in draw_list.commands() {
+    /// ```
+    fn next_draw_cmd(&mut self) -> Option<imgui::DrawCmd> {
+        loop {
+            if let Some(cmds) = self.draw_cmds.as_mut() {
+                if let Some(cmd) = cmds.next() {
+                    return Some(cmd);
+                } else {
+                    self.draw_cmds = None;
+                }
+            }
+
+            if let Some(draw_list) = self.draw_lists.next() {
+                self.draw_cmds = Some(draw_list.commands());
+                self.draw_list = Some(draw_list);
+            } else {
+                return None;
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for DrawParamsIterator<'a> {
+    type Item = DrawItem<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.fb_width <= 0.0 || self.fb_height <= 0.0 {
+            return None;
+        }
+
+        let clip_off = self.clip_off;
+        let clip_scale = self.clip_scale;
+        let fb_width = self.fb_width;
+        let fb_height = self.fb_height;
+        let display_rect = self.display_rect.clone();
+
+        'next: loop {
+            return match self.next_draw_cmd()? {
+                DrawCmd::Elements { count, cmd_params } => {
+                    if count == 0 {
+                        continue 'next;
+                    }
+
+                    let (scissor, clip_rect) = match self::clip_command(
+                        cmd_params.clip_rect,
+                        clip_off,
+                        clip_scale,
+                        fb_width,
+                        fb_height,
+                    ) {
+                        Some(clip) => clip,
+                        None => continue 'next,
+                    };
+
+                    Some(DrawItem::Draw(DrawParams {
+                        display: display_rect.clone(),
+                        framebuffer_scale: clip_scale,
+                        vtx_buffer: self.draw_list.unwrap().vtx_buffer(),
+                        vtx_offset: cmd_params.vtx_offset,
+                        idx_buffer: self.draw_list.unwrap().idx_buffer(),
+                        idx_offset: cmd_params.idx_offset,
+                        n_elems: count,
+                        tex_id: cmd_params.texture_id,
+                        scissor,
+                        clip_rect,
+                    }))
+                }
+                DrawCmd::ResetRenderState => Some(DrawItem::ResetState),
+                DrawCmd::RawCallback { callback, raw_cmd } => {
+                    // SAFETY: `raw_cmd` points to a command of the current draw list
+                    let c = unsafe { (*raw_cmd).ClipRect };
+                    let clip_rect = self::clip_command(
+                        [c.x, c.y, c.z, c.w],
+                        clip_off,
+                        clip_scale,
+                        fb_width,
+                        fb_height,
+                    )
+                    .map(|(_, clip_rect)| clip_rect);
+
+                    Some(DrawItem::Callback(DrawCallback {
+                        display: display_rect.clone(),
+                        framebuffer_scale: clip_scale,
+                        clip_rect,
+                        // always set by `next_draw_cmd`
+                        draw_list: self.draw_list.unwrap(),
+                        callback,
+                        raw_cmd,
+                    }))
+                }
+            };
+        }
+    }
+}
+
+/// Builds random draw lists and checks the parameters yielded by [`DrawParamsIterator`]
+///
+/// Draw lists include empty windows, random (inverted, off-screen, huge) clip rectangles, images
+/// with many texture IDs and meshes big enough to need `vtx_offset`.
+#[cfg(test)]
+mod tests {
+    use imgui::{BackendFlags, Condition, DrawCmd, DrawListMut, TextureId, Ui, Window};
+
+    use crate::{
+        helper::{DrawParams, DrawParamsIterator},
+        Platform,
+    };
+
+    use super::test_util::{self, Rng};
+
+    const W: f32 = 1280.0;
+    const H: f32 = 720.0;
+
+    const N_SEEDS: u64 = 32;
+
+    /// Point in `[-W, 2W) x [-H, 2H)` so that some shapes are off-screen
+    fn point(rng: &mut Rng) -> [f32; 2] {
+        [rng.coord(-W, 2.0 * W), rng.coord(-H, 2.0 * H)]
+    }
+
+    #[test]
+    fn fuzz_draw_params() {
+        let _lock = crate::lock_imgui_context();
+        let (mut imgui, mut platform) = test_util::context([W, H]);
+        // allow more than 64K vertices per draw list
+        imgui
+            .io_mut()
+            .backend_flags
+            .insert(BackendFlags::RENDERER_HAS_VTX_OFFSET);
+
+        for seed in 0..N_SEEDS {
+            let mut rng = Rng::new(seed);
+
+            imgui.io_mut().delta_time = 1.0 / 60.0;
+            platform.prepare_frame(imgui.io_mut(), &());
+
+            let ui = imgui.frame();
+            self::build(&ui, &mut rng);
+            let draw_data = ui.render();
+
+            let expected = self::count_visible(draw_data);
+            let mut yielded = 0;
+            for params in DrawParamsIterator::new(draw_data).draw_calls() {
+                self::check(&params, seed);
+                yielded += 1;
+            }
+
+            assert_eq!(
+                yielded, expected,
+                "seed {}: {} draw calls yielded, {} expected",
+                seed, yielded, expected
+            );
+        }
+    }
+
+    fn build(ui: &Ui, rng: &mut Rng) {
+        let n_windows = rng.below(6);
+        for i in 0..n_windows {
+            Window::new(format!("window {}", i))
+                .position(self::point(rng), Condition::Always)
+                .size(
+                    [rng.below(800) as f32 + 1.0, rng.below(600) as f32 + 1.0],
+                    Condition::Always,
+                )
+                .build(ui, || {
+                    // some windows stay empty
+                    if rng.below(4) == 0 {
+                        return;
+                    }
+                    let draw_list = ui.get_window_draw_list();
+                    self::shapes(&draw_list, rng);
+                });
+        }
+
+        let background = ui.get_background_draw_list();
+        self::shapes(&background, rng);
+    }
+
+    fn shapes(draw_list: &DrawListMut, rng: &mut Rng) {
+        let n_shapes = rng.below(64);
+        for _ in 0..n_shapes {
+            let (a, b) = (self::point(rng), self::point(rng));
+            let col = [1.0, 1.0, 1.0, 1.0];
+
+            match rng.below(6) {
+                0 => draw_list.add_rect(a, b, col).filled(true).build(),
+                1 => draw_list
+                    .add_line(a, b, col)
+                    .thickness(rng.below(16) as f32 + 0.5)
+                    .build(),
+                2 => draw_list.add_text(a, col, "fuzz"),
+                3 => draw_list
+                    .add_image(TextureId::from(rng.below(8) as usize), a, b)
+                    .build(),
+                4 => draw_list.with_clip_rect(a, b, || {
+                    draw_list
+                        .add_rect(self::point(rng), self::point(rng), col)
+                        .build();
+                }),
+                _ => {
+                    // huge mesh: more than 64K vertices
+                    let n = rng.below(4) * 4000;
+                    for _ in 0..n {
+                        let p = self::point(rng);
+                        draw_list
+                            .add_circle(p, 8.0, col)
+                            .num_segments(12)
+                            .filled(true)
+                            .build();
+                    }
+                }
+            }
+        }
+    }
+
+    /// The iterator's contract, written independently of it
+    fn count_visible(draw_data: &imgui::DrawData) -> usize {
+        let fb = [
+            draw_data.display_size[0] * draw_data.framebuffer_scale[0],
+            draw_data.display_size[1] * draw_data.framebuffer_scale[1],
+        ];
+
+        let mut n = 0;
+        for list in draw_data.draw_lists() {
+            for cmd in list.commands() {
+                if let DrawCmd::Elements { count, cmd_params } = cmd {
+                    let off = draw_data.display_pos;
+                    let scale = draw_data.framebuffer_scale;
+                    let c = cmd_params.clip_rect;
+                    let (x1, y1) = ((c[0] - off[0]) * scale[0], (c[1] - off[1]) * scale[1]);
+                    let (x2, y2) = ((c[2] - off[0]) * scale[0], (c[3] - off[1]) * scale[1]);
+
+                    let visible = count > 0
+                        && x1 < fb[0]
+                        && y1 < fb[1]
+                        && x2 > 0.0
+                        && y2 > 0.0
+                        && x1 < x2
+                        && y1 < y2;
+                    if visible {
+                        n += 1;
+                    }
+                }
+            }
+        }
+        n
+    }
+
+    fn check(params: &DrawParams, seed: u64) {
+        assert!(params.n_elems > 0, "seed {}: empty draw call", seed);
+        assert!(
+            params.n_elems % 3 == 0,
+            "seed {}: not a triangle list",
+            seed
+        );
+        assert!(
+            params.idx_offset + params.n_elems <= params.idx_buffer.len(),
+            "seed {}: draw call out of index buffer",
+            seed
+        );
+        assert!(
+            params.vtx_offset + params.n_vertices() <= params.vtx_buffer.len(),
+            "seed {}: draw call out of vertex buffer",
+            seed
+        );
+
+        let fb = params.framebuffer_size();
+        let [x1, y1, x2, y2] = params.clip_rect;
+        assert!(
+            0.0 <= x1
+                && x1 < x2
+                && x2 <= fb.0[0] as f32
+                && 0.0 <= y1
+                && y1 < y2
+                && y2 <= fb.0[1] as f32,
+            "seed {}: clip rect {:?} is out of framebuffer {:?}",
+            seed,
+            params.clip_rect,
+            fb
+        );
+    }
+}
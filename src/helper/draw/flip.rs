@@ -0,0 +1,262 @@
+//! Vertical orientation of the UI in the render target
+
+use super::{DrawParams, Rect};
+
+/// Flips the UI vertically. Set it for engines that use bottom-left origin for the UI space
+/// (e.g. rendering into a texture that is sampled upside down)
+///
+/// Renderers use it for both the projection matrix and the scissor rectangles so that they stay
+/// consistent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FlipY(pub bool);
+
+impl FlipY {
+    /// `[bottom, top]` arguments of an orthographic projection for the display
+    pub fn bottom_top(&self, display: &Rect) -> [f32; 2] {
+        // `display.top()` is the bottom edge in screen space (y axis goes down in ImGUI)
+        if self.0 {
+            [display.bottom(), display.top()]
+        } else {
+            [display.top(), display.bottom()]
+        }
+    }
+
+    /// OpenGL orthographic projection matrix (column-major) for the display
+    pub fn projection(&self, display: &Rect) -> [f32; 16] {
+        let [bottom, top] = self.bottom_top(display);
+        super::ortho_mat_gl(display.left(), display.right(), bottom, top, 0.0, 1.0)
+    }
+
+    /// Scissor rectangle `[x, y, w, h]` in physical pixels, clamped to the framebuffer
+    ///
+    /// `origin_bottom_left` is the origin of the device's scissor coordinates (`true` for
+    /// OpenGL). Matches `imgui_impl_opengl3` for unflipped UI; see the tests below.
+    pub fn scissor(&self, params: &DrawParams, origin_bottom_left: bool) -> [i32; 4] {
+        let [x1, y1, x2, y2] = params.clip_rect;
+
+        // `scissor` is `clip_rect` upside down
+        let y = if self.0 != origin_bottom_left {
+            params.scissor.bottom()
+        } else {
+            y1
+        };
+
+        [x1 as i32, y as i32, (x2 - x1) as i32, (y2 - y1) as i32]
+    }
+}
+
+/// Compares scissor rectangles with the ones Dear ImGUI's reference OpenGL backend
+/// (`imgui_impl_opengl3.cpp`) computes for the same draw data
+///
+/// Draw lists have windows, child windows and custom clip rectangles at fractional positions and
+/// are rendered at several framebuffer scales.
+#[cfg(test)]
+mod tests {
+    use imgui::{ChildWindow, Condition, DrawCmd, Ui, Window};
+
+    use crate::{
+        helper::{DrawParams, DrawParamsIterator, FlipY},
+        Platform,
+    };
+
+    use super::super::test_util::{self, Rng};
+
+    const W: f32 = 1280.0;
+    const H: f32 = 720.0;
+
+    const N_SEEDS: u64 = 100;
+
+    const SCALES: &[[f32; 2]] = &[[1.0, 1.0], [1.25, 1.25], [1.5, 1.5], [2.0, 2.0], [2.0, 1.0]];
+
+    /// Coordinate in `[-max/2, 3max/2)`
+    fn coord(rng: &mut Rng, max: f32) -> f32 {
+        rng.coord(-max / 2.0, 1.5 * max)
+    }
+
+    fn point(rng: &mut Rng) -> [f32; 2] {
+        [self::coord(rng, W), self::coord(rng, H)]
+    }
+
+    /// `glScissor` arguments of `imgui_impl_opengl3.cpp`, or `None` if the command is skipped
+    fn reference(clip_rect: [f32; 4], fb_scale: [f32; 2], fb_height: i32) -> Option<[i32; 4]> {
+        // `display_pos` is zero without multi-viewports
+        let clip_min = [clip_rect[0] * fb_scale[0], clip_rect[1] * fb_scale[1]];
+        let clip_max = [clip_rect[2] * fb_scale[0], clip_rect[3] * fb_scale[1]];
+        if clip_max[0] <= clip_min[0] || clip_max[1] <= clip_min[1] {
+            return None;
+        }
+
+        Some([
+            clip_min[0] as i32,
+            (fb_height as f32 - clip_max[1]) as i32,
+            (clip_max[0] - clip_min[0]) as i32,
+            (clip_max[1] - clip_min[1]) as i32,
+        ])
+    }
+
+    /// If the clip rectangle intersects with the framebuffer
+    fn is_visible(c: [f32; 4], scale: [f32; 2], fb: [i32; 2]) -> bool {
+        let (x1, y1) = (c[0] * scale[0], c[1] * scale[1]);
+        let (x2, y2) = (c[2] * scale[0], c[3] * scale[1]);
+        x1 < fb[0] as f32 && y1 < fb[1] as f32 && x2 > 0.0 && y2 > 0.0 && x1 < x2 && y1 < y2
+    }
+
+    /// `[x1, y1, x2, y2]` intersection of `[x, y, w, h]` and the framebuffer
+    fn clamp(rect: [i32; 4], fb: [i32; 2]) -> [i32; 4] {
+        let [x, y, w, h] = rect;
+        [x.max(0), y.max(0), (x + w).min(fb[0]), (y + h).min(fb[1])]
+    }
+
+    #[test]
+    fn scissor_matches_reference() {
+        let _lock = crate::lock_imgui_context();
+        let (mut imgui, mut platform) = test_util::context([W, H]);
+
+        for seed in 0..N_SEEDS {
+            let mut rng = Rng::new(seed);
+            let scale = SCALES[seed as usize % SCALES.len()];
+
+            imgui.io_mut().delta_time = 1.0 / 60.0;
+            platform.prepare_frame(imgui.io_mut(), &());
+            imgui.io_mut().display_framebuffer_scale = scale;
+
+            let ui = imgui.frame();
+            self::build(&ui, &mut rng);
+            let draw_data = ui.render();
+
+            let fb = [(W * scale[0]) as i32, (H * scale[1]) as i32];
+            let mut visible = draw_data
+                .draw_lists()
+                .flat_map(|list| list.commands())
+                .filter_map(|cmd| match cmd {
+                    DrawCmd::Elements { count, cmd_params } if count > 0 => {
+                        Some(cmd_params.clip_rect)
+                    }
+                    _ => None,
+                })
+                .filter(|c| self::is_visible(*c, scale, fb));
+
+            for params in DrawParamsIterator::new(draw_data).draw_calls() {
+                let clip_rect = visible
+                    .next()
+                    .unwrap_or_else(|| panic!("seed {}: extra draw call", seed));
+                let case = format!("seed {}, scale {:?}", seed, scale);
+                self::check(&params, clip_rect, scale, fb, &case);
+            }
+
+            assert!(
+                visible.next().is_none(),
+                "seed {}: visible command dropped",
+                seed
+            );
+        }
+    }
+
+    fn build(ui: &Ui, rng: &mut Rng) {
+        let n_windows = rng.below(6) + 1;
+        for i in 0..n_windows {
+            Window::new(format!("window {}", i))
+                .position(self::point(rng), Condition::Always)
+                .size(
+                    [
+                        rng.below(3200) as f32 / 4.0 + 32.0,
+                        rng.below(2400) as f32 / 4.0 + 32.0,
+                    ],
+                    Condition::Always,
+                )
+                .build(ui, || {
+                    ui.text("scissor");
+                    ChildWindow::new("child")
+                        .size([self::coord(rng, W) / 2.0, self::coord(rng, H) / 2.0])
+                        .border(true)
+                        .build(ui, || {
+                            for i in 0..rng.below(32) {
+                                ui.text(format!("line {}", i));
+                            }
+                        });
+
+                    let draw_list = ui.get_window_draw_list();
+                    draw_list.with_clip_rect(self::point(rng), self::point(rng), || {
+                        draw_list
+                            .add_rect(self::point(rng), self::point(rng), [1.0, 1.0, 1.0, 1.0])
+                            .filled(true)
+                            .build();
+                    });
+                });
+        }
+    }
+
+    fn check(params: &DrawParams, clip_rect: [f32; 4], scale: [f32; 2], fb: [i32; 2], case: &str) {
+        let rect = FlipY(false).scissor(params, true);
+        let [x, y, w, h] = rect;
+        let [x1, y1, x2, y2] = [x, y, x + w, y + h];
+
+        assert!(
+            w > 0 && h > 0 && x1 >= 0 && y1 >= 0 && x2 <= fb[0] && y2 <= fb[1],
+            "{}: scissor {:?} empty or out of framebuffer {:?}",
+            case,
+            rect,
+            fb
+        );
+
+        // the reference rectangle is truncated, so it can be smaller than the clip rectangle (or
+        // even empty)
+        let reference = self::reference(clip_rect, scale, fb[1]).unwrap();
+        let [r1, s1, r2, s2] = self::clamp(reference, fb);
+        assert!(
+            r1 >= r2 || s1 >= s2 || (x1 <= r1 && y1 <= s1 && x2 >= r2 && y2 >= s2),
+            "{}: scissor {:?} doesn't cover reference {:?}",
+            case,
+            rect,
+            reference
+        );
+
+        // exact clip rectangle with bottom-left origin, clamped to the framebuffer
+        let exact = [
+            (clip_rect[0] * scale[0]).max(0.0),
+            (fb[1] as f32 - clip_rect[3] * scale[1]).max(0.0),
+            (clip_rect[2] * scale[0]).min(fb[0] as f32),
+            (fb[1] as f32 - clip_rect[1] * scale[1]).min(fb[1] as f32),
+        ];
+        let ours = [x1 as f32, y1 as f32, x2 as f32, y2 as f32];
+        let covers = ours[0] <= exact[0]
+            && ours[1] <= exact[1]
+            && ours[2] >= exact[2]
+            && ours[3] >= exact[3];
+        let tight = (0..4).all(|i| (ours[i] - exact[i]).abs() < 1.0);
+        assert!(
+            covers && tight,
+            "{}: scissor {:?} is not the pixel bounds of clip rectangle {:?}",
+            case,
+            rect,
+            exact
+        );
+
+        if exact.iter().all(|v| v.fract() == 0.0) {
+            assert_eq!(
+                [x1, y1, x2, y2],
+                [r1, s1, r2, s2],
+                "{}: scissor {:?} differs from reference {:?}",
+                case,
+                rect,
+                reference
+            );
+        }
+
+        // flipping mirrors the rectangle
+        let [fx, fy, fw, fh] = FlipY(true).scissor(params, true);
+        assert!(
+            [fx, fw, fh] == [x, w, h] && fy == fb[1] - y2,
+            "{}: flipped scissor {:?} doesn't mirror {:?}",
+            case,
+            [fx, fy, fw, fh],
+            rect
+        );
+        assert_eq!(
+            FlipY(true).scissor(params, false),
+            rect,
+            "{}: flipped top-left scissor differs from bottom-left one",
+            case
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Fixtures shared by the draw data tests
+
+use crate::{
+    helper::{Locale, QuickStart},
+    platform::NullPlatform,
+};
+
+/// xorshift64
+pub struct Rng(u64);
+
+impl Rng {
+    /// Sequences of different seeds are unrelated
+    pub fn new(seed: u64) -> Self {
+        Self(0x9E37_79B9_7F4A_7C15 ^ (seed + 1))
+    }
+
+    pub fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next() % n.max(1)
+    }
+
+    /// Coordinate in `[min, max)` in quarter pixels
+    pub fn coord(&mut self, min: f32, max: f32) -> f32 {
+        min + self.below(((max - min) * 4.0) as u64) as f32 / 4.0
+    }
+}
+
+/// Context with a built font atlas and no `imgui.ini`, and a platform of the display size. Hold
+/// [`crate::lock_imgui_context`] while using them
+pub fn context(display_size: [f32; 2]) -> (imgui::Context, NullPlatform) {
+    let mut imgui = QuickStart {
+        display_size,
+        fontsize: 13.0,
+        hidpi_factor: 1.0,
+        locale: Locale::English,
+        input_timing: None,
+        fonts: None,
+        keyboard_nav: true,
+    }
+    .create_context();
+    imgui.set_ini_filename(None);
+    // `NewFrame` asserts that the font atlas is built
+    imgui.fonts().build_rgba32_texture();
+
+    let platform = NullPlatform::new(&mut imgui, display_size);
+    (imgui, platform)
+}
@@ -0,0 +1,229 @@
+//! Modal file open/save dialog written in pure ImGUI
+
+use {
+    imgui::{ChildWindow, PopupModal, Selectable, Ui},
+    std::{
+        fs,
+        path::{Path, PathBuf},
+    },
+};
+
+use super::localize::{tr, UiText};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileDialogMode {
+    Open,
+    Save,
+}
+
+#[derive(Debug, Clone)]
+struct DirEntry {
+    name: String,
+    is_dir: bool,
+}
+
+/// Modal file browser. Call [`FileDialog::open`] once and [`FileDialog::show`] every frame:
+///
+/// ```no_run
+/// if ui.button("Open..") {
+///     dialog.open();
+/// }
+/// if let Some(path) = dialog.show(&ui) {
+///     // load the file
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileDialog {
+    title: String,
+    mode: FileDialogMode,
+    dir: PathBuf,
+    /// Extensions without dot. Empty means any file
+    filters: Vec<String>,
+    favorites: Vec<PathBuf>,
+    file_name: String,
+    entries: Vec<DirEntry>,
+    error: Option<String>,
+    open_requested: bool,
+}
+
+impl FileDialog {
+    pub fn new(title: impl Into<String>, mode: FileDialogMode) -> Self {
+        Self {
+            title: title.into(),
+            mode,
+            dir: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            filters: Vec::new(),
+            favorites: Vec::new(),
+            file_name: String::new(),
+            entries: Vec::new(),
+            error: None,
+            open_requested: false,
+        }
+    }
+
+    /// Sets extension filters, e.g. `&["png", "jpg"]`
+    pub fn filters(mut self, exts: &[&str]) -> Self {
+        self.filters = exts.iter().map(|s| s.to_lowercase()).collect();
+        self
+    }
+
+    pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    pub fn add_favorite(&mut self, path: impl Into<PathBuf>) {
+        self.favorites.push(path.into());
+    }
+
+    /// Opens the dialog on next [`Self::show`]
+    pub fn open(&mut self) {
+        self.open_requested = true;
+        self.refresh();
+    }
+
+    fn matches_filter(&self, name: &str) -> bool {
+        if self.filters.is_empty() {
+            return true;
+        }
+
+        match Path::new(name).extension() {
+            Some(ext) => self
+                .filters
+                .iter()
+                .any(|f| *f == ext.to_string_lossy().to_lowercase()),
+            None => false,
+        }
+    }
+
+    fn refresh(&mut self) {
+        self.entries.clear();
+        self.error = None;
+
+        let read_dir = match fs::read_dir(&self.dir) {
+            Ok(read_dir) => read_dir,
+            Err(err) => {
+                self.error = Some(err.to_string());
+                return;
+            }
+        };
+
+        for entry in read_dir.flatten() {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if is_dir || self.matches_filter(&name) {
+                self.entries.push(DirEntry { name, is_dir });
+            }
+        }
+
+        // directories first
+        self.entries
+            .sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.cmp(&b.name)));
+    }
+
+    fn change_dir(&mut self, dir: PathBuf) {
+        self.dir = dir;
+        self.refresh();
+    }
+
+    /// Shows the modal if it's open. Returns the chosen path when the user confirms
+    pub fn show(&mut self, ui: &Ui) -> Option<PathBuf> {
+        if self.open_requested {
+            ui.open_popup(&self.title);
+            self.open_requested = false;
+        }
+
+        let title = self.title.clone();
+        PopupModal::new(&title)
+            .always_auto_resize(true)
+            .build(ui, || self.show_contents(ui))
+            .flatten()
+    }
+
+    fn show_contents(&mut self, ui: &Ui) -> Option<PathBuf> {
+        let mut next_dir = None;
+        let mut chosen = None;
+
+        if ui.button("..") {
+            if let Some(parent) = self.dir.parent() {
+                next_dir = Some(parent.to_path_buf());
+            }
+        }
+        ui.same_line();
+        ui.text(self.dir.to_string_lossy());
+
+        for fav in &self.favorites {
+            ui.same_line();
+            let label = fav
+                .file_name()
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_else(|| fav.to_string_lossy().into_owned());
+            if ui.small_button(&label) {
+                next_dir = Some(fav.clone());
+            }
+        }
+
+        ui.separator();
+
+        let unit = ui.current_font_size();
+        ChildWindow::new("##files")
+            .size([40.0 * unit, 20.0 * unit])
+            .build(ui, || {
+                if let Some(ref err) = self.error {
+                    ui.text_colored([1.0, 0.3, 0.3, 1.0], err);
+                }
+
+                for entry in &self.entries {
+                    let label = if entry.is_dir {
+                        format!("{}/", entry.name)
+                    } else {
+                        entry.name.clone()
+                    };
+
+                    let selected = !entry.is_dir && entry.name == self.file_name;
+                    if Selectable::new(&label)
+                        .selected(selected)
+                        .allow_double_click(true)
+                        .build(ui)
+                    {
+                        if entry.is_dir {
+                            next_dir = Some(self.dir.join(&entry.name));
+                        } else {
+                            self.file_name = entry.name.clone();
+                            if ui.is_mouse_double_clicked(imgui::MouseButton::Left) {
+                                chosen = Some(self.dir.join(&entry.name));
+                            }
+                        }
+                    }
+                }
+            });
+
+        ui.input_text(
+            format!("{}##file-name", tr(UiText::FileName)),
+            &mut self.file_name,
+        )
+        .build();
+
+        let confirm = match self.mode {
+            FileDialogMode::Open => tr(UiText::Open),
+            FileDialogMode::Save => tr(UiText::Save),
+        };
+        if ui.button(confirm) && !self.file_name.is_empty() {
+            chosen = Some(self.dir.join(&self.file_name));
+        }
+        ui.same_line();
+        if ui.button(tr(UiText::Cancel)) {
+            ui.close_current_popup();
+        }
+
+        if let Some(dir) = next_dir {
+            self.change_dir(dir);
+        }
+
+        if chosen.is_some() {
+            ui.close_current_popup();
+        }
+
+        chosen
+    }
+}
@@ -0,0 +1,239 @@
+//! Font fallback chains merged into one ImGUI font
+//!
+//! ```no_run
+//! use imgui_backends::helper::{FontLayer, FontSetup, GlyphRanges, JP_FONT};
+//!
+//! let fonts = FontSetup::new()
+//!     .push(FontLayer::ttf(UI_FONT, GlyphRanges::Default))
+//!     .push(FontLayer::ttf(JP_FONT, GlyphRanges::Japanese))
+//!     .push(FontLayer::ttf(EMOJI_FONT, GlyphRanges::Custom(&[0x1F300, 0x1FAFF, 0])));
+//! fonts.validate()?;
+//! ```
+
+use imgui::{FontConfig, FontGlyphRanges, FontSource};
+use thiserror::Error;
+
+use crate::helper::{Locale, JP_FONT};
+
+/// Glyph ranges of a [`FontLayer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlyphRanges {
+    /// Basic Latin and Latin-1 Supplement
+    Default,
+    Japanese,
+    Korean,
+    ChineseSimplified,
+    /// Inclusive pairs of code points terminated with `0`, as in Dear ImGUI
+    Custom(&'static [u32]),
+}
+
+impl GlyphRanges {
+    fn to_imgui(self) -> FontGlyphRanges {
+        match self {
+            Self::Default => FontGlyphRanges::default(),
+            Self::Japanese => FontGlyphRanges::japanese(),
+            Self::Korean => FontGlyphRanges::korean(),
+            Self::ChineseSimplified => FontGlyphRanges::chinese_simplified_common(),
+            Self::Custom(ranges) => FontGlyphRanges::from_slice(ranges),
+        }
+    }
+
+    /// Ranges surely contained. The CJK presets are sparse and only their dense blocks are listed
+    fn known_ranges(self) -> Vec<[u32; 2]> {
+        let latin = [0x0020, 0x00FF];
+        match self {
+            Self::Default => vec![latin],
+            Self::Japanese => vec![latin, [0x3000, 0x30FF], [0x31F0, 0x31FF], [0xFF00, 0xFFEF]],
+            Self::Korean => vec![latin, [0x3131, 0x3163], [0xAC00, 0xD7A3]],
+            Self::ChineseSimplified => vec![
+                latin,
+                [0x2000, 0x206F],
+                [0x3000, 0x30FF],
+                [0x31F0, 0x31FF],
+                [0xFF00, 0xFFEF],
+            ],
+            Self::Custom(ranges) => ranges
+                .chunks_exact(2)
+                .take_while(|pair| pair[0] != 0)
+                .map(|pair| [pair[0], pair[1]])
+                .collect(),
+        }
+    }
+}
+
+/// Font data of a [`FontLayer`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FontData {
+    /// ProggyClean bundled with Dear ImGUI
+    Default,
+    Ttf(&'static [u8]),
+}
+
+/// One font of a [`FontSetup`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontLayer {
+    pub data: FontData,
+    pub glyph_ranges: GlyphRanges,
+    pub rasterizer_multiply: f32,
+    /// Multiplied to the font size, e.g. to align glyphs of fonts with different metrics
+    pub size_scale: f32,
+}
+
+impl FontLayer {
+    pub fn default_font() -> Self {
+        Self {
+            data: FontData::Default,
+            glyph_ranges: GlyphRanges::Default,
+            rasterizer_multiply: 1.0,
+            size_scale: 1.0,
+        }
+    }
+
+    pub fn ttf(data: &'static [u8], glyph_ranges: GlyphRanges) -> Self {
+        Self {
+            data: FontData::Ttf(data),
+            glyph_ranges,
+            rasterizer_multiply: 1.0,
+            size_scale: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FontSetupError {
+    #[error("no font layer")]
+    Empty,
+    #[error("font layer {index}: glyph ranges must be pairs terminated with 0")]
+    Unterminated { index: usize },
+    #[error("font layer {index}: invalid glyph range {first:#X}..={last:#X}")]
+    InvalidRange { index: usize, first: u32, last: u32 },
+    #[error("font layer {index} adds no glyph; its ranges are covered by earlier layers")]
+    Shadowed { index: usize },
+}
+
+/// Ordered fonts merged into one ImGUI font. Earlier layers take priority: a glyph is taken from
+/// the first layer that has it
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FontSetup {
+    pub layers: Vec<FontLayer>,
+}
+
+impl FontSetup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(mut self, layer: FontLayer) -> Self {
+        self.layers.push(layer);
+        self
+    }
+
+    /// The fonts of [`crate::helper::QuickStart`]: the default font followed by the bundled M+ font
+    /// for non-English locales
+    pub fn for_locale(locale: Locale) -> Self {
+        let setup = Self::new().push(FontLayer::default_font());
+        let ranges = match locale {
+            Locale::English => return setup,
+            Locale::Japanese => GlyphRanges::Japanese,
+            Locale::Korean => GlyphRanges::Korean,
+            Locale::ChineseSimplified => GlyphRanges::ChineseSimplified,
+        };
+        setup.push(FontLayer {
+            rasterizer_multiply: 1.75,
+            ..FontLayer::ttf(JP_FONT, ranges)
+        })
+    }
+
+    /// Checks the glyph ranges and that every layer can add glyphs not covered by earlier layers
+    pub fn validate(&self) -> Result<(), FontSetupError> {
+        if self.layers.is_empty() {
+            return Err(FontSetupError::Empty);
+        }
+
+        let mut covered: Vec<[u32; 2]> = Vec::new();
+        for (index, layer) in self.layers.iter().enumerate() {
+            if let GlyphRanges::Custom(ranges) = layer.glyph_ranges {
+                if ranges.len() % 2 != 1 || ranges.last() != Some(&0) {
+                    return Err(FontSetupError::Unterminated { index });
+                }
+            }
+
+            let ranges = layer.glyph_ranges.known_ranges();
+            for &[first, last] in &ranges {
+                if first == 0 || first > last || last > 0x10FFFF {
+                    return Err(FontSetupError::InvalidRange { index, first, last });
+                }
+            }
+
+            let shadowed = match layer.glyph_ranges {
+                // the CJK presets are sparse; only identical presets are known to shadow
+                GlyphRanges::Custom(_) => ranges.iter().all(|r| self::is_covered(*r, &covered)),
+                preset => self.layers[..index]
+                    .iter()
+                    .any(|l| l.glyph_ranges == preset),
+            };
+            if shadowed {
+                return Err(FontSetupError::Shadowed { index });
+            }
+
+            covered.extend(ranges);
+        }
+
+        Ok(())
+    }
+
+    /// Adds the layers as one font of `size_pixels`
+    pub fn add_to(
+        &self,
+        fonts: &mut imgui::FontAtlas,
+        size_pixels: f32,
+    ) -> Result<imgui::FontId, FontSetupError> {
+        self.validate()?;
+
+        let sources = self
+            .layers
+            .iter()
+            .map(|layer| {
+                let config = FontConfig {
+                    size_pixels: size_pixels * layer.size_scale,
+                    rasterizer_multiply: layer.rasterizer_multiply,
+                    glyph_ranges: layer.glyph_ranges.to_imgui(),
+                    ..FontConfig::default()
+                };
+                match layer.data {
+                    FontData::Default => FontSource::DefaultFontData {
+                        config: Some(config),
+                    },
+                    FontData::Ttf(data) => FontSource::TtfData {
+                        data,
+                        size_pixels: size_pixels * layer.size_scale,
+                        config: Some(config),
+                    },
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(fonts.add_font(&sources))
+    }
+}
+
+/// If the range is contained in the union of the ranges
+fn is_covered(range: [u32; 2], covered: &[[u32; 2]]) -> bool {
+    let mut sorted = covered.to_vec();
+    sorted.sort();
+
+    // sweep from the start of the range
+    let mut next = range[0];
+    for [first, last] in sorted {
+        if first > next {
+            break;
+        }
+        if last >= next {
+            next = last + 1;
+            if next > range[1] {
+                return true;
+            }
+        }
+    }
+    false
+}
@@ -0,0 +1,39 @@
+//! Image loading into renderer textures
+
+use thiserror::Error;
+
+use crate::RegisterTexture;
+
+#[derive(Debug, Error)]
+pub enum LoadImageError<E: std::fmt::Debug> {
+    #[error("failed to decode image: {0}")]
+    Decode(#[from] image::ImageError),
+    #[error("failed to register texture: {0:?}")]
+    Renderer(E),
+}
+
+/// Decodes a PNG/JPEG image and registers it as a texture
+///
+/// Pixels are uploaded as-is (sRGB-encoded) because ImGUI colors are sRGB, too. Grayscale and
+/// 16-bit images are converted to RGBA8.
+pub fn load_image<R>(
+    renderer: &mut R,
+    device: &mut R::Device,
+    bytes: &[u8],
+) -> Result<imgui::TextureId, LoadImageError<R::Error>>
+where
+    R: RegisterTexture,
+    R::Error: std::fmt::Debug,
+{
+    let (pixels, w, h) = self::decode_rgba8(bytes)?;
+    renderer
+        .register_rgba8(device, &pixels, w, h, true)
+        .map_err(LoadImageError::Renderer)
+}
+
+/// Decodes a PNG/JPEG image into RGBA8 pixels and the size
+pub fn decode_rgba8(bytes: &[u8]) -> Result<(Vec<u8>, u32, u32), image::ImageError> {
+    let img = image::load_from_memory(bytes)?.into_rgba8();
+    let (w, h) = img.dimensions();
+    Ok((img.into_raw(), w, h))
+}
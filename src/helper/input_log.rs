@@ -0,0 +1,164 @@
+//! Recent input events with capture decisions, for debugging "why didn't my click reach the game"
+//!
+//! ```no_run
+//! backend.input_log.set_enabled(true);
+//! // events passed to `backend.handle_event` are recorded and shown in the "Input log" window
+//! ```
+
+use {
+    imgui::{ChildWindow, Ui, Window},
+    std::{collections::VecDeque, time::Duration},
+};
+
+use crate::Captured;
+
+/// ImGUI IO state right after an event was handled
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct IoSnapshot {
+    pub mouse_pos: [f32; 2],
+    pub mouse_down: [bool; 5],
+    pub want_capture_mouse: bool,
+    pub want_capture_keyboard: bool,
+    pub want_text_input: bool,
+}
+
+impl IoSnapshot {
+    pub fn new(io: &imgui::Io) -> Self {
+        Self {
+            mouse_pos: io.mouse_pos,
+            mouse_down: io.mouse_down,
+            want_capture_mouse: io.want_capture_mouse,
+            want_capture_keyboard: io.want_capture_keyboard,
+            want_text_input: io.want_text_input,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InputLogEntry {
+    /// Time of [`crate::Backend::timers`]
+    pub time: Duration,
+    /// Description of the event by [`crate::Platform::describe_event`]
+    pub event: String,
+    pub captured: Captured,
+    pub io: IoSnapshot,
+}
+
+/// Events of the last [`InputLog::span`]. Owned by [`crate::Backend`], recorded on
+/// `handle_event` and shown on `end_frame` while enabled
+#[derive(Debug, Clone)]
+pub struct InputLog {
+    enabled: bool,
+    /// How long entries are kept
+    pub span: Duration,
+    entries: VecDeque<InputLogEntry>,
+    /// Hide events that were not captured
+    captured_only: bool,
+}
+
+impl Default for InputLog {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            span: Duration::from_secs(5),
+            entries: VecDeque::new(),
+            captured_only: false,
+        }
+    }
+}
+
+impl InputLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Starts recording and shows the window. Disabling it clears the log
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.entries.clear();
+        }
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = &InputLogEntry> {
+        self.entries.iter()
+    }
+
+    pub fn record(&mut self, entry: InputLogEntry) {
+        if !self.enabled {
+            return;
+        }
+        self.entries.push_back(entry);
+        self.expire(self.entries.back().unwrap().time);
+    }
+
+    /// Drops entries older than [`Self::span`]
+    fn expire(&mut self, now: Duration) {
+        while let Some(e) = self.entries.front() {
+            if now.saturating_sub(e.time) <= self.span {
+                break;
+            }
+            self.entries.pop_front();
+        }
+    }
+
+    /// Shows the log window if enabled. Closing the window disables the log
+    pub fn render(&mut self, ui: &Ui, now: Duration) {
+        if !self.enabled {
+            return;
+        }
+        self.expire(now);
+
+        let mut opened = true;
+        let entries = &self.entries;
+        let captured_only = &mut self.captured_only;
+        let unit = ui.current_font_size();
+
+        Window::new("Input log")
+            .opened(&mut opened)
+            .size([36.0 * unit, 24.0 * unit], imgui::Condition::FirstUseEver)
+            .build(ui, || {
+                let io = IoSnapshot::new(ui.io());
+                ui.text(format!(
+                    "now: mouse {:?} want_capture_mouse {} want_capture_keyboard {} want_text_input {}",
+                    io.mouse_pos, io.want_capture_mouse, io.want_capture_keyboard, io.want_text_input,
+                ));
+                ui.checkbox("captured only", captured_only);
+                ui.separator();
+
+                ChildWindow::new("##input-log").build(ui, || {
+                    for e in entries.iter().rev() {
+                        if *captured_only && !e.captured.is_captured() {
+                            continue;
+                        }
+
+                        let color = if e.captured.is_captured() {
+                            [1.0, 0.6, 0.3, 1.0]
+                        } else {
+                            [0.6, 1.0, 0.6, 1.0]
+                        };
+                        ui.text_colored(
+                            color,
+                            format!(
+                                "{:>8.3}s {:?}: {}",
+                                e.time.as_secs_f32(),
+                                e.captured,
+                                e.event
+                            ),
+                        );
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text(format!("{:#?}", e.io));
+                        }
+                    }
+                });
+            });
+
+        if !opened {
+            self.set_enabled(false);
+        }
+    }
+}
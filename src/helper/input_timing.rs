@@ -0,0 +1,104 @@
+//! Mouse and keyboard timing/threshold settings of [`imgui::Io`]
+
+/// Double-click, drag and key repeat settings
+///
+/// Defaults are tuned for 96 DPI mice; distances feel too short on high-DPI touchpads. Platforms
+/// scale them with [`Self::for_dpi_scale`] unless they're overridden in [`crate::helper::QuickStart`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InputTiming {
+    /// Seconds
+    pub mouse_double_click_time: f32,
+    /// Logical pixels
+    pub mouse_double_click_max_dist: f32,
+    /// Logical pixels
+    pub mouse_drag_threshold: f32,
+    /// Seconds
+    pub key_repeat_delay: f32,
+    /// Seconds
+    pub key_repeat_rate: f32,
+}
+
+impl Default for InputTiming {
+    /// Dear ImGUI defaults
+    fn default() -> Self {
+        Self {
+            mouse_double_click_time: 0.30,
+            mouse_double_click_max_dist: 6.0,
+            mouse_drag_threshold: 6.0,
+            key_repeat_delay: 0.275,
+            key_repeat_rate: 0.050,
+        }
+    }
+}
+
+impl InputTiming {
+    /// Defaults with distances scaled by `dpi_scale` (DPI / 96)
+    pub fn for_dpi_scale(dpi_scale: f32) -> Self {
+        let scale = if dpi_scale.is_finite() && dpi_scale > 0.0 {
+            dpi_scale
+        } else {
+            1.0
+        };
+
+        let def = Self::default();
+        Self {
+            mouse_double_click_max_dist: def.mouse_double_click_max_dist * scale,
+            mouse_drag_threshold: def.mouse_drag_threshold * scale,
+            ..def
+        }
+    }
+
+    pub fn from_io(io: &imgui::Io) -> Self {
+        Self {
+            mouse_double_click_time: io.mouse_double_click_time,
+            mouse_double_click_max_dist: io.mouse_double_click_max_dist,
+            mouse_drag_threshold: io.mouse_drag_threshold,
+            key_repeat_delay: io.key_repeat_delay,
+            key_repeat_rate: io.key_repeat_rate,
+        }
+    }
+
+    pub fn apply(&self, io: &mut imgui::Io) {
+        io.mouse_double_click_time = self.mouse_double_click_time;
+        io.mouse_double_click_max_dist = self.mouse_double_click_max_dist;
+        io.mouse_drag_threshold = self.mouse_drag_threshold;
+        io.key_repeat_delay = self.key_repeat_delay;
+        io.key_repeat_rate = self.key_repeat_rate;
+    }
+
+    /// Applies only to the fields still at Dear ImGUI defaults so that user overrides are kept
+    pub fn apply_to_defaults(&self, io: &mut imgui::Io) {
+        let def = Self::default();
+        let set = |field: &mut f32, default: f32, value: f32| {
+            if *field == default {
+                *field = value;
+            }
+        };
+
+        set(
+            &mut io.mouse_double_click_time,
+            def.mouse_double_click_time,
+            self.mouse_double_click_time,
+        );
+        set(
+            &mut io.mouse_double_click_max_dist,
+            def.mouse_double_click_max_dist,
+            self.mouse_double_click_max_dist,
+        );
+        set(
+            &mut io.mouse_drag_threshold,
+            def.mouse_drag_threshold,
+            self.mouse_drag_threshold,
+        );
+        set(
+            &mut io.key_repeat_delay,
+            def.key_repeat_delay,
+            self.key_repeat_delay,
+        );
+        set(
+            &mut io.key_repeat_rate,
+            def.key_repeat_rate,
+            self.key_repeat_rate,
+        );
+    }
+}
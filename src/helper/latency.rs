@@ -0,0 +1,161 @@
+//! Input latency measurement
+//!
+//! Each event is timestamped at `handle_event`. Two latencies are measured:
+//!
+//! * event → `begin_frame` (the event is visible in `io`)
+//! * event → render submission (`end_frame`)
+//!
+//! ```no_run
+//! backend.latency.set_enabled(true);
+//! // .. after some frames
+//! println!("{}", backend.latency.report());
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// Number of samples kept per stage
+const N_SAMPLES: usize = 1024;
+
+/// Percentiles of latency samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Percentiles {
+    pub count: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+impl Percentiles {
+    pub(crate) fn new(samples: &VecDeque<Duration>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+
+        let mut sorted = samples.iter().cloned().collect::<Vec<_>>();
+        sorted.sort();
+        let at = |p: f32| sorted[((sorted.len() - 1) as f32 * p).round() as usize];
+
+        Self {
+            count: sorted.len(),
+            p50: at(0.5),
+            p90: at(0.9),
+            p99: at(0.99),
+            max: sorted[sorted.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for Percentiles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} p50={:?} p90={:?} p99={:?} max={:?}",
+            self.count, self.p50, self.p90, self.p99, self.max
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyReport {
+    /// Event → `begin_frame`
+    pub to_frame: Percentiles,
+    /// Event → render submission
+    pub to_submit: Percentiles,
+}
+
+impl fmt::Display for LatencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "event -> frame:  {}", self.to_frame)?;
+        write!(f, "event -> submit: {}", self.to_submit)
+    }
+}
+
+/// Input latency probe. Owned by [`crate::Backend`] and fed on `handle_event`, `begin_frame` and
+/// `end_frame` while enabled
+#[derive(Debug, Clone, Default)]
+pub struct LatencyProbe {
+    enabled: bool,
+    /// Events not consumed by a frame yet
+    pending: Vec<Instant>,
+    /// Events consumed by the current frame
+    in_frame: Vec<Instant>,
+    to_frame: VecDeque<Duration>,
+    to_submit: VecDeque<Duration>,
+}
+
+fn push_sample(samples: &mut VecDeque<Duration>, sample: Duration) {
+    if samples.len() == N_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+impl LatencyProbe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Disabling it clears the samples
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.in_frame.clear();
+        self.to_frame.clear();
+        self.to_submit.clear();
+    }
+
+    /// An input event arrived
+    pub fn on_event(&mut self) {
+        if self.enabled {
+            self.pending.push(Instant::now());
+        }
+    }
+
+    /// The pending events are now visible in `io`
+    pub fn on_begin_frame(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+        for t in self.pending.drain(..) {
+            self::push_sample(&mut self.to_frame, now - t);
+            self.in_frame.push(t);
+        }
+    }
+
+    /// The frame that consumed the events was submitted. Call it yourself if you render the draw
+    /// data of [`crate::BackendUi::finish`]
+    pub fn on_submit(&mut self) {
+        if !self.enabled {
+            return;
+        }
+
+        let now = Instant::now();
+        for t in self.in_frame.drain(..) {
+            self::push_sample(&mut self.to_submit, now - t);
+        }
+    }
+
+    pub fn report(&self) -> LatencyReport {
+        LatencyReport {
+            to_frame: Percentiles::new(&self.to_frame),
+            to_submit: Percentiles::new(&self.to_submit),
+        }
+    }
+}
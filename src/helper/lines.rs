@@ -0,0 +1,63 @@
+//! Line and curve rendering options
+
+/// Line and curve tessellation options of [`imgui::Style`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LineFidelity {
+    /// Anti-aliased lines and borders
+    pub anti_aliased_lines: bool,
+    /// Anti-aliased lines sampled from the baked lines in the font atlas. They're cheaper but
+    /// limited to thin lines and require linear [`crate::FontSampling`]
+    pub anti_aliased_lines_use_tex: bool,
+    /// Anti-aliased filled shapes
+    pub anti_aliased_fill: bool,
+    /// Tessellation tolerance of bezier curves. Smaller is smoother and makes more vertices
+    pub curve_tessellation_tol: f32,
+}
+
+impl Default for LineFidelity {
+    /// Dear ImGUI defaults
+    fn default() -> Self {
+        Self {
+            anti_aliased_lines: true,
+            anti_aliased_lines_use_tex: true,
+            anti_aliased_fill: true,
+            curve_tessellation_tol: 1.25,
+        }
+    }
+}
+
+impl LineFidelity {
+    /// Geometry-based anti-aliased lines. Works with any font sampling and thick lines
+    pub fn geometry() -> Self {
+        Self {
+            anti_aliased_lines_use_tex: false,
+            ..Default::default()
+        }
+    }
+
+    /// No anti-aliasing. Useful for pixel-art UIs
+    pub fn aliased() -> Self {
+        Self {
+            anti_aliased_lines: false,
+            anti_aliased_lines_use_tex: false,
+            anti_aliased_fill: false,
+            ..Default::default()
+        }
+    }
+
+    pub fn from_style(style: &imgui::Style) -> Self {
+        Self {
+            anti_aliased_lines: style.anti_aliased_lines,
+            anti_aliased_lines_use_tex: style.anti_aliased_lines_use_tex,
+            anti_aliased_fill: style.anti_aliased_fill,
+            curve_tessellation_tol: style.curve_tessellation_tol,
+        }
+    }
+
+    pub fn apply(&self, style: &mut imgui::Style) {
+        style.anti_aliased_lines = self.anti_aliased_lines;
+        style.anti_aliased_lines_use_tex = self.anti_aliased_lines_use_tex;
+        style.anti_aliased_fill = self.anti_aliased_fill;
+        style.curve_tessellation_tol = self.curve_tessellation_tol;
+    }
+}
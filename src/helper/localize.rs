@@ -0,0 +1,97 @@
+//! Localization of the strings shown by the helper widgets
+//!
+//! ```no_run
+//! use {imgui_backends::helper::{self, Localize, UiText}, std::borrow::Cow};
+//!
+//! struct Japanese;
+//!
+//! impl Localize for Japanese {
+//!     fn localize(&self, text: UiText) -> Cow<'static, str> {
+//!         match text {
+//!             UiText::Open => "開く".into(),
+//!             UiText::Cancel => "キャンセル".into(),
+//!             _ => text.english().into(),
+//!         }
+//!     }
+//! }
+//!
+//! helper::set_localizer(Some(Box::new(Japanese)));
+//! ```
+
+use std::{borrow::Cow, cell::RefCell};
+
+/// Locale of the UI. Selects glyph ranges in [`crate::helper::QuickStart`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    English,
+    Japanese,
+    Korean,
+    ChineseSimplified,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self::English
+    }
+}
+
+impl Locale {
+    pub fn glyph_ranges(&self) -> imgui::FontGlyphRanges {
+        match self {
+            Self::English => imgui::FontGlyphRanges::default(),
+            Self::Japanese => imgui::FontGlyphRanges::japanese(),
+            Self::Korean => imgui::FontGlyphRanges::korean(),
+            Self::ChineseSimplified => imgui::FontGlyphRanges::chinese_simplified_common(),
+        }
+    }
+}
+
+/// Strings shown by the helper widgets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiText {
+    Open,
+    Save,
+    Cancel,
+    FileName,
+    CopyValue,
+    CopyLabel,
+    PasteValue,
+    ResetToDefault,
+}
+
+impl UiText {
+    pub fn english(&self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::Save => "Save",
+            Self::Cancel => "Cancel",
+            Self::FileName => "File name",
+            Self::CopyValue => "Copy value",
+            Self::CopyLabel => "Copy label",
+            Self::PasteValue => "Paste value",
+            Self::ResetToDefault => "Reset to default",
+        }
+    }
+}
+
+/// Implement it to translate the helper widgets
+pub trait Localize {
+    fn localize(&self, text: UiText) -> Cow<'static, str>;
+}
+
+thread_local! {
+    static LOCALIZER: RefCell<Option<Box<dyn Localize>>> = RefCell::new(None);
+}
+
+/// Sets the translator used by the helper widgets on this (UI) thread. `None` is English
+pub fn set_localizer(localizer: Option<Box<dyn Localize>>) {
+    LOCALIZER.with(|l| *l.borrow_mut() = localizer);
+}
+
+/// Translates a built-in string
+pub fn tr(text: UiText) -> Cow<'static, str> {
+    LOCALIZER.with(|l| match l.borrow().as_ref() {
+        Some(localizer) => localizer.localize(text),
+        None => Cow::Borrowed(text.english()),
+    })
+}
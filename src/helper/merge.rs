@@ -0,0 +1,260 @@
+//! Combining draw data of multiple ImGUI contexts
+//!
+//! Multi-context setups (e.g. a game HUD and developer tools) produce one [`imgui::DrawData`] per
+//! context. [`merge_draw_data`] copies them into one [`OwnedDrawData`], so that they can be
+//! submitted in one pass. Texture IDs are local to each context and remapped through a table.
+
+use std::collections::HashMap;
+
+use super::{
+    draw::{clip_command, framebuffer_extent, Rect},
+    DrawParams,
+};
+
+/// Texture ID translation of a context. IDs not in the table are kept as is
+pub type TextureTable = HashMap<imgui::TextureId, imgui::TextureId>;
+
+/// `DrawCmd::Elements` copied out of a draw list
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnedDrawCmd {
+    pub count: usize,
+    /// In display coordinates of the merged draw data
+    pub clip_rect: [f32; 4],
+    pub texture_id: imgui::TextureId,
+    pub vtx_offset: usize,
+    pub idx_offset: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OwnedDrawList {
+    pub vtx_buffer: Vec<imgui::DrawVert>,
+    pub idx_buffer: Vec<imgui::DrawIdx>,
+    pub commands: Vec<OwnedDrawCmd>,
+}
+
+/// Draw data that owns its buffers, built by [`merge_draw_data`]
+#[derive(Debug, Clone, Default)]
+pub struct OwnedDrawData {
+    pub display_pos: [f32; 2],
+    pub display_size: [f32; 2],
+    pub framebuffer_scale: [f32; 2],
+    pub draw_lists: Vec<OwnedDrawList>,
+}
+
+impl OwnedDrawData {
+    pub fn total_vtx_count(&self) -> usize {
+        self.draw_lists.iter().map(|l| l.vtx_buffer.len()).sum()
+    }
+
+    pub fn total_idx_count(&self) -> usize {
+        self.draw_lists.iter().map(|l| l.idx_buffer.len()).sum()
+    }
+
+    /// Draw calls, culled and clipped the same way as [`super::DrawParamsIterator`]
+    pub fn draw_params(&self) -> impl Iterator<Item = DrawParams<'_>> {
+        let [fb_width, fb_height] = framebuffer_extent(self.display_size, self.framebuffer_scale);
+        let visible = fb_width > 0.0 && fb_height > 0.0;
+
+        self.draw_lists
+            .iter()
+            .filter(move |_| visible)
+            .flat_map(move |list| {
+                list.commands.iter().filter_map(move |cmd| {
+                    if cmd.count == 0 {
+                        return None;
+                    }
+                    let (scissor, clip_rect) = clip_command(
+                        cmd.clip_rect,
+                        self.display_pos,
+                        self.framebuffer_scale,
+                        fb_width,
+                        fb_height,
+                    )?;
+
+                    Some(DrawParams {
+                        display: Rect::from_display(self.display_pos, self.display_size),
+                        framebuffer_scale: self.framebuffer_scale,
+                        vtx_buffer: &list.vtx_buffer,
+                        vtx_offset: cmd.vtx_offset,
+                        idx_buffer: &list.idx_buffer,
+                        idx_offset: cmd.idx_offset,
+                        n_elems: cmd.count,
+                        tex_id: cmd.texture_id,
+                        scissor,
+                        clip_rect,
+                    })
+                })
+            })
+    }
+}
+
+/// Concatenates draw lists in the order of `data` (later ones are drawn on top). `tables[i]`
+/// remaps texture IDs of `data[i]`; missing tables mean no remapping
+///
+/// The display rectangle and framebuffer scale are taken from the first draw data; the others are
+/// translated into it. Callback commands can't be copied and are dropped.
+pub fn merge_draw_data(data: &[&imgui::DrawData], tables: &[TextureTable]) -> OwnedDrawData {
+    let first = match data.first() {
+        Some(first) => first,
+        None => return OwnedDrawData::default(),
+    };
+
+    let mut merged = OwnedDrawData {
+        display_pos: first.display_pos,
+        display_size: first.display_size,
+        framebuffer_scale: first.framebuffer_scale,
+        draw_lists: Vec::new(),
+    };
+
+    for (i, data) in data.iter().enumerate() {
+        if data.framebuffer_scale != merged.framebuffer_scale {
+            log::warn!(
+                "imgui-backends: merging draw data with framebuffer scale {:?} into {:?}",
+                data.framebuffer_scale,
+                merged.framebuffer_scale
+            );
+        }
+
+        let table = tables.get(i);
+        let offset = [
+            data.display_pos[0] - merged.display_pos[0],
+            data.display_pos[1] - merged.display_pos[1],
+        ];
+
+        for list in data.draw_lists() {
+            let mut vtx_buffer = list.vtx_buffer().to_vec();
+            if offset != [0.0, 0.0] {
+                for v in &mut vtx_buffer {
+                    v.pos[0] -= offset[0];
+                    v.pos[1] -= offset[1];
+                }
+            }
+
+            let mut commands = Vec::new();
+            for cmd in list.commands() {
+                match cmd {
+                    imgui::DrawCmd::Elements { count, cmd_params } => {
+                        let id = cmd_params.texture_id;
+                        let c = cmd_params.clip_rect;
+                        commands.push(OwnedDrawCmd {
+                            count,
+                            clip_rect: [
+                                c[0] - offset[0],
+                                c[1] - offset[1],
+                                c[2] - offset[0],
+                                c[3] - offset[1],
+                            ],
+                            texture_id: table.and_then(|t| t.get(&id)).copied().unwrap_or(id),
+                            vtx_offset: cmd_params.vtx_offset,
+                            idx_offset: cmd_params.idx_offset,
+                        });
+                    }
+                    _ => {
+                        log::debug!("imgui-backends: dropped a callback command while merging");
+                    }
+                }
+            }
+
+            merged.draw_lists.push(OwnedDrawList {
+                vtx_buffer,
+                idx_buffer: list.idx_buffer().to_vec(),
+                commands,
+            });
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use imgui::{Condition, TextureId, Window};
+
+    use super::*;
+    use crate::helper::{Locale, QuickStart};
+
+    #[test]
+    fn concatenates_and_remaps_textures() {
+        let _lock = crate::lock_imgui_context();
+        let mut imgui = QuickStart {
+            display_size: [640.0, 480.0],
+            fontsize: 13.0,
+            hidpi_factor: 1.0,
+            locale: Locale::English,
+            input_timing: None,
+            fonts: None,
+            keyboard_nav: true,
+        }
+        .create_context();
+        imgui.set_ini_filename(None);
+        imgui.fonts().build_rgba32_texture();
+        imgui.io_mut().delta_time = 1.0 / 60.0;
+
+        let ui = imgui.frame();
+        Window::new("merge")
+            .position([10.0, 10.0], Condition::Always)
+            .size([200.0, 200.0], Condition::Always)
+            .build(&ui, || {
+                imgui::Image::new(TextureId::from(7), [32.0, 32.0]).build(&ui);
+            });
+        let data = ui.render();
+
+        let remap = [(TextureId::from(7), TextureId::from(42))];
+        let tables = [TextureTable::new(), remap.iter().copied().collect()];
+        let merged = merge_draw_data(&[data, data], &tables);
+
+        let n_lists = data.draw_lists_count();
+        assert_eq!(merged.draw_lists.len(), 2 * n_lists);
+        assert_eq!(merged.total_vtx_count(), 2 * data.total_vtx_count as usize);
+        assert_eq!(merged.total_idx_count(), 2 * data.total_idx_count as usize);
+
+        let ids = |lists: &[OwnedDrawList]| {
+            lists
+                .iter()
+                .flat_map(|list| list.commands.iter().map(|cmd| cmd.texture_id))
+                .collect::<Vec<_>>()
+        };
+        let (first, second) = merged.draw_lists.split_at(n_lists);
+        assert!(ids(first).contains(&TextureId::from(7)));
+        assert!(!ids(second).contains(&TextureId::from(7)));
+        assert!(ids(second).contains(&TextureId::from(42)));
+    }
+
+    #[test]
+    fn draw_params_cull_and_clip() {
+        let cmd = |count, clip_rect| OwnedDrawCmd {
+            count,
+            clip_rect,
+            texture_id: TextureId::from(1),
+            vtx_offset: 0,
+            idx_offset: 0,
+        };
+        let data = OwnedDrawData {
+            display_pos: [0.0, 0.0],
+            display_size: [100.0, 100.0],
+            framebuffer_scale: [2.0, 2.0],
+            draw_lists: vec![OwnedDrawList {
+                vtx_buffer: Vec::new(),
+                idx_buffer: vec![0; 3],
+                commands: vec![
+                    cmd(3, [0.0, 0.0, 50.0, 50.0]),
+                    // empty
+                    cmd(0, [0.0, 0.0, 50.0, 50.0]),
+                    // out of the framebuffer
+                    cmd(3, [200.0, 200.0, 300.0, 300.0]),
+                    // partially out of the framebuffer
+                    cmd(3, [50.0, 50.0, 150.0, 150.0]),
+                ],
+            }],
+        };
+
+        let clip_rects = data
+            .draw_params()
+            .map(|params| params.clip_rect)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            clip_rects,
+            vec![[0.0, 0.0, 100.0, 100.0], [100.0, 100.0, 200.0, 200.0]]
+        );
+    }
+}
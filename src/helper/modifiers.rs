@@ -0,0 +1,159 @@
+//! Modifier key state shared by the platforms
+//!
+//! Platforms report the pressed modifier keys with [`ModifierKeys`] and set ImGUI IO with the
+//! [`Modifiers`] resolved from them, so that keyboard shortcuts behave the same on every platform.
+
+/// Pressed modifier keys as reported by the OS, per side
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ModifierKeys {
+    pub lctrl: bool,
+    pub rctrl: bool,
+    pub lshift: bool,
+    pub rshift: bool,
+    pub lalt: bool,
+    pub ralt: bool,
+    /// Windows/Command key
+    pub lsuper: bool,
+    pub rsuper: bool,
+    /// AltGr (ISO level 3 shift) reported as its own modifier, e.g. `KMOD_MODE` of SDL on X11
+    pub alt_gr: bool,
+}
+
+impl ModifierKeys {
+    /// Modifiers for ImGUI. AltGr types characters, so it never counts as Ctrl or Alt:
+    ///
+    /// * the right Alt is dropped while AltGr is reported
+    /// * on Windows, AltGr is reported as left Ctrl + right Alt; both are dropped
+    ///
+    /// NOTE: Windows doesn't tell AltGr from a real left Ctrl + right Alt chord, so such
+    /// shortcuts are never seen by ImGUI there.
+    pub fn resolve(self) -> Modifiers {
+        let windows_alt_gr = cfg!(target_os = "windows") && self.lctrl && self.ralt;
+        let alt_gr = self.alt_gr || windows_alt_gr;
+
+        let lctrl = self.lctrl && !windows_alt_gr;
+        let ralt = self.ralt && !alt_gr;
+
+        Modifiers {
+            ctrl: lctrl || self.rctrl,
+            shift: self.lshift || self.rshift,
+            alt: self.lalt || ralt,
+            super_: self.lsuper || self.rsuper,
+        }
+    }
+}
+
+/// Modifier state of ImGUI IO
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+    pub super_: bool,
+}
+
+impl Modifiers {
+    pub fn from_io(io: &imgui::Io) -> Self {
+        Self {
+            ctrl: io.key_ctrl,
+            shift: io.key_shift,
+            alt: io.key_alt,
+            super_: io.key_super,
+        }
+    }
+
+    pub fn apply(self, io: &mut imgui::Io) {
+        io.key_ctrl = self.ctrl;
+        io.key_shift = self.shift;
+        io.key_alt = self.alt;
+        io.key_super = self.super_;
+    }
+
+    /// The shortcut modifier of the OS: Command on macOS, Ctrl otherwise
+    pub fn shortcut(self) -> bool {
+        if cfg!(target_os = "macos") {
+            self.super_
+        } else {
+            self.ctrl
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alt_gr_is_not_alt() {
+        let keys = ModifierKeys {
+            ralt: true,
+            alt_gr: true,
+            ..Default::default()
+        };
+        assert_eq!(keys.resolve(), Modifiers::default());
+    }
+
+    #[test]
+    fn plain_ctrl_and_alt() {
+        let keys = ModifierKeys {
+            rctrl: true,
+            lalt: true,
+            ..Default::default()
+        };
+        let mods = keys.resolve();
+        assert!(mods.ctrl && mods.alt);
+        assert!(!mods.shift && !mods.super_);
+
+        let keys = ModifierKeys {
+            lctrl: true,
+            ..Default::default()
+        };
+        assert!(keys.resolve().ctrl);
+
+        let keys = ModifierKeys {
+            ralt: true,
+            ..Default::default()
+        };
+        assert!(keys.resolve().alt);
+    }
+
+    #[test]
+    fn lctrl_ralt_folds_into_alt_gr_on_windows() {
+        let keys = ModifierKeys {
+            lctrl: true,
+            ralt: true,
+            ..Default::default()
+        };
+        let mods = keys.resolve();
+        if cfg!(target_os = "windows") {
+            assert_eq!(mods, Modifiers::default());
+        } else {
+            assert!(mods.ctrl && mods.alt);
+        }
+
+        // the other sides are kept
+        let keys = ModifierKeys {
+            rctrl: true,
+            lalt: true,
+            ..keys
+        };
+        let mods = keys.resolve();
+        assert!(mods.ctrl && mods.alt);
+    }
+
+    #[test]
+    fn shortcut() {
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..Default::default()
+        };
+        let super_ = Modifiers {
+            super_: true,
+            ..Default::default()
+        };
+        let is_mac = cfg!(target_os = "macos");
+        assert_eq!(ctrl.shortcut(), !is_mac);
+        assert_eq!(super_.shortcut(), is_mac);
+        assert!(!Modifiers::default().shortcut());
+    }
+}
@@ -0,0 +1,59 @@
+//! Reduced-motion preferences honored by the helper widgets
+//!
+//! ```no_run
+//! use imgui_backends::helper::{self, MotionPreferences};
+//!
+//! helper::set_motion_preferences(MotionPreferences::reduced());
+//! ```
+
+use std::cell::Cell;
+
+/// Global animation switches. Helpers read them with [`motion_preferences`]:
+///
+/// * toasts don't fade
+/// * tweens of [`crate::helper::Timers`] jump to the end and blinking timers stay on
+/// * the text cursor doesn't blink
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MotionPreferences {
+    /// Disables UI animations
+    pub reduce_motion: bool,
+    /// `io.config_input_text_cursor_blink`
+    pub cursor_blink: bool,
+}
+
+impl Default for MotionPreferences {
+    fn default() -> Self {
+        Self {
+            reduce_motion: false,
+            cursor_blink: true,
+        }
+    }
+}
+
+impl MotionPreferences {
+    /// No animations at all
+    pub fn reduced() -> Self {
+        Self {
+            reduce_motion: true,
+            cursor_blink: false,
+        }
+    }
+
+    /// Applies the preferences ImGUI itself understands. The backend calls it every frame
+    pub fn apply(&self, io: &mut imgui::Io) {
+        io.config_input_text_cursor_blink = self.cursor_blink;
+    }
+}
+
+thread_local! {
+    static MOTION: Cell<MotionPreferences> = Cell::new(MotionPreferences::default());
+}
+
+/// Sets the motion preferences of this (UI) thread
+pub fn set_motion_preferences(prefs: MotionPreferences) {
+    MOTION.with(|m| m.set(prefs));
+}
+
+pub fn motion_preferences() -> MotionPreferences {
+    MOTION.with(|m| m.get())
+}
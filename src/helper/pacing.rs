@@ -0,0 +1,219 @@
+//! Frame pacing measurement
+//!
+//! Tells UI rendering slowness from presentation issues: the time spent rendering the UI is
+//! measured apart from the present-to-present cadence, which is compared with the refresh period
+//! of the display under vsync.
+//!
+//! ```no_run
+//! backend.pacing.set_enabled(true);
+//! window.set_swap_interval(SwapInterval::VSync);
+//! // .. every frame, after rendering
+//! backend.present(&mut window);
+//! // .. after some frames
+//! println!("{}", backend.pacing.report());
+//! ```
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use super::Percentiles;
+
+/// Number of samples kept per measurement
+const N_SAMPLES: usize = 1024;
+
+/// How presentation waits for vertical blanks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SwapInterval {
+    /// Presents as soon as possible, tearing allowed
+    Immediate,
+    /// Waits for the next vertical blank
+    VSync,
+    /// Waits for the vertical blank unless the frame is late, then tears (late swap tearing)
+    Adaptive,
+}
+
+impl SwapInterval {
+    /// If frames are expected at the refresh rate
+    pub fn is_synced(self) -> bool {
+        !matches!(self, Self::Immediate)
+    }
+}
+
+/// Swap chain of a window. Implemented for windows the backend can present
+pub trait Present {
+    /// Returns `false` if the interval is not supported
+    fn set_swap_interval(&mut self, interval: SwapInterval) -> bool;
+    fn swap_interval(&self) -> SwapInterval;
+    /// Refresh rate of the display in Hz, if known
+    fn refresh_rate(&self) -> Option<f32>;
+    /// Presents the back buffer
+    fn present(&mut self);
+}
+
+/// Summary of [`PacingProbe`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PacingReport {
+    /// Interval of the last present. `None` before the first present
+    pub swap_interval: Option<SwapInterval>,
+    /// Refresh period of the display, if known
+    pub refresh: Option<Duration>,
+    /// Present → present
+    pub frame: Percentiles,
+    /// CPU time of rendering the UI
+    pub render: Percentiles,
+    /// Time blocked in the present call
+    pub present: Percentiles,
+    /// Vertical blanks passed without a present while synced
+    pub missed_vblanks: usize,
+}
+
+impl PacingReport {
+    /// Refresh period the frames are paced to, if any
+    pub fn expected_frame(&self) -> Option<Duration> {
+        match self.swap_interval {
+            Some(interval) if interval.is_synced() => self.refresh,
+            _ => None,
+        }
+    }
+
+    /// If missed vblanks are not explained by UI rendering, i.e. the UI renders within the refresh
+    /// period but frames are still late
+    pub fn is_presentation_bound(&self) -> bool {
+        match self.expected_frame() {
+            Some(period) => self.missed_vblanks > 0 && self.render.p99 < period,
+            None => false,
+        }
+    }
+}
+
+impl fmt::Display for PacingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.swap_interval, self.refresh) {
+            (Some(interval), Some(refresh)) => {
+                writeln!(f, "swap interval: {:?} (refresh {:?})", interval, refresh)?
+            }
+            (Some(interval), None) => writeln!(f, "swap interval: {:?}", interval)?,
+            (None, _) => writeln!(f, "swap interval: -")?,
+        }
+        writeln!(f, "frame:   {}", self.frame)?;
+        writeln!(f, "render:  {}", self.render)?;
+        writeln!(f, "present: {}", self.present)?;
+        write!(f, "missed vblanks: {}", self.missed_vblanks)
+    }
+}
+
+/// Frame pacing probe. Owned by [`crate::Backend`]; UI rendering is timed on
+/// [`crate::PreparedFrame::render`] and presents on [`crate::Backend::present`] while enabled
+#[derive(Debug, Clone, Default)]
+pub struct PacingProbe {
+    enabled: bool,
+    swap_interval: Option<SwapInterval>,
+    refresh: Option<Duration>,
+    render_start: Option<Instant>,
+    last_present: Option<Instant>,
+    frame: VecDeque<Duration>,
+    render: VecDeque<Duration>,
+    present: VecDeque<Duration>,
+    missed_vblanks: usize,
+}
+
+fn push_sample(samples: &mut VecDeque<Duration>, sample: Duration) {
+    if samples.len() == N_SAMPLES {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+impl PacingProbe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Disabling it clears the samples
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.clear();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.swap_interval = None;
+        self.refresh = None;
+        self.render_start = None;
+        self.last_present = None;
+        self.frame.clear();
+        self.render.clear();
+        self.present.clear();
+        self.missed_vblanks = 0;
+    }
+
+    /// UI rendering starts
+    pub fn on_render_begin(&mut self) {
+        if self.enabled {
+            self.render_start = Some(Instant::now());
+        }
+    }
+
+    /// UI rendering was submitted. Call both yourself if you render the draw data of
+    /// [`crate::BackendUi::finish`]
+    pub fn on_render_end(&mut self) {
+        if let Some(start) = self.render_start.take() {
+            self::push_sample(&mut self.render, start.elapsed());
+        }
+    }
+
+    /// A frame was presented: `start` is when the present call was made. Changing the swap
+    /// interval or the refresh rate restarts the measurement
+    pub fn on_present(
+        &mut self,
+        start: Instant,
+        interval: SwapInterval,
+        refresh_rate: Option<f32>,
+    ) {
+        if !self.enabled {
+            return;
+        }
+
+        let refresh = refresh_rate
+            .filter(|hz| *hz > 0.0)
+            .map(|hz| Duration::from_secs_f32(1.0 / hz));
+        if self.swap_interval != Some(interval) || self.refresh != refresh {
+            self.clear();
+            self.swap_interval = Some(interval);
+            self.refresh = refresh;
+        }
+
+        let now = Instant::now();
+        self::push_sample(&mut self.present, now - start);
+
+        if let Some(last) = self.last_present.replace(now) {
+            let dt = now - last;
+            self::push_sample(&mut self.frame, dt);
+
+            if let Some(period) = refresh.filter(|_| interval.is_synced()) {
+                // frames that took 1.5 periods or more missed at least one vblank
+                let n_periods = (dt.as_secs_f32() / period.as_secs_f32()).round() as usize;
+                self.missed_vblanks += n_periods.saturating_sub(1);
+            }
+        }
+    }
+
+    pub fn report(&self) -> PacingReport {
+        PacingReport {
+            swap_interval: self.swap_interval,
+            refresh: self.refresh,
+            frame: Percentiles::new(&self.frame),
+            render: Percentiles::new(&self.render),
+            present: Percentiles::new(&self.present),
+            missed_vblanks: self.missed_vblanks,
+        }
+    }
+}
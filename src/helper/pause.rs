@@ -0,0 +1,105 @@
+//! Sanity clamping of frame delta times
+//!
+//! A debugger break or a laptop sleep shows up as one huge delta time. Fed as is, spinners jump
+//! and toasts and timers expire instantly after resume. [`DeltaClamp`] limits the delta time and
+//! reports such pauses.
+
+use std::{fmt, time::Duration};
+
+/// Limits delta times given to [`crate::Backend::update_delta_time`]
+pub struct DeltaClamp {
+    /// Largest delta time passed to ImGUI and the timers
+    pub max_delta: Duration,
+    /// Delta times from this long are reported as pauses
+    pub pause_threshold: Duration,
+    on_long_pause: Option<Box<dyn FnMut(Duration)>>,
+    /// Pause detected on the last tick
+    last_pause: Option<Duration>,
+}
+
+impl Default for DeltaClamp {
+    fn default() -> Self {
+        Self {
+            max_delta: Duration::from_millis(100),
+            pause_threshold: Duration::from_secs(1),
+            on_long_pause: None,
+            last_pause: None,
+        }
+    }
+}
+
+impl fmt::Debug for DeltaClamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeltaClamp")
+            .field("max_delta", &self.max_delta)
+            .field("pause_threshold", &self.pause_threshold)
+            .field("on_long_pause", &self.on_long_pause.is_some())
+            .field("last_pause", &self.last_pause)
+            .finish()
+    }
+}
+
+impl DeltaClamp {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Called with the raw delta time when a pause is detected, e.g. to skip animations or reset
+    /// timeouts
+    pub fn on_long_pause(&mut self, f: Option<Box<dyn FnMut(Duration)>>) {
+        self.on_long_pause = f;
+    }
+
+    /// Length of the pause detected on the last tick, if any
+    pub fn last_pause(&self) -> Option<Duration> {
+        self.last_pause
+    }
+
+    /// Returns the delta time to use
+    pub fn clamp(&mut self, dt: Duration) -> Duration {
+        self.last_pause = None;
+
+        if dt >= self.pause_threshold {
+            log::debug!("imgui-backends: paused for {:?}", dt);
+            self.last_pause = Some(dt);
+            if let Some(f) = self.on_long_pause.as_mut() {
+                f(dt);
+            }
+        }
+
+        dt.min(self.max_delta)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn clamps_and_reports_pauses() {
+        let pauses = Rc::new(RefCell::new(Vec::new()));
+        let mut clamp = DeltaClamp::new();
+        let log = Rc::clone(&pauses);
+        clamp.on_long_pause(Some(Box::new(move |dt| log.borrow_mut().push(dt))));
+
+        let frame = Duration::from_millis(16);
+        assert_eq!(clamp.clamp(frame), frame);
+        assert_eq!(clamp.last_pause(), None);
+
+        // a hitch: clamped, but not a pause
+        assert_eq!(clamp.clamp(Duration::from_millis(500)), clamp.max_delta);
+        assert_eq!(clamp.last_pause(), None);
+
+        let sleep = Duration::from_secs(30);
+        assert_eq!(clamp.clamp(sleep), clamp.max_delta);
+        assert_eq!(clamp.last_pause(), Some(sleep));
+
+        // reported on the tick it happened only
+        assert_eq!(clamp.clamp(frame), frame);
+        assert_eq!(clamp.last_pause(), None);
+
+        assert_eq!(*pauses.borrow(), vec![sleep]);
+    }
+}
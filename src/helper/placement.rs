@@ -0,0 +1,49 @@
+//! Where the UI pass goes relative to the game's post-processing
+//!
+//! Render the UI after post-processing ([`UiPassPlacement::AfterPostFx`]) unless it has to be
+//! distorted with the scene (e.g. in-world screens). Tone mapping, bloom and color grading are tuned
+//! for the scene and crush or tint UI colors.
+
+/// Placement of the UI pass in the frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiPassPlacement {
+    /// Into the HDR scene target, before tone mapping. UI colors are scaled by the paper white
+    /// level so that they come out of tone mapping at the intended brightness
+    BeforePostFx,
+    /// Into the final (LDR) target after post-processing. Recommended
+    AfterPostFx,
+}
+
+impl Default for UiPassPlacement {
+    fn default() -> Self {
+        Self::AfterPostFx
+    }
+}
+
+impl UiPassPlacement {
+    /// Multiplier of UI colors. `paper_white` is the HDR value that tone maps to UI white
+    pub fn color_scale(self, paper_white: f32) -> f32 {
+        match self {
+            Self::BeforePostFx => paper_white,
+            Self::AfterPostFx => 1.0,
+        }
+    }
+}
+
+/// Render targets of both placements, e.g. framebuffers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UiPassTargets<T> {
+    /// HDR scene target, used for [`UiPassPlacement::BeforePostFx`]
+    pub hdr: T,
+    /// Final target, used for [`UiPassPlacement::AfterPostFx`]
+    pub backbuffer: T,
+}
+
+impl<T> UiPassTargets<T> {
+    pub fn select(&self, placement: UiPassPlacement) -> &T {
+        match placement {
+            UiPassPlacement::BeforePostFx => &self.hdr,
+            UiPassPlacement::AfterPostFx => &self.backbuffer,
+        }
+    }
+}
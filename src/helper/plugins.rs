@@ -0,0 +1,110 @@
+//! Frame lifecycle hooks for reusable middlewares (overlays, consoles, crash guards)
+//!
+//! ```no_run
+//! struct FrameCounter(u64);
+//!
+//! impl<P: Platform> BackendPlugin<P> for FrameCounter {
+//!     fn on_before_render(&mut self, ui: &imgui::Ui) {
+//!         self.0 += 1;
+//!         ui.text(format!("frame {}", self.0));
+//!     }
+//! }
+//!
+//! backend.plugins.add(Box::new(FrameCounter(0)));
+//! ```
+
+use std::fmt;
+
+use crate::{Captured, Platform};
+
+/// Hooks called by [`crate::Backend`] in this order every frame. Every hook does nothing by
+/// default
+pub trait BackendPlugin<P: Platform> {
+    /// Called on `Backend::handle_event(s)` after the platform handled the event
+    fn on_event(&mut self, _event: &P::Event, _captured: Captured) {}
+    /// Called on `Backend::begin_frame`, right after the ImGUI frame started
+    fn on_begin_frame(&mut self, _ui: &imgui::Ui) {}
+    /// Called when the frame is ended, before the draw data is built. UI submitted here is drawn
+    /// on top
+    fn on_before_render(&mut self, _ui: &imgui::Ui) {}
+    /// Called after the renderer rendered the frame. Not called for frames ended with
+    /// `BackendUi::finish`, which you render yourself
+    fn on_after_render(&mut self, _draw_data: &imgui::DrawData) {}
+}
+
+/// Handle of a plugin added to [`Plugins`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PluginId(u64);
+
+/// Plugin registry of [`crate::Backend`]. Plugins are called in the order they were added
+pub struct Plugins<P: Platform> {
+    plugins: Vec<(PluginId, Box<dyn BackendPlugin<P>>)>,
+    next_id: u64,
+}
+
+impl<P: Platform> Default for Plugins<P> {
+    fn default() -> Self {
+        Self {
+            plugins: Vec::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl<P: Platform> fmt::Debug for Plugins<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Plugins")
+            .field("len", &self.plugins.len())
+            .finish()
+    }
+}
+
+impl<P: Platform> Plugins<P> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, plugin: Box<dyn BackendPlugin<P>>) -> PluginId {
+        let id = PluginId(self.next_id);
+        self.next_id += 1;
+        self.plugins.push((id, plugin));
+        id
+    }
+
+    pub fn remove(&mut self, id: PluginId) -> Option<Box<dyn BackendPlugin<P>>> {
+        let i = self.plugins.iter().position(|(x, _)| *x == id)?;
+        Some(self.plugins.remove(i).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.plugins.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    pub fn on_event(&mut self, event: &P::Event, captured: Captured) {
+        for (_, plugin) in &mut self.plugins {
+            plugin.on_event(event, captured);
+        }
+    }
+
+    pub fn on_begin_frame(&mut self, ui: &imgui::Ui) {
+        for (_, plugin) in &mut self.plugins {
+            plugin.on_begin_frame(ui);
+        }
+    }
+
+    pub fn on_before_render(&mut self, ui: &imgui::Ui) {
+        for (_, plugin) in &mut self.plugins {
+            plugin.on_before_render(ui);
+        }
+    }
+
+    pub fn on_after_render(&mut self, draw_data: &imgui::DrawData) {
+        for (_, plugin) in &mut self.plugins {
+            plugin.on_after_render(draw_data);
+        }
+    }
+}
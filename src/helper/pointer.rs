@@ -0,0 +1,91 @@
+//! Pointer input synthesized by the application
+//!
+//! Console builds (e.g. FNA3D on consoles) have no OS mouse; the engine moves a virtual cursor with
+//! a gamepad. [`PointerOverride`] feeds it to ImGUI in place of the platform's mouse state.
+
+use crate::{dpi::LogicalPos, Captured};
+
+/// Pointer state for one frame
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct VirtualPointer {
+    /// In logical pixels
+    pub pos: [f32; 2],
+    /// Left, right, middle and the extra buttons
+    pub buttons: [bool; 5],
+    /// Horizontal and vertical wheel in lines. Consumed on the next frame
+    pub wheel: [f32; 2],
+}
+
+/// Overrides the platform's mouse state while set
+#[derive(Debug, Clone, Default)]
+pub struct PointerOverride {
+    pointer: Option<VirtualPointer>,
+    /// Presses since the last frame, so that clicks shorter than a frame are not missed
+    pressed: [bool; 5],
+    wheel: [f32; 2],
+    /// If ImGUI wanted the mouse on the last frame
+    want_capture: bool,
+}
+
+impl PointerOverride {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.pointer.is_some()
+    }
+
+    pub fn pointer(&self) -> Option<&VirtualPointer> {
+        self.pointer.as_ref()
+    }
+
+    /// Sets the pointer. `None` gives the mouse back to the platform. Returns [`Captured::Mouse`]
+    /// if the UI consumes the pointer (e.g. a click on a window), the same way as events given to
+    /// [`crate::Backend::handle_event`]
+    pub fn set(&mut self, pointer: Option<VirtualPointer>) -> Captured {
+        let pointer = match pointer {
+            Some(pointer) => pointer,
+            None => {
+                *self = Self {
+                    want_capture: self.want_capture,
+                    ..Self::default()
+                };
+                return Captured::No;
+            }
+        };
+
+        for (pressed, down) in self.pressed.iter_mut().zip(pointer.buttons.iter()) {
+            *pressed |= *down;
+        }
+        self.wheel[0] += pointer.wheel[0];
+        self.wheel[1] += pointer.wheel[1];
+        self.pointer = Some(pointer);
+
+        if self.want_capture {
+            Captured::Mouse
+        } else {
+            Captured::No
+        }
+    }
+
+    /// Overwrites the mouse state of the platform. Called after the platform prepared the frame
+    pub fn apply(&mut self, io: &mut imgui::Io) {
+        self.want_capture = io.want_capture_mouse;
+
+        let pointer = match self.pointer.as_ref() {
+            Some(pointer) => pointer,
+            None => return,
+        };
+
+        crate::dpi::set_mouse_pos(io, LogicalPos(pointer.pos));
+        for i in 0..5 {
+            io.mouse_down[i] = pointer.buttons[i] || self.pressed[i];
+        }
+        io.mouse_wheel_h += self.wheel[0];
+        io.mouse_wheel += self.wheel[1];
+
+        self.pressed = [false; 5];
+        self.wheel = [0.0, 0.0];
+    }
+}
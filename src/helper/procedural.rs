@@ -0,0 +1,152 @@
+//! Procedural textures for custom widgets
+//!
+//! ```no_run
+//! # use imgui_backends::helper::{Procedural, ProceduralTextures};
+//! # fn f(
+//! #     ui: &imgui::Ui,
+//! #     procs: &mut ProceduralTextures,
+//! #     register_texture: impl FnOnce(&[u8], u32, u32) -> anyhow::Result<imgui::TextureId>,
+//! # ) -> anyhow::Result<()> {
+//! let key = Procedural::Checkerboard {
+//!     a: [64, 64, 64, 255],
+//!     b: [96, 96, 96, 255],
+//!     cell: 8,
+//!     size: 64,
+//! };
+//! // e.g. `|pixels, w, h| renderer.register_texture(gl, pixels, w, h, true)` for glow
+//! let tex = procs.get_or_insert(&key, register_texture)?;
+//! ui.get_window_draw_list()
+//!     .add_image(tex, [0.0, 0.0], [64.0, 64.0])
+//!     .build();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+
+/// Parameters of a procedural texture. RGBA8 colors
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Procedural {
+    /// Linear gradient of a `size` x `size` texture
+    Gradient {
+        from: [u8; 4],
+        to: [u8; 4],
+        size: u32,
+        vertical: bool,
+    },
+    /// `size` x `size` texture filled with `cell` x `cell` squares
+    Checkerboard {
+        a: [u8; 4],
+        b: [u8; 4],
+        cell: u32,
+        size: u32,
+    },
+    /// `width` x 1 lookup table interpolating evenly placed color stops
+    Ramp { stops: Vec<[u8; 4]>, width: u32 },
+}
+
+fn lerp(a: [u8; 4], b: [u8; 4], t: f32) -> [u8; 4] {
+    let mut c = [0; 4];
+    for i in 0..4 {
+        c[i] = (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u8;
+    }
+    c
+}
+
+impl Procedural {
+    /// Generates RGBA8 pixels and the size
+    pub fn generate(&self) -> (Vec<u8>, u32, u32) {
+        match self {
+            Self::Gradient {
+                from,
+                to,
+                size,
+                vertical,
+            } => {
+                let size = (*size).max(1);
+                let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+                for y in 0..size {
+                    for x in 0..size {
+                        let i = if *vertical { y } else { x };
+                        let t = i as f32 / (size - 1).max(1) as f32;
+                        pixels.extend_from_slice(&self::lerp(*from, *to, t));
+                    }
+                }
+                (pixels, size, size)
+            }
+            Self::Checkerboard { a, b, cell, size } => {
+                let (cell, size) = ((*cell).max(1), (*size).max(1));
+                let mut pixels = Vec::with_capacity((size * size * 4) as usize);
+                for y in 0..size {
+                    for x in 0..size {
+                        let c = if (x / cell + y / cell) % 2 == 0 { a } else { b };
+                        pixels.extend_from_slice(c);
+                    }
+                }
+                (pixels, size, size)
+            }
+            Self::Ramp { stops, width } => {
+                let width = (*width).max(1);
+                let mut pixels = Vec::with_capacity((width * 4) as usize);
+                for x in 0..width {
+                    let c = match stops.len() {
+                        0 => [0; 4],
+                        1 => stops[0],
+                        n => {
+                            let t = x as f32 / (width - 1).max(1) as f32 * (n - 1) as f32;
+                            let i = (t.floor() as usize).min(n - 2);
+                            self::lerp(stops[i], stops[i + 1], t - i as f32)
+                        }
+                    };
+                    pixels.extend_from_slice(&c);
+                }
+                (pixels, width, 1)
+            }
+        }
+    }
+}
+
+/// Cache of procedural textures keyed by their parameters
+///
+/// Uploading is delegated to the renderer so that it works with any backend.
+#[derive(Debug, Default)]
+pub struct ProceduralTextures {
+    cache: HashMap<Procedural, imgui::TextureId>,
+}
+
+impl ProceduralTextures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached texture or generates and uploads a new one with `upload(pixels, w, h)`
+    pub fn get_or_insert<E>(
+        &mut self,
+        key: &Procedural,
+        upload: impl FnOnce(&[u8], u32, u32) -> Result<imgui::TextureId, E>,
+    ) -> Result<imgui::TextureId, E> {
+        if let Some(id) = self.cache.get(key) {
+            return Ok(*id);
+        }
+
+        let (pixels, w, h) = key.generate();
+        let id = upload(&pixels, w, h)?;
+        self.cache.insert(key.clone(), id);
+        Ok(id)
+    }
+
+    pub fn get(&self, key: &Procedural) -> Option<imgui::TextureId> {
+        self.cache.get(key).cloned()
+    }
+
+    /// Forgets the texture. The caller is responsible for removing it from the renderer
+    pub fn remove(&mut self, key: &Procedural) -> Option<imgui::TextureId> {
+        self.cache.remove(key)
+    }
+
+    /// Forgets all the textures, returning them so that the caller can remove them from the
+    /// renderer
+    pub fn clear(&mut self) -> Vec<imgui::TextureId> {
+        self.cache.drain().map(|(_, id)| id).collect()
+    }
+}
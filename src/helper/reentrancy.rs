@@ -0,0 +1,149 @@
+//! Guards against nested frames and re-entrant rendering
+//!
+//! Misusing the frame protocol otherwise ends in assertions deep in Dear ImGUI. The state is per
+//! thread like the ImGUI context itself: at most one frame is active on a thread.
+
+use std::cell::Cell;
+
+use thiserror::Error;
+
+/// Misuse of the frame protocol detected by [`crate::Backend`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Error)]
+pub enum ReentrancyError {
+    #[error("a frame is already active: end it with `end_frame`, `prepare_render`, `finish` or `discard` before calling `begin_frame` again")]
+    FrameActive,
+    #[error("event handled during an active frame: handle events before `begin_frame` or after the frame is ended")]
+    EventDuringFrame,
+    #[error(
+        "{0} called from inside a draw callback: callbacks must only issue their own draw calls"
+    )]
+    InDrawCallback(&'static str),
+}
+
+thread_local! {
+    static FRAME_ACTIVE: Cell<bool> = Cell::new(false);
+    static CALLBACK_DEPTH: Cell<usize> = Cell::new(0);
+}
+
+/// If a frame begun with [`crate::Backend::begin_frame`] is not ended yet on this thread
+pub fn is_frame_active() -> bool {
+    FRAME_ACTIVE.with(|x| x.get())
+}
+
+/// If a draw callback is running on this thread
+pub fn is_in_draw_callback() -> bool {
+    CALLBACK_DEPTH.with(|x| x.get()) > 0
+}
+
+fn check_callback(what: &'static str) -> Result<(), ReentrancyError> {
+    if self::is_in_draw_callback() {
+        Err(ReentrancyError::InDrawCallback(what))
+    } else {
+        Ok(())
+    }
+}
+
+pub(crate) fn check_begin_frame() -> Result<(), ReentrancyError> {
+    self::check_callback("`begin_frame`")?;
+    if self::is_frame_active() {
+        return Err(ReentrancyError::FrameActive);
+    }
+    Ok(())
+}
+
+pub(crate) fn check_event() -> Result<(), ReentrancyError> {
+    self::check_callback("event handling")?;
+    if self::is_frame_active() {
+        return Err(ReentrancyError::EventDuringFrame);
+    }
+    Ok(())
+}
+
+pub(crate) fn check_render() -> Result<(), ReentrancyError> {
+    self::check_callback("rendering")
+}
+
+/// Marks the frame active until dropped
+#[derive(Debug)]
+pub(crate) struct FrameScope(());
+
+impl FrameScope {
+    pub fn begin() -> Self {
+        FRAME_ACTIVE.with(|x| x.set(true));
+        Self(())
+    }
+}
+
+impl Drop for FrameScope {
+    fn drop(&mut self) {
+        FRAME_ACTIVE.with(|x| x.set(false));
+    }
+}
+
+/// Marks a draw callback running until dropped
+#[derive(Debug)]
+pub(crate) struct CallbackScope(());
+
+impl CallbackScope {
+    pub fn begin() -> Self {
+        CALLBACK_DEPTH.with(|x| x.set(x.get() + 1));
+        Self(())
+    }
+}
+
+impl Drop for CallbackScope {
+    fn drop(&mut self) {
+        CALLBACK_DEPTH.with(|x| x.set(x.get() - 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_frames_and_events_during_frames() {
+        assert!(check_begin_frame().is_ok());
+        assert!(check_event().is_ok());
+
+        {
+            let _frame = FrameScope::begin();
+            assert!(is_frame_active());
+            assert_eq!(check_begin_frame(), Err(ReentrancyError::FrameActive));
+            assert_eq!(check_event(), Err(ReentrancyError::EventDuringFrame));
+            assert!(check_render().is_ok());
+        }
+
+        assert!(!is_frame_active());
+        assert!(check_begin_frame().is_ok());
+        assert!(check_event().is_ok());
+    }
+
+    #[test]
+    fn calls_from_draw_callbacks() {
+        {
+            let _outer = CallbackScope::begin();
+            {
+                let _inner = CallbackScope::begin();
+                assert!(is_in_draw_callback());
+            }
+            // still inside the outer callback
+            assert!(is_in_draw_callback());
+            assert_eq!(
+                check_render(),
+                Err(ReentrancyError::InDrawCallback("rendering"))
+            );
+            assert!(matches!(
+                check_begin_frame(),
+                Err(ReentrancyError::InDrawCallback(_))
+            ));
+            assert!(matches!(
+                check_event(),
+                Err(ReentrancyError::InDrawCallback(_))
+            ));
+        }
+
+        assert!(!is_in_draw_callback());
+        assert!(check_render().is_ok());
+    }
+}
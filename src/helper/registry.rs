@@ -0,0 +1,335 @@
+//! Texture registry
+
+use std::{collections::HashMap, fmt};
+
+use crate::FontSampling;
+
+/// Where to get pixels from when restoring a texture after device loss
+pub enum TextureSource {
+    /// Retained CPU copy of RGBA8 pixels
+    Pixels(Vec<u8>),
+    /// User callback that returns RGBA8 pixels
+    Reupload(Box<dyn FnMut() -> Vec<u8>>),
+}
+
+impl fmt::Debug for TextureSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pixels(pixels) => write!(f, "Pixels({} bytes)", pixels.len()),
+            Self::Reupload(_) => f.write_str("Reupload(..)"),
+        }
+    }
+}
+
+impl TextureSource {
+    fn pixels(&mut self) -> Vec<u8> {
+        match self {
+            Self::Pixels(pixels) => pixels.clone(),
+            Self::Reupload(f) => f(),
+        }
+    }
+}
+
+/// Who releases a registered GPU texture
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextureOwnership {
+    /// Created by the renderer and released on unregistration
+    Renderer,
+    /// Shared with the application and released when the last reference is dropped
+    Shared,
+    /// Owned by the application. The renderer never releases it
+    Application,
+}
+
+impl Default for TextureOwnership {
+    fn default() -> Self {
+        Self::Renderer
+    }
+}
+
+/// How a GPU texture was created, so that it's re-created the same way from its source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TextureParams {
+    pub filter: FontSampling,
+    /// If the texture can be updated after creation
+    pub is_dynamic: bool,
+}
+
+/// Metadata of a registered texture
+#[derive(Debug)]
+pub struct TextureEntry {
+    pub w: u32,
+    pub h: u32,
+    /// `None` if the texture can't be restored after device loss
+    pub source: Option<TextureSource>,
+    pub params: TextureParams,
+    pub ownership: TextureOwnership,
+    /// Name shown in debug UIs
+    pub label: Option<String>,
+}
+
+/// Live texture yielded by [`TextureRegistry::textures_iter`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureInfo<'a> {
+    pub id: imgui::TextureId,
+    /// `[w, h]` in pixels
+    pub size: [u32; 2],
+    pub ownership: TextureOwnership,
+    pub label: Option<&'a str>,
+}
+
+/// RGBA8 pixels for [`crate::RegisterTexture::register_textures`]
+#[derive(Debug, Clone, Copy)]
+pub struct TextureUpload<'a> {
+    pub pixels: &'a [u8],
+    pub w: u32,
+    pub h: u32,
+    /// Keep a CPU copy of the pixels to restore the texture after device loss
+    pub retain: bool,
+    /// Debug name. See [`crate::RegisterTexture::set_texture_label`]
+    pub label: Option<&'a str>,
+}
+
+/// [`imgui::Textures`] with metadata and optional CPU-side sources for device-loss recovery
+#[derive(Debug)]
+pub struct TextureRegistry<T> {
+    textures: imgui::Textures<T>,
+    entries: HashMap<imgui::TextureId, TextureEntry>,
+    /// Unregistered GPU textures waiting for [`TextureRegistry::flush_removed`]
+    removed: Vec<T>,
+}
+
+impl<T> Default for TextureRegistry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TextureRegistry<T> {
+    pub fn new() -> Self {
+        Self {
+            textures: imgui::Textures::new(),
+            entries: HashMap::new(),
+            removed: Vec::new(),
+        }
+    }
+
+    pub fn insert(
+        &mut self,
+        texture: T,
+        w: u32,
+        h: u32,
+        source: Option<TextureSource>,
+    ) -> imgui::TextureId {
+        let id = self.textures.insert(texture);
+        self.entries.insert(
+            id,
+            TextureEntry {
+                w,
+                h,
+                source,
+                params: TextureParams::default(),
+                ownership: TextureOwnership::default(),
+                label: None,
+            },
+        );
+        id
+    }
+
+    /// Records who releases the texture. Textures are [`TextureOwnership::Renderer`] by default
+    pub fn set_ownership(&mut self, id: imgui::TextureId, ownership: TextureOwnership) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.ownership = ownership;
+        }
+    }
+
+    /// Records how the texture was created. See [`Self::restore_entries`]
+    pub fn set_params(&mut self, id: imgui::TextureId, params: TextureParams) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.params = params;
+        }
+    }
+
+    /// Names the texture for debug UIs
+    pub fn set_label(&mut self, id: imgui::TextureId, label: impl Into<String>) {
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.label = Some(label.into());
+        }
+    }
+
+    /// Replaces the GPU texture, keeping the metadata
+    pub fn replace(&mut self, id: imgui::TextureId, texture: T) -> Option<T> {
+        self.textures.replace(id, texture)
+    }
+
+    pub fn remove(&mut self, id: imgui::TextureId) -> Option<T> {
+        self.entries.remove(&id);
+        self.textures.remove(id)
+    }
+
+    /// Unregisters textures at once. Their GPU textures are released together on
+    /// [`Self::flush_removed`], which renderers call at the beginning of `render`
+    pub fn remove_many(&mut self, ids: impl IntoIterator<Item = imgui::TextureId>) -> usize {
+        let n_before = self.removed.len();
+        for id in ids {
+            self.entries.remove(&id);
+            if let Some(texture) = self.textures.remove(id) {
+                self.removed.push(texture);
+            }
+        }
+
+        let n = self.removed.len() - n_before;
+        log::debug!("imgui-backends: unregistered {} textures", n);
+        n
+    }
+
+    /// Releases GPU textures unregistered with [`Self::remove_many`]. Returns how many were released
+    pub fn flush_removed(&mut self) -> usize {
+        let n = self.removed.len();
+        self.removed.clear();
+        n
+    }
+
+    /// Takes GPU textures unregistered with [`Self::remove_many`] instead of releasing them, for
+    /// renderers that have to wait for frames in flight
+    pub fn take_removed(&mut self) -> Vec<T> {
+        std::mem::take(&mut self.removed)
+    }
+
+    pub fn get(&self, id: imgui::TextureId) -> Option<&T> {
+        self.textures.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: imgui::TextureId) -> Option<&mut T> {
+        self.textures.get_mut(id)
+    }
+
+    pub fn entry(&self, id: imgui::TextureId) -> Option<&TextureEntry> {
+        self.entries.get(&id)
+    }
+
+    /// Live textures in registration order, e.g. for texture inspectors. Textures waiting for
+    /// [`Self::restore`] are skipped
+    pub fn textures_iter(&self) -> impl Iterator<Item = TextureInfo<'_>> {
+        let mut infos = self
+            .entries
+            .iter()
+            .filter(|(id, _)| self.textures.get(**id).is_some())
+            .map(|(id, entry)| TextureInfo {
+                id: *id,
+                size: [entry.w, entry.h],
+                ownership: entry.ownership,
+                label: entry.label.as_deref(),
+            })
+            .collect::<Vec<_>>();
+        infos.sort_by_key(|info| info.id.id());
+        infos.into_iter()
+    }
+
+    /// Drops every GPU texture. Textures with sources are kept (with their IDs) so that they can be
+    /// restored with [`Self::restore`]; others are unregistered
+    pub fn invalidate(&mut self) {
+        self.removed.clear();
+        let textures = &mut self.textures;
+        self.entries.retain(|id, entry| {
+            textures.remove(*id);
+            if entry.source.is_none() {
+                log::warn!(
+                    "imgui-backends: texture {:?} has no source and can't be restored",
+                    id
+                );
+            }
+            entry.source.is_some()
+        });
+    }
+
+    /// Re-creates GPU textures from their sources, keeping their IDs
+    pub fn restore<E>(
+        &mut self,
+        mut create: impl FnMut(&[u8], u32, u32) -> Result<T, E>,
+    ) -> Result<(), E> {
+        self.restore_entries(|pixels, entry| create(pixels, entry.w, entry.h))
+    }
+
+    /// [`Self::restore`] with the metadata of each texture, e.g. to re-create it with its
+    /// [`TextureParams`]
+    pub fn restore_entries<E>(
+        &mut self,
+        mut create: impl FnMut(&[u8], &TextureEntry) -> Result<T, E>,
+    ) -> Result<(), E> {
+        for (id, entry) in self.entries.iter_mut() {
+            if let Some(source) = entry.source.as_mut() {
+                let pixels = source.pixels();
+                let texture = create(&pixels, entry)?;
+                self.textures.replace(*id, texture);
+            }
+        }
+        Ok(())
+    }
+
+    /// Re-creates a GPU texture from its source, keeping the ID. Returns `Ok(false)` if the texture
+    /// has no source or the source returned pixels of wrong size
+    pub fn reload<E>(
+        &mut self,
+        id: imgui::TextureId,
+        create: impl FnOnce(&[u8], u32, u32) -> Result<T, E>,
+    ) -> Result<bool, E> {
+        self.reload_entry(id, |pixels, entry| create(pixels, entry.w, entry.h))
+    }
+
+    /// [`Self::reload`] with the metadata of the texture
+    pub fn reload_entry<E>(
+        &mut self,
+        id: imgui::TextureId,
+        create: impl FnOnce(&[u8], &TextureEntry) -> Result<T, E>,
+    ) -> Result<bool, E> {
+        let entry = match self.entries.get_mut(&id) {
+            Some(entry) => entry,
+            None => return Ok(false),
+        };
+        let source = match entry.source.as_mut() {
+            Some(source) => source,
+            None => return Ok(false),
+        };
+
+        let pixels = source.pixels();
+        if pixels.len() != (entry.w as usize) * (entry.h as usize) * 4 {
+            log::warn!(
+                "imgui-backends: reloaded texture {:?} is not {}x{}",
+                id,
+                entry.w,
+                entry.h
+            );
+            return Ok(false);
+        }
+
+        let texture = create(&pixels, entry)?;
+        // the old texture is dropped after the new one is created
+        self.textures.replace(id, texture);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restore_keeps_params() {
+        let params = TextureParams {
+            filter: FontSampling::Nearest,
+            is_dynamic: true,
+        };
+
+        let mut textures = TextureRegistry::new();
+        let id = textures.insert(params, 1, 1, Some(TextureSource::Pixels(vec![0; 4])));
+        textures.set_params(id, params);
+        textures.invalidate();
+        assert!(textures.get(id).is_none());
+
+        textures
+            .restore_entries::<()>(|_pixels, entry| Ok(entry.params))
+            .unwrap();
+        assert_eq!(textures.get(id), Some(&params));
+    }
+}
@@ -0,0 +1,77 @@
+//! Hot-reload notifications from the host application
+
+use std::{cell::RefCell, rc::Rc};
+
+use super::{TextureEntry, TextureRegistry};
+
+/// Resource changed on the host side
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReloadEvent {
+    /// Re-create the texture from its [`super::TextureSource`]. Use
+    /// [`super::TextureSource::Reupload`] to load the new pixels from disk
+    Texture(imgui::TextureId),
+    /// Shader with a renderer-defined name
+    Shader(String),
+}
+
+/// Queue of [`ReloadEvent`] s shared by the host (e.g. an asset watcher) and the renderer.
+/// Renderers handle the events at the beginning of the next render
+#[derive(Debug, Clone, Default)]
+pub struct ReloadBus {
+    queue: Rc<RefCell<Vec<ReloadEvent>>>,
+}
+
+impl ReloadBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn texture_changed(&self, id: imgui::TextureId) {
+        self.announce(ReloadEvent::Texture(id));
+    }
+
+    pub fn shader_changed(&self, name: impl Into<String>) {
+        self.announce(ReloadEvent::Shader(name.into()));
+    }
+
+    pub fn announce(&self, ev: ReloadEvent) {
+        let mut queue = self.queue.borrow_mut();
+        if !queue.contains(&ev) {
+            queue.push(ev);
+        }
+    }
+
+    /// Drains the queued events
+    pub fn take(&self) -> Vec<ReloadEvent> {
+        std::mem::take(&mut *self.queue.borrow_mut())
+    }
+
+    /// Drains the queue, reloading changed textures in the registry. Returns other events
+    pub fn reload_textures<T, E>(
+        &self,
+        textures: &mut TextureRegistry<T>,
+        mut create: impl FnMut(&[u8], u32, u32) -> Result<T, E>,
+    ) -> Result<Vec<ReloadEvent>, E> {
+        self.reload_texture_entries(textures, |pixels, entry| create(pixels, entry.w, entry.h))
+    }
+
+    /// [`Self::reload_textures`] with the metadata of each texture
+    pub fn reload_texture_entries<T, E>(
+        &self,
+        textures: &mut TextureRegistry<T>,
+        mut create: impl FnMut(&[u8], &TextureEntry) -> Result<T, E>,
+    ) -> Result<Vec<ReloadEvent>, E> {
+        let mut rest = Vec::new();
+        for ev in self.take() {
+            match ev {
+                ReloadEvent::Texture(id) => {
+                    if !textures.reload_entry(id, &mut create)? {
+                        log::debug!("imgui-backends: texture {:?} was not reloaded", id);
+                    }
+                }
+                ev => rest.push(ev),
+            }
+        }
+        Ok(rest)
+    }
+}
@@ -0,0 +1,79 @@
+//! UI sound feedback hooks
+
+use std::fmt;
+
+/// UI events that may deserve sound feedback
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiSoundEvent {
+    /// An item became active (button pressed, checkbox toggled, slider grabbed..)
+    Activate,
+    /// The active item was released
+    Deactivate,
+    /// Keyboard/gamepad navigation highlight became visible
+    NavStart,
+    /// Keyboard/gamepad navigation highlight was hidden
+    NavEnd,
+}
+
+/// Implement it to play sounds through the host engine's audio system
+pub trait UiSounds {
+    fn play(&mut self, event: UiSoundEvent);
+}
+
+/// Detects [`UiSoundEvent`] s each frame and forwards them to the [`UiSounds`] implementation
+#[derive(Default)]
+pub struct SoundHooks {
+    sounds: Option<Box<dyn UiSounds>>,
+    was_active: bool,
+    was_nav_visible: bool,
+}
+
+impl fmt::Debug for SoundHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SoundHooks")
+            .field("is_set", &self.sounds.is_some())
+            .field("was_active", &self.was_active)
+            .field("was_nav_visible", &self.was_nav_visible)
+            .finish()
+    }
+}
+
+impl SoundHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, sounds: Option<Box<dyn UiSounds>>) {
+        self.sounds = sounds;
+    }
+
+    /// Called by the backend at the end of each frame
+    pub fn update(&mut self, ui: &imgui::Ui) {
+        let is_active = ui.is_any_item_active();
+        let is_nav_visible = ui.io().nav_visible;
+
+        let sounds = match self.sounds.as_mut() {
+            Some(sounds) => sounds,
+            None => {
+                self.was_active = is_active;
+                self.was_nav_visible = is_nav_visible;
+                return;
+            }
+        };
+
+        match (self.was_active, is_active) {
+            (false, true) => sounds.play(UiSoundEvent::Activate),
+            (true, false) => sounds.play(UiSoundEvent::Deactivate),
+            _ => {}
+        }
+
+        match (self.was_nav_visible, is_nav_visible) {
+            (false, true) => sounds.play(UiSoundEvent::NavStart),
+            (true, false) => sounds.play(UiSoundEvent::NavEnd),
+            _ => {}
+        }
+
+        self.was_active = is_active;
+        self.was_nav_visible = is_nav_visible;
+    }
+}
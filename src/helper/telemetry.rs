@@ -0,0 +1,118 @@
+//! Usage events of tool UIs for anonymized analytics
+//!
+//! ```no_run
+//! backend.set_ui_observer(Some(Box::new(MyObserver)));
+//!
+//! let mut ui = backend.begin_frame(&window);
+//! Window::new("Level editor").build(&ui, || {
+//!     ui.telemetry().track_window(&ui, "Level editor");
+//!     if ui.button("Bake") { /* .. */ }
+//!     ui.telemetry().track_item(&ui, "Bake");
+//! });
+//! ```
+
+use std::{collections::HashSet, fmt};
+
+/// Hash of a window name or an item label. Labels are not sent to the observer
+pub type LabelHash = u64;
+
+/// FNV-1a hash of a label
+pub fn label_hash(label: &str) -> LabelHash {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for b in label.bytes() {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// High-level UI events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiUsageEvent {
+    /// A tracked window appeared
+    WindowOpened(LabelHash),
+    /// A tracked window was not submitted in the last frame
+    WindowClosed(LabelHash),
+    /// A tracked item was activated (button pressed, slider grabbed..)
+    ItemActivated(LabelHash),
+}
+
+/// Implement it to collect usage metrics
+pub trait UiObserver {
+    fn observe(&mut self, event: UiUsageEvent);
+}
+
+/// Derives [`UiUsageEvent`] s from ImGUI queries and forwards them to the [`UiObserver`]
+///
+/// Tracking calls return immediately while no observer is set.
+#[derive(Default)]
+pub struct TelemetryHooks {
+    observer: Option<Box<dyn UiObserver>>,
+    /// Windows submitted in the last frame
+    windows: HashSet<LabelHash>,
+    /// Windows submitted in this frame
+    frame_windows: HashSet<LabelHash>,
+}
+
+impl fmt::Debug for TelemetryHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TelemetryHooks")
+            .field("is_set", &self.observer.is_some())
+            .field("windows", &self.windows)
+            .finish()
+    }
+}
+
+impl TelemetryHooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&mut self, observer: Option<Box<dyn UiObserver>>) {
+        self.observer = observer;
+        self.windows.clear();
+        self.frame_windows.clear();
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.observer.is_some()
+    }
+
+    /// Call it inside a window every frame it's shown
+    pub fn track_window(&mut self, _ui: &imgui::Ui, name: &str) {
+        if self.observer.is_none() {
+            return;
+        }
+        self.frame_windows.insert(self::label_hash(name));
+    }
+
+    /// Call it right after the item
+    pub fn track_item(&mut self, ui: &imgui::Ui, label: &str) {
+        let observer = match self.observer.as_mut() {
+            Some(o) => o,
+            None => return,
+        };
+
+        if ui.is_item_activated() {
+            observer.observe(UiUsageEvent::ItemActivated(self::label_hash(label)));
+        }
+    }
+
+    /// Called by the backend at the end of each frame
+    pub fn update(&mut self, _ui: &imgui::Ui) {
+        let observer = match self.observer.as_mut() {
+            Some(o) => o,
+            None => return,
+        };
+
+        for w in self.frame_windows.difference(&self.windows) {
+            observer.observe(UiUsageEvent::WindowOpened(*w));
+        }
+        for w in self.windows.difference(&self.frame_windows) {
+            observer.observe(UiUsageEvent::WindowClosed(*w));
+        }
+
+        std::mem::swap(&mut self.windows, &mut self.frame_windows);
+        self.frame_windows.clear();
+    }
+}
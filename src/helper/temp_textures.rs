@@ -0,0 +1,54 @@
+//! Textures valid only for one frame
+
+use crate::RegisterTexture;
+
+/// Temporary textures of the current frame. Owned by [`crate::Backend`]; push them with
+/// [`crate::BackendUi::temp_texture`]
+#[derive(Debug)]
+pub struct TempTextures<R> {
+    ids: Vec<imgui::TextureId>,
+    /// Set on the first push, where the renderer is known to be [`RegisterTexture`]
+    remove: Option<fn(&mut R, Vec<imgui::TextureId>) -> usize>,
+}
+
+impl<R> Default for TempTextures<R> {
+    fn default() -> Self {
+        Self {
+            ids: Vec::new(),
+            remove: None,
+        }
+    }
+}
+
+impl<R> TempTextures<R> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    pub fn push(&mut self, id: imgui::TextureId)
+    where
+        R: RegisterTexture,
+    {
+        self.ids.push(id);
+        self.remove = Some(|renderer, ids| renderer.remove_textures(ids));
+    }
+
+    /// Unregisters the textures. GPU textures are released at the beginning of the next render
+    pub fn release(&mut self, renderer: &mut R) {
+        if self.ids.is_empty() {
+            return;
+        }
+        let ids = std::mem::take(&mut self.ids);
+        if let Some(remove) = self.remove {
+            remove(renderer, ids);
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Gamma-aware blending of text
+//!
+//! Dear ImGUI alpha-blends glyph coverage in sRGB space: light text on dark backgrounds looks thin
+//! and dark text looks smudged, which hurts readability of 10-12 px text. [`TextBlending`] adjusts
+//! the coverage of the font atlas by the text color instead.
+//!
+//! All fonts share one atlas texture, so the blending applies to every font. Tune the weight of a
+//! single font with [`super::FontLayer::rasterizer_multiply`].
+
+/// How renderers blend glyphs of the font atlas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextBlending {
+    /// Alpha blending of the coverage, as Dear ImGUI expects
+    Standard,
+    /// Coverage is raised to a power picked by the luminance of the text color: bright text gets
+    /// bolder and dark text thinner. One alpha for all channels
+    Luminance { gamma: f32 },
+    /// Like `Luminance`, but per color channel with dual-source blending, closer to LCD-style text.
+    /// Falls back to `Luminance` where dual-source blending is not supported
+    PerChannel { gamma: f32 },
+}
+
+impl Default for TextBlending {
+    fn default() -> Self {
+        Self::Standard
+    }
+}
+
+impl TextBlending {
+    /// Gamma commonly used for LCD text
+    pub const DEFAULT_GAMMA: f32 = 1.8;
+
+    /// `0` (standard), `1` (luminance) or `2` (per channel), as passed to shaders
+    pub fn shader_mode(self) -> i32 {
+        match self {
+            Self::Standard => 0,
+            Self::Luminance { .. } => 1,
+            Self::PerChannel { .. } => 2,
+        }
+    }
+
+    /// Gamma clamped to a usable range. `1.0` for `Standard`
+    pub fn gamma(self) -> f32 {
+        match self {
+            Self::Standard => 1.0,
+            Self::Luminance { gamma } | Self::PerChannel { gamma } => gamma.max(1.0).min(4.0),
+        }
+    }
+
+    /// `PerChannel` replaced with `Luminance` if dual-source blending is not available
+    pub fn supported(self, dual_source: bool) -> Self {
+        match self {
+            Self::PerChannel { gamma } if !dual_source => Self::Luminance { gamma },
+            _ => self,
+        }
+    }
+}
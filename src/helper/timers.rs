@@ -0,0 +1,165 @@
+//! Frame-rate independent timers for UI code
+
+use std::{collections::HashMap, time::Duration};
+
+/// Handle of a timer in [`Timers`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TimerId(u64);
+
+/// Easing function of a tween
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ease {
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+}
+
+impl Ease {
+    /// Maps `t` in `[0, 1]` to `[0, 1]`
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Self::Linear => t,
+            Self::InQuad => t * t,
+            Self::OutQuad => t * (2.0 - t),
+            Self::InOutQuad => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Timer {
+    /// Fires once at `at`
+    Delay { at: Duration },
+    Tween {
+        start: Duration,
+        duration: Duration,
+        ease: Ease,
+    },
+    /// On for the first half of each period
+    Blink { start: Duration, period: Duration },
+}
+
+/// Timer service ticked by [`crate::Backend::update_delta_time`]
+///
+/// ```no_run
+/// // on opening a popup
+/// let fade = backend.timers.tween(Duration::from_millis(200), Ease::OutQuad);
+///
+/// // in UI code
+/// let alpha = backend.timers.progress(fade).unwrap_or(1.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Timers {
+    now: Duration,
+    next_id: u64,
+    timers: HashMap<TimerId, Timer>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Advances the clock
+    pub fn tick(&mut self, dt: Duration) {
+        self.now += dt;
+    }
+
+    /// Total time ticked so far
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    fn add(&mut self, timer: Timer) -> TimerId {
+        let id = TimerId(self.next_id);
+        self.next_id += 1;
+        self.timers.insert(id, timer);
+        id
+    }
+
+    /// One-shot timer that fires after `after`
+    pub fn delay(&mut self, after: Duration) -> TimerId {
+        self.add(Timer::Delay {
+            at: self.now + after,
+        })
+    }
+
+    pub fn tween(&mut self, duration: Duration, ease: Ease) -> TimerId {
+        self.add(Timer::Tween {
+            start: self.now,
+            duration,
+            ease,
+        })
+    }
+
+    /// Blinking timer (e.g. for cursors)
+    pub fn blink(&mut self, period: Duration) -> TimerId {
+        self.add(Timer::Blink {
+            start: self.now,
+            period,
+        })
+    }
+
+    pub fn remove(&mut self, id: TimerId) {
+        self.timers.remove(&id);
+    }
+
+    /// If the one-shot timer has fired or the tween has finished
+    pub fn is_done(&self, id: TimerId) -> bool {
+        match self.timers.get(&id) {
+            Some(Timer::Delay { at }) => self.now >= *at,
+            Some(Timer::Tween {
+                start, duration, ..
+            }) => self.now >= *start + *duration,
+            _ => false,
+        }
+    }
+
+    /// Returns true only once when the timer is done, removing it
+    pub fn take_done(&mut self, id: TimerId) -> bool {
+        let done = self.is_done(id);
+        if done {
+            self.remove(id);
+        }
+        done
+    }
+
+    /// Eased progress of a tween in `[0, 1]`. Always `1.0` if motion is reduced
+    pub fn progress(&self, id: TimerId) -> Option<f32> {
+        let reduce_motion = super::motion_preferences().reduce_motion;
+        match self.timers.get(&id)? {
+            Timer::Tween {
+                start,
+                duration,
+                ease,
+            } => {
+                let t = if reduce_motion || duration.as_secs_f32() <= 0.0 {
+                    1.0
+                } else {
+                    ((self.now - *start).as_secs_f32() / duration.as_secs_f32()).min(1.0)
+                };
+                Some(ease.apply(t))
+            }
+            _ => None,
+        }
+    }
+
+    /// If the blinking timer is in its "on" phase. Always on if motion is reduced
+    pub fn is_on(&self, id: TimerId) -> bool {
+        match self.timers.get(&id) {
+            Some(Timer::Blink { .. }) if super::motion_preferences().reduce_motion => true,
+            Some(Timer::Blink { start, period }) if period.as_nanos() > 0 => {
+                let elapsed = (self.now - *start).as_nanos();
+                elapsed % period.as_nanos() < period.as_nanos() / 2
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Toast notifications rendered with the foreground draw list
+
+use std::{collections::VecDeque, time::Duration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToastLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Look of [`Toasts`]. Lengths are in units of the current font size so that they scale with DPI
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToastTheme {
+    pub corner: Corner,
+    pub background: [f32; 4],
+    pub text: [f32; 4],
+    /// Accent colors of the left bar, indexed by [`ToastLevel`]
+    pub info: [f32; 4],
+    pub warn: [f32; 4],
+    pub error: [f32; 4],
+    pub padding: f32,
+    pub spacing: f32,
+    pub rounding: f32,
+    pub fade: Duration,
+}
+
+impl Default for ToastTheme {
+    fn default() -> Self {
+        Self {
+            corner: Corner::BottomRight,
+            background: [0.1, 0.1, 0.12, 0.9],
+            text: [1.0, 1.0, 1.0, 1.0],
+            info: [0.3, 0.6, 1.0, 1.0],
+            warn: [1.0, 0.75, 0.2, 1.0],
+            error: [1.0, 0.3, 0.3, 1.0],
+            padding: 0.5,
+            spacing: 0.4,
+            rounding: 0.3,
+            fade: Duration::from_millis(300),
+        }
+    }
+}
+
+impl ToastTheme {
+    fn accent(&self, level: ToastLevel) -> [f32; 4] {
+        match level {
+            ToastLevel::Info => self.info,
+            ToastLevel::Warn => self.warn,
+            ToastLevel::Error => self.error,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Toast {
+    level: ToastLevel,
+    text: String,
+    duration: Duration,
+    elapsed: Duration,
+}
+
+impl Toast {
+    /// Fades in and out unless motion is reduced
+    fn alpha(&self, fade: Duration) -> f32 {
+        let fade = fade.as_secs_f32();
+        if fade <= 0.0 || super::motion_preferences().reduce_motion {
+            return 1.0;
+        }
+
+        let t_in = self.elapsed.as_secs_f32() / fade;
+        let t_out = (self.duration.as_secs_f32() - self.elapsed.as_secs_f32()) / fade;
+        t_in.min(t_out).max(0.0).min(1.0)
+    }
+}
+
+/// Stacked, fading notifications. Owned by [`crate::Backend`] and rendered on `end_frame`
+#[derive(Debug, Clone, Default)]
+pub struct Toasts {
+    toasts: VecDeque<Toast>,
+    pub theme: ToastTheme,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, level: ToastLevel, text: impl Into<String>, duration: Duration) {
+        self.toasts.push_back(Toast {
+            level,
+            text: text.into(),
+            duration,
+            elapsed: Duration::default(),
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.toasts.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.toasts.is_empty()
+    }
+
+    /// Advances the toasts with `io.delta_time` and draws them onto the foreground draw list
+    pub fn render(&mut self, ui: &imgui::Ui) {
+        let dt = Duration::from_secs_f32(ui.io().delta_time.max(0.0));
+        for toast in self.toasts.iter_mut() {
+            toast.elapsed += dt;
+        }
+        self.toasts.retain(|t| t.elapsed < t.duration);
+
+        if self.toasts.is_empty() {
+            return;
+        }
+
+        let theme = &self.theme;
+        let unit = ui.current_font_size();
+        let padding = theme.padding * unit;
+        let spacing = theme.spacing * unit;
+        let rounding = theme.rounding * unit;
+        let bar_w = 0.25 * unit;
+        let display = ui.io().display_size;
+
+        let draw_list = ui.get_foreground_draw_list();
+        // newest toast is closest to the corner
+        let mut offset = spacing;
+        for toast in self.toasts.iter().rev() {
+            let alpha = toast.alpha(theme.fade);
+            let text_size = ui.calc_text_size(&toast.text);
+            let size = [
+                text_size[0] + bar_w + 2.0 * padding,
+                text_size[1] + 2.0 * padding,
+            ];
+
+            let x = match theme.corner {
+                Corner::TopLeft | Corner::BottomLeft => spacing,
+                Corner::TopRight | Corner::BottomRight => display[0] - spacing - size[0],
+            };
+            let y = match theme.corner {
+                Corner::TopLeft | Corner::TopRight => offset,
+                Corner::BottomLeft | Corner::BottomRight => display[1] - offset - size[1],
+            };
+            offset += size[1] + spacing;
+
+            let with_alpha = |mut c: [f32; 4]| {
+                c[3] *= alpha;
+                c
+            };
+
+            draw_list
+                .add_rect(
+                    [x, y],
+                    [x + size[0], y + size[1]],
+                    with_alpha(theme.background),
+                )
+                .filled(true)
+                .rounding(rounding)
+                .build();
+            draw_list
+                .add_rect(
+                    [x, y],
+                    [x + bar_w, y + size[1]],
+                    with_alpha(theme.accent(toast.level)),
+                )
+                .filled(true)
+                .build();
+            draw_list.add_text(
+                [x + bar_w + padding, y + padding],
+                with_alpha(theme.text),
+                &toast.text,
+            );
+        }
+    }
+}
@@ -0,0 +1,157 @@
+/*!
+Headless UI test runner on the null backend
+
+`imgui-sys` doesn't ship Dear ImGui's C++ test engine, so this is a small Rust-side equivalent:
+register tests, run each one for some frames with [`NullPlatform`] and [`NullRenderer`], and collect
+the reports.
+
+```no_run
+let mut engine = UiTestEngine::new([1280.0, 720.0]);
+engine.register("button is clickable", 3, |cx| {
+    cx.platform.mouse_pos = [20.0, 40.0];
+    cx.platform.mouse_down[0] = cx.frame == 1;
+    let clicked = cx.ui.button("OK");
+    cx.check(cx.frame != 2 || clicked, "button was not clicked");
+});
+
+for report in engine.run_all() {
+    println!("{}", report);
+}
+```
+*/
+
+use std::{fmt, time::Duration};
+
+use crate::{platform::NullPlatform, renderer::NullRenderer, Backend};
+
+/// Passed to a test every frame
+pub struct UiTestContext<'a, 'ui> {
+    pub ui: &'a imgui::Ui<'ui>,
+    /// Frame index starting from zero
+    pub frame: usize,
+    /// Simulated input, applied from the next frame
+    pub platform: &'a mut NullPlatform,
+    errors: &'a mut Vec<String>,
+}
+
+impl<'a, 'ui> UiTestContext<'a, 'ui> {
+    /// Records a failure if `cond` is false
+    pub fn check(&mut self, cond: bool, msg: impl fmt::Display) {
+        if !cond {
+            self.errors.push(format!("frame {}: {}", self.frame, msg));
+        }
+    }
+}
+
+type TestFn = Box<dyn FnMut(&mut UiTestContext)>;
+
+struct UiTest {
+    name: String,
+    n_frames: usize,
+    f: TestFn,
+}
+
+/// Result of a test
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UiTestReport {
+    pub name: String,
+    pub errors: Vec<String>,
+}
+
+impl UiTestReport {
+    pub fn passed(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl fmt::Display for UiTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.passed() {
+            write!(f, "[ok] {}", self.name)
+        } else {
+            write!(f, "[FAILED] {}", self.name)?;
+            for err in &self.errors {
+                write!(f, "\n    {}", err)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Registry and runner of headless UI tests
+pub struct UiTestEngine {
+    display_size: [f32; 2],
+    tests: Vec<UiTest>,
+}
+
+impl fmt::Debug for UiTestEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("UiTestEngine")
+            .field("display_size", &self.display_size)
+            .field("n_tests", &self.tests.len())
+            .finish()
+    }
+}
+
+impl UiTestEngine {
+    pub fn new(display_size: [f32; 2]) -> Self {
+        Self {
+            display_size,
+            tests: Vec::new(),
+        }
+    }
+
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        n_frames: usize,
+        f: impl FnMut(&mut UiTestContext) + 'static,
+    ) {
+        self.tests.push(UiTest {
+            name: name.into(),
+            n_frames,
+            f: Box::new(f),
+        });
+    }
+
+    /// Runs every test on a fresh ImGUI context
+    pub fn run_all(&mut self) -> Vec<UiTestReport> {
+        let display_size = self.display_size;
+        self.tests
+            .iter_mut()
+            .map(|test| Self::run(display_size, test))
+            .collect()
+    }
+
+    fn run(display_size: [f32; 2], test: &mut UiTest) -> UiTestReport {
+        let mut imgui = imgui::Context::create();
+        imgui.set_ini_filename(None);
+        let platform = NullPlatform::new(&mut imgui, display_size);
+        let renderer = NullRenderer::new(&mut imgui);
+        let mut backend = Backend::new(imgui, platform, renderer);
+
+        let mut errors = Vec::new();
+        for frame in 0..test.n_frames {
+            backend.update_delta_time(Duration::from_nanos(1_000_000_000 / 60));
+
+            let mut platform = backend.platform.clone();
+            let ui = backend.begin_frame(&());
+            (test.f)(&mut UiTestContext {
+                ui: &ui,
+                frame,
+                platform: &mut platform,
+                errors: &mut errors,
+            });
+            if let Err(err) = ui.end_frame(&mut (), &mut ()) {
+                match err {}
+            }
+
+            backend.platform = platform;
+        }
+
+        UiTestReport {
+            name: test.name.clone(),
+            errors,
+        }
+    }
+}
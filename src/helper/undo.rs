@@ -0,0 +1,200 @@
+//! Undo/redo stack for tool state
+
+use {
+    imgui::{Key, Ui},
+    std::time::Duration,
+};
+
+/// Undo/redo stack of snapshots
+///
+/// Edits recorded with the same tag within [`UndoStack::coalesce_window`] are merged into one, so
+/// that dragging a slider produces a single undo step. Use the backend clock
+/// ([`crate::helper::Timers::now`]) as the time source.
+///
+/// ```no_run
+/// let before = state.clone();
+/// if Slider::new("speed", 0.0, 10.0).build(&ui, &mut state.speed) {
+///     undo.record("speed", before, timers.now());
+/// }
+/// undo.handle_shortcuts(&ui, &mut state);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UndoStack<T> {
+    undo: Vec<T>,
+    redo: Vec<T>,
+    /// Tag and time of the last recorded edit
+    last: Option<(String, Duration)>,
+    pub coalesce_window: Duration,
+    /// Max number of undo steps
+    pub capacity: usize,
+}
+
+impl<T> Default for UndoStack<T> {
+    fn default() -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            last: None,
+            coalesce_window: Duration::from_millis(500),
+            capacity: 256,
+        }
+    }
+}
+
+impl<T> UndoStack<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the state *before* an edit
+    pub fn record(&mut self, tag: &str, before: T, now: Duration) {
+        self.redo.clear();
+
+        let coalesce = match self.last {
+            Some((ref last_tag, last_time)) => {
+                last_tag == tag
+                    && now.checked_sub(last_time).unwrap_or_default() <= self.coalesce_window
+            }
+            None => false,
+        };
+        self.last = Some((tag.to_string(), now));
+
+        // keep the oldest snapshot of the coalesced edits
+        if coalesce && !self.undo.is_empty() {
+            return;
+        }
+
+        self.undo.push(before);
+        if self.undo.len() > self.capacity {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Stops merging the next edit into the last one
+    pub fn break_coalescing(&mut self) {
+        self.last = None;
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.last = None;
+    }
+
+    pub fn undo(&mut self, current: &mut T) -> bool {
+        match self.undo.pop() {
+            Some(prev) => {
+                self.redo.push(std::mem::replace(current, prev));
+                self.last = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn redo(&mut self, current: &mut T) -> bool {
+        match self.redo.pop() {
+            Some(next) => {
+                self.undo.push(std::mem::replace(current, next));
+                self.last = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Ctrl+Z to undo, Ctrl+Y or Ctrl+Shift+Z to redo. Skipped while a text field is active so
+    /// that ImGUI's own text undo works
+    pub fn handle_shortcuts(&mut self, ui: &Ui, current: &mut T) -> bool {
+        let io = ui.io();
+        if !io.key_ctrl || io.want_text_input {
+            return false;
+        }
+
+        if ui.is_key_pressed(Key::Z) {
+            if io.key_shift {
+                self.redo(current)
+            } else {
+                self.undo(current)
+            }
+        } else if ui.is_key_pressed(Key::Y) {
+            self.redo(current)
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn coalesces_edits_with_the_same_tag() {
+        let mut undo = UndoStack::new();
+        let mut state = 3;
+
+        // a slider dragged from 0 to 3
+        undo.record("speed", 0, ms(0));
+        undo.record("speed", 1, ms(100));
+        undo.record("speed", 2, ms(200));
+        assert!(undo.undo(&mut state));
+        assert_eq!(state, 0);
+        assert!(!undo.can_undo());
+
+        // another tag or a pause starts a new step
+        undo.record("speed", 0, ms(1000));
+        undo.record("size", 1, ms(1100));
+        undo.record("size", 2, ms(2000));
+        assert_eq!(undo.undo.len(), 3);
+
+        // so does an explicit break
+        undo.break_coalescing();
+        undo.record("size", 3, ms(2100));
+        assert_eq!(undo.undo.len(), 4);
+    }
+
+    #[test]
+    fn evicts_the_oldest_step_over_capacity() {
+        let mut undo = UndoStack::new();
+        undo.capacity = 2;
+        let mut state = 3;
+
+        for i in 0..3 {
+            undo.record("edit", i, ms(i as u64 * 1000));
+        }
+        assert!(undo.undo(&mut state));
+        assert!(undo.undo(&mut state));
+        assert_eq!(state, 1);
+        assert!(!undo.undo(&mut state));
+    }
+
+    #[test]
+    fn recording_clears_redo() {
+        let mut undo = UndoStack::new();
+        let mut state = 2;
+
+        undo.record("a", 0, ms(0));
+        undo.record("b", 1, ms(0));
+        assert!(undo.undo(&mut state));
+        assert_eq!(state, 1);
+        assert!(undo.can_redo());
+
+        undo.record("c", 1, ms(0));
+        assert!(!undo.can_redo());
+        assert!(!undo.redo(&mut state));
+        assert_eq!(state, 1);
+    }
+}
@@ -0,0 +1,36 @@
+//! Memory layout of [`imgui::DrawVert`]
+
+use std::mem::{size_of, MaybeUninit};
+
+/// Byte offsets of the [`imgui::DrawVert`] members
+///
+/// They're derived from the actual struct so that renderers don't hardcode the default 20 bytes
+/// layout (builds with `IMGUI_OVERRIDE_DRAWVERT_STRUCT_LAYOUT` may reorder or add members).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VertexLayout {
+    /// Size of a vertex in bytes
+    pub stride: usize,
+    /// `[f32; 2]`
+    pub pos: usize,
+    /// `[f32; 2]`
+    pub uv: usize,
+    /// `[u8; 4]`, normalized
+    pub col: usize,
+}
+
+impl VertexLayout {
+    pub fn draw_vert() -> Self {
+        let vert = MaybeUninit::<imgui::DrawVert>::uninit();
+        let base = vert.as_ptr();
+        // we never read the uninitialized vertex; only take addresses of the members
+        unsafe {
+            let offset = |member: *const u8| member as usize - base as usize;
+            Self {
+                stride: size_of::<imgui::DrawVert>(),
+                pos: offset(std::ptr::addr_of!((*base).pos) as *const u8),
+                uv: offset(std::ptr::addr_of!((*base).uv) as *const u8),
+                col: offset(std::ptr::addr_of!((*base).col) as *const u8),
+            }
+        }
+    }
+}
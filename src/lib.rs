@@ -12,6 +12,7 @@ Backend creation:
 use imgui_backends::{helper::QuickStart, platform::ImGuiSdl2, renderer::ImGuiGlow};
 pub type Backend = imgui_backends::Backend<ImGuiSdl2, ImGuiGlow>;
 
+let mut glow = std::rc::Rc::new(glow);
 let mut backend = {
     let mut imgui = QuickStart { /* omitted */ }
         .create_context();
@@ -19,11 +20,7 @@ let mut backend = {
     let platform = ImGuiSdl2::new(&mut imgui, &window);
     let renderer = ImGuiGlow::new(&mut imgui, &glow)?;
 
-    Backend {
-        imgui,
-        platform,
-        renderer,
-    }
+    Backend::new(imgui, platform, renderer)
 };
 ```
 
@@ -39,35 +36,280 @@ let ui = backend.begin_frame(&window);
 ui.end_frame(&mut window, &mut glow)
     .map_err(Error::msg)?;
 ```
+
+`BackendUi` must be ended exactly once. Use `ui.prepare_render(&mut window)` and then
+`PreparedFrame::render` to render the UI at a later point of your frame.
+
+# Safety
+
+No public function is `unsafe`. Raw pointers and FFI calls stay in private renderer modules, and
+byte reinterpretation of vertex data is done in one place (`helper::as_bytes`). The remaining
+caller-side invariants are documented where they apply: raw FNA3D textures must outlive their
+registration. `ImGuiGlow` shares the `glow::Context` through `Rc`.
 */
 
 pub extern crate imgui;
 
+pub mod dpi;
 pub mod helper;
 pub mod platform;
+pub mod prelude;
 pub mod renderer;
 
+#[cfg(feature = "overlay")]
+pub mod overlay;
+
 use imgui::{Context, Io, Ui};
 use std::{
     ops::{Deref, DerefMut},
     time::Duration,
 };
 
+/// Which subsystem of ImGUI consumed an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Captured {
+    /// Not captured; forward the event to your application
+    No,
+    Mouse,
+    Keyboard,
+    TextInput,
+}
+
+impl Captured {
+    pub fn is_captured(self) -> bool {
+        self != Self::No
+    }
+}
+
+impl Default for Captured {
+    fn default() -> Self {
+        Self::No
+    }
+}
+
+/// Error of renderers that can tell device loss from other failures
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BackendError {
+    /// The device (e.g. the GL context) was lost and the frame was aborted. GPU objects of the
+    /// renderer are forgotten; recreate the device and call [`Backend::resume`] with it
+    #[error("device lost")]
+    DeviceLost,
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<String> for BackendError {
+    fn from(s: String) -> Self {
+        Self::Other(s)
+    }
+}
+
+/// Serializes ImGUI contexts of headless harnesses and tests: Dear ImGUI has one current context
+/// per process, so contexts of parallel tests would collide
+#[cfg(any(test, feature = "test-engine"))]
+pub(crate) fn lock_imgui_context() -> std::sync::MutexGuard<'static, ()> {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    // a failed test poisons the lock, but its context is dropped anyways
+    LOCK.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Configuration of `imgui` this crate was built with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// `ImWchar` is 32 bits, i.e. glyphs outside of the BMP (such as emoji) can be loaded
+    pub wchar32: bool,
+    pub freetype: bool,
+    pub docking: bool,
+    /// Size of [`imgui::DrawIdx`] in bytes
+    pub index_size: usize,
+}
+
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    version: env!("CARGO_PKG_VERSION"),
+    wchar32: std::mem::size_of::<imgui::sys::ImWchar>() == 4,
+    freetype: cfg!(feature = "freetype"),
+    docking: cfg!(feature = "docking"),
+    index_size: std::mem::size_of::<imgui::DrawIdx>(),
+};
+
+/// Features of this crate
+const FEATURES: &[(&str, bool)] = &[
+    ("sdl2", cfg!(feature = "sdl2")),
+    ("winit", cfg!(feature = "winit")),
+    ("windows", cfg!(feature = "windows")),
+    ("glow", cfg!(feature = "glow")),
+    ("wgpu", cfg!(feature = "wgpu")),
+    ("ash", cfg!(feature = "ash")),
+    ("fna3d", cfg!(feature = "fna3d")),
+    ("rokol", cfg!(feature = "rokol")),
+    ("rfd", cfg!(feature = "rfd")),
+    ("arboard", cfg!(feature = "arboard")),
+    ("image", cfg!(feature = "image")),
+    ("file-dialog", cfg!(feature = "file-dialog")),
+    ("test-engine", cfg!(feature = "test-engine")),
+    ("dev-shaders", cfg!(feature = "dev-shaders")),
+    ("overlay", cfg!(feature = "overlay")),
+    ("freetype", cfg!(feature = "freetype")),
+    ("docking", cfg!(feature = "docking")),
+];
+
+impl BuildInfo {
+    /// Enabled features of this crate
+    pub fn features(&self) -> Vec<&'static str> {
+        FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+}
+
+/// Backend name given to `set_platform_name` and `set_renderer_name`:
+/// `<name> <version> (<details>; <os>-<arch>; <features>)`
+///
+/// `details` are what the backend can query at runtime, e.g. the GL version and driver.
+pub fn backend_name(name: &str, details: &[&str]) -> String {
+    let mut parts = details
+        .iter()
+        .filter(|d| !d.is_empty())
+        .map(|d| d.to_string())
+        .collect::<Vec<_>>();
+    parts.push(format!(
+        "{}-{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    ));
+    parts.push(BUILD_INFO.features().join(" "));
+
+    format!("{} {} ({})", name, BUILD_INFO.version, parts.join("; "))
+}
+
+/// Version and configuration of the backend, e.g. for an about window or bug reports. See
+/// [`Backend::about`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct About {
+    /// Set by the platform with [`backend_name`]
+    pub platform_name: Option<String>,
+    /// Set by the renderer with [`backend_name`]
+    pub renderer_name: Option<String>,
+    /// Version of Dear ImGUI
+    pub imgui_version: &'static str,
+    pub build: BuildInfo,
+}
+
+impl std::fmt::Display for About {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Dear ImGUI {}", self.imgui_version)?;
+        writeln!(
+            f,
+            "platform: {}",
+            self.platform_name.as_deref().unwrap_or("<unknown>")
+        )?;
+        writeln!(
+            f,
+            "renderer: {}",
+            self.renderer_name.as_deref().unwrap_or("<unknown>")
+        )?;
+        write!(
+            f,
+            "imgui: {}-bit indices, wchar32: {}, freetype: {}, docking: {}",
+            self.build.index_size * 8,
+            self.build.wchar32,
+            self.build.freetype,
+            self.build.docking
+        )
+    }
+}
+
 /// Half of an `imgui-rs` backend
 pub trait Platform {
     type Event;
     /// Dependency
     type Window;
-    /// Return if the event is captured by ImGUI
+    /// Return which subsystem of ImGUI captured the event, if any
     fn handle_event(
         &mut self,
         imgui: &mut Context,
         window: &Self::Window,
         event: &Self::Event,
-    ) -> bool;
+    ) -> Captured;
+    /// Handles a batch of events, e.g. the events collected in a frame. Returns the capture
+    /// decision of each event in order
+    ///
+    /// Platforms may coalesce compatible events (e.g. consecutive mouse motions) internally.
+    fn handle_events<'e, I>(
+        &mut self,
+        imgui: &mut Context,
+        window: &Self::Window,
+        events: I,
+    ) -> Vec<Captured>
+    where
+        I: IntoIterator<Item = &'e Self::Event>,
+        Self::Event: 'e,
+    {
+        events
+            .into_iter()
+            .map(|ev| self.handle_event(imgui, window, ev))
+            .collect()
+    }
+    /// Window size in logical pixels (ImGUI's display size)
+    fn window_size(&self, window: &Self::Window) -> dpi::LogicalSize;
+    /// Drawable size of the window in physical pixels
+    fn framebuffer_size(&self, window: &Self::Window) -> dpi::PhysicalSize;
+    /// Sets a mapping from OS mouse coordinates to UI coordinates. `None` resets it to identity
+    fn set_pointer_transform(&mut self, transform: Option<dpi::PointerTransform>);
     /// Sets up input state
     fn prepare_frame(&mut self, io: &mut Io, window: &Self::Window);
     fn prepare_render(&mut self, ui: &Ui<'_>, window: &Self::Window);
+    /// One-line description of the event for [`helper::InputLog`]
+    fn describe_event(&self, _event: &Self::Event) -> String {
+        "<event>".to_string()
+    }
+    /// Reduced-motion setting of the OS accessibility options, if the platform can query it
+    fn motion_preferences(&self) -> Option<helper::MotionPreferences> {
+        None
+    }
+    /// Enables or disables IME composition, e.g. while a password field is focused. Called every
+    /// frame before [`Self::prepare_render`]
+    fn set_ime_enabled(&mut self, _window: &Self::Window, _enabled: bool) {}
+    /// Image the user pasted (e.g. Ctrl+V) while the OS clipboard held one, if the platform supports
+    /// clipboard images. Upload it with [`BackendUi::temp_texture`] for an instant preview
+    fn take_clipboard_image(&mut self) -> Option<platform::ClipboardImage> {
+        None
+    }
+    /// Unhooks OS integrations (clipboard, IME, mouse capture) while the window is still alive
+    fn shutdown(&mut self, _imgui: &mut Context, _window: &Self::Window) {}
+}
+
+/// Sampling filter of the font atlas texture
+///
+/// `Nearest` makes small pixel fonts sharp, but it only looks right when the UI scale is an exact
+/// integer. It's independent of user textures (filtered as configured per renderer, linear by
+/// default) and survives recreation of the atlas texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FontSampling {
+    Linear,
+    Nearest,
+}
+
+impl Default for FontSampling {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+/// Alias of [`FontSampling`]
+pub type FontTextureFilter = FontSampling;
+
+/// Font atlas texture of a renderer. See [`Backend::font_atlas_info`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FontAtlasInfo {
+    pub tex_id: imgui::TextureId,
+    /// Size in pixels
+    pub size: [u32; 2],
+    /// Incremented every time the atlas texture is (re)created or its pixels are replaced
+    pub generation: u64,
 }
 
 /// Half of an `imgui-rs` backend
@@ -75,12 +317,144 @@ pub trait Renderer {
     /// Rendering context
     type Device;
     type Error;
+    /// If the renderer honors `vtx_offset` of draw commands (`RENDERER_HAS_VTX_OFFSET`)
+    const HAS_VTX_OFFSET: bool = false;
+    /// If the renderer writes sRGB-correct colors
+    const SUPPORTS_SRGB: bool = false;
+    /// If registered textures can be updated in place
+    const SUPPORTS_TEXTURE_UPDATE: bool = false;
+    /// Largest index type the renderer can draw, in bytes
+    const MAX_INDEX_SIZE: usize = 2;
+    /// Maximum width/height of textures on the device, if it can be queried
+    fn max_texture_size(&self, _device: &Self::Device) -> Option<u32> {
+        None
+    }
+    /// Font atlas texture. `None` while device objects are invalidated or if the renderer doesn't
+    /// expose it
+    fn font_atlas_info(&self) -> Option<FontAtlasInfo> {
+        None
+    }
     /// Render
     fn render(
         &mut self,
         draw_data: &imgui::DrawData,
         device: &mut Self::Device,
     ) -> std::result::Result<(), Self::Error>;
+    /// Called by [`Backend`] on the first frame and whenever the framebuffer size changes, in
+    /// physical pixels. Renderers that cache framebuffer-dependent state update it here
+    fn on_resize(&mut self, _width: u32, _height: u32) {}
+    /// Runs a user callback in the middle of the UI pass. The default calls it as Dear ImGUI
+    /// expects. Render states changed by the callback are kept until a `ResetRenderState` command
+    fn run_callback(
+        &mut self,
+        _device: &mut Self::Device,
+        callback: &helper::DrawCallback,
+    ) -> std::result::Result<(), Self::Error> {
+        callback.invoke();
+        Ok(())
+    }
+    /// Sets the sampling filter of the font atlas texture, applied from the next render
+    fn set_font_sampling(&mut self, sampling: FontSampling);
+    /// Multiplies the alpha of the whole UI layer by `opacity` in `[0, 1]`
+    fn set_ui_opacity(&mut self, opacity: f32);
+    /// Releases GPU objects (shaders, buffers, font and user textures) while the device is still
+    /// alive, e.g. before the GL context is lost on Android
+    fn invalidate_device_objects(&mut self, device: &mut Self::Device);
+    /// (Re)creates GPU objects released by [`Self::invalidate_device_objects`]
+    fn create_device_objects(
+        &mut self,
+        imgui: &mut imgui::Context,
+        device: &mut Self::Device,
+    ) -> std::result::Result<(), Self::Error>;
+    /// Replaces the font atlas texture with RGBA8 pixels, keeping its texture ID. Returns
+    /// `Ok(false)` if the renderer can't (e.g. while device objects are invalidated)
+    fn replace_font_atlas(
+        &mut self,
+        _device: &mut Self::Device,
+        _pixels: &[u8],
+        _w: u32,
+        _h: u32,
+    ) -> std::result::Result<bool, Self::Error> {
+        Ok(false)
+    }
+}
+
+/// [`Renderer`] that can upload user textures
+pub trait RegisterTexture: Renderer {
+    /// Uploads an RGBA8 texture. If `retain` is true, a CPU copy of the pixels is kept so that the
+    /// texture can be restored after device loss
+    fn register_rgba8(
+        &mut self,
+        device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> Result<imgui::TextureId, Self::Error>;
+    /// Uploads textures at once, e.g. hundreds of thumbnails of an asset browser. Logs one
+    /// diagnostics event for the whole batch
+    fn register_textures<'p, I>(
+        &mut self,
+        device: &mut Self::Device,
+        uploads: I,
+    ) -> Result<Vec<imgui::TextureId>, Self::Error>
+    where
+        I: IntoIterator<Item = helper::TextureUpload<'p>>,
+    {
+        let mut n_bytes = 0;
+        let ids = uploads
+            .into_iter()
+            .map(|up| {
+                n_bytes += up.pixels.len();
+                let id = self.register_rgba8(device, up.pixels, up.w, up.h, up.retain)?;
+                if let Some(label) = up.label {
+                    self.set_texture_label(device, id, label);
+                }
+                Ok(id)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        log::debug!(
+            "imgui-backends: registered {} textures ({} bytes)",
+            ids.len(),
+            n_bytes
+        );
+        Ok(ids)
+    }
+    /// Unregisters textures at once. Their GPU textures are released together at the beginning of
+    /// the next render. Returns how many textures were unregistered
+    fn remove_textures<I>(&mut self, ids: I) -> usize
+    where
+        I: IntoIterator<Item = imgui::TextureId>;
+    /// Names a user texture for debug UIs and, where the API supports object labels or markers,
+    /// for graphics debuggers such as RenderDoc
+    fn set_texture_label(&mut self, device: &mut Self::Device, id: imgui::TextureId, label: &str);
+    /// Live user textures, e.g. for texture inspectors. See
+    /// [`helper::TextureRegistry::textures_iter`]
+    fn texture_infos(&self) -> Vec<helper::TextureInfo<'_>>;
+}
+
+/// Sets ImGUI backend flags from the capabilities of the renderer. [`Backend::new`] calls it for
+/// you
+pub fn set_renderer_flags<R: Renderer>(io: &mut Io) {
+    if R::HAS_VTX_OFFSET {
+        io.backend_flags
+            .insert(imgui::BackendFlags::RENDERER_HAS_VTX_OFFSET);
+    }
+}
+
+/// Checks if the renderer can draw what [`BUILD_INFO`] `imgui` generates. [`Backend::new`] panics
+/// on failure
+pub fn check_renderer<R: Renderer>() -> Result<(), BackendError> {
+    if BUILD_INFO.index_size > R::MAX_INDEX_SIZE {
+        return Err(BackendError::Other(format!(
+            "`imgui` is built with {}-bit indices, but `{}` draws up to {}-bit indices",
+            BUILD_INFO.index_size * 8,
+            std::any::type_name::<R>(),
+            R::MAX_INDEX_SIZE * 8,
+        )));
+    }
+
+    Ok(())
 }
 
 /// `imgui-rs` backend = `imgui::Context` + `Platform` + `Renderer`
@@ -93,6 +467,122 @@ where
     pub imgui: imgui::Context,
     pub platform: P,
     pub renderer: R,
+    /// Ticked by [`Self::update_delta_time`]
+    pub timers: helper::Timers,
+    /// Applied to delta times given to [`Self::update_delta_time`]
+    pub delta_clamp: helper::DeltaClamp,
+    /// Rendered on [`BackendUi::end_frame`]
+    pub toasts: helper::Toasts,
+    /// Updated on [`BackendUi::end_frame`]
+    pub sounds: helper::SoundHooks,
+    /// Recorded on [`Self::handle_event`] and shown on [`BackendUi::end_frame`] while enabled
+    pub input_log: helper::InputLog,
+    /// Updated on [`BackendUi::end_frame`]
+    pub telemetry: helper::TelemetryHooks,
+    /// Input latency measurement, fed while enabled
+    pub latency: helper::LatencyProbe,
+    /// Frame pacing measurement, fed while enabled
+    pub pacing: helper::PacingProbe,
+    /// Textures of [`BackendUi::temp_texture`], released after the frame is rendered
+    temp_textures: helper::TempTextures<R>,
+    /// Middlewares called on each step of the frame
+    pub plugins: helper::Plugins<P>,
+    /// Virtual cursor set with [`Self::set_virtual_pointer`]
+    pointer_override: helper::PointerOverride,
+    /// Set until the first [`Self::begin_frame`] unless the backend is built eagerly
+    first_frame: bool,
+    /// Font atlas built on the first frame, uploaded on the next render
+    pending_font: Option<FontPixels>,
+    /// Framebuffer size of the last frame, to tell the renderer about resizes
+    fb_size: Option<dpi::PhysicalSize>,
+    /// Set with [`Self::set_ui_visible`]
+    ui_visible: bool,
+}
+
+/// CPU copy of a font atlas waiting for upload
+#[derive(Debug)]
+struct FontPixels {
+    pixels: Vec<u8>,
+    w: u32,
+    h: u32,
+}
+
+/// Builder of [`Backend`]
+///
+/// By default the first frame is set up lazily: on the first [`Backend::begin_frame`], the display
+/// size is read from the window and fonts added after the renderer was created are built and then
+/// uploaded on the first render. So the backend can be created before the window is shown or
+/// resized, and before fonts are added.
+#[derive(Debug)]
+pub struct BackendBuilder<P, R>
+where
+    P: Platform,
+    R: Renderer,
+{
+    imgui: imgui::Context,
+    platform: P,
+    renderer: R,
+    eager: bool,
+}
+
+impl<P, R> BackendBuilder<P, R>
+where
+    P: Platform,
+    R: Renderer,
+{
+    pub fn new(imgui: imgui::Context, platform: P, renderer: R) -> Self {
+        Self {
+            imgui,
+            platform,
+            renderer,
+            eager: false,
+        }
+    }
+
+    /// Keeps the old behavior: the font atlas of the renderer is used as is and the first frame
+    /// starts without any setup
+    pub fn eager(mut self) -> Self {
+        self.eager = true;
+        self
+    }
+
+    pub fn build(self) -> Backend<P, R> {
+        let Self {
+            mut imgui,
+            platform,
+            renderer,
+            eager,
+        } = self;
+
+        if let Err(err) = self::check_renderer::<R>() {
+            panic!("imgui-backends: unsupported build configuration: {}", err);
+        }
+        self::set_renderer_flags::<R>(imgui.io_mut());
+        if let Some(prefs) = platform.motion_preferences() {
+            helper::set_motion_preferences(prefs);
+        }
+
+        Backend {
+            imgui,
+            platform,
+            renderer,
+            timers: helper::Timers::new(),
+            delta_clamp: helper::DeltaClamp::new(),
+            toasts: helper::Toasts::new(),
+            sounds: helper::SoundHooks::new(),
+            input_log: helper::InputLog::new(),
+            telemetry: helper::TelemetryHooks::new(),
+            latency: helper::LatencyProbe::new(),
+            pacing: helper::PacingProbe::new(),
+            temp_textures: helper::TempTextures::new(),
+            plugins: helper::Plugins::new(),
+            pointer_override: helper::PointerOverride::new(),
+            first_frame: !eager,
+            pending_font: None,
+            fb_size: None,
+            ui_visible: true,
+        }
+    }
 }
 
 impl<P, R> Backend<P, R>
@@ -100,33 +590,400 @@ where
     P: Platform,
     R: Renderer,
 {
-    pub fn handle_event(&mut self, window: &P::Window, event: &P::Event) {
-        self.platform.handle_event(&mut self.imgui, window, event);
+    /// Creates the backend with the lazy first frame. See [`BackendBuilder`]
+    pub fn new(imgui: imgui::Context, platform: P, renderer: R) -> Self {
+        BackendBuilder::new(imgui, platform, renderer).build()
     }
 
+    pub fn builder(imgui: imgui::Context, platform: P, renderer: R) -> BackendBuilder<P, R> {
+        BackendBuilder::new(imgui, platform, renderer)
+    }
+
+    /// Returns which subsystem of ImGUI captured the event, if any
+    ///
+    /// Events handled during an active frame or inside a draw callback are dropped: it's logged,
+    /// and panics in debug builds. See [`Self::try_handle_event`].
+    pub fn handle_event(&mut self, window: &P::Window, event: &P::Event) -> Captured {
+        match self.try_handle_event(window, event) {
+            Ok(captured) => captured,
+            Err(err) => self::misuse(err, Captured::No),
+        }
+    }
+
+    /// [`Self::handle_event`] returning misuse of the frame protocol as an error
+    pub fn try_handle_event(
+        &mut self,
+        window: &P::Window,
+        event: &P::Event,
+    ) -> Result<Captured, helper::ReentrancyError> {
+        helper::check_event()?;
+        self.latency.on_event();
+        let mut captured = self.platform.handle_event(&mut self.imgui, window, event);
+        if !self.ui_visible {
+            // a hidden UI doesn't block the application's input
+            captured = Captured::No;
+        }
+
+        if self.input_log.is_enabled() {
+            self.input_log.record(helper::InputLogEntry {
+                time: self.timers.now(),
+                event: self.platform.describe_event(event),
+                captured,
+                io: helper::IoSnapshot::new(self.imgui.io()),
+            });
+        }
+        self.plugins.on_event(event, captured);
+
+        Ok(captured)
+    }
+
+    /// Handles a batch of events in one call. Returns the capture decision of each event in order.
+    /// Dropped like [`Self::handle_event`] during an active frame
+    pub fn handle_events<'e, I>(&mut self, window: &P::Window, events: I) -> Vec<Captured>
+    where
+        I: IntoIterator<Item = &'e P::Event>,
+        P::Event: 'e,
+    {
+        if let Err(err) = helper::check_event() {
+            let dropped = events.into_iter().map(|_| Captured::No).collect();
+            return self::misuse(err, dropped);
+        }
+
+        if !self.input_log.is_enabled() && !self.latency.is_enabled() && self.plugins.is_empty() {
+            let mut captured = self.platform.handle_events(&mut self.imgui, window, events);
+            if !self.ui_visible {
+                captured.iter_mut().for_each(|c| *c = Captured::No);
+            }
+            return captured;
+        }
+
+        let events = events.into_iter().collect::<Vec<_>>();
+        for _ in 0..events.len() {
+            self.latency.on_event();
+        }
+        let mut captured =
+            self.platform
+                .handle_events(&mut self.imgui, window, events.iter().cloned());
+        if !self.ui_visible {
+            captured.iter_mut().for_each(|c| *c = Captured::No);
+        }
+
+        for (ev, captured) in events.iter().zip(captured.iter()) {
+            self.plugins.on_event(ev, *captured);
+            if !self.input_log.is_enabled() {
+                continue;
+            }
+            self.input_log.record(helper::InputLogEntry {
+                time: self.timers.now(),
+                event: self.platform.describe_event(ev),
+                captured: *captured,
+                io: helper::IoSnapshot::new(self.imgui.io()),
+            });
+        }
+
+        captured
+    }
+
+    /// Advances ImGUI and the timers. Delta times are clamped with [`Self::delta_clamp`] so that
+    /// long pauses (debugger breaks, sleep) don't skip animations and timeouts
     pub fn update_delta_time(&mut self, dt: Duration) {
+        let dt = self.delta_clamp.clamp(dt);
         self.imgui.io_mut().update_delta_time(dt);
+        self.timers.tick(dt);
+    }
+
+    /// Presents the frame, timed by [`Self::pacing`] while it's enabled
+    pub fn present(&mut self, target: &mut impl helper::Present) {
+        let start = std::time::Instant::now();
+        target.present();
+        self.pacing
+            .on_present(start, target.swap_interval(), target.refresh_rate());
+    }
+
+    /// Hides the UI (e.g. "hide all debug UI" hotkeys). Frames are still begun and ended so that
+    /// ImGUI state, `imgui.ini` and timers stay consistent, but [`PreparedFrame::render`] submits
+    /// nothing and events are not reported as captured
+    ///
+    /// Keep calling `end_frame` while the UI is hidden; skipping it breaks ImGUI's frame state.
+    pub fn set_ui_visible(&mut self, visible: bool) {
+        self.ui_visible = visible;
+    }
+
+    pub fn is_ui_visible(&self) -> bool {
+        self.ui_visible
+    }
+
+    /// Releases GPU resources of the renderer. Call it before the device is lost (e.g. when the
+    /// app enters background on mobile). User textures without retained sources are unregistered
+    pub fn suspend(&mut self, device: &mut R::Device) {
+        self.renderer.invalidate_device_objects(device);
+    }
+
+    /// Recreates GPU resources of the renderer released by [`Self::suspend`]
+    pub fn resume(&mut self, device: &mut R::Device) -> Result<(), R::Error> {
+        self.renderer.create_device_objects(&mut self.imgui, device)
+    }
+
+    /// Tears down the backend in order while the window and the device are still alive: saves
+    /// `imgui.ini`, unhooks the platform and releases GPU resources of the renderer
+    ///
+    /// Prefer it to relying on `Drop`, which may run after the device is gone.
+    pub fn shutdown(mut self, window: &P::Window, device: &mut R::Device) -> std::io::Result<()> {
+        let saved = self.save_ini();
+        self.platform.shutdown(&mut self.imgui, window);
+        self.renderer.invalidate_device_objects(device);
+        saved
+    }
+
+    /// Writes `imgui.ini` now and stops ImGUI from writing it again on drop
+    fn save_ini(&mut self) -> std::io::Result<()> {
+        let path = match self.imgui.ini_filename() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+
+        let mut buf = String::new();
+        self.imgui.save_ini_settings(&mut buf);
+        self.imgui.set_ini_filename(None);
+        std::fs::write(path, buf)
+    }
+
+    /// Drives the ImGUI mouse with a pointer synthesized by the application (e.g. a gamepad-driven
+    /// cursor on consoles) instead of the OS mouse. Call it every frame before
+    /// [`Self::begin_frame`]; `None` gives the mouse back to the platform
+    ///
+    /// Returns [`Captured::Mouse`] if the UI consumes the pointer, so that the engine can skip its
+    /// own handling of the virtual click.
+    pub fn set_virtual_pointer(&mut self, pointer: Option<helper::VirtualPointer>) -> Captured {
+        self.pointer_override.set(pointer)
+    }
+
+    /// Replaces the clipboard used for copy and paste in ImGUI text fields. `None` disables it
+    pub fn set_clipboard(&mut self, clipboard: Option<Box<dyn platform::ClipboardBackend>>) {
+        let clipboard = clipboard.unwrap_or_else(|| Box::new(platform::NullClipboard));
+        platform::set_clipboard(&mut self.imgui, clipboard);
+    }
+
+    /// Sets the sound feedback hooks. `None` disables them
+    pub fn set_ui_sounds(&mut self, sounds: Option<Box<dyn helper::UiSounds>>) {
+        self.sounds.set(sounds);
     }
 
-    /// TODO: begin frame with backbuffer size
+    /// Sets the usage event observer. `None` disables it
+    pub fn set_ui_observer(&mut self, observer: Option<Box<dyn helper::UiObserver>>) {
+        self.telemetry.set(observer);
+    }
+
+    /// Font atlas texture of the renderer, e.g. for embedding it in debug views. Watch `generation`
+    /// to tell when it's rebuilt
+    pub fn font_atlas_info(&self) -> Option<FontAtlasInfo> {
+        self.renderer.font_atlas_info()
+    }
+
+    /// Backend names, versions and build configuration. Print it in an about window or attach it
+    /// to bug reports
+    pub fn about(&self) -> About {
+        About {
+            platform_name: self.imgui.platform_name().map(|s| s.to_string()),
+            renderer_name: self.imgui.renderer_name().map(|s| s.to_string()),
+            imgui_version: imgui::dear_imgui_version(),
+            build: BUILD_INFO,
+        }
+    }
+
+    /// Fades the whole UI layer, e.g. to make it see-through while gameplay continues
+    pub fn set_ui_opacity(&mut self, opacity: f32) {
+        self.renderer.set_ui_opacity(opacity.max(0.0).min(1.0));
+    }
+
+    /// Switches the font atlas sampling. Prefer [`FontSampling::Nearest`] only when the UI scale is
+    /// an exact integer
+    pub fn set_font_sampling(&mut self, sampling: FontSampling) {
+        self.renderer.set_font_sampling(sampling);
+    }
+
+    /// Builds the font atlas if fonts were added after the renderer was created. The renderer
+    /// texture is replaced on the next render
+    fn setup_first_frame(&mut self) {
+        self.first_frame = false;
+
+        let mut fonts = self.imgui.fonts();
+        if fonts.is_built() {
+            return;
+        }
+
+        let atlas = fonts.build_rgba32_texture();
+        log::debug!(
+            "imgui-backends: font atlas rebuilt on the first frame ({}x{})",
+            atlas.width,
+            atlas.height
+        );
+        self.pending_font = Some(FontPixels {
+            pixels: atlas.data.to_vec(),
+            w: atlas.width,
+            h: atlas.height,
+        });
+    }
+
+    /// Begins a frame with the backbuffer size given by the platform
+    /// ([`Platform::framebuffer_size`]), so the display size follows window resizes
+    ///
+    /// Panics if a frame is already active or if it's called from inside a draw callback. See
+    /// [`Self::try_begin_frame`].
     pub fn begin_frame<'a>(&'a mut self, window: &P::Window) -> BackendUi<'a, P, R> {
+        let physical = self.platform.framebuffer_size(window);
+        let scale = physical.scale_from(self.platform.window_size(window));
+        self.begin_frame_with_size(window, physical.0, scale)
+    }
+
+    /// [`Self::begin_frame`] returning misuse of the frame protocol as an error
+    pub fn try_begin_frame<'a>(
+        &'a mut self,
+        window: &P::Window,
+    ) -> Result<BackendUi<'a, P, R>, helper::ReentrancyError> {
+        let physical = self.platform.framebuffer_size(window);
+        let scale = physical.scale_from(self.platform.window_size(window));
+        self.try_begin_frame_with_size(window, physical.0, scale)
+    }
+
+    /// Begins a frame with the backbuffer size in physical pixels and the framebuffer scale, e.g.
+    /// when the UI is rendered to a render target of a different size than the window
+    ///
+    /// `display_size` is set to `physical_size / scale`. Panics like [`Self::begin_frame`]
+    pub fn begin_frame_with_size<'a>(
+        &'a mut self,
+        window: &P::Window,
+        physical_size: [u32; 2],
+        scale: [f32; 2],
+    ) -> BackendUi<'a, P, R> {
+        match self.try_begin_frame_with_size(window, physical_size, scale) {
+            Ok(ui) => ui,
+            // ImGUI would assert on a nested frame anyway
+            Err(err) => panic!("imgui-backends: {}", err),
+        }
+    }
+
+    /// [`Self::begin_frame_with_size`] returning misuse of the frame protocol as an error
+    pub fn try_begin_frame_with_size<'a>(
+        &'a mut self,
+        window: &P::Window,
+        physical_size: [u32; 2],
+        scale: [f32; 2],
+    ) -> Result<BackendUi<'a, P, R>, helper::ReentrancyError> {
+        helper::check_begin_frame()?;
+        let scope = helper::FrameScope::begin();
+
+        if self.first_frame {
+            self.setup_first_frame();
+        }
         self.platform.prepare_frame(self.imgui.io_mut(), window);
-        BackendUi {
-            ui: self.imgui.frame(),
-            platform: &mut self.platform,
-            renderer: &mut self.renderer,
+
+        // overrides the display set by the platform
+        let scale = [
+            if scale[0] > 0.0 { scale[0] } else { 1.0 },
+            if scale[1] > 0.0 { scale[1] } else { 1.0 },
+        ];
+        let physical = dpi::PhysicalSize(physical_size);
+        dpi::set_display(self.imgui.io_mut(), physical.to_logical(scale), physical);
+        if self.fb_size != Some(physical) {
+            self.fb_size = Some(physical);
+            self.renderer.on_resize(physical_size[0], physical_size[1]);
         }
+
+        self.pointer_override.apply(self.imgui.io_mut());
+        helper::motion_preferences().apply(self.imgui.io_mut());
+        self.latency.on_begin_frame();
+        // left by frames ended with `finish` or `discard`
+        self.temp_textures.release(&mut self.renderer);
+        let ui = self.imgui.frame();
+        self.plugins.on_begin_frame(&ui);
+        Ok(BackendUi {
+            frame: Some(Frame {
+                ui,
+                scope,
+                platform: &mut self.platform,
+                renderer: &mut self.renderer,
+                toasts: &mut self.toasts,
+                sounds: &mut self.sounds,
+                input_log: &mut self.input_log,
+                telemetry: &mut self.telemetry,
+                latency: &mut self.latency,
+                pacing: &mut self.pacing,
+                temp_textures: &mut self.temp_textures,
+                plugins: &mut self.plugins,
+                pending_font: &mut self.pending_font,
+                ui_visible: self.ui_visible,
+                ime_blocked: false,
+                now: self.timers.now(),
+            }),
+        })
     }
 }
 
+/// Reports misuse of the frame protocol by APIs that can't return it: logged, and panics in debug
+/// builds
+fn misuse<T>(err: helper::ReentrancyError, fallback: T) -> T {
+    log::error!("imgui-backends: {}", err);
+    debug_assert!(false, "imgui-backends: {}", err);
+    fallback
+}
+
+/// Frame in progress, returned by [`Backend::begin_frame`]
+///
+/// The frame has to be ended exactly once, with [`Self::end_frame`], [`Self::prepare_render`],
+/// [`Self::finish`] or [`Self::discard`]. They consume the `BackendUi`, so a frame can't be
+/// rendered twice. Dropping it without ending the frame (e.g. early return with `?`) skips the
+/// backend's end-of-frame work: it's logged, and panics in debug builds.
 pub struct BackendUi<'a, P, R>
+where
+    P: Platform,
+    R: Renderer,
+{
+    /// `None` after the frame is ended
+    frame: Option<Frame<'a, P, R>>,
+}
+
+struct Frame<'a, P, R>
 where
     P: Platform,
     R: Renderer,
 {
     ui: imgui::Ui<'a>,
+    /// Marks the frame active on this thread until the frame is ended or dropped
+    scope: helper::FrameScope,
     platform: &'a mut P,
     renderer: &'a mut R,
+    toasts: &'a mut helper::Toasts,
+    sounds: &'a mut helper::SoundHooks,
+    input_log: &'a mut helper::InputLog,
+    telemetry: &'a mut helper::TelemetryHooks,
+    latency: &'a mut helper::LatencyProbe,
+    pacing: &'a mut helper::PacingProbe,
+    temp_textures: &'a mut helper::TempTextures<R>,
+    plugins: &'a mut helper::Plugins<P>,
+    pending_font: &'a mut Option<FontPixels>,
+    ui_visible: bool,
+    /// Set by [`BackendUi::input_password_imesafe`] while the field is focused
+    ime_blocked: bool,
+    now: Duration,
+}
+
+impl<'a, P, R> Drop for BackendUi<'a, P, R>
+where
+    P: Platform,
+    R: Renderer,
+{
+    fn drop(&mut self) {
+        if self.frame.is_none() || std::thread::panicking() {
+            return;
+        }
+
+        log::error!("imgui-backends: `BackendUi` dropped without ending the frame");
+        debug_assert!(
+            false,
+            "`BackendUi` dropped without ending the frame. Call `end_frame`, `prepare_render`, `finish` or `discard`"
+        );
+    }
 }
 
 impl<'a, P, R> Deref for BackendUi<'a, P, R>
@@ -136,7 +993,7 @@ where
 {
     type Target = imgui::Ui<'a>;
     fn deref(&self) -> &Self::Target {
-        &self.ui
+        &self.frame().ui
     }
 }
 
@@ -146,7 +1003,7 @@ where
     R: Renderer,
 {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.ui
+        &mut self.frame_mut().ui
     }
 }
 
@@ -156,7 +1013,7 @@ where
     R: Renderer,
 {
     fn as_ref(&self) -> &imgui::Ui<'a> {
-        &self.ui
+        &self.frame().ui
     }
 }
 
@@ -166,7 +1023,7 @@ where
     R: Renderer,
 {
     fn as_mut(&mut self) -> &mut imgui::Ui<'a> {
-        &mut self.ui
+        &mut self.frame_mut().ui
     }
 }
 
@@ -175,9 +1032,186 @@ where
     P: Platform,
     R: Renderer,
 {
+    // the frame is only taken by methods consuming `self`
+    fn frame(&self) -> &Frame<'a, P, R> {
+        self.frame.as_ref().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame<'a, P, R> {
+        self.frame.as_mut().unwrap()
+    }
+
+    /// Push notifications from inside a frame
+    pub fn toasts(&mut self) -> &mut helper::Toasts {
+        self.frame_mut().toasts
+    }
+
+    /// Track windows and items for the usage observer from inside a frame
+    pub fn telemetry(&mut self) -> &mut helper::TelemetryHooks {
+        self.frame_mut().telemetry
+    }
+
+    /// Password field with masked input. IME composition is disabled while it's focused so that
+    /// the password doesn't show up in the IME candidate window. Needs platform support (see
+    /// [`Platform::set_ime_enabled`])
+    pub fn input_password_imesafe(&mut self, label: &str, buf: &mut String) -> bool {
+        let frame = self.frame_mut();
+        let changed = frame.ui.input_text(label, buf).password(true).build();
+        if frame.ui.is_item_active() {
+            frame.ime_blocked = true;
+        }
+        changed
+    }
+
+    /// Ends the frame and renders it. Same as `prepare_render(window).render(device)`
     pub fn end_frame(self, window: &mut P::Window, device: &mut R::Device) -> Result<(), R::Error> {
-        self.platform.prepare_render(&self.ui, window);
-        self.renderer.render(self.ui.render(), device)?;
+        self.prepare_render(window).render(device)
+    }
+
+    /// Ends the frame and builds the draw data. Render it later in the frame with
+    /// [`PreparedFrame::render`]
+    pub fn prepare_render(self, window: &mut P::Window) -> PreparedFrame<'a, P, R> {
+        self.finish_impl(window)
+    }
+
+    /// Ends the frame without rendering. Submit the returned draw data yourself, e.g. at a custom
+    /// point of your frame or to multiple render targets
+    ///
+    /// Call [`helper::LatencyProbe::on_submit`] after submitting if you measure input latency, and
+    /// [`helper::PacingProbe::on_render_begin`] / `on_render_end` around it if you measure pacing.
+    /// Temporary textures of the frame are released on the next [`Backend::begin_frame`]. A font
+    /// atlas rebuilt on the first frame is uploaded on the next [`PreparedFrame::render`]. Skip
+    /// submission while [`Backend::is_ui_visible`] is `false`.
+    pub fn finish(self, window: &mut P::Window) -> &'a imgui::DrawData {
+        self.finish_impl(window).draw_data
+    }
+
+    /// Ends the frame without building draw data, e.g. when the frame is abandoned halfway
+    pub fn discard(mut self) {
+        // `imgui::Ui` ends the ImGUI frame on drop
+        self.frame = None;
+    }
+
+    fn finish_impl(mut self, window: &mut P::Window) -> PreparedFrame<'a, P, R> {
+        let Frame {
+            ui,
+            scope,
+            platform,
+            renderer,
+            toasts,
+            sounds,
+            input_log,
+            telemetry,
+            latency,
+            pacing,
+            temp_textures,
+            plugins,
+            pending_font,
+            ui_visible,
+            ime_blocked,
+            now,
+        } = self.frame.take().unwrap();
+
+        toasts.render(&ui);
+        input_log.render(&ui, now);
+        sounds.update(&ui);
+        telemetry.update(&ui);
+        platform.set_ime_enabled(window, !ime_blocked);
+        plugins.on_before_render(&ui);
+        platform.prepare_render(&ui, window);
+        let draw_data = ui.render();
+        drop(scope);
+        PreparedFrame {
+            draw_data,
+            renderer,
+            latency,
+            pacing,
+            temp_textures,
+            plugins,
+            pending_font,
+            ui_visible,
+        }
+    }
+}
+
+impl<'a, P, R> BackendUi<'a, P, R>
+where
+    P: Platform,
+    R: RegisterTexture,
+{
+    /// Uploads an RGBA8 texture valid only for this frame, e.g. for a one-off preview. It's
+    /// unregistered after the frame is rendered
+    pub fn temp_texture(
+        &mut self,
+        device: &mut R::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> Result<imgui::TextureId, R::Error> {
+        let frame = self.frame_mut();
+        let id = frame.renderer.register_rgba8(device, pixels, w, h, false)?;
+        frame.temp_textures.push(id);
+        Ok(id)
+    }
+}
+
+/// Ended frame waiting to be rendered, returned by [`BackendUi::prepare_render`]
+pub struct PreparedFrame<'a, P: Platform, R: Renderer> {
+    draw_data: &'a imgui::DrawData,
+    renderer: &'a mut R,
+    latency: &'a mut helper::LatencyProbe,
+    pacing: &'a mut helper::PacingProbe,
+    temp_textures: &'a mut helper::TempTextures<R>,
+    plugins: &'a mut helper::Plugins<P>,
+    pending_font: &'a mut Option<FontPixels>,
+    ui_visible: bool,
+}
+
+impl<'a, P: Platform, R: Renderer> PreparedFrame<'a, P, R> {
+    pub fn draw_data(&self) -> &'a imgui::DrawData {
+        self.draw_data
+    }
+
+    /// Renders the frame. It can be rendered only once. Nothing is submitted while the UI is hidden
+    /// with [`Backend::set_ui_visible`]
+    ///
+    /// Rendering from inside a draw callback is skipped: it's logged, and panics in debug builds.
+    pub fn render(self, device: &mut R::Device) -> Result<(), R::Error> {
+        if let Err(err) = helper::check_render() {
+            self.temp_textures.release(self.renderer);
+            return self::misuse(err, Ok(()));
+        }
+
+        if !self.ui_visible {
+            self.temp_textures.release(self.renderer);
+            return Ok(());
+        }
+
+        if let Some(font) = self.pending_font.take() {
+            match self
+                .renderer
+                .replace_font_atlas(device, &font.pixels, font.w, font.h)
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    log::warn!("imgui-backends: the renderer can't replace the font atlas texture");
+                }
+                Err(err) => {
+                    // retried on the next render
+                    *self.pending_font = Some(font);
+                    self.temp_textures.release(self.renderer);
+                    return Err(err);
+                }
+            }
+        }
+
+        self.pacing.on_render_begin();
+        let result = self.renderer.render(self.draw_data, device);
+        self.pacing.on_render_end();
+        self.temp_textures.release(self.renderer);
+        result?;
+        self.latency.on_submit();
+        self.plugins.on_after_render(self.draw_data);
         Ok(())
     }
 }
@@ -0,0 +1,127 @@
+/*!
+Running the backend as a debug overlay inside an external engine loop
+
+Engines such as Bevy own the event loop, the window and the GPU surface. [`Overlay`] adapts
+[`Backend`] to such a loop: it only decides *when* the backend touches them.
+
+# Ownership handshake
+
+* The overlay owns the [`imgui::Context`] (through the [`Backend`]). Engine systems never hold it;
+  they queue UI with [`Overlay::queue`] instead.
+* The engine owns the window and the device, and lends them to the overlay for the duration of
+  each call.
+* Events are fed with [`Overlay::handle_event`] as soon as the engine receives them, *before* its
+  input systems run. Hide captured events from the game.
+* The UI is built and rendered in one late render step, [`Overlay::render`]: after the engine
+  has drawn the scene to the surface and before it presents. The renderer must load (not clear)
+  the surface.
+
+```no_run
+// event stage
+let captured = overlay.handle_event(&window, &event);
+
+// update stage (any system)
+overlay.queue(|ui| ui.text("entities: 42"));
+
+// late render step
+overlay.tick(dt);
+overlay.render(&mut window, &mut device, |_ui| {})?;
+```
+
+Wire the three calls from your engine's systems. See `examples/engine-overlay.rs`.
+*/
+
+use std::time::Duration;
+
+use crate::{Backend, BackendUi, Captured, Platform, Renderer};
+
+/// UI queued by engine systems for the next overlay frame
+pub type OverlayUi = Box<dyn FnOnce(&imgui::Ui)>;
+
+/// [`Backend`] driven by an external engine loop. See the module documentation
+pub struct Overlay<P: Platform, R: Renderer> {
+    backend: Backend<P, R>,
+    queue: Vec<OverlayUi>,
+    visible: bool,
+}
+
+impl<P: Platform, R: Renderer> std::fmt::Debug for Overlay<P, R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Overlay")
+            .field("queue", &self.queue.len())
+            .field("visible", &self.visible)
+            .finish()
+    }
+}
+
+impl<P: Platform, R: Renderer> Overlay<P, R> {
+    pub fn new(backend: Backend<P, R>) -> Self {
+        Self {
+            backend,
+            queue: Vec::new(),
+            visible: true,
+        }
+    }
+
+    pub fn backend(&self) -> &Backend<P, R> {
+        &self.backend
+    }
+
+    pub fn backend_mut(&mut self) -> &mut Backend<P, R> {
+        &mut self.backend
+    }
+
+    pub fn into_backend(self) -> Backend<P, R> {
+        self.backend
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Hidden overlays capture no events and skip rendering. Queued UI is dropped
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    /// Feeds an event. Call it before the engine's input systems see the event
+    pub fn handle_event(&mut self, window: &P::Window, event: &P::Event) -> Captured {
+        if !self.visible {
+            return Captured::No;
+        }
+        self.backend.handle_event(window, event)
+    }
+
+    /// Queues UI for the next [`Self::render`], e.g. from an engine system
+    pub fn queue(&mut self, ui: impl FnOnce(&imgui::Ui) + 'static) {
+        if self.visible {
+            self.queue.push(Box::new(ui));
+        }
+    }
+
+    /// Advances the timers. Call it once per engine frame
+    pub fn tick(&mut self, dt: Duration) {
+        self.backend.update_delta_time(dt);
+    }
+
+    /// Builds the UI (`build` first, then the queued UI in order) and renders it on top of the
+    /// surface. Call it after the scene is drawn and before the surface is presented
+    pub fn render(
+        &mut self,
+        window: &mut P::Window,
+        device: &mut R::Device,
+        build: impl FnOnce(&mut BackendUi<P, R>),
+    ) -> Result<(), R::Error> {
+        let queue = std::mem::take(&mut self.queue);
+        if !self.visible {
+            return Ok(());
+        }
+
+        let mut ui = self.backend.begin_frame(window);
+        build(&mut ui);
+        for f in queue {
+            f(&ui);
+        }
+        ui.end_frame(window, device)
+    }
+}
@@ -2,7 +2,43 @@
 Half of an `imgui-rs` backend
 */
 
+/// Image pasted from the OS clipboard
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardImage {
+    /// RGBA8 pixels
+    pub pixels: Vec<u8>,
+    pub w: u32,
+    pub h: u32,
+}
+
+mod clipboard;
+pub use self::clipboard::*;
+
+mod text;
+pub use self::text::*;
+
+mod keys;
+pub use self::keys::*;
+
+pub mod null;
+pub use self::null::NullPlatform;
+
+#[cfg(feature = "rfd")]
+pub mod dialogs;
+#[cfg(feature = "rfd")]
+pub use self::dialogs::{NativeDialogs, RfdDialogs};
+
 #[cfg(feature = "sdl2")]
 pub mod sdl2;
 #[cfg(feature = "sdl2")]
-pub use self::sdl2::ImGuiSdl2;
+pub use self::sdl2::{ImGuiSdl2, ScrollDirection, Sdl2Clipboard, TextInputPolicy, VirtualMouse};
+
+#[cfg(feature = "winit")]
+pub mod winit;
+#[cfg(feature = "winit")]
+pub use self::winit::ImGuiWinit;
+
+#[cfg(all(windows, feature = "windows"))]
+pub mod win32;
+#[cfg(all(windows, feature = "windows"))]
+pub use self::win32::{ImGuiWin32, Win32Message};
@@ -0,0 +1,125 @@
+//! OS clipboard for ImGUI text fields
+//!
+//! Platforms install their clipboard on creation. Replace it with [`crate::Backend::set_clipboard`],
+//! e.g. to share the clipboard with an engine or to isolate headless tests.
+
+/// Text clipboard used by ImGUI for copy and paste
+pub trait ClipboardBackend: 'static {
+    /// `None` if the clipboard is empty or doesn't hold text
+    fn get_text(&mut self) -> Option<String>;
+    fn set_text(&mut self, text: &str);
+}
+
+/// Clipboard that holds nothing
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullClipboard;
+
+impl ClipboardBackend for NullClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        None
+    }
+
+    fn set_text(&mut self, _text: &str) {}
+}
+
+/// Clipboard local to the process, e.g. for headless tests
+#[derive(Debug, Clone, Default)]
+pub struct MemoryClipboard {
+    pub text: Option<String>,
+}
+
+impl ClipboardBackend for MemoryClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.text.clone()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        self.text = Some(text.to_string());
+    }
+}
+
+/// OS clipboard through `arboard`, for platforms without clipboard API (winit)
+#[cfg(feature = "arboard")]
+pub struct ArboardClipboard {
+    clipboard: arboard::Clipboard,
+}
+
+#[cfg(feature = "arboard")]
+impl std::fmt::Debug for ArboardClipboard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArboardClipboard").finish()
+    }
+}
+
+#[cfg(feature = "arboard")]
+impl ArboardClipboard {
+    /// `None` if the OS clipboard can't be opened
+    pub fn new() -> Option<Self> {
+        match arboard::Clipboard::new() {
+            Ok(clipboard) => Some(Self { clipboard }),
+            Err(err) => {
+                log::warn!("imgui-backends: failed to open the clipboard: {}", err);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(feature = "arboard")]
+impl ClipboardBackend for ArboardClipboard {
+    fn get_text(&mut self) -> Option<String> {
+        self.clipboard.get_text().ok()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        if let Err(err) = self.clipboard.set_text(text.to_string()) {
+            log::debug!("imgui-backends: failed to copy to the clipboard: {}", err);
+        }
+    }
+}
+
+/// Bridges [`ClipboardBackend`] to `imgui`
+struct ImGuiClipboard(Box<dyn ClipboardBackend>);
+
+impl imgui::ClipboardBackend for ImGuiClipboard {
+    fn get(&mut self) -> Option<String> {
+        let mut text = self.0.get_text()?;
+        // ImGUI strings are NUL-terminated
+        if let Some(nul) = text.find('\0') {
+            log::debug!("imgui-backends: pasted text is cut at NUL");
+            text.truncate(nul);
+        }
+        Some(text)
+    }
+
+    fn set(&mut self, value: &str) {
+        self.0.set_text(value);
+    }
+}
+
+/// Sets the clipboard of the ImGUI context
+pub fn set_clipboard(imgui: &mut imgui::Context, clipboard: Box<dyn ClipboardBackend>) {
+    imgui.set_clipboard_backend(ImGuiClipboard(clipboard));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paste(text: &str) -> Option<String> {
+        let mut clipboard = ImGuiClipboard(Box::new(MemoryClipboard {
+            text: Some(text.to_string()),
+        }));
+        imgui::ClipboardBackend::get(&mut clipboard)
+    }
+
+    #[test]
+    fn paste_is_cut_at_nul() {
+        assert_eq!(paste("before\0after").as_deref(), Some("before"));
+        assert_eq!(paste("\0after").as_deref(), Some(""));
+        assert_eq!(paste("a😀b").as_deref(), Some("a😀b"));
+
+        let mut clipboard = ImGuiClipboard(Box::new(NullClipboard));
+        assert_eq!(imgui::ClipboardBackend::get(&mut clipboard), None);
+    }
+}
@@ -0,0 +1,112 @@
+/*!
+Native file dialogs that don't block the render thread
+*/
+
+use std::{
+    future::Future,
+    path::PathBuf,
+    pin::Pin,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+/// Extension filter: `(name, extensions)`, e.g. `("Images", &["png", "jpg"])`
+pub type DialogFilter<'a> = (&'a str, &'a [&'a str]);
+
+/// Native file dialogs on the platform layer
+///
+/// Dialogs are opened asynchronously; poll the result every frame:
+///
+/// ```no_run
+/// if ui.button("Open..") {
+///     backend.platform.open_file(&[("Images", &["png"])]);
+/// }
+/// if let Some(Some(path)) = backend.platform.poll_dialog() {
+///     // load the file
+/// }
+/// ```
+pub trait NativeDialogs {
+    fn open_file(&mut self, filters: &[DialogFilter]);
+    fn save_file(&mut self, filters: &[DialogFilter]);
+    /// Returns `Some` once when the dialog is closed. The inner value is `None` if cancelled
+    fn poll_dialog(&mut self) -> Option<Option<PathBuf>>;
+}
+
+type DialogFuture = Pin<Box<dyn Future<Output = Option<PathBuf>>>>;
+
+fn noop_waker() -> Waker {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    unsafe { Waker::from_raw(RawWaker::new(std::ptr::null(), &VTABLE)) }
+}
+
+/// [`NativeDialogs`] implementation with `rfd`, polled without an executor
+#[derive(Default)]
+pub struct RfdDialogs {
+    pending: Option<DialogFuture>,
+}
+
+impl std::fmt::Debug for RfdDialogs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RfdDialogs")
+            .field("is_pending", &self.pending.is_some())
+            .finish()
+    }
+}
+
+impl RfdDialogs {
+    fn dialog(filters: &[DialogFilter]) -> rfd::AsyncFileDialog {
+        filters
+            .iter()
+            .fold(rfd::AsyncFileDialog::new(), |dialog, (name, exts)| {
+                dialog.add_filter(name, exts)
+            })
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+}
+
+impl NativeDialogs for RfdDialogs {
+    fn open_file(&mut self, filters: &[DialogFilter]) {
+        if self.pending.is_some() {
+            log::warn!("imgui-backends: a file dialog is already open");
+            return;
+        }
+
+        let future = Self::dialog(filters).pick_file();
+        self.pending = Some(Box::pin(async move {
+            future.await.map(|handle| handle.path().to_path_buf())
+        }));
+    }
+
+    fn save_file(&mut self, filters: &[DialogFilter]) {
+        if self.pending.is_some() {
+            log::warn!("imgui-backends: a file dialog is already open");
+            return;
+        }
+
+        let future = Self::dialog(filters).save_file();
+        self.pending = Some(Box::pin(async move {
+            future.await.map(|handle| handle.path().to_path_buf())
+        }));
+    }
+
+    fn poll_dialog(&mut self) -> Option<Option<PathBuf>> {
+        let future = self.pending.as_mut()?;
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(path) => {
+                self.pending = None;
+                Some(path)
+            }
+            Poll::Pending => None,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Key map shared by platforms
+//!
+//! Dear ImGUI reads navigation and shortcut keys through `io.key_map`, indices into
+//! `io.keys_down`. Platforms translate every [`UiKey`] into their native key index with
+//! [`set_key_map`], so that no key is left unmapped by accident (an unmapped `Tab` or arrow key
+//! silently breaks keyboard navigation).
+
+/// Keys Dear ImGUI needs for navigation, text editing and shortcuts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UiKey {
+    Tab,
+    Left,
+    Right,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    Insert,
+    Delete,
+    Backspace,
+    Space,
+    Enter,
+    Escape,
+    KeyPadEnter,
+    A,
+    C,
+    V,
+    X,
+    Y,
+    Z,
+}
+
+impl UiKey {
+    pub const ALL: [UiKey; 22] = [
+        UiKey::Tab,
+        UiKey::Left,
+        UiKey::Right,
+        UiKey::Up,
+        UiKey::Down,
+        UiKey::PageUp,
+        UiKey::PageDown,
+        UiKey::Home,
+        UiKey::End,
+        UiKey::Insert,
+        UiKey::Delete,
+        UiKey::Backspace,
+        UiKey::Space,
+        UiKey::Enter,
+        UiKey::Escape,
+        UiKey::KeyPadEnter,
+        UiKey::A,
+        UiKey::C,
+        UiKey::V,
+        UiKey::X,
+        UiKey::Y,
+        UiKey::Z,
+    ];
+
+    pub fn imgui_key(self) -> imgui::Key {
+        use imgui::Key;
+        match self {
+            Self::Tab => Key::Tab,
+            Self::Left => Key::LeftArrow,
+            Self::Right => Key::RightArrow,
+            Self::Up => Key::UpArrow,
+            Self::Down => Key::DownArrow,
+            Self::PageUp => Key::PageUp,
+            Self::PageDown => Key::PageDown,
+            Self::Home => Key::Home,
+            Self::End => Key::End,
+            Self::Insert => Key::Insert,
+            Self::Delete => Key::Delete,
+            Self::Backspace => Key::Backspace,
+            Self::Space => Key::Space,
+            Self::Enter => Key::Enter,
+            Self::Escape => Key::Escape,
+            Self::KeyPadEnter => Key::KeyPadEnter,
+            Self::A => Key::A,
+            Self::C => Key::C,
+            Self::V => Key::V,
+            Self::X => Key::X,
+            Self::Y => Key::Y,
+            Self::Z => Key::Z,
+        }
+    }
+}
+
+/// Fills `io.key_map` with the native key index of every [`UiKey`]. `native` returns `None` for
+/// keys the platform can't report
+pub fn set_key_map(io: &mut imgui::Io, native: impl Fn(UiKey) -> Option<u32>) {
+    for &key in UiKey::ALL.iter() {
+        match native(key) {
+            Some(index) => io.key_map[key.imgui_key() as usize] = index,
+            None => log::debug!("imgui-backends: {:?} is not mapped", key),
+        }
+    }
+}
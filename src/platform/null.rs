@@ -0,0 +1,77 @@
+/*!
+Platform that does nothing. Useful for headless runs such as generating fixtures
+*/
+
+use imgui::{Context, Io, Ui};
+
+use crate::{
+    dpi::{self, LogicalPos, LogicalSize, PhysicalSize, PointerTransform},
+    platform::UiKey,
+    Captured, Platform,
+};
+
+/// Platform with no window or events. It feeds a fixed display size and simulated mouse state to
+/// ImGUI
+#[derive(Debug, Clone)]
+pub struct NullPlatform {
+    pub display_size: [f32; 2],
+    /// Simulated mouse position
+    pub mouse_pos: [f32; 2],
+    /// Simulated mouse buttons: left, right, middle, x1, x2
+    pub mouse_down: [bool; 5],
+    /// Simulated keys held down. The key map indexes `io.keys_down` with [`UiKey`] itself
+    pub keys_down: Vec<UiKey>,
+    pointer_transform: Option<PointerTransform>,
+}
+
+impl NullPlatform {
+    pub fn new(imgui: &mut Context, display_size: [f32; 2]) -> Self {
+        imgui.set_platform_name(Some(crate::backend_name("imgui-null-platform", &[])));
+        super::set_key_map(imgui.io_mut(), |key| Some(key as u32));
+
+        Self {
+            display_size,
+            mouse_pos: [-f32::MAX, -f32::MAX],
+            mouse_down: [false; 5],
+            keys_down: Vec::new(),
+            pointer_transform: None,
+        }
+    }
+}
+
+impl Platform for NullPlatform {
+    type Event = ();
+    type Window = ();
+
+    fn handle_event(&mut self, _imgui: &mut Context, _window: &(), _event: &()) -> Captured {
+        Captured::No
+    }
+
+    fn window_size(&self, _window: &()) -> LogicalSize {
+        LogicalSize(self.display_size)
+    }
+
+    fn framebuffer_size(&self, _window: &()) -> PhysicalSize {
+        LogicalSize(self.display_size).to_physical([1.0, 1.0])
+    }
+
+    fn set_pointer_transform(&mut self, transform: Option<PointerTransform>) {
+        self.pointer_transform = transform;
+    }
+
+    fn prepare_frame(&mut self, io: &mut Io, window: &()) {
+        dpi::set_display(io, self.window_size(window), self.framebuffer_size(window));
+
+        let mut pos = LogicalPos(self.mouse_pos);
+        if let Some(ref transform) = self.pointer_transform {
+            pos = transform.apply(pos);
+        }
+        dpi::set_mouse_pos(io, pos);
+        io.mouse_down = self.mouse_down;
+        for &key in UiKey::ALL.iter() {
+            io.keys_down[key as usize] = self.keys_down.contains(&key);
+        }
+    }
+
+    fn prepare_render(&mut self, _ui: &Ui<'_>, _window: &()) {}
+}
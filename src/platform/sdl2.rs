@@ -7,43 +7,223 @@ This is very much a copy of [rust-imgui-sdl2]. Many thanks to them!
 */
 
 use {
-    imgui::{ConfigFlags, Context, Key, MouseCursor},
+    imgui::{BackendFlags, ConfigFlags, Context, MouseCursor, NavInput},
     sdl2::{
+        controller::{Axis, Button},
         event::Event,
         keyboard::Scancode,
-        mouse::{Cursor, SystemCursor},
+        mouse::{Cursor, MouseWheelDirection, SystemCursor},
+        pixels::PixelFormatEnum,
+        surface::Surface,
         video::Window,
     },
     std::fmt,
 };
 
-use crate::Platform;
+use crate::{
+    dpi::{self, LogicalPos, LogicalSize, PhysicalSize, PointerTransform},
+    helper::{ControllerArbiter, GamepadNav, InputSource, InputTiming, ModifierKeys},
+    platform::{ClipboardBackend, NullClipboard, UiKey},
+    Backend, Captured, Platform, Renderer,
+};
+
+/// Sets the SDL hints the backend relies on. [`ImGuiSdl2::new`] calls it; call it before
+/// `sdl2::init` as well so that the hints apply from the first event
+///
+/// * `SDL_IME_SHOW_UI`: shows the IME candidate list (Windows)
+/// * `SDL_TOUCH_MOUSE_EVENTS`: touches are reported as mouse events too; ImGUI reads mouse events
+///   only
+/// * `SDL_MOUSE_FOCUS_CLICKTHROUGH`: the click that focuses the window reaches ImGUI
+///
+/// Wheel direction is set with [`ImGuiSdl2::set_scroll_direction`] regardless of the hints.
+pub fn configure_hints() {
+    let hints = [
+        ("SDL_IME_SHOW_UI", "1"),
+        ("SDL_TOUCH_MOUSE_EVENTS", "1"),
+        ("SDL_MOUSE_FOCUS_CLICKTHROUGH", "1"),
+    ];
+
+    for (name, value) in hints.iter() {
+        if !sdl2::hint::set(name, value) {
+            log::debug!("ImGuiSdl2: failed to set hint {}={}", name, value);
+        }
+    }
+}
+
+/// Direction of mouse wheel scrolling in ImGUI
+///
+/// SDL reports wheel values with the "natural scrolling" setting of the OS (macOS trackpads)
+/// applied and marks them as [`MouseWheelDirection::Flipped`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScrollDirection {
+    /// Scroll like other apps on the OS: values are used as reported
+    FollowOs,
+    /// Content moves against the wheel/fingers regardless of the OS setting
+    Traditional,
+    /// Content moves with the wheel/fingers regardless of the OS setting
+    Natural,
+}
+
+impl Default for ScrollDirection {
+    fn default() -> Self {
+        Self::FollowOs
+    }
+}
+
+impl ScrollDirection {
+    /// Sign applied to the reported wheel values
+    fn sign(self, direction: MouseWheelDirection) -> i32 {
+        let flipped = direction == MouseWheelDirection::Flipped;
+        match self {
+            Self::FollowOs => 1,
+            Self::Traditional if flipped => -1,
+            Self::Traditional => 1,
+            Self::Natural if flipped => 1,
+            Self::Natural => -1,
+        }
+    }
+}
+
+/// Who starts and stops SDL text input (`SDL_StartTextInput` / `SDL_StopTextInput`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextInputPolicy {
+    /// Text input is kept running
+    AlwaysOn,
+    /// Text input runs only while ImGUI wants text input
+    ImGui,
+    /// The application starts and stops text input, e.g. for its own chat box. The backend never
+    /// touches it
+    App,
+}
+
+impl Default for TextInputPolicy {
+    fn default() -> Self {
+        Self::AlwaysOn
+    }
+}
+
+/// Settings of a virtual mouse cursor moved with the left stick of a game controller. The A button
+/// is the left click
+///
+/// Useful for kiosk/console builds where no mouse exists. The cursor is drawn by ImGUI (software
+/// cursor). NOTE: The controller has to be opened by the application to receive events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualMouse {
+    /// Cursor speed in logical pixels per second at full tilt
+    pub speed: f32,
+    /// Exponent applied to the stick tilt. Larger values make slow movement more precise
+    pub acceleration: f32,
+    /// Stick tilt ignored in `[0, 1]`
+    pub dead_zone: f32,
+}
+
+impl Default for VirtualMouse {
+    fn default() -> Self {
+        Self {
+            speed: 800.0,
+            acceleration: 2.0,
+            dead_zone: 0.2,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct VirtualMouseState {
+    config: VirtualMouse,
+    pos: [f32; 2],
+    /// Left stick in `[-1, 1]`
+    stick: [f32; 2],
+    click: bool,
+}
+
+impl VirtualMouseState {
+    fn update(&mut self, dt: f32, size: LogicalSize) {
+        let [x, y] = self.stick;
+        let tilt = (x * x + y * y).sqrt().min(1.0);
+        if tilt > self.config.dead_zone {
+            let t = (tilt - self.config.dead_zone) / (1.0 - self.config.dead_zone);
+            let v = self.config.speed * t.powf(self.config.acceleration) * dt / tilt;
+            self.pos[0] += x * v;
+            self.pos[1] += y * v;
+        }
+
+        self.pos[0] = self.pos[0].max(0.0).min(size.0[0]);
+        self.pos[1] = self.pos[1].max(0.0).min(size.0[1]);
+    }
+}
 
 /// SDL2 platform for `imgui-rs`
 pub struct ImGuiSdl2 {
     mouse_press: [bool; 5],
     ignore_mouse: bool,
     ignore_keyboard: bool,
+    ignore_text_input: bool,
+    /// Cursor shape set to the OS. `None` if hidden or not set yet
     cursor: Option<MouseCursor>,
-    /// TODO: use it?
-    sdl_cursor: Option<Cursor>,
+    /// System cursors created on first use. Indexed by `imgui::MouseCursor`
+    system_cursors: Vec<Option<Cursor>>,
+    /// Indexed by `imgui::MouseCursor`
+    custom_cursors: Vec<Option<Cursor>>,
+    pointer_transform: Option<PointerTransform>,
+    virtual_mouse: Option<VirtualMouseState>,
+    /// `io.mouse_draw_cursor` before the virtual mouse turned it on. Restored when it's disabled
+    saved_draw_cursor: Option<bool>,
+    /// Events from other windows are ignored
+    window_id: u32,
+    scroll_direction: ScrollDirection,
+    /// IME composition is suppressed: `TextEditing` events are swallowed and the IME UI is hidden
+    ime_disabled: bool,
+    /// `SDL_IME_SHOW_UI` before it was turned off for [`Self::ime_disabled`]
+    ime_show_ui: Option<String>,
+    text_input_policy: TextInputPolicy,
+    /// Text input state set by the backend. SDL starts text input on initialization
+    text_input_on: bool,
+    controllers: ControllerArbiter,
+    /// Navigation inputs of the active controller
+    gamepad_nav: GamepadNav,
+    #[cfg(feature = "rfd")]
+    dialogs: super::RfdDialogs,
+    /// Image read from the clipboard on the last paste
+    #[cfg(feature = "arboard")]
+    clipboard_image: Option<super::ClipboardImage>,
 }
 
 impl fmt::Debug for ImGuiSdl2 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ImGuiSdl2")
             .field("mouse_press", &self.mouse_press)
+            .field("ignore_mouse", &self.ignore_mouse)
             .field("ignore_keyboard", &self.ignore_keyboard)
+            .field("ignore_text_input", &self.ignore_text_input)
             .field("cursor", &self.cursor)
-            .field("sdl_cursor", &"DEBUG unimplemented")
+            .field("system_cursors", &"DEBUG unimplemented")
+            .field("custom_cursors", &"DEBUG unimplemented")
+            .field("pointer_transform", &self.pointer_transform)
+            .field("virtual_mouse", &self.virtual_mouse)
+            .field("saved_draw_cursor", &self.saved_draw_cursor)
+            .field("window_id", &self.window_id)
+            .field("scroll_direction", &self.scroll_direction)
+            .field("ime_disabled", &self.ime_disabled)
+            .field("ime_show_ui", &self.ime_show_ui)
+            .field("text_input_policy", &self.text_input_policy)
+            .field("text_input_on", &self.text_input_on)
+            .field("controllers", &self.controllers)
+            .field("gamepad_nav", &self.gamepad_nav)
             .finish()
     }
 }
 
-struct Sdl2ClipboardBackend(sdl2::clipboard::ClipboardUtil);
+/// OS clipboard through SDL. Installed by [`ImGuiSdl2::new`]
+pub struct Sdl2Clipboard(pub sdl2::clipboard::ClipboardUtil);
 
-impl imgui::ClipboardBackend for Sdl2ClipboardBackend {
-    fn get(&mut self) -> Option<String> {
+impl fmt::Debug for Sdl2Clipboard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sdl2Clipboard").finish()
+    }
+}
+
+impl ClipboardBackend for Sdl2Clipboard {
+    fn get_text(&mut self) -> Option<String> {
         if !self.0.has_clipboard_text() {
             return None;
         }
@@ -51,52 +231,338 @@ impl imgui::ClipboardBackend for Sdl2ClipboardBackend {
         self.0.clipboard_text().ok()
     }
 
-    fn set(&mut self, value: &str) {
-        let _ = self.0.set_clipboard_text(value);
+    fn set_text(&mut self, text: &str) {
+        if let Err(err) = self.0.set_clipboard_text(text) {
+            log::debug!("ImGuiSdl2: failed to copy to the clipboard: {}", err);
+        }
     }
 }
 
+/// Reads an image from the OS clipboard. SDL only supports text
+#[cfg(feature = "arboard")]
+fn read_clipboard_image() -> Option<super::ClipboardImage> {
+    let mut clipboard = match arboard::Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(err) => {
+            log::debug!("ImGuiSdl2: failed to open the clipboard: {}", err);
+            return None;
+        }
+    };
+
+    // fails when the clipboard doesn't hold an image
+    let image = clipboard.get_image().ok()?;
+    Some(super::ClipboardImage {
+        pixels: image.bytes.into_owned(),
+        w: image.width as u32,
+        h: image.height as u32,
+    })
+}
+
+/// Native key of a [`UiKey`], used as the index of `io.keys_down`
+fn scancode(key: UiKey) -> Scancode {
+    match key {
+        UiKey::Tab => Scancode::Tab,
+        UiKey::Left => Scancode::Left,
+        UiKey::Right => Scancode::Right,
+        UiKey::Up => Scancode::Up,
+        UiKey::Down => Scancode::Down,
+        UiKey::PageUp => Scancode::PageUp,
+        UiKey::PageDown => Scancode::PageDown,
+        UiKey::Home => Scancode::Home,
+        UiKey::End => Scancode::End,
+        UiKey::Insert => Scancode::Insert,
+        UiKey::Delete => Scancode::Delete,
+        UiKey::Backspace => Scancode::Backspace,
+        UiKey::Space => Scancode::Space,
+        UiKey::Enter => Scancode::Return,
+        UiKey::Escape => Scancode::Escape,
+        UiKey::KeyPadEnter => Scancode::KpEnter,
+        UiKey::A => Scancode::A,
+        UiKey::C => Scancode::C,
+        UiKey::V => Scancode::V,
+        UiKey::X => Scancode::X,
+        UiKey::Y => Scancode::Y,
+        UiKey::Z => Scancode::Z,
+    }
+}
+
+/// Wraps RGBA8 pixels (in byte order) in an SDL surface
+fn rgba8_surface(pixels: &mut [u8], w: u32, h: u32) -> Result<Surface<'_>, String> {
+    if pixels.len() != (w as usize) * (h as usize) * 4 {
+        return Err(format!("pixel size mismatch: {}x{}", w, h));
+    }
+
+    // SDL pixel formats are packed; RGBA in byte order is ABGR8888 on little endian
+    let format = if cfg!(target_endian = "little") {
+        PixelFormatEnum::ABGR8888
+    } else {
+        PixelFormatEnum::RGBA8888
+    };
+    Surface::from_data(pixels, w, h, w * 4, format)
+}
+
 impl ImGuiSdl2 {
     pub fn new(imgui: &mut Context, window: &Window) -> Self {
+        self::configure_hints();
+
+        let video = window.subsystem();
+        imgui.set_platform_name(Some(crate::backend_name(
+            "imgui-sdl2-platform",
+            &[
+                &format!("SDL {}", sdl2::version::version()),
+                video.current_video_driver(),
+            ],
+        )));
+
         let clipboard_util = window.subsystem().clipboard();
-        imgui.set_clipboard_backend(Sdl2ClipboardBackend(clipboard_util));
-
-        imgui.io_mut().key_map[Key::Tab as usize] = Scancode::Tab as u32;
-        imgui.io_mut().key_map[Key::LeftArrow as usize] = Scancode::Left as u32;
-        imgui.io_mut().key_map[Key::RightArrow as usize] = Scancode::Right as u32;
-        imgui.io_mut().key_map[Key::UpArrow as usize] = Scancode::Up as u32;
-        imgui.io_mut().key_map[Key::DownArrow as usize] = Scancode::Down as u32;
-        imgui.io_mut().key_map[Key::PageUp as usize] = Scancode::PageUp as u32;
-        imgui.io_mut().key_map[Key::PageDown as usize] = Scancode::PageDown as u32;
-        imgui.io_mut().key_map[Key::Home as usize] = Scancode::Home as u32;
-        imgui.io_mut().key_map[Key::End as usize] = Scancode::End as u32;
-        imgui.io_mut().key_map[Key::Delete as usize] = Scancode::Delete as u32;
-        imgui.io_mut().key_map[Key::Backspace as usize] = Scancode::Backspace as u32;
-        imgui.io_mut().key_map[Key::Enter as usize] = Scancode::Return as u32;
-        imgui.io_mut().key_map[Key::Escape as usize] = Scancode::Escape as u32;
-        imgui.io_mut().key_map[Key::Space as usize] = Scancode::Space as u32;
-        imgui.io_mut().key_map[Key::A as usize] = Scancode::A as u32;
-        imgui.io_mut().key_map[Key::C as usize] = Scancode::C as u32;
-        imgui.io_mut().key_map[Key::V as usize] = Scancode::V as u32;
-        imgui.io_mut().key_map[Key::X as usize] = Scancode::X as u32;
-        imgui.io_mut().key_map[Key::Y as usize] = Scancode::Y as u32;
-        imgui.io_mut().key_map[Key::Z as usize] = Scancode::Z as u32;
+        super::set_clipboard(imgui, Box::new(Sdl2Clipboard(clipboard_util)));
+
+        super::set_key_map(imgui.io_mut(), |key| Some(self::scancode(key) as u32));
+
+        // SDL doesn't expose the system double-click time or key repeat rate; only distances are
+        // scaled
+        let timing = InputTiming::for_dpi_scale(self::dpi_scale(window));
+        timing.apply_to_defaults(imgui.io_mut());
+
+        imgui
+            .io_mut()
+            .backend_flags
+            .insert(BackendFlags::HAS_GAMEPAD | BackendFlags::HAS_MOUSE_CURSORS);
 
         Self {
             mouse_press: [false; 5],
             ignore_keyboard: false,
             ignore_mouse: false,
+            ignore_text_input: false,
             cursor: None,
-            sdl_cursor: None,
+            system_cursors: (0..MouseCursor::COUNT).map(|_| None).collect(),
+            custom_cursors: (0..MouseCursor::COUNT).map(|_| None).collect(),
+            pointer_transform: None,
+            virtual_mouse: None,
+            saved_draw_cursor: None,
+            window_id: window.id(),
+            scroll_direction: ScrollDirection::default(),
+            ime_disabled: false,
+            ime_show_ui: None,
+            text_input_policy: TextInputPolicy::default(),
+            text_input_on: true,
+            controllers: ControllerArbiter::new(),
+            gamepad_nav: GamepadNav::new(),
+            #[cfg(feature = "rfd")]
+            dialogs: Default::default(),
+            #[cfg(feature = "arboard")]
+            clipboard_image: None,
+        }
+    }
+
+    /// Call it after recreating the SDL window (e.g. to change GL attributes). Resets input and
+    /// cursor states tied to the old window and starts accepting events from the new one
+    pub fn rebind_window(&mut self, imgui: &mut Context, window: &Window) {
+        let clipboard_util = window.subsystem().clipboard();
+        super::set_clipboard(imgui, Box::new(Sdl2Clipboard(clipboard_util)));
+
+        self.window_id = window.id();
+        self.mouse_press = [false; 5];
+        // force re-setting the cursor on the next frame
+        self.cursor = None;
+
+        let io = imgui.io_mut();
+        io.mouse_down = [false; 5];
+        io.keys_down.iter_mut().for_each(|k| *k = false);
+        io.key_ctrl = false;
+        io.key_alt = false;
+        io.key_shift = false;
+        io.key_super = false;
+
+        dpi::set_display(io, self.window_size(window), self.framebuffer_size(window));
+    }
+
+    /// Sets the window icon from RGBA8 pixels
+    pub fn set_window_icon(
+        &mut self,
+        window: &mut Window,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> Result<(), String> {
+        let mut pixels = pixels.to_vec();
+        let surface = self::rgba8_surface(&mut pixels, w, h)?;
+        window.set_icon(surface);
+        Ok(())
+    }
+
+    /// Replaces the system cursor for `cursor` with RGBA8 pixels. `hot` is the click point
+    pub fn set_custom_cursor(
+        &mut self,
+        cursor: MouseCursor,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        hot: [i32; 2],
+    ) -> Result<(), String> {
+        let mut pixels = pixels.to_vec();
+        let surface = self::rgba8_surface(&mut pixels, w, h)?;
+        let sdl_cursor = Cursor::from_surface(surface, hot[0], hot[1])?;
+        self.custom_cursors[cursor as usize] = Some(sdl_cursor);
+        // force re-setting the cursor on the next frame
+        self.cursor = None;
+        Ok(())
+    }
+
+    /// Goes back to the system cursor for `cursor`
+    pub fn clear_custom_cursor(&mut self, cursor: MouseCursor) {
+        self.custom_cursors[cursor as usize] = None;
+        self.cursor = None;
+    }
+
+    /// Sets the OS cursor, preferring custom cursors. Keeps the current cursor if SDL can't create
+    /// the system cursor
+    fn set_cursor(&mut self, cursor: MouseCursor) {
+        let i = cursor as usize;
+        if let Some(custom) = &self.custom_cursors[i] {
+            custom.set();
+            self.cursor = Some(cursor);
+            return;
         }
+
+        if self.system_cursors[i].is_none() {
+            let system = match cursor {
+                MouseCursor::Arrow => SystemCursor::Arrow,
+                MouseCursor::TextInput => SystemCursor::IBeam,
+                MouseCursor::ResizeAll => SystemCursor::SizeAll,
+                MouseCursor::ResizeNS => SystemCursor::SizeNS,
+                MouseCursor::ResizeEW => SystemCursor::SizeWE,
+                MouseCursor::ResizeNESW => SystemCursor::SizeNESW,
+                MouseCursor::ResizeNWSE => SystemCursor::SizeNWSE,
+                MouseCursor::Hand => SystemCursor::Hand,
+                MouseCursor::NotAllowed => SystemCursor::No,
+            };
+            match Cursor::from_system(system) {
+                Ok(c) => self.system_cursors[i] = Some(c),
+                Err(err) => {
+                    log::warn!("ImGuiSdl2: failed to create cursor {:?}: {}", cursor, err);
+                    // don't retry every frame
+                    self.cursor = Some(cursor);
+                    return;
+                }
+            }
+        }
+
+        self.system_cursors[i].as_ref().unwrap().set();
+        self.cursor = Some(cursor);
     }
 
-    fn ignore_event(&self, event: &Event) -> bool {
+    /// Sets the window icon from PNG/JPEG bytes
+    #[cfg(feature = "image")]
+    pub fn load_window_icon(&mut self, window: &mut Window, bytes: &[u8]) -> Result<(), String> {
+        let (pixels, w, h) = crate::helper::decode_rgba8(bytes).map_err(|e| e.to_string())?;
+        self.set_window_icon(window, &pixels, w, h)
+    }
+
+    /// Overrides the OS "natural scrolling" setting for ImGUI
+    pub fn set_scroll_direction(&mut self, direction: ScrollDirection) {
+        self.scroll_direction = direction;
+    }
+
+    /// Enables or disables the controller-driven [`VirtualMouse`]. `io.mouse_draw_cursor` is
+    /// restored on the next frame after it's disabled
+    pub fn set_virtual_mouse(&mut self, config: Option<VirtualMouse>) {
+        self.virtual_mouse = config.map(|config| VirtualMouseState {
+            config,
+            pos: [0.0, 0.0],
+            stick: [0.0, 0.0],
+            click: false,
+        });
+    }
+
+    /// Applied on the next render. Switching to [`TextInputPolicy::App`] leaves text input as is
+    pub fn set_text_input_policy(&mut self, policy: TextInputPolicy) {
+        self.text_input_policy = policy;
+    }
+
+    fn sync_text_input(&mut self, window: &Window, want_text_input: bool) {
+        let on = match self.text_input_policy {
+            TextInputPolicy::App => return,
+            TextInputPolicy::AlwaysOn => true,
+            TextInputPolicy::ImGui => want_text_input,
+        };
+        if self.text_input_on == on {
+            return;
+        }
+        self.text_input_on = on;
+
+        let text_input = window.subsystem().text_input();
+        if on {
+            text_input.start();
+        } else {
+            text_input.stop();
+        }
+    }
+
+    /// Source of the most recent input. Only the active controller drives gamepad navigation
+    pub fn active_input_source(&self) -> InputSource {
+        self.controllers.active()
+    }
+
+    /// Accepts input from the controller (SDL joystick instance ID) only. `None` switches to the
+    /// source of the most recent input again
+    pub fn lock_controller(&mut self, id: Option<u32>) {
+        let prev = self.controllers.active();
+        self.controllers.lock(id);
+        if self.controllers.active() != prev {
+            self.gamepad_nav.clear();
+        }
+    }
+
+    fn on_keyboard_mouse(&mut self) {
+        let prev = self.controllers.active();
+        self.controllers.on_keyboard_mouse();
+        if self.controllers.active() != prev {
+            self.gamepad_nav.clear();
+        }
+    }
+
+    /// Returns if the input of the controller should be handled
+    fn accept_controller(&mut self, id: u32, magnitude: f32) -> bool {
+        let prev = self.controllers.active();
+        let accepted = self.controllers.on_controller(id, magnitude);
+        if self.controllers.active() != prev {
+            self.gamepad_nav.clear();
+        }
+        accepted
+    }
+
+    /// Same mapping as the Dear ImGUI SDL backend
+    fn set_nav_button(&mut self, button: Button, value: f32) {
+        let inputs: &[NavInput] = match button {
+            Button::A => &[NavInput::Activate],
+            Button::B => &[NavInput::Cancel],
+            Button::X => &[NavInput::Menu],
+            Button::Y => &[NavInput::Input],
+            Button::DPadLeft => &[NavInput::DpadLeft],
+            Button::DPadRight => &[NavInput::DpadRight],
+            Button::DPadUp => &[NavInput::DpadUp],
+            Button::DPadDown => &[NavInput::DpadDown],
+            Button::LeftShoulder => &[NavInput::FocusPrev, NavInput::TweakSlow],
+            Button::RightShoulder => &[NavInput::FocusNext, NavInput::TweakFast],
+            _ => &[],
+        };
+        for input in inputs {
+            self.gamepad_nav.set(*input, value);
+        }
+    }
+
+    fn ignore_event(&self, event: &Event) -> Captured {
         match *event {
-            Event::KeyDown { .. }
-            | Event::KeyUp { .. }
-            | Event::TextEditing { .. }
-            | Event::TextInput { .. } => self.ignore_keyboard,
+            Event::KeyDown { .. } | Event::KeyUp { .. } if self.ignore_keyboard => {
+                Captured::Keyboard
+            }
+            Event::TextEditing { .. } if self.ime_disabled => Captured::TextInput,
+            Event::TextEditing { .. } | Event::TextInput { .. }
+                if self.ignore_text_input || self.ignore_keyboard =>
+            {
+                Captured::TextInput
+            }
             Event::MouseMotion { .. }
             | Event::MouseButtonDown { .. }
             | Event::MouseButtonUp { .. }
@@ -106,8 +572,19 @@ impl ImGuiSdl2 {
             | Event::FingerMotion { .. }
             | Event::DollarGesture { .. }
             | Event::DollarRecord { .. }
-            | Event::MultiGesture { .. } => self.ignore_mouse,
-            _ => false,
+            | Event::MultiGesture { .. }
+                if self.ignore_mouse =>
+            {
+                Captured::Mouse
+            }
+            Event::ControllerAxisMotion { .. }
+            | Event::ControllerButtonDown { .. }
+            | Event::ControllerButtonUp { .. }
+                if self.ignore_mouse && self.virtual_mouse.is_some() =>
+            {
+                Captured::Mouse
+            }
+            _ => Captured::No,
         }
     }
 }
@@ -121,25 +598,47 @@ impl Platform for ImGuiSdl2 {
         imgui: &mut Context,
         _window: &Self::Window,
         event: &Self::Event,
-    ) -> bool {
+    ) -> Captured {
         use sdl2::keyboard;
         use sdl2::mouse::MouseButton;
 
         fn set_mod(imgui: &mut Context, keymod: keyboard::Mod) {
-            let ctrl = keymod.intersects(keyboard::Mod::RCTRLMOD | keyboard::Mod::LCTRLMOD);
-            let alt = keymod.intersects(keyboard::Mod::RALTMOD | keyboard::Mod::LALTMOD);
-            let shift = keymod.intersects(keyboard::Mod::RSHIFTMOD | keyboard::Mod::LSHIFTMOD);
-            let super_ = keymod.intersects(keyboard::Mod::RGUIMOD | keyboard::Mod::LGUIMOD);
-
-            imgui.io_mut().key_ctrl = ctrl;
-            imgui.io_mut().key_alt = alt;
-            imgui.io_mut().key_shift = shift;
-            imgui.io_mut().key_super = super_;
+            let keys = ModifierKeys {
+                lctrl: keymod.contains(keyboard::Mod::LCTRLMOD),
+                rctrl: keymod.contains(keyboard::Mod::RCTRLMOD),
+                lshift: keymod.contains(keyboard::Mod::LSHIFTMOD),
+                rshift: keymod.contains(keyboard::Mod::RSHIFTMOD),
+                lalt: keymod.contains(keyboard::Mod::LALTMOD),
+                ralt: keymod.contains(keyboard::Mod::RALTMOD),
+                lsuper: keymod.contains(keyboard::Mod::LGUIMOD),
+                rsuper: keymod.contains(keyboard::Mod::RGUIMOD),
+                alt_gr: keymod.contains(keyboard::Mod::MODEMOD),
+            };
+            keys.resolve().apply(imgui.io_mut());
+        }
+
+        if let Some(id) = event.get_window_id() {
+            if id != self.window_id {
+                log::debug!("ImGuiSdl2: ignoring event from stale window {}", id);
+                return Captured::No;
+            }
+        }
+
+        match *event {
+            Event::KeyDown { .. } | Event::MouseButtonDown { .. } | Event::MouseWheel { .. } => {
+                self.on_keyboard_mouse();
+            }
+            _ => {}
         }
 
         match *event {
-            Event::MouseWheel { y, .. } => {
-                imgui.io_mut().mouse_wheel = y as f32;
+            Event::MouseWheel {
+                x, y, direction, ..
+            } => {
+                let sign = self.scroll_direction.sign(direction);
+                // accumulate so that no wheel event in a frame is lost
+                imgui.io_mut().mouse_wheel_h += (sign * x) as f32;
+                imgui.io_mut().mouse_wheel += (sign * y) as f32;
             }
             Event::MouseButtonDown { mouse_btn, .. } => {
                 if mouse_btn != MouseButton::Unknown {
@@ -154,11 +653,71 @@ impl Platform for ImGuiSdl2 {
                     self.mouse_press[index] = true;
                 }
             }
-            Event::TextInput { ref text, .. } => {
-                for chr in text.chars() {
-                    imgui.io_mut().add_input_character(chr);
+            Event::MouseMotion { x, y, .. } => {
+                // keep the virtual cursor where the real one is
+                if let Some(vm) = self.virtual_mouse.as_mut() {
+                    vm.pos = [x as f32, y as f32];
                 }
             }
+            Event::ControllerAxisMotion {
+                which, axis, value, ..
+            } => {
+                let value = value as f32 / i16::MAX as f32;
+                if self.accept_controller(which, value.abs()) {
+                    if let Some(vm) = self.virtual_mouse.as_mut() {
+                        match axis {
+                            Axis::LeftX => vm.stick[0] = value,
+                            Axis::LeftY => vm.stick[1] = value,
+                            _ => {}
+                        }
+                    }
+
+                    let dead_zone = self.controllers.dead_zone;
+                    let nav = &mut self.gamepad_nav;
+                    match axis {
+                        Axis::LeftX => nav.set_axis(
+                            NavInput::LStickLeft,
+                            NavInput::LStickRight,
+                            value,
+                            dead_zone,
+                        ),
+                        Axis::LeftY => {
+                            nav.set_axis(NavInput::LStickUp, NavInput::LStickDown, value, dead_zone)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Event::ControllerButtonDown { which, button, .. } => {
+                if self.accept_controller(which, 1.0) {
+                    if button == Button::A {
+                        if let Some(vm) = self.virtual_mouse.as_mut() {
+                            vm.click = true;
+                            self.mouse_press[0] = true;
+                        }
+                    }
+                    self.set_nav_button(button, 1.0);
+                }
+            }
+            Event::ControllerButtonUp { which, button, .. } => {
+                if self.accept_controller(which, 0.0) {
+                    if button == Button::A {
+                        if let Some(vm) = self.virtual_mouse.as_mut() {
+                            vm.click = false;
+                        }
+                    }
+                    self.set_nav_button(button, 0.0);
+                }
+            }
+            Event::ControllerDeviceRemoved { which, .. } => {
+                if self.controllers.active() == InputSource::Controller(which) {
+                    self.gamepad_nav.clear();
+                }
+                self.controllers.on_removed(which);
+            }
+            Event::TextInput { ref text, .. } => {
+                super::add_input_text(imgui.io_mut(), text);
+            }
             Event::KeyDown {
                 scancode, keymod, ..
             } => {
@@ -166,6 +725,14 @@ impl Platform for ImGuiSdl2 {
                 if let Some(scancode) = scancode {
                     imgui.io_mut().keys_down[scancode as usize] = true;
                 }
+
+                #[cfg(feature = "arboard")]
+                {
+                    let paste = crate::helper::Modifiers::from_io(imgui.io()).shortcut();
+                    if paste && scancode == Some(Scancode::V) {
+                        self.clipboard_image = self::read_clipboard_image();
+                    }
+                }
             }
             Event::KeyUp {
                 scancode, keymod, ..
@@ -181,23 +748,96 @@ impl Platform for ImGuiSdl2 {
         self.ignore_event(event)
     }
 
+    /// Coalesces runs of mouse motions; only the last one of each run is handled
+    fn handle_events<'e, I>(
+        &mut self,
+        imgui: &mut Context,
+        window: &Self::Window,
+        events: I,
+    ) -> Vec<Captured>
+    where
+        I: IntoIterator<Item = &'e Self::Event>,
+    {
+        let is_motion = |ev: &Event| matches!(ev, Event::MouseMotion { .. });
+
+        let events = events.into_iter().collect::<Vec<_>>();
+        let mut captured = vec![Captured::No; events.len()];
+        let mut run_start = 0;
+
+        for (i, ev) in events.iter().enumerate() {
+            if !is_motion(ev) {
+                captured[i] = self.handle_event(imgui, window, ev);
+                run_start = i + 1;
+                continue;
+            }
+
+            if events.get(i + 1).map_or(false, |next| is_motion(next)) {
+                // superseded by the next motion
+                continue;
+            }
+
+            let c = self.handle_event(imgui, window, ev);
+            for slot in &mut captured[run_start..=i] {
+                *slot = c;
+            }
+            run_start = i + 1;
+        }
+
+        captured
+    }
+
+    /// SDL can't turn IME off alone. Text input keeps running so that committed text still
+    /// arrives; only the composition is suppressed: `TextEditing` events are swallowed and
+    /// `SDL_IME_SHOW_UI` is turned off (Windows)
+    fn set_ime_enabled(&mut self, _window: &Self::Window, enabled: bool) {
+        if self.ime_disabled == !enabled {
+            return;
+        }
+        self.ime_disabled = !enabled;
+
+        if self.ime_disabled {
+            self.ime_show_ui = sdl2::hint::get("SDL_IME_SHOW_UI");
+            sdl2::hint::set("SDL_IME_SHOW_UI", "0");
+        } else {
+            let prev = self.ime_show_ui.take();
+            sdl2::hint::set("SDL_IME_SHOW_UI", prev.as_deref().unwrap_or("0"));
+        }
+    }
+
+    fn describe_event(&self, event: &Self::Event) -> String {
+        format!("{:?}", event)
+    }
+
+    #[cfg(feature = "arboard")]
+    fn take_clipboard_image(&mut self) -> Option<super::ClipboardImage> {
+        self.clipboard_image.take()
+    }
+
+    fn window_size(&self, window: &Self::Window) -> LogicalSize {
+        let (w, h) = window.size();
+        LogicalSize([w as f32, h as f32])
+    }
+
+    fn framebuffer_size(&self, window: &Self::Window) -> PhysicalSize {
+        let (w, h) = window.drawable_size();
+        PhysicalSize([w, h])
+    }
+
+    fn set_pointer_transform(&mut self, transform: Option<PointerTransform>) {
+        self.pointer_transform = transform;
+    }
+
     fn prepare_frame(&mut self, io: &mut imgui::Io, window: &Self::Window) {
         // Here we're CHEATING. We don't have acecss to `EventPump` but we can get the mouse state
         // (though (x, y) values can't be seet to it).
         let (mut x, mut y) = (0, 0);
+        // SAFETY: SDL is initialized since we have a window, and `x` and `y` are valid pointers
         let mouse_state: u32 = unsafe { sdl2::sys::SDL_GetMouseState(&mut x, &mut y) };
         let mouse_state = sdl2::mouse::MouseState::from_sdl_state(mouse_state);
 
         let mouse_util = window.subsystem().sdl().mouse();
 
-        let (win_w, win_h) = window.size();
-        let (draw_w, draw_h) = window.drawable_size();
-
-        io.display_size = [win_w as f32, win_h as f32];
-        io.display_framebuffer_scale = [
-            (draw_w as f32) / (win_w as f32),
-            (draw_h as f32) / (win_h as f32),
-        ];
+        dpi::set_display(io, self.window_size(window), self.framebuffer_size(window));
 
         // Merging the mousedown events we received into the current state prevents us from missing
         // clicks that happen faster than a frame
@@ -213,14 +853,38 @@ impl Platform for ImGuiSdl2 {
         let any_mouse_down = io.mouse_down.iter().any(|&b| b);
         mouse_util.capture(any_mouse_down);
 
-        io.mouse_pos = [x as f32, y as f32];
+        // SDL reports the mouse position in window (logical) coordinates
+        let mut pos = LogicalPos([x as f32, y as f32]);
+        if let Some(ref transform) = self.pointer_transform {
+            pos = transform.apply(pos);
+        }
+
+        let window_size = self.window_size(window);
+        if let Some(vm) = self.virtual_mouse.as_mut() {
+            vm.update(io.delta_time, window_size);
+            // already in UI space
+            pos = LogicalPos(vm.pos);
+            io.mouse_down[0] |= vm.click;
+            if self.saved_draw_cursor.is_none() {
+                self.saved_draw_cursor = Some(io.mouse_draw_cursor);
+            }
+            io.mouse_draw_cursor = true;
+        } else if let Some(draw_cursor) = self.saved_draw_cursor.take() {
+            io.mouse_draw_cursor = draw_cursor;
+        }
+        dpi::set_mouse_pos(io, pos);
+
+        self.gamepad_nav.apply(io);
 
         self.ignore_keyboard = io.want_capture_keyboard;
         self.ignore_mouse = io.want_capture_mouse;
+        self.ignore_text_input = io.want_text_input;
     }
 
     fn prepare_render(&mut self, ui: &imgui::Ui, window: &Self::Window) {
         let io = ui.io();
+        self.sync_text_input(window, io.want_text_input);
+
         if io
             .config_flags
             .contains(ConfigFlags::NO_MOUSE_CURSOR_CHANGE)
@@ -230,33 +894,121 @@ impl Platform for ImGuiSdl2 {
 
         let mouse_util = window.subsystem().sdl().mouse();
         match ui.mouse_cursor() {
-            Some(mouse_cursor) if !io.mouse_draw_cursor => {
-                mouse_util.show_cursor(true);
-
-                let sdl_cursor = match mouse_cursor {
-                    MouseCursor::Arrow => SystemCursor::Arrow,
-                    MouseCursor::TextInput => SystemCursor::IBeam,
-                    MouseCursor::ResizeAll => SystemCursor::SizeAll,
-                    MouseCursor::ResizeNS => SystemCursor::SizeNS,
-                    MouseCursor::ResizeEW => SystemCursor::SizeWE,
-                    MouseCursor::ResizeNESW => SystemCursor::SizeNESW,
-                    MouseCursor::ResizeNWSE => SystemCursor::SizeNWSE,
-                    MouseCursor::Hand => SystemCursor::Hand,
-                    MouseCursor::NotAllowed => SystemCursor::No,
-                };
-
+            // ImGUI draws the cursor itself
+            Some(_) if io.mouse_draw_cursor => {
+                self.cursor = None;
+                mouse_util.show_cursor(false);
+            }
+            Some(mouse_cursor) => {
                 if self.cursor != Some(mouse_cursor) {
-                    let sdl_cursor = Cursor::from_system(sdl_cursor).unwrap();
-                    sdl_cursor.set();
-                    self.cursor = Some(mouse_cursor);
-                    self.sdl_cursor = Some(sdl_cursor);
+                    self.set_cursor(mouse_cursor);
+                    mouse_util.show_cursor(true);
                 }
             }
-            _ => {
+            None => {
                 self.cursor = None;
-                self.sdl_cursor = None;
                 mouse_util.show_cursor(false);
             }
         }
     }
+
+    fn shutdown(&mut self, imgui: &mut Context, window: &Self::Window) {
+        super::set_clipboard(imgui, Box::new(NullClipboard));
+
+        self.set_ime_enabled(window, true);
+
+        let video = window.subsystem();
+        video.text_input().stop();
+
+        let mouse_util = video.sdl().mouse();
+        mouse_util.capture(false);
+        mouse_util.show_cursor(true);
+
+        self.cursor = None;
+        self.system_cursors.iter_mut().for_each(|c| *c = None);
+        self.custom_cursors.iter_mut().for_each(|c| *c = None);
+    }
+}
+
+#[cfg(feature = "rfd")]
+impl super::NativeDialogs for ImGuiSdl2 {
+    fn open_file(&mut self, filters: &[super::dialogs::DialogFilter]) {
+        self.dialogs.open_file(filters);
+    }
+
+    fn save_file(&mut self, filters: &[super::dialogs::DialogFilter]) {
+        self.dialogs.save_file(filters);
+    }
+
+    fn poll_dialog(&mut self) -> Option<Option<std::path::PathBuf>> {
+        self.dialogs.poll_dialog()
+    }
+}
+
+impl<R: Renderer> Backend<ImGuiSdl2, R> {
+    /// Drains the event pump, routing every event through the platform and forwarding the ones not
+    /// captured by ImGUI to `on_event`
+    pub fn pump_events(
+        &mut self,
+        window: &Window,
+        pump: &mut sdl2::EventPump,
+        mut on_event: impl FnMut(&Event),
+    ) {
+        for ev in pump.poll_iter() {
+            if !self.handle_event(window, &ev).is_captured() {
+                on_event(&ev);
+            }
+        }
+    }
+}
+
+/// Display DPI / 96. Falls back to `1.0` if SDL can't tell
+fn dpi_scale(window: &Window) -> f32 {
+    let video = window.subsystem();
+    window
+        .display_index()
+        .and_then(|i| video.display_dpi(i))
+        .map(|(ddpi, _hdpi, _vdpi)| ddpi / 96.0)
+        .unwrap_or(1.0)
+}
+
+/// OpenGL swap chain of the window
+impl crate::helper::Present for Window {
+    fn set_swap_interval(&mut self, interval: crate::helper::SwapInterval) -> bool {
+        use {crate::helper::SwapInterval as Interval, sdl2::video::SwapInterval};
+
+        let interval = match interval {
+            Interval::Immediate => SwapInterval::Immediate,
+            Interval::VSync => SwapInterval::VSync,
+            Interval::Adaptive => SwapInterval::LateSwapTearing,
+        };
+        match self.subsystem().gl_set_swap_interval(interval) {
+            Ok(()) => true,
+            Err(err) => {
+                log::debug!("ImGuiSdl2: failed to set swap interval: {}", err);
+                false
+            }
+        }
+    }
+
+    fn swap_interval(&self) -> crate::helper::SwapInterval {
+        use {crate::helper::SwapInterval as Interval, sdl2::video::SwapInterval};
+
+        match self.subsystem().gl_get_swap_interval() {
+            SwapInterval::Immediate => Interval::Immediate,
+            SwapInterval::VSync => Interval::VSync,
+            SwapInterval::LateSwapTearing => Interval::Adaptive,
+        }
+    }
+
+    fn refresh_rate(&self) -> Option<f32> {
+        self.display_mode()
+            .ok()
+            .map(|mode| mode.refresh_rate as f32)
+            .filter(|hz| *hz > 0.0)
+    }
+
+    fn present(&mut self) {
+        self.gl_swap_window();
+    }
 }
@@ -0,0 +1,93 @@
+//! Text input shared by platforms
+//!
+//! Characters outside of the BMP (emoji, rare CJK ideographs) don't fit in a 16-bit `ImWchar`.
+//! Dear ImGUI would store them as `IM_UNICODE_CODEPOINT_INVALID`, so they're replaced with U+FFFD
+//! here, visibly and consistently across platforms. `imgui-sys` builds with 32-bit `ImWchar` by
+//! default.
+
+/// If Dear ImGUI is built with `IMGUI_USE_WCHAR32`
+pub const WCHAR32: bool = std::mem::size_of::<imgui::sys::ImWchar>() == 4;
+
+/// Character fed to ImGUI for `c`, or `None` if it's not text input
+pub fn input_char(c: char) -> Option<char> {
+    if c.is_control() && c != '\t' && c != '\n' {
+        return None;
+    }
+    if !WCHAR32 && c as u32 > 0xFFFF {
+        return Some(std::char::REPLACEMENT_CHARACTER);
+    }
+    Some(c)
+}
+
+/// Queues a character of text input
+pub fn add_input_char(io: &mut imgui::Io, c: char) {
+    if let Some(c) = self::input_char(c) {
+        io.add_input_character(c);
+    }
+}
+
+/// Queues UTF-8 text input, e.g. of an IME commit
+pub fn add_input_text(io: &mut imgui::Io, text: &str) {
+    for c in text.chars() {
+        self::add_input_char(io, c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPLACEMENT: u32 = std::char::REPLACEMENT_CHARACTER as u32;
+
+    /// Code points queued in ImGUI IO by [`add_input_text`]
+    fn queued(text: &str) -> Vec<u32> {
+        let _lock = crate::lock_imgui_context();
+        let mut imgui = imgui::Context::create();
+        self::add_input_text(imgui.io_mut(), text);
+
+        // `imgui::Io` mirrors `ImGuiIO`
+        let io = unsafe { &*(imgui.io() as *const imgui::Io as *const imgui::sys::ImGuiIO) };
+        let queue = &io.InputQueueCharacters;
+        (0..queue.Size as usize)
+            .map(|i| unsafe { *queue.Data.add(i) } as u32)
+            .collect()
+    }
+
+    #[test]
+    fn control_characters_are_not_text() {
+        assert_eq!(input_char('\u{8}'), None);
+        assert_eq!(input_char('\r'), None);
+        assert_eq!(input_char('\t'), Some('\t'));
+        assert_eq!(input_char('\n'), Some('\n'));
+        assert_eq!(queued("a\u{1b}b"), vec!['a' as u32, 'b' as u32]);
+    }
+
+    #[test]
+    fn non_bmp_without_wchar32() {
+        assert_eq!(input_char('日'), Some('日'));
+
+        let expected = if WCHAR32 {
+            '😀'
+        } else {
+            std::char::REPLACEMENT_CHARACTER
+        };
+        assert_eq!(input_char('😀'), Some(expected));
+        assert_eq!(queued("😀"), vec![expected as u32]);
+    }
+
+    #[test]
+    fn surrogates() {
+        // surrogate pair of U+1D11E from UTF-16 text input
+        let units = [0x61, 0xd834, 0xdd1e, 0x62];
+        let text = String::from_utf16(&units).unwrap();
+        let clef = if WCHAR32 { 0x1d11e } else { REPLACEMENT };
+        assert_eq!(queued(&text), vec![0x61, clef, 0x62]);
+
+        // unpaired surrogates are never queued as is
+        let text = String::from_utf16_lossy(&[0xd834, 0x61, 0xdd1e]);
+        assert_eq!(queued(&text), vec![REPLACEMENT, 0x61, REPLACEMENT]);
+        assert!(queued("a😀b𝄞c")
+            .iter()
+            .all(|&c| !(0xd800..=0xdfff).contains(&c)));
+    }
+}
@@ -0,0 +1,454 @@
+/*!
+Win32 platform integration for `imgui-rs`
+
+For engines running their own window procedure without SDL or winit. Forward messages from your
+`WndProc` and skip your own handling if they're captured:
+
+```no_run
+extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    let backend = /* omitted */;
+    let captured = backend.handle_event(&hwnd, &Win32Message::new(msg, wparam, lparam));
+    // we've set the cursor shape
+    if msg == WM_SETCURSOR && captured.is_captured() {
+        return LRESULT(1);
+    }
+    unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+}
+```
+
+HiDPI: call [`enable_dpi_awareness`] before creating windows. The DPI is read from the window every
+frame, so `WM_DPICHANGED` needs no handling here. The clipboard is left to ImGUI's built-in Win32
+implementation.
+*/
+
+mod sys;
+
+use {
+    imgui::{BackendFlags, ConfigFlags, Context, MouseCursor},
+    windows::Win32::{
+        Foundation::{HWND, LPARAM, WPARAM},
+        UI::{
+            Input::KeyboardAndMouse::*,
+            WindowsAndMessaging::{
+                HTCLIENT, WHEEL_DELTA, WM_CHAR, WM_KEYDOWN, WM_KEYUP, WM_KILLFOCUS,
+                WM_LBUTTONDBLCLK, WM_LBUTTONDOWN, WM_LBUTTONUP, WM_MBUTTONDBLCLK, WM_MBUTTONDOWN,
+                WM_MBUTTONUP, WM_MOUSEHWHEEL, WM_MOUSELEAVE, WM_MOUSEMOVE, WM_MOUSEWHEEL,
+                WM_RBUTTONDBLCLK, WM_RBUTTONDOWN, WM_RBUTTONUP, WM_SETCURSOR, WM_SYSKEYDOWN,
+                WM_SYSKEYUP, WM_XBUTTONDBLCLK, WM_XBUTTONDOWN, WM_XBUTTONUP, XBUTTON1,
+            },
+        },
+    },
+};
+
+use crate::{
+    dpi::{self, LogicalPos, LogicalSize, PhysicalPos, PhysicalSize, PointerTransform},
+    helper::{InputTiming, Modifiers},
+    platform::UiKey,
+    Captured, Platform,
+};
+
+/// Makes the process per-monitor DPI aware (v2). Call it before creating windows. Returns false if
+/// it failed, e.g. when the awareness is already set by the manifest
+pub fn enable_dpi_awareness() -> bool {
+    sys::enable_dpi_awareness()
+}
+
+/// DPI scale of the window (`GetDpiForWindow / 96`)
+pub fn dpi_scale(hwnd: HWND) -> f32 {
+    sys::dpi(hwnd) as f32 / sys::DEFAULT_DPI as f32
+}
+
+/// Native key of a [`UiKey`], used as the index of `io.keys_down`
+fn virtual_key(key: UiKey) -> u16 {
+    match key {
+        UiKey::Tab => VK_TAB.0,
+        UiKey::Left => VK_LEFT.0,
+        UiKey::Right => VK_RIGHT.0,
+        UiKey::Up => VK_UP.0,
+        UiKey::Down => VK_DOWN.0,
+        UiKey::PageUp => VK_PRIOR.0,
+        UiKey::PageDown => VK_NEXT.0,
+        UiKey::Home => VK_HOME.0,
+        UiKey::End => VK_END.0,
+        UiKey::Insert => VK_INSERT.0,
+        UiKey::Delete => VK_DELETE.0,
+        UiKey::Backspace => VK_BACK.0,
+        UiKey::Space => VK_SPACE.0,
+        UiKey::Enter => VK_RETURN.0,
+        UiKey::Escape => VK_ESCAPE.0,
+        // no separate virtual key; the same as `Enter`
+        UiKey::KeyPadEnter => VK_RETURN.0,
+        UiKey::A => b'A' as u16,
+        UiKey::C => b'C' as u16,
+        UiKey::V => b'V' as u16,
+        UiKey::X => b'X' as u16,
+        UiKey::Y => b'Y' as u16,
+        UiKey::Z => b'Z' as u16,
+    }
+}
+
+/// Window message forwarded from a `WndProc`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Win32Message {
+    pub msg: u32,
+    pub wparam: WPARAM,
+    pub lparam: LPARAM,
+}
+
+impl Win32Message {
+    pub fn new(msg: u32, wparam: WPARAM, lparam: LPARAM) -> Self {
+        Self {
+            msg,
+            wparam,
+            lparam,
+        }
+    }
+
+    fn lo_word(&self) -> u16 {
+        (self.lparam.0 & 0xffff) as u16
+    }
+
+    /// `GET_X_LPARAM` and `GET_Y_LPARAM`
+    fn pos(&self) -> [f32; 2] {
+        let x = (self.lparam.0 & 0xffff) as u16 as i16;
+        let y = ((self.lparam.0 >> 16) & 0xffff) as u16 as i16;
+        [x as f32, y as f32]
+    }
+
+    /// `GET_WHEEL_DELTA_WPARAM` in notches
+    fn wheel(&self) -> f32 {
+        let delta = ((self.wparam.0 >> 16) & 0xffff) as u16 as i16;
+        delta as f32 / WHEEL_DELTA as f32
+    }
+}
+
+/// Joins UTF-16 code units of `WM_CHAR` into characters. Characters outside of the BMP (e.g. emoji)
+/// come in two messages, a high surrogate and then a low surrogate
+#[derive(Debug, Clone, Default)]
+pub struct Utf16Decoder {
+    high: Option<u16>,
+}
+
+impl Utf16Decoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a character once it's complete. Unpaired surrogates are dropped
+    pub fn push(&mut self, unit: u16) -> Option<char> {
+        match unit {
+            0xd800..=0xdbff => {
+                if self.high.replace(unit).is_some() {
+                    log::debug!("ImGuiWin32: dropped an unpaired high surrogate");
+                }
+                None
+            }
+            0xdc00..=0xdfff => {
+                let high = self.high.take()?;
+                std::char::decode_utf16([high, unit].iter().cloned())
+                    .next()
+                    .and_then(|c| c.ok())
+            }
+            _ => {
+                self.high = None;
+                std::char::from_u32(unit as u32)
+            }
+        }
+    }
+}
+
+/// Win32 platform for `imgui-rs`
+#[derive(Debug)]
+pub struct ImGuiWin32 {
+    /// Last cursor position in physical pixels. `None` while the cursor is out of the window
+    mouse_pos: Option<PhysicalPos>,
+    /// `WM_MOUSELEAVE` is requested. Tracking ends when it's sent
+    mouse_tracked: bool,
+    /// Presses in this frame, so that clicks faster than a frame are not missed
+    mouse_press: [bool; 5],
+    mouse_down: [bool; 5],
+    ignore_mouse: bool,
+    ignore_keyboard: bool,
+    ignore_text_input: bool,
+    /// Cursor set to the OS. `None` before the first frame
+    cursor: Option<Option<MouseCursor>>,
+    pointer_transform: Option<PointerTransform>,
+    chars: Utf16Decoder,
+    /// Events from other windows are ignored
+    hwnd: HWND,
+}
+
+impl ImGuiWin32 {
+    pub fn new(imgui: &mut Context, hwnd: HWND) -> Self {
+        imgui.set_platform_name(Some(crate::backend_name("imgui-win32-platform", &[])));
+
+        let io = imgui.io_mut();
+        io.backend_flags
+            .insert(BackendFlags::HAS_MOUSE_CURSORS | BackendFlags::HAS_SET_MOUSE_POS);
+
+        super::set_key_map(io, |key| Some(self::virtual_key(key) as u32));
+
+        // the system double-click time and key repeat rate are left to ImGUI; only distances are
+        // scaled
+        let timing = InputTiming::for_dpi_scale(self::dpi_scale(hwnd));
+        timing.apply_to_defaults(io);
+
+        dpi::set_display(io, Self::logical_size(hwnd), Self::physical_size(hwnd));
+
+        Self {
+            mouse_pos: None,
+            mouse_tracked: false,
+            mouse_press: [false; 5],
+            mouse_down: [false; 5],
+            ignore_mouse: false,
+            ignore_keyboard: false,
+            ignore_text_input: false,
+            cursor: None,
+            pointer_transform: None,
+            chars: Utf16Decoder::new(),
+            hwnd,
+        }
+    }
+
+    fn physical_size(hwnd: HWND) -> PhysicalSize {
+        PhysicalSize(sys::client_size(hwnd))
+    }
+
+    fn logical_size(hwnd: HWND) -> LogicalSize {
+        let scale = self::dpi_scale(hwnd);
+        Self::physical_size(hwnd).to_logical([scale, scale])
+    }
+
+    fn on_mouse_button(&mut self, index: usize, pressed: bool) -> Captured {
+        if pressed {
+            if !self.mouse_down.iter().any(|b| *b) {
+                sys::capture_mouse(self.hwnd);
+            }
+            self.mouse_press[index] = true;
+        }
+        self.mouse_down[index] = pressed;
+        if !pressed && !self.mouse_down.iter().any(|b| *b) {
+            sys::release_mouse(self.hwnd);
+        }
+        self.capture(Captured::Mouse, self.ignore_mouse)
+    }
+
+    fn capture(&self, captured: Captured, ignore: bool) -> Captured {
+        if ignore {
+            captured
+        } else {
+            Captured::No
+        }
+    }
+}
+
+impl Platform for ImGuiWin32 {
+    type Event = Win32Message;
+    type Window = HWND;
+
+    /// `WM_SETCURSOR` is reported as captured when we set the cursor shape; return `TRUE` from the
+    /// window procedure then
+    fn handle_event(
+        &mut self,
+        imgui: &mut Context,
+        hwnd: &Self::Window,
+        event: &Self::Event,
+    ) -> Captured {
+        if *hwnd != self.hwnd {
+            log::debug!("ImGuiWin32: ignoring message to window {:?}", hwnd);
+            return Captured::No;
+        }
+
+        let io = imgui.io_mut();
+        match event.msg {
+            WM_MOUSEMOVE => {
+                // first move after entering the window
+                if !self.mouse_tracked {
+                    sys::track_mouse_leave(self.hwnd);
+                    self.mouse_tracked = true;
+                }
+                self.mouse_pos = Some(PhysicalPos(event.pos()));
+                self.capture(Captured::Mouse, self.ignore_mouse)
+            }
+            WM_MOUSELEAVE => {
+                self.mouse_tracked = false;
+                self.mouse_pos = None;
+                Captured::No
+            }
+            WM_LBUTTONDOWN | WM_LBUTTONDBLCLK => self.on_mouse_button(0, true),
+            WM_RBUTTONDOWN | WM_RBUTTONDBLCLK => self.on_mouse_button(1, true),
+            WM_MBUTTONDOWN | WM_MBUTTONDBLCLK => self.on_mouse_button(2, true),
+            WM_LBUTTONUP => self.on_mouse_button(0, false),
+            WM_RBUTTONUP => self.on_mouse_button(1, false),
+            WM_MBUTTONUP => self.on_mouse_button(2, false),
+            WM_XBUTTONDOWN | WM_XBUTTONDBLCLK | WM_XBUTTONUP => {
+                // `GET_XBUTTON_WPARAM`
+                let button = ((event.wparam.0 >> 16) & 0xffff) as u16;
+                let index = if button == XBUTTON1 as u16 { 3 } else { 4 };
+                self.on_mouse_button(index, event.msg != WM_XBUTTONUP)
+            }
+            WM_MOUSEWHEEL => {
+                io.mouse_wheel += event.wheel();
+                self.capture(Captured::Mouse, self.ignore_mouse)
+            }
+            WM_MOUSEHWHEEL => {
+                io.mouse_wheel_h += event.wheel();
+                self.capture(Captured::Mouse, self.ignore_mouse)
+            }
+            WM_KEYDOWN | WM_SYSKEYDOWN | WM_KEYUP | WM_SYSKEYUP => {
+                let down = event.msg == WM_KEYDOWN || event.msg == WM_SYSKEYDOWN;
+                if let Some(key) = io.keys_down.get_mut(event.wparam.0) {
+                    *key = down;
+                }
+                self.capture(Captured::Keyboard, self.ignore_keyboard)
+            }
+            WM_CHAR => {
+                if let Some(c) = self.chars.push(event.wparam.0 as u16) {
+                    super::add_input_char(io, c);
+                }
+                self.capture(
+                    Captured::TextInput,
+                    self.ignore_text_input || self.ignore_keyboard,
+                )
+            }
+            WM_KILLFOCUS => {
+                io.keys_down.iter_mut().for_each(|k| *k = false);
+                Modifiers::default().apply(io);
+                self.mouse_down = [false; 5];
+                self.mouse_press = [false; 5];
+                Captured::No
+            }
+            WM_SETCURSOR => {
+                // we only set it in the client area
+                if event.lo_word() != HTCLIENT as u16 {
+                    return Captured::No;
+                }
+                match self.cursor {
+                    Some(cursor)
+                        if !io
+                            .config_flags
+                            .contains(ConfigFlags::NO_MOUSE_CURSOR_CHANGE) =>
+                    {
+                        sys::set_cursor(cursor);
+                        Captured::Mouse
+                    }
+                    _ => Captured::No,
+                }
+            }
+            _ => Captured::No,
+        }
+    }
+
+    fn describe_event(&self, event: &Self::Event) -> String {
+        format!(
+            "Win32Message {{ msg: {:#06x}, wparam: {:#x}, lparam: {:#x} }}",
+            event.msg, event.wparam.0, event.lparam.0
+        )
+    }
+
+    fn window_size(&self, hwnd: &Self::Window) -> LogicalSize {
+        Self::logical_size(*hwnd)
+    }
+
+    fn framebuffer_size(&self, hwnd: &Self::Window) -> PhysicalSize {
+        Self::physical_size(*hwnd)
+    }
+
+    fn set_pointer_transform(&mut self, transform: Option<PointerTransform>) {
+        self.pointer_transform = transform;
+    }
+
+    fn prepare_frame(&mut self, io: &mut imgui::Io, hwnd: &Self::Window) {
+        let hwnd = *hwnd;
+        let scale = self::dpi_scale(hwnd);
+        dpi::set_display(io, self.window_size(&hwnd), self.framebuffer_size(&hwnd));
+
+        Modifiers {
+            ctrl: sys::is_key_down(VK_CONTROL.0),
+            shift: sys::is_key_down(VK_SHIFT.0),
+            alt: sys::is_key_down(VK_MENU.0),
+            super_: sys::is_key_down(VK_LWIN.0) || sys::is_key_down(VK_RWIN.0),
+        }
+        .apply(io);
+
+        for i in 0..5 {
+            io.mouse_down[i] = self.mouse_down[i] || self.mouse_press[i];
+        }
+        self.mouse_press = [false; 5];
+
+        if io.want_set_mouse_pos {
+            let [x, y] = io.mouse_pos;
+            sys::set_cursor_pos(hwnd, [(x * scale) as i32, (y * scale) as i32]);
+        }
+
+        let pos = match self.mouse_pos {
+            Some(pos) => {
+                let mut pos = pos.to_logical([scale, scale]);
+                if let Some(ref transform) = self.pointer_transform {
+                    pos = transform.apply(pos);
+                }
+                pos
+            }
+            None => LogicalPos([-f32::MAX, -f32::MAX]),
+        };
+        dpi::set_mouse_pos(io, pos);
+
+        self.ignore_keyboard = io.want_capture_keyboard;
+        self.ignore_mouse = io.want_capture_mouse;
+        self.ignore_text_input = io.want_text_input;
+    }
+
+    fn prepare_render(&mut self, ui: &imgui::Ui, _hwnd: &Self::Window) {
+        let io = ui.io();
+        if io
+            .config_flags
+            .contains(ConfigFlags::NO_MOUSE_CURSOR_CHANGE)
+        {
+            return;
+        }
+
+        let cursor = if io.mouse_draw_cursor {
+            None
+        } else {
+            ui.mouse_cursor()
+        };
+        if self.cursor == Some(cursor) {
+            return;
+        }
+        self.cursor = Some(cursor);
+        // only while the cursor is on the window; otherwise `WM_SETCURSOR` sets it on enter
+        if self.mouse_pos.is_some() {
+            sys::set_cursor(cursor);
+        }
+    }
+
+    fn shutdown(&mut self, imgui: &mut Context, hwnd: &Self::Window) {
+        sys::release_mouse(*hwnd);
+        sys::set_cursor(Some(MouseCursor::Arrow));
+        self.cursor = None;
+        let io = imgui.io_mut();
+        io.keys_down.iter_mut().for_each(|k| *k = false);
+        io.mouse_down = [false; 5];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Utf16Decoder;
+
+    #[test]
+    fn utf16_decoder_joins_surrogates() {
+        let mut decoder = Utf16Decoder::new();
+        assert_eq!(decoder.push(0x61), Some('a'));
+        assert_eq!(decoder.push(0xd83d), None);
+        assert_eq!(decoder.push(0xde00), Some('😀'));
+
+        // unpaired surrogates are dropped
+        assert_eq!(decoder.push(0xd83d), None);
+        assert_eq!(decoder.push(0x62), Some('b'));
+        assert_eq!(decoder.push(0xde00), None);
+        assert_eq!(decoder.push(0xd83d), None);
+        assert_eq!(decoder.push(0xd83d), None);
+        assert_eq!(decoder.push(0xde00), Some('😀'));
+    }
+}
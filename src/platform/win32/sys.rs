@@ -0,0 +1,136 @@
+//! Win32 calls used by [`super::ImGuiWin32`]
+//!
+//! Every `unsafe` call of the platform is here. They take a window handle given by the
+//! application, which must be a live window of this thread (Win32 fails gracefully otherwise).
+
+use {
+    imgui::MouseCursor,
+    windows::Win32::{
+        Foundation::{HWND, POINT, RECT},
+        Graphics::Gdi::ClientToScreen,
+        UI::{
+            HiDpi::{
+                GetDpiForWindow, SetProcessDpiAwarenessContext,
+                DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+            },
+            Input::KeyboardAndMouse::{
+                GetCapture, GetKeyState, ReleaseCapture, SetCapture, TrackMouseEvent, TME_LEAVE,
+                TRACKMOUSEEVENT,
+            },
+            WindowsAndMessaging::{
+                GetClientRect, LoadCursorW, SetCursor, SetCursorPos, HCURSOR, IDC_ARROW, IDC_HAND,
+                IDC_IBEAM, IDC_NO, IDC_SIZEALL, IDC_SIZENESW, IDC_SIZENS, IDC_SIZENWSE, IDC_SIZEWE,
+            },
+        },
+    },
+};
+
+/// `USER_DEFAULT_SCREEN_DPI`
+pub const DEFAULT_DPI: u32 = 96;
+
+/// Size of the client area in pixels
+pub fn client_size(hwnd: HWND) -> [u32; 2] {
+    let mut rect = RECT::default();
+    // SAFETY: `rect` is a valid out pointer
+    if !unsafe { GetClientRect(hwnd, &mut rect) }.as_bool() {
+        return [0, 0];
+    }
+    [
+        (rect.right - rect.left).max(0) as u32,
+        (rect.bottom - rect.top).max(0) as u32,
+    ]
+}
+
+/// DPI of the monitor the window is on. Falls back to 96 on failure
+pub fn dpi(hwnd: HWND) -> u32 {
+    // SAFETY: returns 0 for invalid windows
+    match unsafe { GetDpiForWindow(hwnd) } {
+        0 => DEFAULT_DPI,
+        dpi => dpi,
+    }
+}
+
+pub fn enable_dpi_awareness() -> bool {
+    // SAFETY: no pointers involved
+    unsafe { SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2) }.as_bool()
+}
+
+/// If the virtual key is down
+pub fn is_key_down(vk: u16) -> bool {
+    // SAFETY: no pointers involved
+    let state = unsafe { GetKeyState(vk as i32) };
+    state < 0
+}
+
+/// Captures the mouse unless it's captured already, so that drags out of the window are tracked
+pub fn capture_mouse(hwnd: HWND) {
+    // SAFETY: no pointers involved
+    unsafe {
+        if GetCapture().0 == 0 {
+            SetCapture(hwnd);
+        }
+    }
+}
+
+/// Releases the mouse if the window captured it
+pub fn release_mouse(hwnd: HWND) {
+    // SAFETY: no pointers involved
+    unsafe {
+        if GetCapture() == hwnd {
+            ReleaseCapture();
+        }
+    }
+}
+
+/// Requests `WM_MOUSELEAVE` for when the cursor leaves the client area. It's sent once per call
+pub fn track_mouse_leave(hwnd: HWND) {
+    let mut tme = TRACKMOUSEEVENT {
+        cbSize: std::mem::size_of::<TRACKMOUSEEVENT>() as u32,
+        dwFlags: TME_LEAVE,
+        hwndTrack: hwnd,
+        dwHoverTime: 0,
+    };
+    // SAFETY: `tme` is a valid in pointer
+    unsafe {
+        TrackMouseEvent(&mut tme);
+    }
+}
+
+/// Moves the OS cursor to a position in client pixels
+pub fn set_cursor_pos(hwnd: HWND, pos: [i32; 2]) {
+    let mut point = POINT {
+        x: pos[0],
+        y: pos[1],
+    };
+    // SAFETY: `point` is a valid in/out pointer
+    unsafe {
+        if ClientToScreen(hwnd, &mut point).as_bool() {
+            SetCursorPos(point.x, point.y);
+        }
+    }
+}
+
+/// Sets the OS cursor shape. `None` hides it
+pub fn set_cursor(cursor: Option<MouseCursor>) {
+    let name = match cursor {
+        Some(MouseCursor::Arrow) => IDC_ARROW,
+        Some(MouseCursor::TextInput) => IDC_IBEAM,
+        Some(MouseCursor::ResizeAll) => IDC_SIZEALL,
+        Some(MouseCursor::ResizeNS) => IDC_SIZENS,
+        Some(MouseCursor::ResizeEW) => IDC_SIZEWE,
+        Some(MouseCursor::ResizeNESW) => IDC_SIZENESW,
+        Some(MouseCursor::ResizeNWSE) => IDC_SIZENWSE,
+        Some(MouseCursor::Hand) => IDC_HAND,
+        Some(MouseCursor::NotAllowed) => IDC_NO,
+        None => {
+            // SAFETY: a null cursor hides the cursor
+            unsafe { SetCursor(HCURSOR(0)) };
+            return;
+        }
+    };
+
+    // SAFETY: system cursors are loaded with a null instance and live for the whole process
+    unsafe {
+        SetCursor(LoadCursorW(None, name));
+    }
+}
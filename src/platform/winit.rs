@@ -0,0 +1,384 @@
+/*!
+winit platform integration for `imgui-rs`
+
+Clipboard: winit has no clipboard API. With the `arboard` feature, the OS clipboard is installed
+with [`super::ArboardClipboard`]; otherwise set one with [`crate::Backend::set_clipboard`].
+
+HiDPI: the scale factor is read from the window every frame, so `ScaleFactorChanged` events need
+no handling (they're dropped by `Event::to_static` anyways). Feed events with:
+
+```no_run
+event_loop.run(move |event, _, control_flow| {
+    if let Some(event) = event.to_static() {
+        backend.handle_event(&window, &event);
+    }
+});
+```
+*/
+
+use {
+    imgui::{ConfigFlags, Context, MouseCursor},
+    std::fmt,
+    winit::{
+        dpi::{LogicalPosition, PhysicalPosition},
+        event::{
+            ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode,
+            WindowEvent,
+        },
+        window::{CursorIcon, Window},
+    },
+};
+
+use crate::{
+    dpi::{self, LogicalPos, LogicalSize, PhysicalSize, PointerTransform},
+    helper::{InputTiming, Modifiers},
+    platform::UiKey,
+    Captured, Platform,
+};
+
+/// Native key of a [`UiKey`], used as the index of `io.keys_down`
+fn virtual_key(key: UiKey) -> VirtualKeyCode {
+    match key {
+        UiKey::Tab => VirtualKeyCode::Tab,
+        UiKey::Left => VirtualKeyCode::Left,
+        UiKey::Right => VirtualKeyCode::Right,
+        UiKey::Up => VirtualKeyCode::Up,
+        UiKey::Down => VirtualKeyCode::Down,
+        UiKey::PageUp => VirtualKeyCode::PageUp,
+        UiKey::PageDown => VirtualKeyCode::PageDown,
+        UiKey::Home => VirtualKeyCode::Home,
+        UiKey::End => VirtualKeyCode::End,
+        UiKey::Insert => VirtualKeyCode::Insert,
+        UiKey::Delete => VirtualKeyCode::Delete,
+        UiKey::Backspace => VirtualKeyCode::Back,
+        UiKey::Space => VirtualKeyCode::Space,
+        UiKey::Enter => VirtualKeyCode::Return,
+        UiKey::Escape => VirtualKeyCode::Escape,
+        UiKey::KeyPadEnter => VirtualKeyCode::NumpadEnter,
+        UiKey::A => VirtualKeyCode::A,
+        UiKey::C => VirtualKeyCode::C,
+        UiKey::V => VirtualKeyCode::V,
+        UiKey::X => VirtualKeyCode::X,
+        UiKey::Y => VirtualKeyCode::Y,
+        UiKey::Z => VirtualKeyCode::Z,
+    }
+}
+
+/// Index of `io.mouse_down` for `MouseButton::Other`: back (X1) is 3 and forward (X2) is 4. Other
+/// buttons are dropped
+fn extra_button_index(n: u16) -> Option<usize> {
+    // winit reports the button number of the platform
+    let (back, forward) = if cfg!(windows) {
+        (1, 2)
+    } else if cfg!(target_os = "macos") {
+        (3, 4)
+    } else {
+        // X11
+        (8, 9)
+    };
+    match n {
+        _ if n == back => Some(3),
+        _ if n == forward => Some(4),
+        _ => None,
+    }
+}
+
+/// winit platform for `imgui-rs`
+pub struct ImGuiWinit<T: 'static = ()> {
+    /// Last cursor position in logical pixels. `None` while the cursor is out of the window
+    mouse_pos: Option<LogicalPos>,
+    /// Presses in this frame, so that clicks faster than a frame are not missed
+    mouse_press: [bool; 5],
+    mouse_down: [bool; 5],
+    ignore_mouse: bool,
+    ignore_keyboard: bool,
+    ignore_text_input: bool,
+    cursor: Option<Option<MouseCursor>>,
+    pointer_transform: Option<PointerTransform>,
+    /// Events from other windows are ignored
+    window_id: winit::window::WindowId,
+    _user_event: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: 'static> fmt::Debug for ImGuiWinit<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImGuiWinit")
+            .field("mouse_pos", &self.mouse_pos)
+            .field("mouse_press", &self.mouse_press)
+            .field("mouse_down", &self.mouse_down)
+            .field("ignore_mouse", &self.ignore_mouse)
+            .field("ignore_keyboard", &self.ignore_keyboard)
+            .field("ignore_text_input", &self.ignore_text_input)
+            .field("cursor", &self.cursor)
+            .field("pointer_transform", &self.pointer_transform)
+            .field("window_id", &self.window_id)
+            .finish()
+    }
+}
+
+impl<T: 'static> ImGuiWinit<T> {
+    pub fn new(imgui: &mut Context, window: &Window) -> Self {
+        imgui.set_platform_name(Some(crate::backend_name("imgui-winit-platform", &[])));
+
+        let io = imgui.io_mut();
+        super::set_key_map(io, |key| Some(self::virtual_key(key) as u32));
+
+        // winit doesn't expose the system double-click time or key repeat rate; only distances are
+        // scaled
+        let timing = InputTiming::for_dpi_scale(window.scale_factor() as f32);
+        timing.apply_to_defaults(io);
+
+        dpi::set_display(io, Self::logical_size(window), Self::physical_size(window));
+
+        // winit has no clipboard API
+        #[cfg(feature = "arboard")]
+        {
+            if let Some(clipboard) = super::ArboardClipboard::new() {
+                super::set_clipboard(imgui, Box::new(clipboard));
+            }
+        }
+
+        Self {
+            mouse_pos: None,
+            mouse_press: [false; 5],
+            mouse_down: [false; 5],
+            ignore_mouse: false,
+            ignore_keyboard: false,
+            ignore_text_input: false,
+            cursor: None,
+            pointer_transform: None,
+            window_id: window.id(),
+            _user_event: std::marker::PhantomData,
+        }
+    }
+
+    fn logical_size(window: &Window) -> LogicalSize {
+        let size = window.inner_size().to_logical::<f32>(window.scale_factor());
+        LogicalSize([size.width, size.height])
+    }
+
+    fn physical_size(window: &Window) -> PhysicalSize {
+        let size = window.inner_size();
+        PhysicalSize([size.width, size.height])
+    }
+
+    /// Handles an event of the window. Useful for events borrowed from the event loop, which
+    /// [`Platform::handle_event`] can't take
+    pub fn handle_window_event(
+        &mut self,
+        imgui: &mut Context,
+        window: &Window,
+        event: &WindowEvent<'_>,
+    ) -> Captured {
+        let io = imgui.io_mut();
+
+        match *event {
+            WindowEvent::ModifiersChanged(state) => {
+                Modifiers {
+                    ctrl: state.ctrl(),
+                    shift: state.shift(),
+                    alt: state.alt(),
+                    super_: state.logo(),
+                }
+                .apply(io);
+            }
+            WindowEvent::KeyboardInput {
+                input:
+                    KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state,
+                        ..
+                    },
+                ..
+            } => {
+                if let Some(down) = io.keys_down.get_mut(key as usize) {
+                    *down = state == ElementState::Pressed;
+                }
+                return self.capture(Captured::Keyboard, self.ignore_keyboard);
+            }
+            WindowEvent::ReceivedCharacter(chr) => {
+                // control characters (e.g. delete on macOS) are dropped
+                super::add_input_char(io, chr);
+                return self.capture(
+                    Captured::TextInput,
+                    self.ignore_text_input || self.ignore_keyboard,
+                );
+            }
+            WindowEvent::Focused(false) => {
+                io.keys_down.iter_mut().for_each(|k| *k = false);
+                Modifiers::default().apply(io);
+                // the button releases may go to another window
+                self.mouse_down = [false; 5];
+                self.mouse_press = [false; 5];
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let pos: LogicalPosition<f32> = position.to_logical(window.scale_factor());
+                self.mouse_pos = Some(LogicalPos([pos.x, pos.y]));
+                return self.capture(Captured::Mouse, self.ignore_mouse);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.mouse_pos = None;
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                // accumulate so that no wheel event in a frame is lost
+                match delta {
+                    MouseScrollDelta::LineDelta(x, y) => {
+                        io.mouse_wheel_h += x;
+                        io.mouse_wheel += y;
+                    }
+                    MouseScrollDelta::PixelDelta(PhysicalPosition { x, y }) => {
+                        // trackpads report pixels; one event scrolls one line
+                        let sign = |v: f64| (v > 0.0) as i32 as f32 - (v < 0.0) as i32 as f32;
+                        io.mouse_wheel_h += sign(x);
+                        io.mouse_wheel += sign(y);
+                    }
+                }
+                return self.capture(Captured::Mouse, self.ignore_mouse);
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let index = match button {
+                    MouseButton::Left => 0,
+                    MouseButton::Right => 1,
+                    MouseButton::Middle => 2,
+                    MouseButton::Other(n) => match self::extra_button_index(n) {
+                        Some(index) => index,
+                        None => return Captured::No,
+                    },
+                };
+                let pressed = state == ElementState::Pressed;
+                self.mouse_down[index] = pressed;
+                if pressed {
+                    self.mouse_press[index] = true;
+                }
+                return self.capture(Captured::Mouse, self.ignore_mouse);
+            }
+            _ => {}
+        }
+
+        Captured::No
+    }
+
+    fn capture(&self, captured: Captured, ignore: bool) -> Captured {
+        if ignore {
+            captured
+        } else {
+            Captured::No
+        }
+    }
+}
+
+impl<T: 'static> Platform for ImGuiWinit<T> {
+    type Event = Event<'static, T>;
+    type Window = Window;
+
+    fn handle_event(
+        &mut self,
+        imgui: &mut Context,
+        window: &Self::Window,
+        event: &Self::Event,
+    ) -> Captured {
+        match event {
+            Event::WindowEvent { window_id, event } if *window_id == self.window_id => {
+                self.handle_window_event(imgui, window, event)
+            }
+            Event::WindowEvent { window_id, .. } => {
+                log::debug!("ImGuiWinit: ignoring event from window {:?}", window_id);
+                Captured::No
+            }
+            _ => Captured::No,
+        }
+    }
+
+    fn describe_event(&self, event: &Self::Event) -> String {
+        format!("{:?}", event)
+    }
+
+    fn window_size(&self, window: &Self::Window) -> LogicalSize {
+        Self::logical_size(window)
+    }
+
+    fn framebuffer_size(&self, window: &Self::Window) -> PhysicalSize {
+        Self::physical_size(window)
+    }
+
+    fn set_pointer_transform(&mut self, transform: Option<PointerTransform>) {
+        self.pointer_transform = transform;
+    }
+
+    fn prepare_frame(&mut self, io: &mut imgui::Io, window: &Self::Window) {
+        dpi::set_display(io, self.window_size(window), self.framebuffer_size(window));
+
+        for i in 0..5 {
+            io.mouse_down[i] = self.mouse_down[i] || self.mouse_press[i];
+        }
+        self.mouse_press = [false; 5];
+
+        if io.want_set_mouse_pos {
+            let [x, y] = io.mouse_pos;
+            let pos = LogicalPosition::new(x as f64, y as f64);
+            if let Err(err) = window.set_cursor_position(pos) {
+                log::debug!("ImGuiWinit: failed to set cursor position: {}", err);
+            }
+        }
+
+        let pos = match self.mouse_pos {
+            Some(mut pos) => {
+                if let Some(ref transform) = self.pointer_transform {
+                    pos = transform.apply(pos);
+                }
+                pos
+            }
+            None => LogicalPos([-f32::MAX, -f32::MAX]),
+        };
+        dpi::set_mouse_pos(io, pos);
+
+        self.ignore_keyboard = io.want_capture_keyboard;
+        self.ignore_mouse = io.want_capture_mouse;
+        self.ignore_text_input = io.want_text_input;
+    }
+
+    fn prepare_render(&mut self, ui: &imgui::Ui, window: &Self::Window) {
+        let io = ui.io();
+        if io
+            .config_flags
+            .contains(ConfigFlags::NO_MOUSE_CURSOR_CHANGE)
+        {
+            return;
+        }
+
+        let cursor = if io.mouse_draw_cursor {
+            None
+        } else {
+            ui.mouse_cursor()
+        };
+        if self.cursor == Some(cursor) {
+            return;
+        }
+        self.cursor = Some(cursor);
+
+        match cursor {
+            Some(cursor) => {
+                window.set_cursor_visible(true);
+                window.set_cursor_icon(match cursor {
+                    MouseCursor::Arrow => CursorIcon::Default,
+                    MouseCursor::TextInput => CursorIcon::Text,
+                    MouseCursor::ResizeAll => CursorIcon::Move,
+                    MouseCursor::ResizeNS => CursorIcon::NsResize,
+                    MouseCursor::ResizeEW => CursorIcon::EwResize,
+                    MouseCursor::ResizeNESW => CursorIcon::NeswResize,
+                    MouseCursor::ResizeNWSE => CursorIcon::NwseResize,
+                    MouseCursor::Hand => CursorIcon::Hand,
+                    MouseCursor::NotAllowed => CursorIcon::NotAllowed,
+                });
+            }
+            None => window.set_cursor_visible(false),
+        }
+    }
+
+    fn shutdown(&mut self, imgui: &mut Context, window: &Self::Window) {
+        window.set_cursor_visible(true);
+        let io = imgui.io_mut();
+        io.keys_down.iter_mut().for_each(|k| *k = false);
+        io.mouse_down = [false; 5];
+    }
+}
@@ -0,0 +1,38 @@
+/*!
+Common imports and pre-made backend aliases
+
+```no_run
+use imgui_backends::prelude::*;
+
+let mut backend: Sdl2GlowBackend = Backend::new(imgui, platform, renderer);
+```
+*/
+
+pub use crate::{
+    helper::{Locale, QuickStart},
+    Backend, BackendUi, Captured, FontSampling, Platform, PreparedFrame, Renderer,
+};
+
+#[cfg(feature = "sdl2")]
+pub use crate::platform::ImGuiSdl2;
+
+#[cfg(feature = "glow")]
+pub use crate::renderer::ImGuiGlow;
+
+#[cfg(feature = "fna3d")]
+pub use crate::renderer::ImGuiFna3d;
+
+#[cfg(feature = "rokol")]
+pub use crate::renderer::ImGuiRokolGfx;
+
+/// SDL2 + glow
+#[cfg(all(feature = "sdl2", feature = "glow"))]
+pub type Sdl2GlowBackend = Backend<ImGuiSdl2, ImGuiGlow>;
+
+/// SDL2 + FNA3D
+#[cfg(all(feature = "sdl2", feature = "fna3d"))]
+pub type Sdl2Fna3dBackend = Backend<ImGuiSdl2, ImGuiFna3d>;
+
+/// SDL2 + `rokol::gfx`
+#[cfg(all(feature = "sdl2", feature = "rokol"))]
+pub type Sdl2RokolBackend = Backend<ImGuiSdl2, ImGuiRokolGfx>;
@@ -2,17 +2,30 @@
 Half of an `imgui-rs` backend
 */
 
+pub mod null;
+pub use self::null::NullRenderer;
+
 #[cfg(feature = "fna3d")]
 pub mod fna3d;
 #[cfg(feature = "fna3d")]
-pub use self::fna3d::ImGuiFna3d;
+pub use self::fna3d::{Fna3dConfig, ImGuiFna3d};
 
 #[cfg(feature = "rokol")]
 pub mod rokol;
 #[cfg(feature = "rokol")]
-pub use self::rokol::ImGuiRokolGfx;
+pub use self::rokol::{ImGuiRokolGfx, RokolConfig};
 
 #[cfg(feature = "glow")]
 pub mod glow;
 #[cfg(feature = "glow")]
-pub use self::glow::ImGuiGlow;
+pub use self::glow::{GlowConfig, ImGuiGlow, ImGuiGlowConfig};
+
+#[cfg(feature = "wgpu")]
+pub mod wgpu;
+#[cfg(feature = "wgpu")]
+pub use self::wgpu::ImGuiWgpu;
+
+#[cfg(feature = "ash")]
+pub mod ash;
+#[cfg(feature = "ash")]
+pub use self::ash::ImGuiAsh;
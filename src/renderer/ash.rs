@@ -0,0 +1,1300 @@
+/*!
+Vulkan (`ash`) integration for `imgui-rs`
+
+The UI is recorded into [`AshDevice::command_buffer`], which must be inside subpass 0 of
+[`AshDevice::render_pass`] when rendering. The color attachment is expected to be a UNORM format.
+
+Vertex and index buffers are kept per frame in flight: set [`AshDevice::frame_index`] before
+rendering. The GPU work of a frame has to be complete when its index comes around again, as usual
+with Vulkan swapchains. Textures are uploaded synchronously on [`AshDevice::queue`].
+
+The SPIR-V shaders are compiled from `ash/shader.{vert,frag}` and committed. Run
+`ash/compile-shaders.sh` (requires `glslc`) after editing the GLSL sources.
+
+Wait for the device to be idle before invalidating device objects or dropping [`ImGuiAsh`].
+*/
+
+use std::{
+    ffi::{CStr, CString},
+    fmt,
+    io::Cursor,
+    rc::Rc,
+};
+
+use ash::vk;
+use thiserror::Error;
+
+use crate::{
+    helper::{
+        as_bytes, DrawItem, DrawParams, DrawParamsIterator, TextureInfo, TextureRegistry,
+        TextureSource, VertexLayout,
+    },
+    FontSampling, RegisterTexture, Renderer,
+};
+
+#[cfg(feature = "test-engine")]
+mod conformance;
+
+pub const FONT_TEXTUER_ID: usize = usize::MAX;
+
+/// Size of a vertex in bytes
+pub const VERT_SIZE: usize = std::mem::size_of::<imgui::DrawVert>();
+
+const VERT_SPV: &[u8] = include_bytes!("ash/shader.vert.spv");
+const FRAG_SPV: &[u8] = include_bytes!("ash/shader.frag.spv");
+
+/// Size of the push constants in bytes: scale, translate and opacity
+const PUSH_CONSTANTS_SIZE: u32 = 20;
+
+/// Number of descriptor sets per pool. Pools are added on demand
+const SETS_PER_POOL: u32 = 64;
+
+#[derive(Debug, Error)]
+pub enum AshRendererError {
+    #[error("vulkan error: {0}")]
+    Vk(#[from] vk::Result),
+    #[error("bad texture id")]
+    BadTexture(imgui::TextureId),
+    #[error("no memory type for {0:?}")]
+    NoMemoryType(vk::MemoryPropertyFlags),
+    #[error("frame index {index} is out of {frames_in_flight} frames in flight")]
+    BadFrameIndex {
+        index: usize,
+        frames_in_flight: usize,
+    },
+    #[error("device objects are invalidated")]
+    Invalidated,
+    #[error("pixel data size mismatch: expected {expected} bytes, given {given} bytes")]
+    PixelSizeMismatch { expected: usize, given: usize },
+}
+
+/// Rendering context of [`ImGuiAsh`]
+pub struct AshDevice {
+    pub device: ash::Device,
+    /// Memory properties of the physical device of `device`
+    pub memory_properties: vk::PhysicalDeviceMemoryProperties,
+    /// Graphics queue for texture uploads
+    pub queue: vk::Queue,
+    /// Pool of the one-time command buffers for texture uploads
+    pub command_pool: vk::CommandPool,
+    /// Command buffer the UI is recorded into
+    pub command_buffer: vk::CommandBuffer,
+    /// Render pass the pipeline is created for
+    pub render_pass: vk::RenderPass,
+    /// Frame in flight in `0..frames_in_flight`, as given to [`ImGuiAsh::new`]
+    pub frame_index: usize,
+    /// Names GPU objects for graphics debuggers if `VK_EXT_debug_utils` is enabled
+    pub debug_utils: Option<ash::extensions::ext::DebugUtils>,
+}
+
+impl AshDevice {
+    /// Names a Vulkan object for graphics debuggers. No-op without [`Self::debug_utils`]
+    pub fn set_object_name<H: vk::Handle>(&self, handle: H, name: &str) {
+        let debug_utils = match self.debug_utils.as_ref() {
+            Some(debug_utils) => debug_utils,
+            None => return,
+        };
+        let name = match CString::new(name) {
+            Ok(name) => name,
+            Err(_) => return,
+        };
+
+        let info = vk::DebugUtilsObjectNameInfoEXT::builder()
+            .object_type(H::TYPE)
+            .object_handle(handle.as_raw())
+            .object_name(&name);
+        if let Err(err) =
+            unsafe { debug_utils.debug_utils_set_object_name(self.device.handle(), &info) }
+        {
+            log::warn!("ImGuiAsh: failed to name {:?}: {}", name, err);
+        }
+    }
+}
+
+impl fmt::Debug for AshDevice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AshDevice")
+            .field("device", &self.device.handle())
+            .field("queue", &self.queue)
+            .field("command_pool", &self.command_pool)
+            .field("command_buffer", &self.command_buffer)
+            .field("render_pass", &self.render_pass)
+            .field("frame_index", &self.frame_index)
+            .field("debug_utils", &self.debug_utils.is_some())
+            .finish()
+    }
+}
+
+fn find_memory_type(
+    props: &vk::PhysicalDeviceMemoryProperties,
+    type_bits: u32,
+    flags: vk::MemoryPropertyFlags,
+) -> Result<u32, AshRendererError> {
+    (0..props.memory_type_count)
+        .find(|&i| {
+            type_bits & (1 << i) != 0
+                && props.memory_types[i as usize]
+                    .property_flags
+                    .contains(flags)
+        })
+        .ok_or(AshRendererError::NoMemoryType(flags))
+}
+
+/// Persistently mapped host-visible buffer
+struct HostBuffer {
+    device: Rc<ash::Device>,
+    buffer: vk::Buffer,
+    memory: vk::DeviceMemory,
+    size: u64,
+    ptr: *mut u8,
+}
+
+impl fmt::Debug for HostBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostBuffer")
+            .field("buffer", &self.buffer)
+            .field("size", &self.size)
+            .finish()
+    }
+}
+
+impl HostBuffer {
+    fn new(
+        device: &Rc<ash::Device>,
+        props: &vk::PhysicalDeviceMemoryProperties,
+        size: u64,
+        usage: vk::BufferUsageFlags,
+    ) -> Result<Self, AshRendererError> {
+        unsafe {
+            let info = vk::BufferCreateInfo::builder()
+                .size(size)
+                .usage(usage)
+                .sharing_mode(vk::SharingMode::EXCLUSIVE);
+            // `Drop` releases whatever is created so far on the early returns below
+            let mut buf = Self {
+                device: Rc::clone(device),
+                buffer: device.create_buffer(&info, None)?,
+                memory: vk::DeviceMemory::null(),
+                size,
+                ptr: std::ptr::null_mut(),
+            };
+
+            let req = device.get_buffer_memory_requirements(buf.buffer);
+            let memory_type = self::find_memory_type(
+                props,
+                req.memory_type_bits,
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT,
+            )?;
+            let alloc = vk::MemoryAllocateInfo::builder()
+                .allocation_size(req.size)
+                .memory_type_index(memory_type);
+            buf.memory = device.allocate_memory(&alloc, None)?;
+            device.bind_buffer_memory(buf.buffer, buf.memory, 0)?;
+            buf.ptr =
+                device.map_memory(buf.memory, 0, vk::WHOLE_SIZE, vk::MemoryMapFlags::empty())?
+                    as *mut u8;
+
+            Ok(buf)
+        }
+    }
+
+    /// Copies `bytes` at `offset`. The caller makes sure the buffer is large enough
+    unsafe fn write(&self, offset: usize, bytes: &[u8]) {
+        debug_assert!((offset + bytes.len()) as u64 <= self.size);
+        std::ptr::copy_nonoverlapping(bytes.as_ptr(), self.ptr.add(offset), bytes.len());
+    }
+}
+
+impl Drop for HostBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.ptr.is_null() {
+                self.device.unmap_memory(self.memory);
+            }
+            self.device.destroy_buffer(self.buffer, None);
+            self.device.free_memory(self.memory, None);
+        }
+    }
+}
+
+/// Device-local RGBA8 image
+struct Image {
+    device: Rc<ash::Device>,
+    image: vk::Image,
+    memory: vk::DeviceMemory,
+    view: vk::ImageView,
+}
+
+impl fmt::Debug for Image {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Image")
+            .field("image", &self.image)
+            .field("view", &self.view)
+            .finish()
+    }
+}
+
+impl Drop for Image {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_image_view(self.view, None);
+            self.device.destroy_image(self.image, None);
+            self.device.free_memory(self.memory, None);
+        }
+    }
+}
+
+/// GPU texture bound with a sampler. Dropping it frees the descriptor set and the image
+#[derive(Debug)]
+pub struct Texture {
+    image: Image,
+    set: vk::DescriptorSet,
+    pool: vk::DescriptorPool,
+}
+
+impl Drop for Texture {
+    fn drop(&mut self) {
+        unsafe {
+            self.image
+                .device
+                .free_descriptor_sets(self.pool, &[self.set]);
+        }
+    }
+}
+
+/// Growable list of descriptor pools for combined image samplers. Owns the set layout
+struct DescriptorAllocator {
+    device: Rc<ash::Device>,
+    layout: vk::DescriptorSetLayout,
+    pools: Vec<vk::DescriptorPool>,
+}
+
+impl fmt::Debug for DescriptorAllocator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DescriptorAllocator")
+            .field("layout", &self.layout)
+            .field("pools", &self.pools)
+            .finish()
+    }
+}
+
+impl Drop for DescriptorAllocator {
+    fn drop(&mut self) {
+        unsafe {
+            for &pool in &self.pools {
+                self.device.destroy_descriptor_pool(pool, None);
+            }
+            self.device.destroy_descriptor_set_layout(self.layout, None);
+        }
+    }
+}
+
+impl DescriptorAllocator {
+    fn allocate(&mut self) -> Result<(vk::DescriptorPool, vk::DescriptorSet), AshRendererError> {
+        let device = &self.device;
+        let layouts = [self.layout];
+
+        if let Some(&pool) = self.pools.last() {
+            let info = vk::DescriptorSetAllocateInfo::builder()
+                .descriptor_pool(pool)
+                .set_layouts(&layouts);
+            match unsafe { device.allocate_descriptor_sets(&info) } {
+                Ok(sets) => return Ok((pool, sets[0])),
+                Err(vk::Result::ERROR_OUT_OF_POOL_MEMORY)
+                | Err(vk::Result::ERROR_FRAGMENTED_POOL) => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+
+        let sizes = [vk::DescriptorPoolSize {
+            ty: vk::DescriptorType::COMBINED_IMAGE_SAMPLER,
+            descriptor_count: SETS_PER_POOL,
+        }];
+        let info = vk::DescriptorPoolCreateInfo::builder()
+            .flags(vk::DescriptorPoolCreateFlags::FREE_DESCRIPTOR_SET)
+            .max_sets(SETS_PER_POOL)
+            .pool_sizes(&sizes);
+        let pool = unsafe { device.create_descriptor_pool(&info, None)? };
+        self.pools.push(pool);
+        log::debug!("ImGuiAsh: {} descriptor pools", self.pools.len());
+
+        let info = vk::DescriptorSetAllocateInfo::builder()
+            .descriptor_pool(pool)
+            .set_layouts(&layouts);
+        let sets = unsafe { device.allocate_descriptor_sets(&info)? };
+        Ok((pool, sets[0]))
+    }
+}
+
+/// Graphics pipeline and its layout
+struct Pipeline {
+    device: Rc<ash::Device>,
+    layout: vk::PipelineLayout,
+    pipeline: vk::Pipeline,
+}
+
+impl fmt::Debug for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("layout", &self.layout)
+            .field("pipeline", &self.pipeline)
+            .finish()
+    }
+}
+
+impl Drop for Pipeline {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_pipeline(self.pipeline, None);
+            self.device.destroy_pipeline_layout(self.layout, None);
+        }
+    }
+}
+
+/// Linear and nearest samplers for [`FontSampling`]
+struct Samplers {
+    device: Rc<ash::Device>,
+    linear: vk::Sampler,
+    nearest: vk::Sampler,
+}
+
+impl fmt::Debug for Samplers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Samplers")
+            .field("linear", &self.linear)
+            .field("nearest", &self.nearest)
+            .finish()
+    }
+}
+
+impl Drop for Samplers {
+    fn drop(&mut self) {
+        unsafe {
+            self.device.destroy_sampler(self.linear, None);
+            self.device.destroy_sampler(self.nearest, None);
+        }
+    }
+}
+
+/// Vertex and index buffers of a frame in flight. They grow on demand
+#[derive(Debug, Default)]
+struct FrameBuffers {
+    vbuf: Option<HostBuffer>,
+    ibuf: Option<HostBuffer>,
+}
+
+/// GPU objects released on [`Renderer::invalidate_device_objects`]
+#[derive(Debug)]
+struct DeviceObjects {
+    device: Rc<ash::Device>,
+    memory_properties: vk::PhysicalDeviceMemoryProperties,
+    pipeline: Pipeline,
+    descriptors: DescriptorAllocator,
+    samplers: Samplers,
+    font_image: Image,
+    /// Font image bound with the linear and nearest samplers
+    font_sets: [vk::DescriptorSet; 2],
+    font_size: [u32; 2],
+    frames: Vec<FrameBuffers>,
+}
+
+impl DeviceObjects {
+    fn new(
+        device: &AshDevice,
+        mut fonts: imgui::FontAtlasRefMut,
+        frames_in_flight: usize,
+    ) -> Result<Self, AshRendererError> {
+        let rc_device = Rc::new(device.device.clone());
+        let vk_device = &device.device;
+
+        let bindings = [vk::DescriptorSetLayoutBinding::builder()
+            .binding(0)
+            .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+            .descriptor_count(1)
+            .stage_flags(vk::ShaderStageFlags::FRAGMENT)
+            .build()];
+        let set_layout = unsafe {
+            vk_device.create_descriptor_set_layout(
+                &vk::DescriptorSetLayoutCreateInfo::builder().bindings(&bindings),
+                None,
+            )?
+        };
+        let mut descriptors = DescriptorAllocator {
+            device: Rc::clone(&rc_device),
+            layout: set_layout,
+            pools: Vec::new(),
+        };
+
+        let push_constants = [vk::PushConstantRange {
+            stage_flags: vk::ShaderStageFlags::VERTEX,
+            offset: 0,
+            size: PUSH_CONSTANTS_SIZE,
+        }];
+        let set_layouts = [set_layout];
+        let pipeline_layout = unsafe {
+            vk_device.create_pipeline_layout(
+                &vk::PipelineLayoutCreateInfo::builder()
+                    .set_layouts(&set_layouts)
+                    .push_constant_ranges(&push_constants),
+                None,
+            )?
+        };
+
+        let mut pipeline = Pipeline {
+            device: Rc::clone(&rc_device),
+            layout: pipeline_layout,
+            pipeline: vk::Pipeline::null(),
+        };
+        pipeline.pipeline = self::create_pipeline(vk_device, device.render_pass, pipeline.layout)?;
+        device.set_object_name(pipeline.pipeline, "imgui-ash pipeline");
+
+        let sampler = |filter| {
+            let info = vk::SamplerCreateInfo::builder()
+                .mag_filter(filter)
+                .min_filter(filter)
+                .mipmap_mode(if filter == vk::Filter::LINEAR {
+                    vk::SamplerMipmapMode::LINEAR
+                } else {
+                    vk::SamplerMipmapMode::NEAREST
+                })
+                .address_mode_u(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_v(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .address_mode_w(vk::SamplerAddressMode::CLAMP_TO_EDGE)
+                .max_lod(vk::LOD_CLAMP_NONE);
+            unsafe { vk_device.create_sampler(&info, None) }
+        };
+        let mut samplers = Samplers {
+            device: Rc::clone(&rc_device),
+            linear: vk::Sampler::null(),
+            nearest: vk::Sampler::null(),
+        };
+        samplers.linear = sampler(vk::Filter::LINEAR)?;
+        samplers.nearest = sampler(vk::Filter::NEAREST)?;
+
+        let (font_image, font_size) = {
+            let atlas = fonts.build_rgba32_texture();
+            let image =
+                self::create_image(&rc_device, device, atlas.data, atlas.width, atlas.height)?;
+            (image, [atlas.width, atlas.height])
+        };
+        device.set_object_name(font_image.image, "imgui-ash font atlas");
+        // NOTE: we have to set the ID *AFTER* creating the font atlas texture
+        fonts.tex_id = imgui::TextureId::from(FONT_TEXTUER_ID);
+
+        let (_, linear_set) = descriptors.allocate()?;
+        let (_, nearest_set) = descriptors.allocate()?;
+        self::write_set(vk_device, linear_set, font_image.view, samplers.linear);
+        self::write_set(vk_device, nearest_set, font_image.view, samplers.nearest);
+
+        Ok(Self {
+            device: rc_device,
+            memory_properties: device.memory_properties,
+            pipeline,
+            descriptors,
+            samplers,
+            font_image,
+            font_sets: [linear_set, nearest_set],
+            font_size,
+            frames: (0..frames_in_flight.max(1))
+                .map(|_| FrameBuffers::default())
+                .collect(),
+        })
+    }
+
+    fn register(
+        &mut self,
+        device: &AshDevice,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> Result<Texture, AshRendererError> {
+        let image = self::create_image(&self.device, device, pixels, w, h)?;
+        let (pool, set) = self.descriptors.allocate()?;
+        self::write_set(&self.device, set, image.view, self.samplers.linear);
+        Ok(Texture { image, set, pool })
+    }
+
+    /// Grows the vertex and index buffers of a frame to hold the draw data
+    fn reserve(
+        &mut self,
+        frame: usize,
+        vbuf_size: u64,
+        ibuf_size: u64,
+    ) -> Result<(), AshRendererError> {
+        let props = self.memory_properties;
+        let device = &self.device;
+        let bufs = &mut self.frames[frame];
+
+        // the frame's previous use is complete, so the old buffers can be released
+        if bufs.vbuf.as_ref().map_or(0, |buf| buf.size) < vbuf_size {
+            bufs.vbuf = None;
+            bufs.vbuf = Some(HostBuffer::new(
+                device,
+                &props,
+                vbuf_size.next_power_of_two(),
+                vk::BufferUsageFlags::VERTEX_BUFFER,
+            )?);
+        }
+        if bufs.ibuf.as_ref().map_or(0, |buf| buf.size) < ibuf_size {
+            bufs.ibuf = None;
+            bufs.ibuf = Some(HostBuffer::new(
+                device,
+                &props,
+                ibuf_size.next_power_of_two(),
+                vk::BufferUsageFlags::INDEX_BUFFER,
+            )?);
+        }
+
+        Ok(())
+    }
+
+    /// Binds the pipeline and the buffers of the frame and sets the viewport and the projection
+    fn set_render_state(
+        &self,
+        cmd: vk::CommandBuffer,
+        frame: usize,
+        draw_data: &imgui::DrawData,
+        fb_size: [f32; 2],
+        opacity: f32,
+    ) {
+        let device = &self.device;
+        let bufs = &self.frames[frame];
+        let (vbuf, ibuf) = match (bufs.vbuf.as_ref(), bufs.ibuf.as_ref()) {
+            (Some(vbuf), Some(ibuf)) => (vbuf, ibuf),
+            _ => return,
+        };
+
+        let [x, y] = draw_data.display_pos;
+        let [w, h] = draw_data.display_size;
+        let scale = [2.0 / w, 2.0 / h];
+        let push_constants: [f32; 5] = [
+            scale[0],
+            scale[1],
+            -1.0 - x * scale[0],
+            -1.0 - y * scale[1],
+            opacity,
+        ];
+
+        unsafe {
+            device.cmd_bind_pipeline(cmd, vk::PipelineBindPoint::GRAPHICS, self.pipeline.pipeline);
+            device.cmd_bind_vertex_buffers(cmd, 0, &[vbuf.buffer], &[0]);
+            device.cmd_bind_index_buffer(
+                cmd,
+                ibuf.buffer,
+                0,
+                if std::mem::size_of::<imgui::DrawIdx>() == 2 {
+                    vk::IndexType::UINT16
+                } else {
+                    vk::IndexType::UINT32
+                },
+            );
+            device.cmd_set_viewport(
+                cmd,
+                0,
+                &[vk::Viewport {
+                    x: 0.0,
+                    y: 0.0,
+                    width: fb_size[0],
+                    height: fb_size[1],
+                    min_depth: 0.0,
+                    max_depth: 1.0,
+                }],
+            );
+            device.cmd_push_constants(
+                cmd,
+                self.pipeline.layout,
+                vk::ShaderStageFlags::VERTEX,
+                0,
+                as_bytes(&push_constants),
+            );
+        }
+    }
+}
+
+fn create_pipeline(
+    device: &ash::Device,
+    render_pass: vk::RenderPass,
+    layout: vk::PipelineLayout,
+) -> Result<vk::Pipeline, AshRendererError> {
+    let module = |spv: &[u8]| {
+        // SPIR-V from `include_bytes` is not aligned to `u32`
+        let code = ash::util::read_spv(&mut Cursor::new(spv)).expect("bad SPIR-V");
+        let info = vk::ShaderModuleCreateInfo::builder().code(&code);
+        unsafe { device.create_shader_module(&info, None) }
+    };
+    let vert = module(VERT_SPV)?;
+    let frag = match module(FRAG_SPV) {
+        Ok(frag) => frag,
+        Err(err) => {
+            unsafe { device.destroy_shader_module(vert, None) };
+            return Err(err.into());
+        }
+    };
+
+    let entry = CStr::from_bytes_with_nul(b"main\0").unwrap();
+    let stages = [
+        vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::VERTEX)
+            .module(vert)
+            .name(entry)
+            .build(),
+        vk::PipelineShaderStageCreateInfo::builder()
+            .stage(vk::ShaderStageFlags::FRAGMENT)
+            .module(frag)
+            .name(entry)
+            .build(),
+    ];
+
+    let vertex_layout = VertexLayout::draw_vert();
+    let bindings = [vk::VertexInputBindingDescription {
+        binding: 0,
+        stride: vertex_layout.stride as u32,
+        input_rate: vk::VertexInputRate::VERTEX,
+    }];
+    let attributes = [
+        vk::VertexInputAttributeDescription {
+            location: 0,
+            binding: 0,
+            format: vk::Format::R32G32_SFLOAT,
+            offset: vertex_layout.pos as u32,
+        },
+        vk::VertexInputAttributeDescription {
+            location: 1,
+            binding: 0,
+            format: vk::Format::R32G32_SFLOAT,
+            offset: vertex_layout.uv as u32,
+        },
+        vk::VertexInputAttributeDescription {
+            location: 2,
+            binding: 0,
+            format: vk::Format::R8G8B8A8_UNORM,
+            offset: vertex_layout.col as u32,
+        },
+    ];
+    let vertex_input = vk::PipelineVertexInputStateCreateInfo::builder()
+        .vertex_binding_descriptions(&bindings)
+        .vertex_attribute_descriptions(&attributes);
+
+    let input_assembly = vk::PipelineInputAssemblyStateCreateInfo::builder()
+        .topology(vk::PrimitiveTopology::TRIANGLE_LIST);
+    // set dynamically
+    let viewport = vk::PipelineViewportStateCreateInfo::builder()
+        .viewport_count(1)
+        .scissor_count(1);
+    let rasterization = vk::PipelineRasterizationStateCreateInfo::builder()
+        .polygon_mode(vk::PolygonMode::FILL)
+        .cull_mode(vk::CullModeFlags::NONE)
+        .front_face(vk::FrontFace::COUNTER_CLOCKWISE)
+        .line_width(1.0);
+    let multisample = vk::PipelineMultisampleStateCreateInfo::builder()
+        .rasterization_samples(vk::SampleCountFlags::TYPE_1);
+    let depth_stencil = vk::PipelineDepthStencilStateCreateInfo::builder();
+
+    // premultiplied alpha is not used; alpha is accumulated like other backends
+    let attachments = [vk::PipelineColorBlendAttachmentState::builder()
+        .blend_enable(true)
+        .src_color_blend_factor(vk::BlendFactor::SRC_ALPHA)
+        .dst_color_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .color_blend_op(vk::BlendOp::ADD)
+        .src_alpha_blend_factor(vk::BlendFactor::ONE)
+        .dst_alpha_blend_factor(vk::BlendFactor::ONE_MINUS_SRC_ALPHA)
+        .alpha_blend_op(vk::BlendOp::ADD)
+        .color_write_mask(vk::ColorComponentFlags::all())
+        .build()];
+    let color_blend = vk::PipelineColorBlendStateCreateInfo::builder().attachments(&attachments);
+
+    let dynamic_states = [vk::DynamicState::VIEWPORT, vk::DynamicState::SCISSOR];
+    let dynamic = vk::PipelineDynamicStateCreateInfo::builder().dynamic_states(&dynamic_states);
+
+    let info = vk::GraphicsPipelineCreateInfo::builder()
+        .stages(&stages)
+        .vertex_input_state(&vertex_input)
+        .input_assembly_state(&input_assembly)
+        .viewport_state(&viewport)
+        .rasterization_state(&rasterization)
+        .multisample_state(&multisample)
+        .depth_stencil_state(&depth_stencil)
+        .color_blend_state(&color_blend)
+        .dynamic_state(&dynamic)
+        .layout(layout)
+        .render_pass(render_pass)
+        .subpass(0)
+        .build();
+
+    let pipelines =
+        unsafe { device.create_graphics_pipelines(vk::PipelineCache::null(), &[info], None) };
+    unsafe {
+        device.destroy_shader_module(vert, None);
+        device.destroy_shader_module(frag, None);
+    }
+
+    match pipelines {
+        Ok(pipelines) => Ok(pipelines[0]),
+        Err((_, err)) => Err(err.into()),
+    }
+}
+
+/// Creates an RGBA8 image and uploads the pixels through a staging buffer, waiting for the queue
+fn create_image(
+    rc_device: &Rc<ash::Device>,
+    device: &AshDevice,
+    pixels: &[u8],
+    w: u32,
+    h: u32,
+) -> Result<Image, AshRendererError> {
+    let vk_device = &device.device;
+    let range = vk::ImageSubresourceRange {
+        aspect_mask: vk::ImageAspectFlags::COLOR,
+        base_mip_level: 0,
+        level_count: 1,
+        base_array_layer: 0,
+        layer_count: 1,
+    };
+
+    let image = unsafe {
+        let info = vk::ImageCreateInfo::builder()
+            .image_type(vk::ImageType::TYPE_2D)
+            .format(vk::Format::R8G8B8A8_UNORM)
+            .extent(vk::Extent3D {
+                width: w,
+                height: h,
+                depth: 1,
+            })
+            .mip_levels(1)
+            .array_layers(1)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .tiling(vk::ImageTiling::OPTIMAL)
+            .usage(vk::ImageUsageFlags::SAMPLED | vk::ImageUsageFlags::TRANSFER_DST)
+            .sharing_mode(vk::SharingMode::EXCLUSIVE)
+            .initial_layout(vk::ImageLayout::UNDEFINED);
+        // `Drop` releases whatever is created so far on the early returns below
+        let mut image = Image {
+            device: Rc::clone(rc_device),
+            image: vk_device.create_image(&info, None)?,
+            memory: vk::DeviceMemory::null(),
+            view: vk::ImageView::null(),
+        };
+
+        let req = vk_device.get_image_memory_requirements(image.image);
+        let memory_type = self::find_memory_type(
+            &device.memory_properties,
+            req.memory_type_bits,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )?;
+        let alloc = vk::MemoryAllocateInfo::builder()
+            .allocation_size(req.size)
+            .memory_type_index(memory_type);
+        image.memory = vk_device.allocate_memory(&alloc, None)?;
+        vk_device.bind_image_memory(image.image, image.memory, 0)?;
+
+        let info = vk::ImageViewCreateInfo::builder()
+            .image(image.image)
+            .view_type(vk::ImageViewType::TYPE_2D)
+            .format(vk::Format::R8G8B8A8_UNORM)
+            .subresource_range(range);
+        image.view = vk_device.create_image_view(&info, None)?;
+
+        image
+    };
+
+    let staging = HostBuffer::new(
+        rc_device,
+        &device.memory_properties,
+        pixels.len() as u64,
+        vk::BufferUsageFlags::TRANSFER_SRC,
+    )?;
+    unsafe {
+        staging.write(0, pixels);
+    }
+
+    unsafe {
+        let info = vk::CommandBufferAllocateInfo::builder()
+            .command_pool(device.command_pool)
+            .level(vk::CommandBufferLevel::PRIMARY)
+            .command_buffer_count(1);
+        let cmd = vk_device.allocate_command_buffers(&info)?[0];
+
+        let result = (|| -> Result<(), AshRendererError> {
+            vk_device.begin_command_buffer(
+                cmd,
+                &vk::CommandBufferBeginInfo::builder()
+                    .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+            )?;
+
+            let to_transfer = vk::ImageMemoryBarrier::builder()
+                .dst_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .old_layout(vk::ImageLayout::UNDEFINED)
+                .new_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(image.image)
+                .subresource_range(range)
+                .build();
+            vk_device.cmd_pipeline_barrier(
+                cmd,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_transfer],
+            );
+
+            let region = vk::BufferImageCopy::builder()
+                .image_subresource(vk::ImageSubresourceLayers {
+                    aspect_mask: vk::ImageAspectFlags::COLOR,
+                    mip_level: 0,
+                    base_array_layer: 0,
+                    layer_count: 1,
+                })
+                .image_extent(vk::Extent3D {
+                    width: w,
+                    height: h,
+                    depth: 1,
+                })
+                .build();
+            vk_device.cmd_copy_buffer_to_image(
+                cmd,
+                staging.buffer,
+                image.image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[region],
+            );
+
+            let to_shader = vk::ImageMemoryBarrier::builder()
+                .src_access_mask(vk::AccessFlags::TRANSFER_WRITE)
+                .dst_access_mask(vk::AccessFlags::SHADER_READ)
+                .old_layout(vk::ImageLayout::TRANSFER_DST_OPTIMAL)
+                .new_layout(vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL)
+                .src_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .dst_queue_family_index(vk::QUEUE_FAMILY_IGNORED)
+                .image(image.image)
+                .subresource_range(range)
+                .build();
+            vk_device.cmd_pipeline_barrier(
+                cmd,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::FRAGMENT_SHADER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[to_shader],
+            );
+
+            vk_device.end_command_buffer(cmd)?;
+
+            let cmds = [cmd];
+            let submit = vk::SubmitInfo::builder().command_buffers(&cmds).build();
+            vk_device.queue_submit(device.queue, &[submit], vk::Fence::null())?;
+            vk_device.queue_wait_idle(device.queue)?;
+            Ok(())
+        })();
+
+        vk_device.free_command_buffers(device.command_pool, &[cmd]);
+        result?;
+    }
+
+    Ok(image)
+}
+
+fn write_set(
+    device: &ash::Device,
+    set: vk::DescriptorSet,
+    view: vk::ImageView,
+    sampler: vk::Sampler,
+) {
+    let image_info = [vk::DescriptorImageInfo {
+        sampler,
+        image_view: view,
+        image_layout: vk::ImageLayout::SHADER_READ_ONLY_OPTIMAL,
+    }];
+    let write = vk::WriteDescriptorSet::builder()
+        .dst_set(set)
+        .dst_binding(0)
+        .descriptor_type(vk::DescriptorType::COMBINED_IMAGE_SAMPLER)
+        .image_info(&image_info)
+        .build();
+    unsafe {
+        device.update_descriptor_sets(&[write], &[]);
+    }
+}
+
+/// `ash` (Vulkan) ImGUI renderer
+#[derive(Debug)]
+pub struct ImGuiAsh {
+    // textures are dropped before the descriptor pools of `objs`
+    textures: TextureRegistry<Texture>,
+    /// Removed textures and the number of renders until they're released
+    graveyard: Vec<(usize, Texture)>,
+    font_sampling: FontSampling,
+    opacity: f32,
+    frames_in_flight: usize,
+    /// `None` while the device objects are invalidated
+    objs: Option<DeviceObjects>,
+    /// Incremented every time the font texture is created
+    font_generation: u64,
+}
+
+impl ImGuiAsh {
+    /// `frames_in_flight` is the number of frames the application records ahead, typically the
+    /// number of swapchain images or fewer
+    pub fn new(
+        imgui: &mut imgui::Context,
+        device: &AshDevice,
+        frames_in_flight: usize,
+    ) -> Result<Self, AshRendererError> {
+        imgui.set_renderer_name(Some(crate::backend_name(
+            "imgui-ash-renderer",
+            &[&format!("{} frames in flight", frames_in_flight)],
+        )));
+
+        let frames_in_flight = frames_in_flight.max(1);
+        let objs = DeviceObjects::new(device, imgui.fonts(), frames_in_flight)?;
+
+        Ok(Self {
+            textures: TextureRegistry::new(),
+            graveyard: Vec::new(),
+            font_sampling: FontSampling::default(),
+            opacity: 1.0,
+            frames_in_flight,
+            objs: Some(objs),
+            font_generation: 1,
+        })
+    }
+
+    /// Uploads an RGBA8 texture. If `retain` is true, a CPU copy of the pixels is kept so that the
+    /// texture can be restored after device loss
+    pub fn register_texture(
+        &mut self,
+        device: &AshDevice,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> Result<imgui::TextureId, AshRendererError> {
+        let expected = (w as usize) * (h as usize) * 4;
+        if pixels.len() != expected {
+            return Err(AshRendererError::PixelSizeMismatch {
+                expected,
+                given: pixels.len(),
+            });
+        }
+
+        let objs = self.objs.as_mut().ok_or(AshRendererError::Invalidated)?;
+        let tex = objs.register(device, pixels, w, h)?;
+        let source = if retain {
+            Some(TextureSource::Pixels(pixels.to_vec()))
+        } else {
+            None
+        };
+        Ok(self.textures.insert(tex, w, h, source))
+    }
+
+    pub fn textures(&self) -> &TextureRegistry<Texture> {
+        &self.textures
+    }
+
+    pub fn textures_mut(&mut self) -> &mut TextureRegistry<Texture> {
+        &mut self.textures
+    }
+
+    /// Releases removed textures that are no longer used by frames in flight
+    fn collect_garbage(&mut self) {
+        for (n, _) in &mut self.graveyard {
+            *n -= 1;
+        }
+        self.graveyard.retain(|(n, _)| *n > 0);
+
+        let frames = self.frames_in_flight;
+        self.graveyard.extend(
+            self.textures
+                .take_removed()
+                .into_iter()
+                .map(|tex| (frames, tex)),
+        );
+    }
+
+    fn descriptor_set(&self, tex_id: imgui::TextureId) -> Option<vk::DescriptorSet> {
+        if tex_id.id() == FONT_TEXTUER_ID {
+            let objs = self.objs.as_ref()?;
+            return Some(match self.font_sampling {
+                FontSampling::Linear => objs.font_sets[0],
+                FontSampling::Nearest => objs.font_sets[1],
+            });
+        }
+
+        self.textures.get(tex_id).map(|tex| tex.set)
+    }
+
+    /// Uploads every draw list to the buffers of the frame. Returns the base vertex and base index
+    /// of each list, keyed with the address of its index buffer
+    fn upload(
+        &mut self,
+        frame: usize,
+        draw_data: &imgui::DrawData,
+    ) -> Result<Vec<(*const imgui::DrawIdx, usize, usize)>, AshRendererError> {
+        let idx_size = std::mem::size_of::<imgui::DrawIdx>();
+        let objs = self.objs.as_mut().unwrap();
+        objs.reserve(
+            frame,
+            (draw_data.total_vtx_count as usize * VERT_SIZE) as u64,
+            (draw_data.total_idx_count as usize * idx_size) as u64,
+        )?;
+
+        let bufs = &objs.frames[frame];
+        let (vbuf, ibuf) = (bufs.vbuf.as_ref().unwrap(), bufs.ibuf.as_ref().unwrap());
+
+        let mut bases = Vec::with_capacity(draw_data.draw_lists_count());
+        let (mut n_vtx, mut n_idx) = (0, 0);
+        for list in draw_data.draw_lists() {
+            bases.push((list.idx_buffer().as_ptr(), n_vtx, n_idx));
+            // the buffers are host-coherent
+            unsafe {
+                vbuf.write(n_vtx * VERT_SIZE, as_bytes(list.vtx_buffer()));
+                ibuf.write(n_idx * idx_size, as_bytes(list.idx_buffer()));
+            }
+            n_vtx += list.vtx_buffer().len();
+            n_idx += list.idx_buffer().len();
+        }
+
+        Ok(bases)
+    }
+
+    fn draw(
+        &self,
+        cmd: vk::CommandBuffer,
+        params: &DrawParams,
+        base: (usize, usize),
+    ) -> Result<(), AshRendererError> {
+        let set = self
+            .descriptor_set(params.tex_id)
+            .ok_or(AshRendererError::BadTexture(params.tex_id))?;
+        let objs = self.objs.as_ref().unwrap();
+        let device = &objs.device;
+
+        let [x1, y1, x2, y2] = params.clip_rect;
+        let scissor = vk::Rect2D {
+            offset: vk::Offset2D {
+                x: x1.max(0.0) as i32,
+                y: y1.max(0.0) as i32,
+            },
+            extent: vk::Extent2D {
+                width: (x2 - x1).max(0.0) as u32,
+                height: (y2 - y1).max(0.0) as u32,
+            },
+        };
+
+        let (vtx_base, idx_base) = base;
+        unsafe {
+            device.cmd_set_scissor(cmd, 0, &[scissor]);
+            device.cmd_bind_descriptor_sets(
+                cmd,
+                vk::PipelineBindPoint::GRAPHICS,
+                objs.pipeline.layout,
+                0,
+                &[set],
+                &[],
+            );
+            device.cmd_draw_indexed(
+                cmd,
+                params.n_elems as u32,
+                1,
+                (idx_base + params.idx_offset) as u32,
+                (vtx_base + params.vtx_offset) as i32,
+                0,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+impl RegisterTexture for ImGuiAsh {
+    fn register_rgba8(
+        &mut self,
+        device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> Result<imgui::TextureId, Self::Error> {
+        self.register_texture(device, pixels, w, h, retain)
+    }
+
+    fn remove_textures<I>(&mut self, ids: I) -> usize
+    where
+        I: IntoIterator<Item = imgui::TextureId>,
+    {
+        self.textures.remove_many(ids)
+    }
+
+    fn set_texture_label(&mut self, device: &mut Self::Device, id: imgui::TextureId, label: &str) {
+        self.textures.set_label(id, label);
+        if let Some(tex) = self.textures.get(id) {
+            device.set_object_name(tex.image.image, label);
+        }
+    }
+
+    fn texture_infos(&self) -> Vec<TextureInfo<'_>> {
+        self.textures.textures_iter().collect()
+    }
+}
+
+impl Renderer for ImGuiAsh {
+    type Device = AshDevice;
+    type Error = AshRendererError;
+    const HAS_VTX_OFFSET: bool = true;
+    const MAX_INDEX_SIZE: usize = 4;
+
+    fn font_atlas_info(&self) -> Option<crate::FontAtlasInfo> {
+        self.objs.as_ref().map(|objs| crate::FontAtlasInfo {
+            tex_id: imgui::TextureId::from(FONT_TEXTUER_ID),
+            size: objs.font_size,
+            generation: self.font_generation,
+        })
+    }
+
+    fn render(
+        &mut self,
+        draw_data: &imgui::DrawData,
+        device: &mut Self::Device,
+    ) -> Result<(), Self::Error> {
+        if self.objs.is_none() {
+            return Err(AshRendererError::Invalidated);
+        }
+        let frame = device.frame_index;
+        if frame >= self.frames_in_flight {
+            return Err(AshRendererError::BadFrameIndex {
+                index: frame,
+                frames_in_flight: self.frames_in_flight,
+            });
+        }
+
+        self.collect_garbage();
+
+        let fb_size = [
+            draw_data.display_size[0] * draw_data.framebuffer_scale[0],
+            draw_data.display_size[1] * draw_data.framebuffer_scale[1],
+        ];
+        if fb_size[0] <= 0.0 || fb_size[1] <= 0.0 || draw_data.total_idx_count == 0 {
+            return Ok(());
+        }
+
+        let bases = self.upload(frame, draw_data)?;
+        let cmd = device.command_buffer;
+        self.objs
+            .as_ref()
+            .unwrap()
+            .set_render_state(cmd, frame, draw_data, fb_size, self.opacity);
+
+        // draw lists are yielded in order
+        let mut cursor = 0;
+        for item in DrawParamsIterator::new(draw_data) {
+            let params = match item {
+                DrawItem::Draw(params) => params,
+                // the scissor and the descriptor set are set on every draw call
+                DrawItem::ResetState => {
+                    self.objs.as_ref().unwrap().set_render_state(
+                        cmd,
+                        frame,
+                        draw_data,
+                        fb_size,
+                        self.opacity,
+                    );
+                    continue;
+                }
+                DrawItem::Callback(callback) => {
+                    self.run_callback(device, &callback)?;
+                    continue;
+                }
+            };
+            let ptr = params.idx_buffer.as_ptr();
+            while bases[cursor].0 != ptr {
+                cursor += 1;
+            }
+            let (_, vtx_base, idx_base) = bases[cursor];
+            self.draw(cmd, &params, (vtx_base, idx_base))?;
+        }
+
+        Ok(())
+    }
+
+    fn set_font_sampling(&mut self, sampling: FontSampling) {
+        self.font_sampling = sampling;
+    }
+
+    fn set_ui_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    fn invalidate_device_objects(&mut self, _device: &mut Self::Device) {
+        // textures free their descriptor sets before the pools are destroyed
+        self.graveyard.clear();
+        self.textures.invalidate();
+        self.objs = None;
+    }
+
+    fn create_device_objects(
+        &mut self,
+        imgui: &mut imgui::Context,
+        device: &mut Self::Device,
+    ) -> Result<(), Self::Error> {
+        self.graveyard.clear();
+        self.textures.invalidate();
+        self.objs = None;
+
+        let mut objs = DeviceObjects::new(device, imgui.fonts(), self.frames_in_flight)?;
+        self.font_generation += 1;
+
+        let device = &*device;
+        self.textures
+            .restore(|pixels, w, h| objs.register(device, pixels, w, h))?;
+        self.objs = Some(objs);
+
+        // restored textures are new objects
+        for info in self.textures.textures_iter() {
+            if let (Some(label), Some(tex)) = (info.label, self.textures.get(info.id)) {
+                device.set_object_name(tex.image.image, label);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn replace_font_atlas(
+        &mut self,
+        device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> Result<bool, Self::Error> {
+        let objs = match self.objs.as_mut() {
+            Some(objs) => objs,
+            None => return Ok(false),
+        };
+
+        // the old font image may be used by frames in flight
+        unsafe {
+            objs.device.device_wait_idle()?;
+        }
+
+        let image = self::create_image(&objs.device, device, pixels, w, h)?;
+        self::write_set(
+            &objs.device,
+            objs.font_sets[0],
+            image.view,
+            objs.samplers.linear,
+        );
+        self::write_set(
+            &objs.device,
+            objs.font_sets[1],
+            image.view,
+            objs.samplers.nearest,
+        );
+        device.set_object_name(image.image, "imgui-ash font atlas");
+        objs.font_image = image;
+        objs.font_size = [w, h];
+        self.font_generation += 1;
+        Ok(true)
+    }
+}
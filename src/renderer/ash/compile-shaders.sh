@@ -0,0 +1,9 @@
+#!/bin/sh
+# Regenerates `shader.{vert,frag}.spv` from the GLSL sources with `glslc` (Vulkan SDK).
+# Run it after editing the shaders and commit the SPIR-V. Set `GLSLC` to override the path
+set -e
+cd "$(dirname "$0")"
+GLSLC="${GLSLC:-glslc}"
+for name in shader.vert shader.frag; do
+    "$GLSLC" "$name" -o "$name.spv"
+done
@@ -0,0 +1,318 @@
+//! [`RendererConformance`] of [`ImGuiAsh`] on a headless Vulkan device
+
+use std::cell::RefCell;
+
+use ash::vk;
+
+use crate::helper::conformance::RendererConformance;
+
+use super::{AshDevice, ImGuiAsh};
+
+const FORMAT: vk::Format = vk::Format::R8G8B8A8_UNORM;
+const EXTENT: vk::Extent2D = vk::Extent2D {
+    width: 1280,
+    height: 720,
+};
+
+thread_local! {
+    /// Created on first use and kept for the rest of the thread
+    static HEADLESS: RefCell<Option<Headless>> = RefCell::new(None);
+}
+
+impl RendererConformance for ImGuiAsh {
+    fn create(imgui: &mut imgui::Context) -> Result<(Self, Self::Device), String> {
+        let device = HEADLESS.with(|cell| -> Result<AshDevice, String> {
+            let mut cell = cell.borrow_mut();
+            if cell.is_none() {
+                *cell = Some(unsafe { Headless::new() }?);
+            }
+            Ok(cell.as_ref().unwrap().ash_device())
+        })?;
+        let renderer = Self::new(imgui, &device, 1).map_err(|err| err.to_string())?;
+        Ok((renderer, device))
+    }
+
+    fn begin_frame(&mut self, device: &mut Self::Device) -> Result<(), String> {
+        let framebuffer = HEADLESS
+            .with(|cell| cell.borrow().as_ref().map(|x| x.framebuffer))
+            .ok_or_else(|| "no headless device".to_string())?;
+
+        let clear = [vk::ClearValue {
+            color: vk::ClearColorValue {
+                float32: [0.0, 0.0, 0.0, 1.0],
+            },
+        }];
+        let pass = vk::RenderPassBeginInfo::builder()
+            .render_pass(device.render_pass)
+            .framebuffer(framebuffer)
+            .render_area(vk::Rect2D {
+                offset: vk::Offset2D { x: 0, y: 0 },
+                extent: EXTENT,
+            })
+            .clear_values(&clear);
+
+        unsafe {
+            device.device.begin_command_buffer(
+                device.command_buffer,
+                &vk::CommandBufferBeginInfo::builder()
+                    .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT),
+            )
+        }
+        .map_err(|err| err.to_string())?;
+        unsafe {
+            device.device.cmd_begin_render_pass(
+                device.command_buffer,
+                &pass,
+                vk::SubpassContents::INLINE,
+            );
+        }
+        Ok(())
+    }
+
+    fn end_frame(&mut self, device: &mut Self::Device) -> Result<(), String> {
+        let vk_device = &device.device;
+        let result = unsafe {
+            (|| -> ash::prelude::VkResult<()> {
+                vk_device.cmd_end_render_pass(device.command_buffer);
+                vk_device.end_command_buffer(device.command_buffer)?;
+
+                let cmds = [device.command_buffer];
+                let submit = vk::SubmitInfo::builder().command_buffers(&cmds).build();
+                vk_device.queue_submit(device.queue, &[submit], vk::Fence::null())?;
+                vk_device.queue_wait_idle(device.queue)
+            })()
+        };
+        result.map_err(|err| err.to_string())
+    }
+}
+
+/// Vulkan objects without a surface: the UI is rendered to an image
+struct Headless {
+    // the entry has to outlive the instance
+    _entry: ash::Entry,
+    instance: ash::Instance,
+    device: ash::Device,
+    memory_properties: vk::PhysicalDeviceMemoryProperties,
+    queue: vk::Queue,
+    command_pool: vk::CommandPool,
+    command_buffer: vk::CommandBuffer,
+    render_pass: vk::RenderPass,
+    image: vk::Image,
+    memory: vk::DeviceMemory,
+    view: vk::ImageView,
+    framebuffer: vk::Framebuffer,
+}
+
+impl Headless {
+    unsafe fn new() -> Result<Self, String> {
+        let entry = ash::Entry::new().map_err(|err| format!("{:?}", err))?;
+        let instance = entry
+            .create_instance(&vk::InstanceCreateInfo::builder(), None)
+            .map_err(|err| format!("{:?}", err))?;
+
+        let (pdev, family) = instance
+            .enumerate_physical_devices()
+            .map_err(|err| err.to_string())?
+            .into_iter()
+            .find_map(|pdev| {
+                instance
+                    .get_physical_device_queue_family_properties(pdev)
+                    .iter()
+                    .position(|props| props.queue_flags.contains(vk::QueueFlags::GRAPHICS))
+                    .map(|family| (pdev, family as u32))
+            })
+            .ok_or_else(|| "no Vulkan device with a graphics queue".to_string())?;
+        let memory_properties = instance.get_physical_device_memory_properties(pdev);
+
+        let priorities = [1.0];
+        let queue_infos = [vk::DeviceQueueCreateInfo::builder()
+            .queue_family_index(family)
+            .queue_priorities(&priorities)
+            .build()];
+        let device = instance
+            .create_device(
+                pdev,
+                &vk::DeviceCreateInfo::builder().queue_create_infos(&queue_infos),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+        let queue = device.get_device_queue(family, 0);
+
+        // the rest is destroyed with the device on error
+        let mut headless = Self {
+            _entry: entry,
+            instance,
+            device,
+            memory_properties,
+            queue,
+            command_pool: vk::CommandPool::null(),
+            command_buffer: vk::CommandBuffer::null(),
+            render_pass: vk::RenderPass::null(),
+            image: vk::Image::null(),
+            memory: vk::DeviceMemory::null(),
+            view: vk::ImageView::null(),
+            framebuffer: vk::Framebuffer::null(),
+        };
+        headless.create_objects(family)?;
+        Ok(headless)
+    }
+
+    unsafe fn create_objects(&mut self, family: u32) -> Result<(), String> {
+        let device = &self.device;
+
+        self.command_pool = device
+            .create_command_pool(
+                &vk::CommandPoolCreateInfo::builder()
+                    .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
+                    .queue_family_index(family),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+        self.command_buffer = device
+            .allocate_command_buffers(
+                &vk::CommandBufferAllocateInfo::builder()
+                    .command_pool(self.command_pool)
+                    .level(vk::CommandBufferLevel::PRIMARY)
+                    .command_buffer_count(1),
+            )
+            .map_err(|err| err.to_string())?[0];
+
+        let attachments = [vk::AttachmentDescription::builder()
+            .format(FORMAT)
+            .samples(vk::SampleCountFlags::TYPE_1)
+            .load_op(vk::AttachmentLoadOp::CLEAR)
+            .store_op(vk::AttachmentStoreOp::STORE)
+            .stencil_load_op(vk::AttachmentLoadOp::DONT_CARE)
+            .stencil_store_op(vk::AttachmentStoreOp::DONT_CARE)
+            .initial_layout(vk::ImageLayout::UNDEFINED)
+            .final_layout(vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL)
+            .build()];
+        let color_refs = [vk::AttachmentReference {
+            attachment: 0,
+            layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+        }];
+        let subpasses = [vk::SubpassDescription::builder()
+            .pipeline_bind_point(vk::PipelineBindPoint::GRAPHICS)
+            .color_attachments(&color_refs)
+            .build()];
+        self.render_pass = device
+            .create_render_pass(
+                &vk::RenderPassCreateInfo::builder()
+                    .attachments(&attachments)
+                    .subpasses(&subpasses),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+
+        self.image = device
+            .create_image(
+                &vk::ImageCreateInfo::builder()
+                    .image_type(vk::ImageType::TYPE_2D)
+                    .format(FORMAT)
+                    .extent(vk::Extent3D {
+                        width: EXTENT.width,
+                        height: EXTENT.height,
+                        depth: 1,
+                    })
+                    .mip_levels(1)
+                    .array_layers(1)
+                    .samples(vk::SampleCountFlags::TYPE_1)
+                    .tiling(vk::ImageTiling::OPTIMAL)
+                    .usage(vk::ImageUsageFlags::COLOR_ATTACHMENT)
+                    .sharing_mode(vk::SharingMode::EXCLUSIVE)
+                    .initial_layout(vk::ImageLayout::UNDEFINED),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+        let req = device.get_image_memory_requirements(self.image);
+        let memory_type = super::find_memory_type(
+            &self.memory_properties,
+            req.memory_type_bits,
+            vk::MemoryPropertyFlags::DEVICE_LOCAL,
+        )
+        .map_err(|err| err.to_string())?;
+        self.memory = device
+            .allocate_memory(
+                &vk::MemoryAllocateInfo::builder()
+                    .allocation_size(req.size)
+                    .memory_type_index(memory_type),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+        device
+            .bind_image_memory(self.image, self.memory, 0)
+            .map_err(|err| err.to_string())?;
+
+        self.view = device
+            .create_image_view(
+                &vk::ImageViewCreateInfo::builder()
+                    .image(self.image)
+                    .view_type(vk::ImageViewType::TYPE_2D)
+                    .format(FORMAT)
+                    .subresource_range(vk::ImageSubresourceRange {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        base_mip_level: 0,
+                        level_count: 1,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    }),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+
+        let views = [self.view];
+        self.framebuffer = device
+            .create_framebuffer(
+                &vk::FramebufferCreateInfo::builder()
+                    .render_pass(self.render_pass)
+                    .attachments(&views)
+                    .width(EXTENT.width)
+                    .height(EXTENT.height)
+                    .layers(1),
+                None,
+            )
+            .map_err(|err| err.to_string())?;
+
+        Ok(())
+    }
+
+    fn ash_device(&self) -> AshDevice {
+        AshDevice {
+            device: self.device.clone(),
+            memory_properties: self.memory_properties,
+            queue: self.queue,
+            command_pool: self.command_pool,
+            command_buffer: self.command_buffer,
+            render_pass: self.render_pass,
+            frame_index: 0,
+            debug_utils: None,
+        }
+    }
+}
+
+impl Drop for Headless {
+    fn drop(&mut self) {
+        // destroying null handles is a no-op
+        unsafe {
+            let device = &self.device;
+            let _ = device.device_wait_idle();
+            device.destroy_framebuffer(self.framebuffer, None);
+            device.destroy_image_view(self.view, None);
+            device.destroy_image(self.image, None);
+            device.free_memory(self.memory, None);
+            device.destroy_render_pass(self.render_pass, None);
+            device.destroy_command_pool(self.command_pool, None);
+            device.destroy_device(None);
+            self.instance.destroy_instance(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[ignore = "requires a Vulkan driver"]
+    fn conformance() {
+        crate::helper::conformance::assert_conformance::<super::ImGuiAsh>();
+    }
+}
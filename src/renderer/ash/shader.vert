@@ -0,0 +1,21 @@
+#version 450
+
+layout(push_constant) uniform PushConstants {
+    vec2 scale;
+    vec2 translate;
+    float opacity;
+} pc;
+
+layout(location = 0) in vec2 in_pos;
+layout(location = 1) in vec2 in_uv;
+layout(location = 2) in vec4 in_color;
+
+layout(location = 0) out vec4 out_color;
+layout(location = 1) out vec2 out_uv;
+
+void main() {
+    out_color = vec4(in_color.rgb, in_color.a * pc.opacity);
+    out_uv = in_uv;
+    // Vulkan's clip space is Y-down like ImGUI's
+    gl_Position = vec4(in_pos * pc.scale + pc.translate, 0.0, 1.0);
+}
@@ -5,30 +5,37 @@ ImGUI renderer implementation in FNA3D based on [the example]
 
 * FIXME: It is a bad practice to use `raw_device` field because it may drop earlier than Device
 * FIXME: Don't use batcher?
+* NOTE: GPU timing is not supported; FNA3D only exposes occlusion queries
 */
 
 use {
-    imgui::im_str,
     std::{mem::size_of, rc::Rc},
     thiserror::Error,
 };
 
 use crate::{
-    helper::{DrawParams, DrawParamsIterator},
-    Renderer,
+    helper::{
+        DrawItem, DrawParams, DrawParamsIterator, FlipY, ReloadBus, TextureInfo, TextureOwnership,
+        TextureRegistry, TextureSource, UiPassPlacement, VertexLayout,
+    },
+    FontSampling, RegisterTexture, Renderer,
 };
 
+mod capture;
+#[cfg(feature = "test-engine")]
+mod conformance;
+
 /// `SpriteEffect.fxb`
 pub const SHADER: &[u8] = include_bytes!("fna3d/SpriteEffect.fxb");
 
 /// `mplus-1p-regular.ttf`
 pub const JP_FONT: &[u8] = include_bytes!("../../assets/mplus-1p-regular.ttf");
 
-/// Fixed number of quadliterals, used for allocating buffers
+/// Default number of quadliterals, used for allocating buffers. See [`Fna3dConfig`]
 pub const N_QUADS: usize = 8192;
 
 /// Size of a vertex in bytes
-const VERT_SIZE: usize = 20;
+const VERT_SIZE: usize = size_of::<imgui::DrawVert>();
 
 /// Size of an index in bytes
 const INDEX_SIZE: usize = 2;
@@ -44,68 +51,278 @@ pub enum ImGuiRendererError {
 pub type Result<T> = std::result::Result<T, ImGuiRendererError>;
 
 /// GPU texture with size
+///
+/// Invariant: `raw` is a live texture of `device`, created by the renderer or registered with
+/// [`ImGuiFna3d::register_texture_raw`]. It's never null.
 #[derive(Debug)]
 pub struct TextureData2d {
-    pub raw: *mut fna3d::Texture,
+    raw: *mut fna3d::Texture,
     device: fna3d::Device,
     pub w: u32,
     pub h: u32,
+    /// Disposes the texture on drop if true
+    is_owned: bool,
+    /// Shared with [`RawTextureGuard`] while registered
+    _token: Option<Rc<()>>,
 }
 
 impl Drop for TextureData2d {
     fn drop(&mut self) {
-        self.device.add_dispose_texture(self.raw);
+        if self.is_owned {
+            self.device.add_dispose_texture(self.raw);
+        }
+    }
+}
+
+impl TextureData2d {
+    /// Raw handle for FNA3D calls
+    ///
+    /// # Safety
+    ///
+    /// The handle must not be disposed; it's owned by this texture or the application. It must not
+    /// be used after this texture is dropped.
+    pub unsafe fn raw(&self) -> *mut fna3d::Texture {
+        self.raw
     }
 }
 
 /// Reference counted version of [`TextureData2d`]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct RcTexture2d {
     pub texture: Rc<TextureData2d>,
 }
 
 impl RcTexture2d {
-    pub fn new(raw: *mut fna3d::Texture, device: fna3d::Device, w: u32, h: u32) -> Self {
+    /// Creates a non-premultiplied alpha texture from RGBA8 pixels
+    pub fn from_pixels(device: &fna3d::Device, pixels: &[u8], w: u32, h: u32) -> Self {
+        let fmt = fna3d::SurfaceFormat::Color;
+        let raw = device.create_texture_2d(fmt, w, h, 1, false);
+        device.set_texture_data_2d(raw, 0, 0, w, h, 0, pixels);
+        Self::from_raw(raw, device.clone(), w, h)
+    }
+
+    /// Takes ownership of the raw texture, which is disposed when the last reference is dropped.
+    /// `raw` must be a texture of `device` that nobody else disposes
+    fn from_raw(raw: *mut fna3d::Texture, device: fna3d::Device, w: u32, h: u32) -> Self {
+        debug_assert!(!raw.is_null(), "imgui-fna3d: null texture");
         Self {
-            texture: Rc::new(TextureData2d { raw, device, w, h }),
+            texture: Rc::new(TextureData2d {
+                raw,
+                device,
+                w,
+                h,
+                is_owned: true,
+                _token: None,
+            }),
         }
     }
 }
 
-/// FNA3D ImGUI renderer
+/// Returned by [`ImGuiFna3d::register_texture_raw`]. Keep it while the raw texture is alive
+///
+/// Dropping it while the texture is still registered means the texture is disposed while ImGUI can
+/// still draw it, so it panics in debug builds. Unregister with [`ImGuiFna3d::unregister_texture`]
+/// first.
 #[derive(Debug)]
-pub struct ImGuiFna3d {
-    textures: imgui::Textures<RcTexture2d>,
+pub struct RawTextureGuard {
+    pub id: imgui::TextureId,
+    token: Rc<()>,
+}
+
+impl Drop for RawTextureGuard {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) && !std::thread::panicking() && Rc::strong_count(&self.token) > 1
+        {
+            panic!(
+                "imgui-fna3d: raw texture {:?} is disposed while registered",
+                self.id
+            );
+        }
+    }
+}
+
+/// Device states of the game that the UI pass overwrites
+///
+/// FNA3D can't query the current states, so the game describes what it expects after the UI pass.
+#[derive(Debug, Clone)]
+pub struct GameRenderState {
+    pub blend: fna3d::BlendState,
+    pub rasterizer: fna3d::RasterizerState,
+    pub viewport: fna3d::Viewport,
+}
+
+/// Restores [`GameRenderState`] on drop. Create it right before rendering the UI between two game
+/// passes:
+///
+/// ```no_run
+/// {
+///     let _guard = RenderStateGuard::new(&device, &game_state);
+///     ui.end_frame(&mut window, &mut device)?;
+/// }
+/// // post pass with the game's states
+/// ```
+#[derive(Debug)]
+pub struct RenderStateGuard<'a> {
+    device: fna3d::Device,
+    state: &'a GameRenderState,
+}
+
+impl<'a> RenderStateGuard<'a> {
+    pub fn new(device: &fna3d::Device, state: &'a GameRenderState) -> Self {
+        Self {
+            device: device.clone(),
+            state,
+        }
+    }
+}
+
+impl<'a> Drop for RenderStateGuard<'a> {
+    fn drop(&mut self) {
+        self.device.set_blend_state(&self.state.blend);
+        self.device.apply_rasterizer_state(&self.state.rasterizer);
+        self.device.set_viewport(&self.state.viewport);
+    }
+}
+
+/// GPU objects released on [`Renderer::invalidate_device_objects`]
+#[derive(Debug)]
+struct DeviceObjects {
     font_texture: RcTexture2d,
     batch: Batch,
 }
 
+impl DeviceObjects {
+    fn new(
+        device: &fna3d::Device,
+        fonts: imgui::FontAtlasRefMut,
+        config: &Fna3dConfig,
+    ) -> Result<Self> {
+        Ok(Self {
+            font_texture: ImGuiFna3d::load_font_texture(device, fonts, config)?,
+            batch: Batch::new(device.clone(), config.n_vertices, config.n_indices),
+        })
+    }
+}
+
+/// Configuration of [`ImGuiFna3d`], accepted by [`ImGuiFna3d::init_with_config`]
+///
+/// FNA3D has no control over sRGB writes, so there's no sRGB option. Render the UI to a `Color`
+/// target instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fna3dConfig {
+    /// Capacity of the vertex buffer
+    pub n_vertices: usize,
+    /// Capacity of the index buffer
+    pub n_indices: usize,
+    /// Initial sampling of the font atlas. See [`Renderer::set_font_sampling`]
+    pub font_sampling: FontSampling,
+    /// Filter of user textures
+    pub texture_filter: FontSampling,
+    /// User textures have premultiplied alpha (see [`crate::helper::premultiply_rgba8`]) and the
+    /// UI is blended with `BlendState::alpha_blend`. The font atlas and vertex colors are
+    /// premultiplied on CPU
+    pub premultiplied_alpha: bool,
+}
+
+impl Default for Fna3dConfig {
+    fn default() -> Self {
+        Self {
+            n_vertices: 4 * N_QUADS,
+            n_indices: 6 * N_QUADS,
+            font_sampling: FontSampling::default(),
+            texture_filter: FontSampling::Linear,
+            premultiplied_alpha: false,
+        }
+    }
+}
+
+impl Fna3dConfig {
+    pub fn buffer_capacity(mut self, n_vertices: usize, n_indices: usize) -> Self {
+        self.n_vertices = n_vertices;
+        self.n_indices = n_indices;
+        self
+    }
+
+    pub fn font_sampling(mut self, sampling: FontSampling) -> Self {
+        self.font_sampling = sampling;
+        self
+    }
+
+    pub fn texture_filter(mut self, filter: FontSampling) -> Self {
+        self.texture_filter = filter;
+        self
+    }
+
+    pub fn premultiplied_alpha(mut self, premultiplied: bool) -> Self {
+        self.premultiplied_alpha = premultiplied;
+        self
+    }
+}
+
+fn sampler_state(sampling: FontSampling) -> fna3d::SamplerState {
+    match sampling {
+        FontSampling::Linear => fna3d::SamplerState::linear_wrap(),
+        FontSampling::Nearest => fna3d::SamplerState::point_wrap(),
+    }
+}
+
+/// FNA3D ImGUI renderer
+#[derive(Debug)]
+pub struct ImGuiFna3d {
+    config: Fna3dConfig,
+    textures: TextureRegistry<RcTexture2d>,
+    font_sampling: FontSampling,
+    opacity: f32,
+    flip_y: FlipY,
+    placement: UiPassPlacement,
+    /// HDR value of UI white for [`UiPassPlacement::BeforePostFx`]
+    paper_white: f32,
+    /// `None` while the device objects are invalidated
+    objs: Option<DeviceObjects>,
+    /// Incremented every time the font texture is created
+    font_generation: u64,
+    reload_bus: Option<ReloadBus>,
+    /// Index buffer of the current draw list. The first draw call of a list can be culled, so new
+    /// lists are detected by their buffers rather than `idx_offset == 0`
+    list_idx: *const imgui::DrawIdx,
+}
+
 impl ImGuiFna3d {
     /// Add font before loading
     pub fn init(imgui: &mut imgui::Context, device: &fna3d::Device) -> Result<Self> {
-        imgui.set_renderer_name(Some(im_str!(
-            "imgui-fna3d-renderer {}",
-            env!("CARGO_PKG_VERSION")
-        )));
+        Self::init_with_config(imgui, device, Fna3dConfig::default())
+    }
 
-        imgui
-            .io_mut()
-            .backend_flags
-            .insert(imgui::BackendFlags::RENDERER_HAS_VTX_OFFSET);
+    /// [`Self::init`] with the configuration
+    pub fn init_with_config(
+        imgui: &mut imgui::Context,
+        device: &fna3d::Device,
+        config: Fna3dConfig,
+    ) -> Result<Self> {
+        imgui.set_renderer_name(Some(crate::backend_name("imgui-fna3d-renderer", &[])));
 
-        let font_texture = Self::load_font_texture(device, imgui.fonts())?;
+        let objs = DeviceObjects::new(device, imgui.fonts(), &config)?;
 
         Ok(Self {
-            textures: imgui::Textures::new(),
-            font_texture,
-            batch: Batch::new(device.clone()),
+            font_sampling: config.font_sampling,
+            config,
+            textures: TextureRegistry::new(),
+            opacity: 1.0,
+            flip_y: FlipY::default(),
+            placement: UiPassPlacement::default(),
+            paper_white: 1.0,
+            objs: Some(objs),
+            font_generation: 1,
+            reload_bus: None,
+            list_idx: std::ptr::null(),
         })
     }
 
-    /// Be warned that the font texture is  non-premultiplied alpha
+    /// Be warned that the font texture is non-premultiplied alpha unless configured
     fn load_font_texture(
         device: &fna3d::Device,
         mut fonts: imgui::FontAtlasRefMut,
+        config: &Fna3dConfig,
     ) -> Result<RcTexture2d> {
         let atlas_texture = fonts.build_rgba32_texture();
         let (pixels, w, h) = (
@@ -114,58 +331,276 @@ impl ImGuiFna3d {
             atlas_texture.height,
         );
 
-        let raw = {
-            let fmt = fna3d::SurfaceFormat::Color;
-            let gpu_texture = device.create_texture_2d(fmt, w, h, 1, false);
-            device.set_texture_data_2d(gpu_texture, 0, 0, w, h, 0, pixels);
-            gpu_texture
+        let font_texture = Self::font_texture_from_pixels(device, config, pixels, w, h);
+
+        // Note that we have to set the ID *AFTER* creating the font atlas texture
+        fonts.tex_id = imgui::TextureId::from(usize::MAX);
+
+        Ok(font_texture)
+    }
+
+    /// Premultiplies the pixels if configured
+    fn font_texture_from_pixels(
+        device: &fna3d::Device,
+        config: &Fna3dConfig,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> RcTexture2d {
+        if config.premultiplied_alpha {
+            let mut pixels = pixels.to_vec();
+            crate::helper::premultiply_rgba8(&mut pixels);
+            RcTexture2d::from_pixels(device, &pixels, w, h)
+        } else {
+            RcTexture2d::from_pixels(device, pixels, w, h)
+        }
+    }
+
+    /// Flips the UI vertically, e.g. for engines with bottom-left origin UI space
+    pub fn set_flip_y(&mut self, flip_y: FlipY) {
+        self.flip_y = flip_y;
+    }
+
+    /// Selects the UI pass placement. Bind the HDR target or the final target with
+    /// `set_render_targets` before rendering accordingly; the renderer draws to the bound target
+    ///
+    /// Vertex colors are 8 bits, so colors scaled above the paper white are clamped. Prefer
+    /// [`UiPassPlacement::AfterPostFx`].
+    pub fn set_pass_placement(&mut self, placement: UiPassPlacement) {
+        self.placement = placement;
+    }
+
+    /// Sets the HDR value that tone maps to UI white, used while the UI is rendered before
+    /// post-processing. Defaults to `1.0`
+    pub fn set_hdr_paper_white(&mut self, paper_white: f32) {
+        self.paper_white = paper_white;
+    }
+
+    /// Textures announced on the bus are re-created at the beginning of the next render
+    pub fn set_reload_bus(&mut self, bus: Option<ReloadBus>) {
+        self.reload_bus = bus;
+    }
+
+    fn handle_reloads(&mut self, device: &fna3d::Device) {
+        if let Some(bus) = self.reload_bus.as_ref() {
+            let rest = bus
+                .reload_textures::<_, std::convert::Infallible>(
+                    &mut self.textures,
+                    |pixels, w, h| Ok(RcTexture2d::from_pixels(device, pixels, w, h)),
+                )
+                .unwrap_or_default();
+            for ev in rest {
+                log::debug!("ImGuiFna3d: unhandled reload event {:?}", ev);
+            }
+        }
+    }
+
+    /// Uploads an RGBA8 texture. If `retain` is true, a CPU copy of the pixels is kept so that the
+    /// texture can be restored after device loss
+    pub fn register_texture(
+        &mut self,
+        device: &fna3d::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> imgui::TextureId {
+        let tex = RcTexture2d::from_pixels(device, pixels, w, h);
+        let source = if retain {
+            Some(TextureSource::Pixels(pixels.to_vec()))
+        } else {
+            None
         };
+        self.textures.insert(tex, w, h, source)
+    }
 
-        let font_texture = TextureData2d {
-            raw,
-            device: device.clone(),
-            w,
-            h,
+    /// Registers a texture shared with the application
+    pub fn register_texture_rc(&mut self, texture: &RcTexture2d) -> imgui::TextureId {
+        let (w, h) = (texture.texture.w, texture.texture.h);
+        let id = self.textures.insert(texture.clone(), w, h, None);
+        self.textures.set_ownership(id, TextureOwnership::Shared);
+        id
+    }
+
+    /// Registers a texture owned by the application. The texture is not disposed by the renderer.
+    /// The returned guard panics in debug builds if it's dropped while the texture is still
+    /// registered. Prefer [`Self::register_texture_rc`]
+    ///
+    /// # Safety
+    ///
+    /// `raw` must be a texture created by `device`. It must stay alive until it's unregistered with
+    /// [`Self::unregister_texture`], and not be disposed while ImGUI can draw it.
+    pub unsafe fn register_texture_raw(
+        &mut self,
+        device: &fna3d::Device,
+        raw: *mut fna3d::Texture,
+        w: u32,
+        h: u32,
+    ) -> RawTextureGuard {
+        assert!(!raw.is_null(), "imgui-fna3d: null texture");
+
+        let token = Rc::new(());
+        let texture = RcTexture2d {
+            texture: Rc::new(TextureData2d {
+                raw,
+                device: device.clone(),
+                w,
+                h,
+                is_owned: false,
+                _token: Some(token.clone()),
+            }),
         };
 
-        // Note that we have to set the ID *AFTER* creating the font atlas texture
-        fonts.tex_id = imgui::TextureId::from(usize::MAX);
+        let id = self.textures.insert(texture, w, h, None);
+        self.textures
+            .set_ownership(id, TextureOwnership::Application);
+        RawTextureGuard { id, token }
+    }
 
-        Ok(RcTexture2d {
-            texture: Rc::new(font_texture),
-        })
+    /// Unregisters a texture. Owned textures are disposed when the last reference is dropped
+    pub fn unregister_texture(&mut self, id: imgui::TextureId) -> Option<RcTexture2d> {
+        self.textures.remove(id)
+    }
+
+    pub fn textures(&self) -> &TextureRegistry<RcTexture2d> {
+        &self.textures
     }
 
-    pub fn textures_mut(&mut self) -> &mut imgui::Textures<RcTexture2d> {
+    pub fn textures_mut(&mut self) -> &mut TextureRegistry<RcTexture2d> {
         &mut self.textures
     }
 
-    /// Be warned that the font texture is  non-premultiplied alpha
-    pub fn font_texture(&self) -> &TextureData2d {
-        &self.font_texture.texture
+    /// Be warned that the font texture is  non-premultiplied alpha. Returns `None` while the device
+    /// objects are invalidated
+    pub fn font_texture(&self) -> Option<&TextureData2d> {
+        self.objs.as_ref().map(|objs| &*objs.font_texture.texture)
+    }
+}
+
+impl RegisterTexture for ImGuiFna3d {
+    fn register_rgba8(
+        &mut self,
+        device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> std::result::Result<imgui::TextureId, Self::Error> {
+        Ok(self.register_texture(device, pixels, w, h, retain))
+    }
+
+    fn remove_textures<I>(&mut self, ids: I) -> usize
+    where
+        I: IntoIterator<Item = imgui::TextureId>,
+    {
+        self.textures.remove_many(ids)
+    }
+
+    /// FNA3D has no object labels; labeled textures are announced with string markers on draw
+    fn set_texture_label(&mut self, _device: &mut Self::Device, id: imgui::TextureId, label: &str) {
+        self.textures.set_label(id, label);
+    }
+
+    fn texture_infos(&self) -> Vec<TextureInfo<'_>> {
+        self.textures.textures_iter().collect()
     }
 }
 
 impl Renderer for ImGuiFna3d {
     type Device = fna3d::Device;
     type Error = anyhow::Error;
+    const HAS_VTX_OFFSET: bool = true;
 
     fn render(
         &mut self,
         draw_data: &imgui::DrawData,
         device: &mut Self::Device,
     ) -> std::result::Result<(), Self::Error> {
+        anyhow::ensure!(
+            self.objs.is_some(),
+            "ImGuiFna3d: device objects are invalidated"
+        );
+
+        self.textures.flush_removed();
+        self.handle_reloads(device);
         self.before_render(device);
-        for params in DrawParamsIterator::new(draw_data) {
-            self.draw(device, &params)?;
+        self.list_idx = std::ptr::null();
+        for item in DrawParamsIterator::new(draw_data) {
+            match item {
+                DrawItem::Draw(params) => self.draw(device, &params)?,
+                // the effect, texture and buffers are applied on every draw call
+                DrawItem::ResetState => self.before_render(device),
+                DrawItem::Callback(callback) => self.run_callback(device, &callback)?,
+            }
         }
         Ok(())
     }
+
+    fn font_atlas_info(&self) -> Option<crate::FontAtlasInfo> {
+        self.font_texture().map(|tex| crate::FontAtlasInfo {
+            tex_id: imgui::TextureId::from(usize::MAX),
+            size: [tex.w, tex.h],
+            generation: self.font_generation,
+        })
+    }
+
+    fn set_font_sampling(&mut self, sampling: FontSampling) {
+        self.font_sampling = sampling;
+    }
+
+    fn set_ui_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    fn invalidate_device_objects(&mut self, _device: &mut Self::Device) {
+        // textures and buffers are queued for disposal on drop
+        self.textures.invalidate();
+        self.objs = None;
+    }
+
+    fn create_device_objects(
+        &mut self,
+        imgui: &mut imgui::Context,
+        device: &mut Self::Device,
+    ) -> std::result::Result<(), Self::Error> {
+        self.objs = None;
+        self.objs = Some(DeviceObjects::new(device, imgui.fonts(), &self.config)?);
+        self.font_generation += 1;
+
+        self.textures
+            .restore::<std::convert::Infallible>(|pixels, w, h| {
+                Ok(RcTexture2d::from_pixels(device, pixels, w, h))
+            })
+            .ok();
+        Ok(())
+    }
+
+    fn replace_font_atlas(
+        &mut self,
+        device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> std::result::Result<bool, Self::Error> {
+        let objs = match self.objs.as_mut() {
+            Some(objs) => objs,
+            None => return Ok(false),
+        };
+
+        objs.font_texture = Self::font_texture_from_pixels(device, &self.config, pixels, w, h);
+        self.font_generation += 1;
+        Ok(true)
+    }
 }
 
 impl ImGuiFna3d {
     fn before_render(&mut self, device: &mut <Self as Renderer>::Device) {
-        device.set_blend_state(&fna3d::BlendState::non_premultiplied());
+        let blend = if self.config.premultiplied_alpha {
+            fna3d::BlendState::alpha_blend()
+        } else {
+            fna3d::BlendState::non_premultiplied()
+        };
+        device.set_blend_state(&blend);
     }
 
     fn draw<'a>(
@@ -173,58 +608,71 @@ impl ImGuiFna3d {
         device: &mut <Self as Renderer>::Device,
         params: &'a DrawParams,
     ) -> std::result::Result<(), <Self as Renderer>::Error> {
-        if params.idx_offset == 0 {
+        // checked on `render`
+        let opacity = self.opacity;
+        let color_scale = self.placement.color_scale(self.paper_white);
+        let premultiplied = self.config.premultiplied_alpha;
+        let flip_y = self.flip_y;
+        let new_list = params.idx_buffer.as_ptr() != self.list_idx;
+        self.list_idx = params.idx_buffer.as_ptr();
+        let objs = self.objs.as_mut().unwrap();
+
+        if new_list {
             // 1. append buffers
-            self.batch
-                .set_buffers(device, params.vtx_buffer, params.idx_buffer);
+            objs.batch.set_buffers(
+                device,
+                params.vtx_buffer,
+                params.idx_buffer,
+                opacity,
+                color_scale,
+                premultiplied,
+            );
 
             // 2. set orthographic projection matrix
+            let [bottom, top] = flip_y.bottom_top(&params.display);
             let mat = fna3d::mojo::orthographic_off_center(
                 // left, right
                 params.display.left(),
                 params.display.right(),
-                // bottom, top
-                // Since we want to flip the y axis so that it goes down, we'll swap top and bottom
-                params.display.top(),
-                params.display.bottom(),
+                bottom,
+                top,
                 // near, far
                 0.0,
                 1.0,
             );
 
-            unsafe {
-                let name = "MatrixTransform";
-                let name = std::ffi::CString::new(name).unwrap();
-                if !fna3d::mojo::set_param(self.batch.effect_data, &name, &mat) {
-                    log::warn!("failed to set projection matrix in FNA3D ImGUI renderer");
-                }
+            if !objs.batch.set_transform(&mat) {
+                log::warn!("failed to set projection matrix in FNA3D ImGUI renderer");
             }
         }
 
         // 1. scissor
-        log::trace!("{}", params.scissor.height());
-        device.set_scissor_rect(&fna3d::Rect {
-            x: params.scissor.left() as i32,
-            y: params.scissor.top() as i32,
-            w: params.scissor.width() as i32,
-            h: params.scissor.height() as i32,
-        });
+        // FNA3D scissor origin is top-left
+        let [x, y, w, h] = flip_y.scissor(params, false);
+        device.set_scissor_rect(&fna3d::Rect { x, y, w, h });
 
         // 2. set texture
         let tex_id = params.tex_id;
-        let texture = if tex_id.id() == usize::MAX {
-            &self.font_texture
+        let (texture, sampler) = if tex_id.id() == usize::MAX {
+            (&objs.font_texture, self::sampler_state(self.font_sampling))
         } else {
-            self.textures
+            let texture = self
+                .textures
                 .get(tex_id)
-                .ok_or_else(|| ImGuiRendererError::BadTexture(tex_id))?
+                .ok_or_else(|| ImGuiRendererError::BadTexture(tex_id))?;
+            (texture, self::sampler_state(self.config.texture_filter))
         };
 
-        self.batch
-            .prepare_draw(device, texture.texture.raw, params.vtx_offset as u32);
+        if let Some(label) = self.textures.entry(tex_id).and_then(|e| e.label.as_deref()) {
+            device.set_string_marker(label);
+        }
+
+        let raw = texture.texture.raw;
+        objs.batch
+            .prepare_draw(device, raw, &sampler, params.vtx_offset as u32);
 
         // 3. draw
-        let n_vertices = params.n_elems as u32 * 2 / 3; // n_verts : n_idx = 4 : 6
+        let n_vertices = params.n_vertices() as u32;
         let n_triangles = params.n_elems / 3;
 
         device.draw_indexed_primitives(
@@ -234,7 +682,7 @@ impl ImGuiFna3d {
             n_vertices,
             params.idx_offset as u32,
             n_triangles as u32,
-            self.batch.ibuf.buf,
+            objs.batch.ibuf.buf,
             fna3d::IndexElementSize::Bits16,
         );
 
@@ -247,7 +695,8 @@ impl ImGuiFna3d {
 
 /// Buffer of GPU buffers
 ///
-/// Drops internal buffers automatically.
+/// Drops internal buffers automatically. The raw handles are created from `device` in
+/// [`Batch::new`], never null and never exposed outside of this module.
 #[derive(Debug)]
 struct Batch {
     device: fna3d::Device,
@@ -255,6 +704,12 @@ struct Batch {
     vbuf: GpuVertexBuffer,
     effect: *mut fna3d::Effect,
     effect_data: *mut fna3d::mojo::Effect,
+    /// Boxed so that `vert_decl` can point to it
+    _vert_elems: Box<[fna3d::VertexElement; 3]>,
+    vert_decl: fna3d::VertexDeclaration,
+    /// CPU copy of vertices with alpha multiplied by the UI opacity (and colors by alpha when
+    /// premultiplied)
+    faded: Vec<imgui::DrawVert>,
 }
 
 impl Drop for Batch {
@@ -266,11 +721,20 @@ impl Drop for Batch {
 }
 
 impl Batch {
-    fn new(device: fna3d::Device) -> Self {
-        let vbuf = GpuVertexBuffer::new(&device, 4 * N_QUADS); // four vertices per quad
-        let ibuf = GpuIndexBuffer::new(&device, 6 * N_QUADS); // six indices per quad
+    fn new(device: fna3d::Device, n_vertices: usize, n_indices: usize) -> Self {
+        let vbuf = GpuVertexBuffer::new(&device, n_vertices);
+        let ibuf = GpuIndexBuffer::new(&device, n_indices);
 
         let (effect, effect_data) = fna3d::mojo::from_bytes(&device, SHADER).unwrap();
+        debug_assert!(!effect.is_null() && !effect_data.is_null());
+
+        let layout = VertexLayout::draw_vert();
+        let mut vert_elems = Box::new(self::vert_elems(&layout));
+        let vert_decl = fna3d::VertexDeclaration {
+            vertexStride: layout.stride as _,
+            elementCount: vert_elems.len() as _,
+            elements: vert_elems.as_mut_ptr(),
+        };
 
         Self {
             device,
@@ -278,6 +742,9 @@ impl Batch {
             ibuf,
             effect,
             effect_data,
+            _vert_elems: vert_elems,
+            vert_decl,
+            faded: Vec::new(),
         }
     }
 
@@ -286,16 +753,47 @@ impl Batch {
         device: &fna3d::Device,
         vbuf: &[imgui::DrawVert],
         ibuf: &[imgui::DrawIdx],
+        opacity: f32,
+        color_scale: f32,
+        premultiplied: bool,
     ) {
-        self.vbuf.upload_vertices(vbuf, device);
+        if opacity < 1.0 || color_scale != 1.0 || premultiplied {
+            // FNA3D's SpriteEffect has no uniform for it, so multiply on CPU
+            self.faded.clear();
+            self.faded.extend(vbuf.iter().map(|v| {
+                let mut v = *v;
+                let alpha = v.col[3] as f32 * opacity;
+                let scale = if premultiplied {
+                    color_scale * alpha / 255.0
+                } else {
+                    color_scale
+                };
+                for c in &mut v.col[..3] {
+                    *c = (*c as f32 * scale).min(255.0) as u8;
+                }
+                v.col[3] = alpha as u8;
+                v
+            }));
+            self.vbuf.upload_vertices(&self.faded, device);
+        } else {
+            self.vbuf.upload_vertices(vbuf, device);
+        }
         self.ibuf.upload_indices(ibuf, device);
     }
 
+    /// Sets the projection matrix. Returns false if the effect has no such parameter
+    fn set_transform(&mut self, mat: &[f32; 16]) -> bool {
+        let name = std::ffi::CString::new("MatrixTransform").unwrap();
+        // SAFETY: `effect_data` is the live effect created in `Batch::new`
+        unsafe { fna3d::mojo::set_param(self.effect_data, &name, mat) }
+    }
+
     /// Sets up rendering pipeline before making a draw call
     fn prepare_draw(
         &mut self,
         device: &fna3d::Device,
         texture: *mut fna3d::Texture,
+        sampler: &fna3d::SamplerState,
         vtx_offset: u32,
     ) {
         // apply effect
@@ -312,14 +810,13 @@ impl Batch {
         device.apply_effect(self.effect, pass, &state_changes);
 
         // set texture
-        let sampler = fna3d::SamplerState::linear_wrap();
         let slot = 0;
-        device.verify_sampler(slot, texture, &sampler);
+        device.verify_sampler(slot, texture, sampler);
 
         // apply vertex buffer binding
         let bind = fna3d::VertexBufferBinding {
             vertexBuffer: self.vbuf.buf,
-            vertexDeclaration: VERT_DECL,
+            vertexDeclaration: self.vert_decl,
             vertexOffset: 0, // FIXME:
             instanceFrequency: 0,
         };
@@ -337,6 +834,7 @@ impl GpuVertexBuffer {
     fn new(device: &fna3d::Device, n_vertices: usize) -> Self {
         let len = VERT_SIZE * n_vertices;
         let buf = device.gen_vertex_buffer(true, fna3d::BufferUsage::None, len as u32);
+        debug_assert!(!buf.is_null());
 
         Self {
             buf,
@@ -372,6 +870,7 @@ impl GpuIndexBuffer {
     fn new(device: &fna3d::Device, n_indices: usize) -> Self {
         let len = INDEX_SIZE * n_indices;
         let buf = device.gen_index_buffer(true, fna3d::BufferUsage::None, len as u32);
+        debug_assert!(!buf.is_null());
 
         Self {
             buf,
@@ -402,29 +901,25 @@ impl GpuIndexBuffer {
 /// * pos: [f32; 2]
 /// * uv: [f32; 2]
 /// * col: [u8; 4]
-const VERT_ELEMS: [fna3d::VertexElement; 3] = [
-    fna3d::VertexElement {
-        offset: 0,
-        vertexElementFormat: fna3d::VertexElementFormat::Vector2 as u32,
-        vertexElementUsage: fna3d::VertexElementUsage::Position as u32,
-        usageIndex: 0,
-    },
-    fna3d::VertexElement {
-        offset: 8,
-        vertexElementFormat: fna3d::VertexElementFormat::Vector2 as u32,
-        vertexElementUsage: fna3d::VertexElementUsage::TextureCoordinate as u32,
-        usageIndex: 0,
-    },
-    fna3d::VertexElement {
-        offset: 16,
-        vertexElementFormat: fna3d::VertexElementFormat::Color as u32,
-        vertexElementUsage: fna3d::VertexElementUsage::Color as u32,
-        usageIndex: 0,
-    },
-];
-
-const VERT_DECL: fna3d::VertexDeclaration = fna3d::VertexDeclaration {
-    vertexStride: 20,
-    elementCount: 3,
-    elements: VERT_ELEMS.as_ptr() as *mut _,
-};
+fn vert_elems(layout: &VertexLayout) -> [fna3d::VertexElement; 3] {
+    [
+        fna3d::VertexElement {
+            offset: layout.pos as _,
+            vertexElementFormat: fna3d::VertexElementFormat::Vector2 as u32,
+            vertexElementUsage: fna3d::VertexElementUsage::Position as u32,
+            usageIndex: 0,
+        },
+        fna3d::VertexElement {
+            offset: layout.uv as _,
+            vertexElementFormat: fna3d::VertexElementFormat::Vector2 as u32,
+            vertexElementUsage: fna3d::VertexElementUsage::TextureCoordinate as u32,
+            usageIndex: 0,
+        },
+        fna3d::VertexElement {
+            offset: layout.col as _,
+            vertexElementFormat: fna3d::VertexElementFormat::Color as u32,
+            vertexElementUsage: fna3d::VertexElementUsage::Color as u32,
+            usageIndex: 0,
+        },
+    ]
+}
@@ -0,0 +1,44 @@
+//! Back buffer readback
+
+use super::ImGuiFna3d;
+use crate::{helper::diff::Image, Renderer};
+
+impl ImGuiFna3d {
+    /// Renders the draw data into the back buffer cleared with transparent black and reads it back
+    /// as RGBA8 pixels (top row first). Nothing is presented. The back buffer has to be at least as
+    /// large as the framebuffer of the draw data
+    pub fn capture(
+        &mut self,
+        device: &mut fna3d::Device,
+        draw_data: &imgui::DrawData,
+    ) -> Result<Image, String> {
+        let w = (draw_data.display_size[0] * draw_data.framebuffer_scale[0]) as u32;
+        let h = (draw_data.display_size[1] * draw_data.framebuffer_scale[1]) as u32;
+        if w == 0 || h == 0 {
+            return Err("ImGuiFna3d: can't capture empty framebuffer".to_string());
+        }
+
+        device.set_viewport(&fna3d::Viewport {
+            x: 0,
+            y: 0,
+            w: w as i32,
+            h: h as i32,
+            minDepth: 0.0,
+            maxDepth: 1.0,
+        });
+        device.clear(
+            fna3d::ClearOptions::TARGET,
+            fna3d::Color::rgba(0, 0, 0, 0).to_vec4(),
+            0.0,
+            0,
+        );
+
+        self.render(draw_data, device).map_err(|e| e.to_string())?;
+
+        // FNA3D reads the back buffer top row first
+        let mut pixels = vec![0; (w as usize) * (h as usize) * 4];
+        device.read_backbuffer(0, 0, w, h, &mut pixels);
+
+        Ok(Image { pixels, w, h })
+    }
+}
@@ -0,0 +1,68 @@
+//! [`RendererConformance`] of [`ImGuiFna3d`] on a hidden SDL2 window
+
+use crate::helper::conformance::RendererConformance;
+
+use super::ImGuiFna3d;
+
+impl RendererConformance for ImGuiFna3d {
+    fn create(imgui: &mut imgui::Context) -> Result<(Self, Self::Device), String> {
+        let device = self::device()?;
+        let renderer = Self::init(imgui, &device).map_err(|err| err.to_string())?;
+        Ok((renderer, device))
+    }
+}
+
+/// Device of a hidden window, kept alive for the rest of the thread
+#[cfg(feature = "sdl2")]
+fn device() -> Result<fna3d::Device, String> {
+    use std::cell::RefCell;
+
+    thread_local! {
+        // the device is dropped before the window
+        static WINDOW: RefCell<Option<(fna3d::Device, sdl2::video::Window)>> =
+            RefCell::new(None);
+    }
+
+    WINDOW.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        if cell.is_none() {
+            let flags = fna3d::prepare_window_attributes();
+            let sdl = sdl2::init()?;
+            let vid = sdl.video()?;
+            let win = vid
+                .window("imgui-fna3d conformance", 1280, 720)
+                .set_window_flags(flags.0)
+                .hidden()
+                .build()
+                .map_err(|err| err.to_string())?;
+
+            let params = fna3d::utils::default_params_from_window_handle(win.raw() as *mut _);
+            let device = fna3d::Device::from_params(params, true);
+            device.set_viewport(&fna3d::Viewport {
+                x: 0,
+                y: 0,
+                w: params.backBufferWidth as i32,
+                h: params.backBufferHeight as i32,
+                minDepth: 0.0,
+                maxDepth: 1.0,
+            });
+            *cell = Some((device, win));
+        }
+
+        Ok(cell.as_ref().unwrap().0.clone())
+    })
+}
+
+#[cfg(not(feature = "sdl2"))]
+fn device() -> Result<fna3d::Device, String> {
+    Err("the `sdl2` feature is required to create a FNA3D device".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[ignore = "requires a display and a FNA3D driver"]
+    fn conformance() {
+        crate::helper::conformance::assert_conformance::<super::ImGuiFna3d>();
+    }
+}
@@ -5,11 +5,15 @@ NOTE: OpenGL is stateful and ImGuiGlow may not always work as expected. Please o
 PR then!
 */
 
-use {anyhow::*, glow::HasContext, imgui::im_str};
+use {anyhow::*, glow::HasContext, std::rc::Rc};
 
 use crate::{
-    helper::{DrawParams, DrawParamsIterator},
-    Renderer,
+    helper::{
+        BudgetReport, BudgetTracker, ColorMode, DrawItem, DrawParams, DrawParamsIterator, FlipY,
+        ReloadBus, SubmitBudget, TextBlending, TextureInfo, TextureRegistry, TextureSource,
+        UiPassPlacement, UiPassTargets,
+    },
+    BackendError, FontSampling, RegisterTexture, Renderer,
 };
 
 mod res;
@@ -18,74 +22,549 @@ use res::*;
 mod tex;
 use tex::*;
 
+mod capture;
+
+mod cache;
+use cache::DrawListCache;
+
+mod timer;
+use timer::GpuTimer;
+
+mod device;
+use device::GlDevice;
+pub use device::StateCounters;
+
+mod array;
+use array::ThumbnailArray;
+
+mod window;
+pub use window::WindowTarget;
+
+#[cfg(feature = "test-engine")]
+mod conformance;
+
 /// `mplus-1p-regular.ttf`
 pub const JP_FONT: &[u8] = include_bytes!("../../assets/mplus-1p-regular.ttf");
 
 pub const FONT_TEXTUER_ID: usize = usize::MAX;
 
-pub struct ImGuiGlow {
-    textures: imgui::Textures<Texture>,
+/// GPU objects released on [`Renderer::invalidate_device_objects`]
+struct DeviceObjects {
     font_texture: Texture,
+    font_size: [u32; 2],
     res: Resources,
-    gl_ptr: *mut glow::Context,
+    cache: DrawListCache,
+    /// Set when the static buffers ran out of room
+    static_full: bool,
+    /// Created on first use
+    timer: Option<GpuTimer>,
+    /// Created by [`ImGuiGlow::enable_thumbnail_array`]
+    thumbnails: Option<ThumbnailArray>,
 }
 
-impl Drop for ImGuiGlow {
-    fn drop(&mut self) {
+impl DeviceObjects {
+    fn new(
+        gl: &Rc<glow::Context>,
+        fonts: imgui::FontAtlasRefMut,
+        font_sampling: FontSampling,
+        config: &GlowConfig,
+    ) -> Result<Self> {
+        let (mut font_texture, font_size) = ImGuiGlow::load_font_texture(gl, fonts)?;
+        font_texture.set_sampling(gl, font_sampling);
+
+        let mut res = Resources::new(gl, config.n_vertices, config.n_indices)?;
+        res.set_output(config.premultiplied_alpha, config.srgb_framebuffer);
+        res.set_texture(font_texture.id());
+
+        Ok(Self {
+            font_texture,
+            font_size,
+            res,
+            cache: DrawListCache::new(),
+            static_full: false,
+            timer: None,
+            thumbnails: None,
+        })
+    }
+
+    /// Binds buffers containing the draw list and returns the vertex and index offsets of it
+    unsafe fn upload_draw_list(
+        &mut self,
+        gl: &glow::Context,
+        vtx: &[imgui::DrawVert],
+        idx: &[imgui::DrawIdx],
+        caching: bool,
+    ) -> std::result::Result<(usize, usize), String> {
+        if caching {
+            let hash = DrawListCache::hash(vtx, idx);
+            if let Some(cached) = self.cache.lookup(hash, vtx.len(), idx.len()) {
+                self.res.bind_buffers(gl, BufferKind::Static);
+                return Ok((cached.vtx_base, cached.idx_base));
+            }
+
+            if self.cache.is_static(hash) && !self.static_full {
+                self.res.bind_buffers(gl, BufferKind::Static);
+                if let Some((vtx_base, idx_base)) = self
+                    .res
+                    .append_buffers(gl, BufferKind::Static, vtx, idx)
+                    .map_err(|e| e.to_string())?
+                {
+                    self.cache
+                        .insert(hash, vtx_base, idx_base, vtx.len(), idx.len());
+                    return Ok((vtx_base, idx_base));
+                }
+                self.static_full = true;
+            }
+        }
+
+        self.res.bind_buffers(gl, BufferKind::Stream);
+        self.res
+            .append_buffers(gl, BufferKind::Stream, vtx, idx)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "ImGuiGlow: vertex/index buffer overflow".to_string())
+    }
+
+    fn free(mut self, gl: &glow::Context) {
         unsafe {
-            let gl = &mut *self.gl_ptr;
             self.res.free(gl);
+            if let Some(timer) = self.timer.take() {
+                timer.free(gl);
+            }
+            if let Some(thumbnails) = self.thumbnails.take() {
+                thumbnails.free(gl);
+            }
         }
     }
 }
 
-impl ImGuiGlow {
-    pub fn new(imgui: &mut imgui::Context, gl: &glow::Context) -> Result<Self> {
-        imgui.set_renderer_name(Some(im_str!(
-            "imgui-glow-renderer {}",
-            env!("CARGO_PKG_VERSION")
-        )));
+/// Configuration of [`ImGuiGlow`], accepted by [`ImGuiGlow::with_config`]
+///
+/// ```no_run
+/// let config = GlowConfig::default()
+///     .buffer_capacity(4 * 16384, 6 * 16384)
+///     .texture_filter(FontSampling::Nearest)
+///     .srgb_framebuffer(true);
+/// let renderer = ImGuiGlow::with_config(&mut imgui, &gl, config)?;
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlowConfig {
+    /// Initial capacity of the vertex buffers. The stream buffer grows on demand
+    pub n_vertices: usize,
+    /// Initial capacity of the index buffers. The stream buffer grows on demand
+    pub n_indices: usize,
+    /// Initial sampling of the font atlas. See [`Renderer::set_font_sampling`]
+    pub font_sampling: FontSampling,
+    /// Filter of user textures
+    pub texture_filter: FontSampling,
+    /// Enables `GL_FRAMEBUFFER_SRGB` during the UI pass and outputs linear colors
+    pub srgb_framebuffer: bool,
+    /// User textures have premultiplied alpha (see [`crate::helper::premultiply_rgba8`]) and the
+    /// UI is blended with `ONE, ONE_MINUS_SRC_ALPHA`. The font atlas is always straight
+    pub premultiplied_alpha: bool,
+}
 
-        imgui
-            .io_mut()
-            .backend_flags
-            .insert(imgui::BackendFlags::RENDERER_HAS_VTX_OFFSET);
+/// Alias of [`GlowConfig`]
+pub type ImGuiGlowConfig = GlowConfig;
+
+impl Default for GlowConfig {
+    fn default() -> Self {
+        Self {
+            n_vertices: 4 * N_QUADS,
+            n_indices: 6 * N_QUADS,
+            font_sampling: FontSampling::default(),
+            texture_filter: FontSampling::Linear,
+            srgb_framebuffer: false,
+            premultiplied_alpha: false,
+        }
+    }
+}
 
-        let font_texture = Self::load_font_texture(gl, imgui.fonts())?;
+impl GlowConfig {
+    pub fn buffer_capacity(mut self, n_vertices: usize, n_indices: usize) -> Self {
+        self.n_vertices = n_vertices;
+        self.n_indices = n_indices;
+        self
+    }
 
-        let mut res = Resources::new(gl)?;
-        res.set_texture(font_texture.id());
+    pub fn font_sampling(mut self, sampling: FontSampling) -> Self {
+        self.font_sampling = sampling;
+        self
+    }
+
+    pub fn texture_filter(mut self, filter: FontSampling) -> Self {
+        self.texture_filter = filter;
+        self
+    }
+
+    pub fn srgb_framebuffer(mut self, srgb: bool) -> Self {
+        self.srgb_framebuffer = srgb;
+        self
+    }
+
+    pub fn premultiplied_alpha(mut self, premultiplied: bool) -> Self {
+        self.premultiplied_alpha = premultiplied;
+        self
+    }
+}
+
+pub struct ImGuiGlow {
+    config: GlowConfig,
+    textures: TextureRegistry<Texture>,
+    font_sampling: FontSampling,
+    opacity: f32,
+    color_mode: ColorMode,
+    flip_y: FlipY,
+    placement: UiPassPlacement,
+    /// Framebuffers bound on render by placement. The bound framebuffer is used if `None`
+    pass_targets: Option<UiPassTargets<Option<glow::Framebuffer>>>,
+    /// HDR value of UI white for [`UiPassPlacement::BeforePostFx`]
+    paper_white: f32,
+    text_blending: TextBlending,
+    /// `None` while the device objects are invalidated
+    objs: Option<DeviceObjects>,
+    reload_bus: Option<ReloadBus>,
+    /// Experimental chunked submission
+    budget: Option<SubmitBudget>,
+    budget_report: Option<BudgetReport>,
+    cache_draw_lists: bool,
+    gpu_timing: bool,
+    /// Disable the scissor test for draws covering the framebuffer
+    skip_full_scissor: bool,
+    /// Vertex and index offsets of the current draw list in the bound buffers
+    list_base: (usize, usize),
+    /// Index buffer of the current draw list. The first draw call of a list can be culled, so new
+    /// lists are detected by their buffers rather than `idx_offset == 0`
+    list_idx: *const imgui::DrawIdx,
+    /// Per-vertex thumbnail layers of each draw list (keyed by its vertex buffer) while batching
+    /// thumbnails
+    list_layers: Vec<(*const imgui::DrawVert, Vec<f32>)>,
+    /// Incremented every time the font texture is created
+    font_generation: u64,
+    device: GlDevice,
+    /// Context given to [`ImGuiGlow::new`] or the last [`Renderer::create_device_objects`], kept
+    /// alive to free GPU objects on drop
+    gl: Rc<glow::Context>,
+}
+
+/// Frees the GPU objects left. Prefer [`crate::Backend::shutdown`] (or
+/// [`Renderer::invalidate_device_objects`]) while the GL context is current
+impl Drop for ImGuiGlow {
+    fn drop(&mut self) {
+        if let Some(objs) = self.objs.take() {
+            objs.free(&self.gl);
+        }
+    }
+}
+
+impl ImGuiGlow {
+    /// The renderer shares the context with its textures so that they're freed on drop
+    pub fn new(imgui: &mut imgui::Context, gl: &Rc<glow::Context>) -> Result<Self> {
+        Self::with_config(imgui, gl, GlowConfig::default())
+    }
+
+    pub fn with_config(
+        imgui: &mut imgui::Context,
+        gl: &Rc<glow::Context>,
+        config: GlowConfig,
+    ) -> Result<Self> {
+        let (version, driver) = unsafe {
+            (
+                gl.get_parameter_string(glow::VERSION),
+                gl.get_parameter_string(glow::RENDERER),
+            )
+        };
+        imgui.set_renderer_name(Some(crate::backend_name(
+            "imgui-glow-renderer",
+            &[&format!("OpenGL {}", version), &driver],
+        )));
+
+        let objs = DeviceObjects::new(gl, imgui.fonts(), config.font_sampling, &config)?;
 
         Ok(Self {
-            textures: imgui::Textures::new(),
-            font_texture,
-            res,
-            gl_ptr: gl as *const _ as *mut _,
+            font_sampling: config.font_sampling,
+            config,
+            textures: TextureRegistry::new(),
+            opacity: 1.0,
+            color_mode: ColorMode::default(),
+            flip_y: FlipY::default(),
+            placement: UiPassPlacement::default(),
+            pass_targets: None,
+            paper_white: 1.0,
+            text_blending: TextBlending::default(),
+            objs: Some(objs),
+            reload_bus: None,
+            budget: None,
+            budget_report: None,
+            cache_draw_lists: false,
+            gpu_timing: false,
+            skip_full_scissor: false,
+            list_base: (0, 0),
+            list_idx: std::ptr::null(),
+            list_layers: Vec::new(),
+            font_generation: 1,
+            device: GlDevice::new(),
+            gl: Rc::clone(gl),
         })
     }
 
     /// Create font texture with ID `FONT_TEXTURE_ID`
-    fn load_font_texture(gl: &glow::Context, mut fonts: imgui::FontAtlasRefMut) -> Result<Texture> {
-        let tex = {
+    fn load_font_texture(
+        gl: &Rc<glow::Context>,
+        mut fonts: imgui::FontAtlasRefMut,
+    ) -> Result<(Texture, [u32; 2])> {
+        let (tex, size) = {
             let atlas_texture = fonts.build_rgba32_texture();
             let (pixels, w, h) = (
                 atlas_texture.data,
                 atlas_texture.width,
                 atlas_texture.height,
             );
-            Texture::new(gl, pixels, w, h)?
+            (Texture::new(gl, pixels, w, h)?, [w, h])
         };
+        unsafe {
+            res::set_object_label(gl, glow::TEXTURE, tex.id(), "imgui-glow font atlas");
+        }
 
         // NOTE: we have to set the ID *AFTER* creating the font atlas texture
         fonts.tex_id = imgui::TextureId::from(FONT_TEXTUER_ID);
 
+        Ok((tex, size))
+    }
+
+    /// User texture sampled with [`GlowConfig::texture_filter`]
+    fn new_user_texture(
+        gl: &Rc<glow::Context>,
+        filter: FontSampling,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> Result<Texture> {
+        let mut tex = Texture::new(gl, pixels, w, h)?;
+        tex.set_sampling(gl, filter);
         Ok(tex)
     }
 
+    /// Uploads an RGBA8 texture. If `retain` is true, a CPU copy of the pixels is kept so that the
+    /// texture can be restored after device loss
+    pub fn register_texture(
+        &mut self,
+        gl: &Rc<glow::Context>,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> Result<imgui::TextureId> {
+        let tex = Self::new_user_texture(gl, self.config.texture_filter, pixels, w, h)?;
+        let source = if retain {
+            Some(TextureSource::Pixels(pixels.to_vec()))
+        } else {
+            None
+        };
+        Ok(self.textures.insert(tex, w, h, source))
+    }
+
+    /// Names a user texture for debug UIs and, with `KHR_debug`, for graphics debuggers
+    pub fn set_texture_label(&mut self, gl: &glow::Context, id: imgui::TextureId, label: &str) {
+        self.textures.set_label(id, label);
+        if let Some(tex) = self.textures.get(id) {
+            unsafe {
+                res::set_object_label(gl, glow::TEXTURE, tex.id(), label);
+            }
+        }
+    }
+
+    /// Labels GL textures again after they're re-created from their sources
+    fn relabel_textures(&self, gl: &glow::Context) {
+        for info in self.textures.textures_iter() {
+            if let (Some(label), Some(tex)) = (info.label, self.textures.get(info.id)) {
+                unsafe {
+                    res::set_object_label(gl, glow::TEXTURE, tex.id(), label);
+                }
+            }
+        }
+    }
+
+    /// Experimental: flushes the GL command stream every `chunk_draw_calls` draw calls so that huge
+    /// UIs are submitted in multiple parts, and measures the submission time
+    pub fn set_submit_budget(&mut self, budget: Option<SubmitBudget>) {
+        self.budget = budget;
+    }
+
+    /// Measurements of the last render if the submit budget is set
+    pub fn budget_report(&self) -> Option<&BudgetReport> {
+        self.budget_report.as_ref()
+    }
+
+    /// Draws whose clip rectangle covers the whole framebuffer (e.g. backgrounds of fullscreen
+    /// windows) disable the scissor test instead of setting a scissor, so that runs of them change
+    /// no scissor state. Off by default. See [`Self::state_counters`]
+    pub fn set_skip_full_scissor(&mut self, skip: bool) {
+        self.skip_full_scissor = skip;
+    }
+
+    /// Scissor and texture changes made and skipped in the last frame
+    pub fn state_counters(&self) -> StateCounters {
+        self.device.last_counters()
+    }
+
+    /// Keeps draw lists (roughly windows) unchanged since the last frame resident in static GPU
+    /// buffers and skips re-uploading them
+    pub fn set_draw_list_caching(&mut self, enable: bool) {
+        self.cache_draw_lists = enable;
+        if let Some(objs) = self.objs.as_mut() {
+            objs.cache.clear();
+            objs.res.clear_static();
+            objs.static_full = false;
+        }
+    }
+
+    /// Measures GPU time of the UI pass with timer queries (requires GL 3.3 or
+    /// `GL_ARB_timer_query`)
+    pub fn set_gpu_timing(&mut self, enable: bool) {
+        self.gpu_timing = enable;
+    }
+
+    /// GPU time of the UI pass. Results arrive a few frames late
+    pub fn gpu_time(&self) -> Option<std::time::Duration> {
+        if !self.gpu_timing {
+            return None;
+        }
+        self.objs.as_ref()?.timer.as_ref()?.last()
+    }
+
+    /// Grayscale or color blindness simulation/compensation of the whole UI pass
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.color_mode = mode;
+    }
+
+    /// Flips the UI vertically, e.g. for engines with bottom-left origin UI space
+    pub fn set_flip_y(&mut self, flip_y: FlipY) {
+        self.flip_y = flip_y;
+    }
+
+    /// Renders the UI into the HDR target or the final target of `targets` (`None` is the default
+    /// framebuffer). Without targets, the UI goes to the framebuffer bound on render and the
+    /// placement only scales colors. The framebuffer is left bound after render
+    pub fn set_pass_placement(
+        &mut self,
+        placement: UiPassPlacement,
+        targets: Option<UiPassTargets<Option<glow::Framebuffer>>>,
+    ) {
+        self.placement = placement;
+        self.pass_targets = targets;
+    }
+
+    /// Sets the HDR value that tone maps to UI white, used while the UI is rendered before
+    /// post-processing. Defaults to `1.0`
+    pub fn set_hdr_paper_white(&mut self, paper_white: f32) {
+        self.paper_white = paper_white;
+    }
+
+    /// Blending of the font atlas, e.g. for readable small text. `PerChannel` uses dual-source
+    /// blending where supported
+    pub fn set_text_blending(&mut self, blending: TextBlending) {
+        self.text_blending = blending;
+    }
+
+    /// `text_blending` downgraded to what the device supports
+    fn text_blending(&self) -> TextBlending {
+        let dual_source = self
+            .objs
+            .as_ref()
+            .map_or(false, |objs| objs.res.supports_dual_source());
+        self.text_blending.supported(dual_source)
+    }
+
+    /// Textures announced on the bus are re-created at the beginning of the next render
+    pub fn set_reload_bus(&mut self, bus: Option<ReloadBus>) {
+        self.reload_bus = bus;
+    }
+
+    fn handle_reloads(&mut self, gl: &Rc<glow::Context>) -> std::result::Result<(), String> {
+        let filter = self.config.texture_filter;
+        if let Some(bus) = self.reload_bus.as_ref() {
+            let rest = bus
+                .reload_textures(&mut self.textures, |pixels, w, h| {
+                    Self::new_user_texture(gl, filter, pixels, w, h)
+                })
+                .map_err(|e| e.to_string())?;
+            for ev in rest {
+                log::debug!("ImGuiGlow: unhandled reload event {:?}", ev);
+            }
+            self.relabel_textures(gl);
+        }
+        Ok(())
+    }
+
+    /// Creates a texture array of `n_layers` thumbnails of `size` pixels, replacing the old one.
+    /// Commands drawing thumbnails are then merged into a few draw calls
+    ///
+    /// Draw list caching is bypassed while it's enabled. Thumbnails are not restored after
+    /// [`Renderer::invalidate_device_objects`].
+    pub fn enable_thumbnail_array(
+        &mut self,
+        gl: &glow::Context,
+        size: [u32; 2],
+        n_layers: u32,
+    ) -> Result<()> {
+        let array = unsafe { ThumbnailArray::new(gl, size, n_layers)? };
+        let objs = self
+            .objs
+            .as_mut()
+            .ok_or_else(|| anyhow!("ImGuiGlow: device objects are invalidated"))?;
+        if let Some(old) = objs.thumbnails.replace(array) {
+            unsafe {
+                old.free(gl);
+            }
+        }
+        Ok(())
+    }
+
+    /// Uploads RGBA8 pixels of the thumbnail size into a free layer of the array
+    pub fn register_thumbnail(
+        &mut self,
+        gl: &glow::Context,
+        pixels: &[u8],
+    ) -> Result<imgui::TextureId> {
+        let array = self
+            .objs
+            .as_mut()
+            .and_then(|objs| objs.thumbnails.as_mut())
+            .ok_or_else(|| anyhow!("ImGuiGlow: thumbnail array is not enabled"))?;
+        unsafe { array.insert(gl, pixels) }
+    }
+
+    /// Frees the layer of the thumbnail. Returns false if it's not registered
+    pub fn unregister_thumbnail(&mut self, id: imgui::TextureId) -> bool {
+        self.objs
+            .as_mut()
+            .and_then(|objs| objs.thumbnails.as_mut())
+            .map_or(false, |array| array.remove(id))
+    }
+
+    fn is_batching_thumbnails(&self) -> bool {
+        self.objs
+            .as_ref()
+            .map_or(false, |objs| objs.thumbnails.is_some())
+    }
+
+    /// Problems found in the last frame: GL errors and, in debug builds, invalid draw states
+    pub fn gl_diagnostics(&self) -> &[String] {
+        self.device.last_frame()
+    }
+
+    pub fn textures(&self) -> &TextureRegistry<Texture> {
+        &self.textures
+    }
+
+    pub fn textures_mut(&mut self) -> &mut TextureRegistry<Texture> {
+        &mut self.textures
+    }
+
     fn lookup_texture(&self, tex_id: imgui::TextureId) -> Option<&Texture> {
         if tex_id.id() == FONT_TEXTUER_ID {
             // we didn't store the font texture in `textures`
-            Some(&self.font_texture)
+            self.objs.as_ref().map(|objs| &objs.font_texture)
         } else if let Some(texture) = self.textures.get(tex_id) {
             Some(texture)
         } else {
@@ -94,35 +573,321 @@ impl ImGuiGlow {
     }
 }
 
+impl RegisterTexture for ImGuiGlow {
+    fn register_rgba8(
+        &mut self,
+        gl: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> std::result::Result<imgui::TextureId, Self::Error> {
+        self.register_texture(gl, pixels, w, h, retain)
+            .map_err(|e| BackendError::Other(e.to_string()))
+    }
+
+    fn remove_textures<I>(&mut self, ids: I) -> usize
+    where
+        I: IntoIterator<Item = imgui::TextureId>,
+    {
+        self.textures.remove_many(ids)
+    }
+
+    fn set_texture_label(&mut self, gl: &mut Self::Device, id: imgui::TextureId, label: &str) {
+        ImGuiGlow::set_texture_label(self, gl, id, label);
+    }
+
+    fn texture_infos(&self) -> Vec<TextureInfo<'_>> {
+        self.textures.textures_iter().collect()
+    }
+}
+
 impl Renderer for ImGuiGlow {
-    type Device = glow::Context;
-    type Error = String;
+    type Device = Rc<glow::Context>;
+    type Error = BackendError;
+    const HAS_VTX_OFFSET: bool = true;
+    const MAX_INDEX_SIZE: usize = 4;
+
+    fn font_atlas_info(&self) -> Option<crate::FontAtlasInfo> {
+        self.objs.as_ref().map(|objs| crate::FontAtlasInfo {
+            tex_id: imgui::TextureId::from(FONT_TEXTUER_ID),
+            size: objs.font_size,
+            generation: self.font_generation,
+        })
+    }
+
+    fn max_texture_size(&self, gl: &Self::Device) -> Option<u32> {
+        let size = unsafe { gl.get_parameter_i32(glow::MAX_TEXTURE_SIZE) };
+        Some(size as u32)
+    }
+
     fn render(
         &mut self,
         draw_data: &imgui::DrawData,
         gl: &mut Self::Device,
     ) -> std::result::Result<(), Self::Error> {
-        self.before_render(gl);
-        for params in DrawParamsIterator::new(draw_data) {
-            self.draw(gl, &params)?;
+        if self.objs.is_none() {
+            return Err(BackendError::Other(
+                "ImGuiGlow: device objects are invalidated".to_string(),
+            ));
+        }
+
+        // don't draw with dangling names
+        if unsafe { self.device.poll_errors(gl) } {
+            self.on_context_lost();
+            return Err(BackendError::DeviceLost);
+        }
+
+        self.textures.flush_removed();
+        self.handle_reloads(gl)?;
+
+        let mut tracker = self.budget.map(BudgetTracker::begin);
+
+        let batching = self.is_batching_thumbnails();
+        if batching {
+            self.list_layers = array::list_layers(draw_data);
+        }
+
+        self.before_render(gl)?;
+        self.list_idx = std::ptr::null();
+        let mut result = Ok(());
+        let items: Box<dyn Iterator<Item = DrawItem>> = if batching {
+            Box::new(array::MergeThumbnails::new(DrawParamsIterator::new(
+                draw_data,
+            )))
+        } else {
+            Box::new(DrawParamsIterator::new(draw_data))
+        };
+        for item in items {
+            let params = match item {
+                DrawItem::Draw(params) => params,
+                DrawItem::ResetState => {
+                    self.reset_render_state(gl);
+                    continue;
+                }
+                DrawItem::Callback(callback) => {
+                    let res = self.run_callback(gl, &callback);
+                    // the callback may bind textures or set the scissor
+                    self.device.invalidate_state();
+                    if let Err(err) = res {
+                        result = Err(err);
+                        break;
+                    }
+                    continue;
+                }
+            };
+            if let Err(err) = self.draw(gl, &params) {
+                // still restore GL states and close the timer query
+                result = Err(err);
+                break;
+            }
+            if let Some(tracker) = tracker.as_mut() {
+                if tracker.on_draw() {
+                    unsafe {
+                        gl.flush();
+                    }
+                }
+            }
+        }
+        let lost = self.after_render(gl);
+        self.list_layers.clear();
+
+        self.budget_report = tracker.map(BudgetTracker::finish);
+        if lost {
+            self.on_context_lost();
+            return Err(BackendError::DeviceLost);
+        }
+        result
+    }
+
+    fn set_font_sampling(&mut self, sampling: FontSampling) {
+        self.font_sampling = sampling;
+    }
+
+    fn set_ui_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    fn invalidate_device_objects(&mut self, gl: &mut Self::Device) {
+        // user textures are deleted while the context is still alive
+        self.textures.invalidate();
+        if let Some(objs) = self.objs.take() {
+            objs.free(gl);
         }
-        self.after_render(gl);
+    }
+
+    fn create_device_objects(
+        &mut self,
+        imgui: &mut imgui::Context,
+        gl: &mut Self::Device,
+    ) -> std::result::Result<(), Self::Error> {
+        if let Some(objs) = self.objs.take() {
+            objs.free(gl);
+        }
+
+        // the context may have been recreated after device loss
+        self.gl = Rc::clone(gl);
+
+        let objs = DeviceObjects::new(gl, imgui.fonts(), self.font_sampling, &self.config)
+            .map_err(|e| e.to_string())?;
+        self.objs = Some(objs);
+        self.font_generation += 1;
+
+        let filter = self.config.texture_filter;
+        self.textures
+            .restore(|pixels, w, h| Self::new_user_texture(gl, filter, pixels, w, h))
+            .map_err(|e| BackendError::Other(e.to_string()))?;
+        self.relabel_textures(gl);
         Ok(())
     }
+
+    fn replace_font_atlas(
+        &mut self,
+        gl: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> std::result::Result<bool, Self::Error> {
+        let objs = match self.objs.as_mut() {
+            Some(objs) => objs,
+            None => return Ok(false),
+        };
+
+        // the sampling is applied on the next render
+        objs.font_texture =
+            Texture::new(gl, pixels, w, h).map_err(|e| BackendError::Other(e.to_string()))?;
+        unsafe {
+            res::set_object_label(
+                gl,
+                glow::TEXTURE,
+                objs.font_texture.id(),
+                "imgui-glow font atlas",
+            );
+        }
+        objs.font_size = [w, h];
+        self.font_generation += 1;
+        Ok(true)
+    }
 }
 
 impl ImGuiGlow {
-    fn before_render(&mut self, gl: &mut <Self as Renderer>::Device) {
+    /// Forgets GL names of the lost context without deleting them
+    fn on_context_lost(&mut self) {
+        log::warn!("ImGuiGlow: GL context lost");
+        // `DeviceObjects` doesn't delete on drop. Textures call `glDeleteTextures` on the lost
+        // context, which is a no-op
+        self.objs = None;
+        self.textures.invalidate();
+        self.list_layers.clear();
+    }
+
+    /// Panics if the device objects are invalidated. It's checked on `render`
+    fn objs_mut(&mut self) -> &mut DeviceObjects {
+        self.objs
+            .as_mut()
+            .expect("ImGuiGlow: device objects are invalidated")
+    }
+
+    fn before_render(
+        &mut self,
+        gl: &mut <Self as Renderer>::Device,
+    ) -> std::result::Result<(), <Self as Renderer>::Error> {
+        let font_sampling = self.font_sampling;
+        let gpu_timing = self.gpu_timing;
+        let mut color_matrix = self.color_mode.matrix4_gl();
+        let scale = self.placement.color_scale(self.paper_white);
+        color_matrix[..11].iter_mut().for_each(|x| *x *= scale);
+        let text_blending = self.text_blending();
+        let objs = self.objs_mut();
+        objs.font_texture.set_sampling(gl, font_sampling);
+
+        unsafe {
+            if gpu_timing {
+                if objs.timer.is_none() {
+                    objs.timer = Some(GpuTimer::new(gl)?);
+                }
+                objs.timer.as_mut().unwrap().begin(gl);
+            }
+
+            let dual_source = matches!(text_blending, TextBlending::PerChannel { .. });
+            objs.res.set_dual_source(gl, dual_source);
+        }
+
+        self.bind_render_state(gl);
+
+        let objs = self.objs_mut();
         unsafe {
-            self.res.bind(gl);
+            objs.res.set_text_blending(gl, text_blending);
+            objs.res.set_output_uniforms(gl);
+            objs.res.set_color_matrix(gl, color_matrix);
             // reset offsets
-            self.res.reset_buf_offsets();
+            objs.res.reset_buf_offsets();
         }
+
+        Ok(())
     }
 
-    fn after_render(&mut self, gl: &mut <Self as Renderer>::Device) {
+    /// Binds the target framebuffer, the pipeline and the blend/scissor states
+    fn bind_render_state(&mut self, gl: &mut <Self as Renderer>::Device) {
+        let target = self
+            .pass_targets
+            .as_ref()
+            .map(|targets| *targets.select(self.placement));
+        self.device.invalidate_state();
+        let objs = self.objs_mut();
+
         unsafe {
-            Resources::unbind(gl);
+            if let Some(fb) = target {
+                gl.bind_framebuffer(glow::FRAMEBUFFER, fb);
+            }
+            objs.res.bind(gl);
+            if let Some(thumbnails) = objs.thumbnails.as_ref() {
+                gl.active_texture(glow::TEXTURE1);
+                gl.bind_texture(glow::TEXTURE_2D_ARRAY, Some(thumbnails.id()));
+                gl.active_texture(glow::TEXTURE0);
+            }
+        }
+    }
+
+    /// Handles [`DrawItem::ResetState`]. Uniforms are kept by the program, so only the bindings
+    /// and the fixed-function states are set again
+    fn reset_render_state(&mut self, gl: &mut <Self as Renderer>::Device) {
+        self.bind_render_state(gl);
+        unsafe {
+            // the buffers of the current draw list
+            self.objs_mut().res.rebind_buffers(gl);
+        }
+    }
+
+    /// Returns true if the context is lost
+    fn after_render(&mut self, gl: &mut <Self as Renderer>::Device) -> bool {
+        if self.gpu_timing {
+            if let Some(timer) = self.objs_mut().timer.as_mut() {
+                unsafe {
+                    timer.end(gl);
+                }
+            }
+        }
+
+        if self.cache_draw_lists {
+            let objs = self.objs_mut();
+            // rebuild the static buffers from scratch rather than defragmenting them
+            if objs.cache.end_frame() || objs.static_full {
+                objs.cache.clear();
+                objs.res.clear_static();
+                objs.static_full = false;
+            }
+        }
+
+        unsafe {
+            if self.is_batching_thumbnails() {
+                gl.active_texture(glow::TEXTURE1);
+                gl.bind_texture(glow::TEXTURE_2D_ARRAY, None);
+                gl.active_texture(glow::TEXTURE0);
+            }
+            self.objs_mut().res.unbind(gl);
+            self.device.end_pass(gl);
+            self.device.end_frame(gl, "ImGuiGlow")
         }
     }
 
@@ -139,52 +904,69 @@ impl ImGuiGlow {
         );
 
         // set states on new set of draw call
-        if params.idx_offset == 0 {
-            // 1. append buffers
-            self.res.append_vbuf(gl, params.vtx_buffer);
-            self.res.append_ibuf(gl, params.idx_buffer);
+        if params.idx_buffer.as_ptr() != self.list_idx {
+            self.list_idx = params.idx_buffer.as_ptr();
+            // 1. append buffers (or reuse cached ones)
+            let batching = !self.list_layers.is_empty();
+            let caching = self.cache_draw_lists && !batching;
+            self.list_base = unsafe {
+                self.objs_mut().upload_draw_list(
+                    gl,
+                    params.vtx_buffer,
+                    params.idx_buffer,
+                    caching,
+                )?
+            };
+
+            // thumbnail layers parallel to the vertices
+            let vtx_ptr = params.vtx_buffer.as_ptr();
+            if let Some((_, layers)) = self.list_layers.iter().find(|(p, _)| *p == vtx_ptr) {
+                let objs = self.objs.as_mut().unwrap();
+                let uploaded = unsafe { objs.res.upload_layers(gl, self.list_base.0, layers) };
+                if !uploaded {
+                    return Err("ImGuiGlow: thumbnail layers don't fit the vertex buffer"
+                        .to_string()
+                        .into());
+                }
+            }
 
             // 2. set orthographic projection matrix
-            let mat = crate::helper::ortho_mat_gl(
-                // left, right
-                params.display.left(),
-                params.display.right(),
-                // bottom, top.
-                // Since we want to flip the y axis so that it goes down, we'll swap top and bottom
-                params.display.top(),
-                params.display.bottom(),
-                // near, far
-                0.0,
-                1.0,
-            );
+            let mat = self.flip_y.projection(&params.display);
 
+            let opacity = self.opacity;
             unsafe {
-                self.res.set_uniforms(gl, mat);
+                self.objs_mut().res.set_uniforms(gl, mat, opacity);
             }
         }
 
         unsafe {
             // 1. scissor
-            gl.scissor(
-                params.scissor.left() as i32,
-                params.scissor.top() as i32,
-                params.scissor.right() as i32,
-                params.scissor.bottom() as i32,
-            );
+            // GL scissor origin is bottom-left
+            let rect = self.flip_y.scissor(params, true);
+            let full = self.skip_full_scissor && params.covers_framebuffer();
+            self.device.scissor(gl, rect, full);
 
             // 2. set texture
             let tex = self
                 .lookup_texture(params.tex_id)
                 .ok_or_else(|| format!("Bad texture id: {:?}", params.tex_id))?;
             let tex_id = tex.id();
-            self.res.set_texture(tex_id);
+            let is_font = params.tex_id.id() == FONT_TEXTUER_ID;
+            let text_blending = self.text_blending != TextBlending::Standard;
+            let objs = self.objs_mut();
+            objs.res.set_texture(tex_id);
+            if objs.res.tracks_font_atlas(text_blending) {
+                objs.res.set_font_atlas(gl, is_font);
+            }
 
             // 3. draw
-            self.res.draw(
+            let (vtx_base, idx_base) = self.list_base;
+            self.device.draw_indexed(
                 gl,
+                tex_id,
                 params.n_elems as i32,
-                params.idx_offset as i32,
-                params.vtx_offset as i32,
+                (idx_base + params.idx_offset) as i32,
+                (vtx_base + params.vtx_offset) as i32,
             );
         }
 
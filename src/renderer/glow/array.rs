@@ -0,0 +1,223 @@
+//! Texture array of equally sized thumbnails (`GL_TEXTURE_2D_ARRAY`)
+//!
+//! Draw commands sampling layers of the array don't switch textures, so consecutive commands are
+//! merged into one draw call. The layer is passed with a per-vertex attribute.
+
+use {anyhow::*, glow::HasContext};
+
+use crate::helper::DrawItem;
+
+/// Maximum number of layers addressable with texture IDs
+pub const MAX_LAYERS: usize = 1 << 16;
+
+/// Texture IDs of array layers are `ARRAY_ID_BASE + layer`, right below the font texture ID
+pub const ARRAY_ID_BASE: usize = super::FONT_TEXTUER_ID - 1 - MAX_LAYERS;
+
+/// Layer of the texture ID if it's in the array ID range
+pub fn layer_of(id: imgui::TextureId) -> Option<u32> {
+    let id = id.id();
+    if id >= ARRAY_ID_BASE && id < ARRAY_ID_BASE + MAX_LAYERS {
+        Some((id - ARRAY_ID_BASE) as u32)
+    } else {
+        None
+    }
+}
+
+pub struct ThumbnailArray {
+    tex: glow::Texture,
+    size: [u32; 2],
+    n_layers: u32,
+    used: Vec<bool>,
+}
+
+impl ThumbnailArray {
+    pub unsafe fn new(gl: &glow::Context, size: [u32; 2], n_layers: u32) -> Result<Self> {
+        ensure!(
+            n_layers as usize <= MAX_LAYERS,
+            "ImGuiGlow: too many thumbnail layers: {}",
+            n_layers
+        );
+
+        let tex = gl.create_texture().map_err(Error::msg)?;
+        gl.bind_texture(glow::TEXTURE_2D_ARRAY, Some(tex));
+        for (param, value) in [
+            (glow::TEXTURE_WRAP_S, glow::CLAMP_TO_EDGE),
+            (glow::TEXTURE_WRAP_T, glow::CLAMP_TO_EDGE),
+            (glow::TEXTURE_MIN_FILTER, glow::LINEAR),
+            (glow::TEXTURE_MAG_FILTER, glow::LINEAR),
+        ]
+        .iter()
+        {
+            gl.tex_parameter_i32(glow::TEXTURE_2D_ARRAY, *param, *value as i32);
+        }
+        gl.tex_image_3d(
+            glow::TEXTURE_2D_ARRAY,
+            0,                  // level
+            glow::RGBA8 as i32, // internal format
+            size[0] as i32,
+            size[1] as i32,
+            n_layers as i32,
+            0,          // border
+            glow::RGBA, // format
+            glow::UNSIGNED_BYTE,
+            None,
+        );
+        gl.bind_texture(glow::TEXTURE_2D_ARRAY, None);
+
+        Ok(Self {
+            tex,
+            size,
+            n_layers,
+            used: vec![false; n_layers as usize],
+        })
+    }
+
+    pub unsafe fn free(&self, gl: &glow::Context) {
+        gl.delete_texture(self.tex);
+    }
+
+    pub fn id(&self) -> glow::Texture {
+        self.tex
+    }
+
+    /// Size of each layer in pixels
+    pub fn size(&self) -> [u32; 2] {
+        self.size
+    }
+
+    pub fn n_free(&self) -> usize {
+        self.used.iter().filter(|u| !**u).count()
+    }
+
+    /// Uploads RGBA8 pixels of [`Self::size`] into a free layer
+    pub unsafe fn insert(&mut self, gl: &glow::Context, pixels: &[u8]) -> Result<imgui::TextureId> {
+        let [w, h] = self.size;
+        ensure!(
+            pixels.len() == (w as usize) * (h as usize) * 4,
+            "ImGuiGlow: thumbnail must be {}x{} RGBA8",
+            w,
+            h
+        );
+
+        let layer = self
+            .used
+            .iter()
+            .position(|u| !*u)
+            .ok_or_else(|| anyhow!("ImGuiGlow: thumbnail array is full ({})", self.n_layers))?;
+
+        gl.bind_texture(glow::TEXTURE_2D_ARRAY, Some(self.tex));
+        gl.tex_sub_image_3d(
+            glow::TEXTURE_2D_ARRAY,
+            0,
+            0,
+            0,
+            layer as i32,
+            w as i32,
+            h as i32,
+            1,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            glow::PixelUnpackData::Slice(pixels),
+        );
+        gl.bind_texture(glow::TEXTURE_2D_ARRAY, None);
+
+        self.used[layer] = true;
+        Ok(imgui::TextureId::from(ARRAY_ID_BASE + layer))
+    }
+
+    /// Frees the layer. Returns false if it's not in use
+    pub fn remove(&mut self, id: imgui::TextureId) -> bool {
+        match self::layer_of(id).and_then(|l| self.used.get_mut(l as usize)) {
+            Some(used) if *used => {
+                *used = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn contains(&self, id: imgui::TextureId) -> bool {
+        self::layer_of(id)
+            .and_then(|l| self.used.get(l as usize).cloned())
+            .unwrap_or(false)
+    }
+}
+
+/// Per-vertex layers (layer + 1, `0` for other textures) of each draw list, keyed by the vertex
+/// buffer
+pub fn list_layers(draw_data: &imgui::DrawData) -> Vec<(*const imgui::DrawVert, Vec<f32>)> {
+    draw_data
+        .draw_lists()
+        .map(|list| {
+            let (vtx, idx) = (list.vtx_buffer(), list.idx_buffer());
+            let mut layers = vec![0.0; vtx.len()];
+
+            for cmd in list.commands() {
+                if let imgui::DrawCmd::Elements { count, cmd_params } = cmd {
+                    let layer = match self::layer_of(cmd_params.texture_id) {
+                        Some(layer) => layer,
+                        None => continue,
+                    };
+                    let span = cmd_params.idx_offset..cmd_params.idx_offset + count;
+                    for i in &idx[span] {
+                        layers[cmd_params.vtx_offset + *i as usize] = (layer + 1) as f32;
+                    }
+                }
+            }
+
+            (vtx.as_ptr(), layers)
+        })
+        .collect()
+}
+
+/// Merges consecutive draw commands of a draw list that share the clip rectangle and sample
+/// thumbnails and at most one other texture. Other items are passed through and are never merged
+/// across
+pub struct MergeThumbnails<'a, I: Iterator<Item = DrawItem<'a>>> {
+    inner: std::iter::Peekable<I>,
+}
+
+impl<'a, I: Iterator<Item = DrawItem<'a>>> MergeThumbnails<'a, I> {
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner: inner.peekable(),
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = DrawItem<'a>>> Iterator for MergeThumbnails<'a, I> {
+    type Item = DrawItem<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut merged = match self.inner.next()? {
+            DrawItem::Draw(params) => params,
+            item => return Some(item),
+        };
+        let mut regular = match self::layer_of(merged.tex_id) {
+            Some(_) => None,
+            None => Some(merged.tex_id),
+        };
+
+        while let Some(DrawItem::Draw(next)) = self.inner.peek() {
+            let is_thumbnail = self::layer_of(next.tex_id).is_some();
+            let can_merge = std::ptr::eq(merged.idx_buffer.as_ptr(), next.idx_buffer.as_ptr())
+                && merged.vtx_offset == next.vtx_offset
+                && merged.idx_offset + merged.n_elems == next.idx_offset
+                && merged.clip_rect == next.clip_rect
+                && (is_thumbnail || regular.map_or(true, |id| id == next.tex_id));
+            if !can_merge {
+                break;
+            }
+
+            if !is_thumbnail {
+                regular = Some(next.tex_id);
+            }
+            merged.n_elems += next.n_elems;
+            self.inner.next();
+        }
+
+        // thumbnails don't care about the bound 2D texture
+        merged.tex_id = regular.unwrap_or(imgui::TextureId::from(super::FONT_TEXTUER_ID));
+        Some(DrawItem::Draw(merged))
+    }
+}
@@ -0,0 +1,132 @@
+//! Static draw list caching
+//!
+//! Draw lists that didn't change since the last frame are moved into static GPU buffers and are
+//! not uploaded again while they stay the same. Draw lists are keyed by the hash of their data, and
+//! the vertex and index counts are compared on lookup so that a hash collision is not drawn with
+//! another list's data.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::Hasher,
+};
+
+use crate::helper::as_bytes;
+
+/// Frames a cached draw list can stay unused before the static buffers are rebuilt
+const MAX_UNUSED_FRAMES: u64 = 60;
+
+/// Element offsets of a draw list in the static buffers
+#[derive(Debug, Clone, Copy)]
+pub struct CachedList {
+    pub vtx_base: usize,
+    pub idx_base: usize,
+    pub n_vertices: usize,
+    pub n_indices: usize,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct DrawListCache {
+    frame: u64,
+    entries: HashMap<u64, CachedList>,
+    /// Hashes of the draw lists seen in the last frame
+    prev_frame: HashSet<u64>,
+    this_frame: HashSet<u64>,
+}
+
+impl DrawListCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hashes the vertex and index data of a draw list
+    pub fn hash(vtx: &[imgui::DrawVert], idx: &[imgui::DrawIdx]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(as_bytes(vtx));
+        hasher.write(as_bytes(idx));
+        hasher.finish()
+    }
+
+    /// Returns the cached offsets, marking the draw list as used in this frame. Entries of other
+    /// sizes are hash collisions and are not returned
+    pub fn lookup(&mut self, hash: u64, n_vertices: usize, n_indices: usize) -> Option<CachedList> {
+        self.this_frame.insert(hash);
+        let frame = self.frame;
+        let entry = self.entries.get_mut(&hash)?;
+        if entry.n_vertices != n_vertices || entry.n_indices != n_indices {
+            log::debug!("ImGuiGlow: draw list hash collision");
+            return None;
+        }
+        entry.last_used = frame;
+        Some(*entry)
+    }
+
+    /// If the draw list was unchanged since the last frame
+    pub fn is_static(&self, hash: u64) -> bool {
+        self.prev_frame.contains(&hash)
+    }
+
+    /// Caches the offsets of a draw list of `n_vertices` and `n_indices`, replacing any entry of
+    /// the hash
+    pub fn insert(
+        &mut self,
+        hash: u64,
+        vtx_base: usize,
+        idx_base: usize,
+        n_vertices: usize,
+        n_indices: usize,
+    ) {
+        self.entries.insert(
+            hash,
+            CachedList {
+                vtx_base,
+                idx_base,
+                n_vertices,
+                n_indices,
+                last_used: self.frame,
+            },
+        );
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns true if the static buffers should be rebuilt because of stale entries
+    pub fn end_frame(&mut self) -> bool {
+        self.prev_frame = std::mem::take(&mut self.this_frame);
+        self.frame += 1;
+
+        let frame = self.frame;
+        let n_stale = self
+            .entries
+            .values()
+            .filter(|e| frame - e.last_used > MAX_UNUSED_FRAMES)
+            .count();
+        n_stale > 0 && n_stale * 2 >= self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DrawListCache;
+
+    #[test]
+    fn lookup_checks_sizes() {
+        let mut cache = DrawListCache::new();
+        cache.insert(42, 16, 32, 4, 6);
+
+        let cached = cache.lookup(42, 4, 6).unwrap();
+        assert_eq!((cached.vtx_base, cached.idx_base), (16, 32));
+
+        // same hash, other data
+        assert!(cache.lookup(42, 5, 6).is_none());
+        assert!(cache.lookup(42, 4, 9).is_none());
+        assert!(cache.lookup(7, 4, 6).is_none());
+
+        // a collision replaces the entry
+        cache.insert(42, 64, 96, 5, 6);
+        assert!(cache.lookup(42, 4, 6).is_none());
+        assert_eq!(cache.lookup(42, 5, 6).unwrap().vtx_base, 64);
+    }
+}
@@ -0,0 +1,103 @@
+//! Offscreen rendering and readback
+
+use {glow::HasContext, std::rc::Rc};
+
+use super::ImGuiGlow;
+use crate::{helper::diff::Image, Renderer};
+
+/// Offscreen color target
+struct Target {
+    fbo: glow::Framebuffer,
+    tex: glow::Texture,
+}
+
+impl Target {
+    unsafe fn new(gl: &glow::Context, w: u32, h: u32) -> Result<Self, String> {
+        let tex = gl.create_texture()?;
+        gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+        gl.tex_image_2d(
+            glow::TEXTURE_2D,
+            0,
+            glow::RGBA as i32,
+            w as i32,
+            h as i32,
+            0,
+            glow::RGBA,
+            glow::UNSIGNED_BYTE,
+            None,
+        );
+        gl.bind_texture(glow::TEXTURE_2D, None);
+
+        let fbo = gl.create_framebuffer()?;
+        gl.bind_framebuffer(glow::FRAMEBUFFER, Some(fbo));
+        gl.framebuffer_texture_2d(
+            glow::FRAMEBUFFER,
+            glow::COLOR_ATTACHMENT0,
+            glow::TEXTURE_2D,
+            Some(tex),
+            0,
+        );
+
+        if gl.check_framebuffer_status(glow::FRAMEBUFFER) != glow::FRAMEBUFFER_COMPLETE {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            gl.delete_framebuffer(fbo);
+            gl.delete_texture(tex);
+            return Err("ImGuiGlow: incomplete capture framebuffer".to_string());
+        }
+
+        Ok(Self { fbo, tex })
+    }
+
+    unsafe fn free(self, gl: &glow::Context) {
+        gl.delete_framebuffer(self.fbo);
+        gl.delete_texture(self.tex);
+    }
+}
+
+impl ImGuiGlow {
+    /// Renders the draw data into an offscreen target cleared with transparent black and reads it
+    /// back as RGBA8 pixels (top row first). The default framebuffer is bound afterwards
+    pub fn capture(
+        &mut self,
+        gl: &mut Rc<glow::Context>,
+        draw_data: &imgui::DrawData,
+    ) -> Result<Image, String> {
+        let w = (draw_data.display_size[0] * draw_data.framebuffer_scale[0]) as u32;
+        let h = (draw_data.display_size[1] * draw_data.framebuffer_scale[1]) as u32;
+        if w == 0 || h == 0 {
+            return Err("ImGuiGlow: can't capture empty framebuffer".to_string());
+        }
+
+        let target = unsafe { Target::new(gl, w, h)? };
+
+        unsafe {
+            gl.viewport(0, 0, w as i32, h as i32);
+            gl.disable(glow::SCISSOR_TEST);
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(glow::COLOR_BUFFER_BIT);
+        }
+
+        let result = self.render(draw_data, gl);
+
+        let mut pixels = vec![0; (w as usize) * (h as usize) * 4];
+        unsafe {
+            gl.read_pixels(
+                0,
+                0,
+                w as i32,
+                h as i32,
+                glow::RGBA,
+                glow::UNSIGNED_BYTE,
+                &mut pixels,
+            );
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            target.free(gl);
+        }
+
+        result.map_err(|e| e.to_string())?;
+
+        let mut image = Image { pixels, w, h };
+        image.flip_y();
+        Ok(image)
+    }
+}
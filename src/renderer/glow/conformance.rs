@@ -0,0 +1,69 @@
+//! [`RendererConformance`] of [`ImGuiGlow`] on a hidden SDL2 window
+
+use std::rc::Rc;
+
+use crate::helper::conformance::RendererConformance;
+
+use super::ImGuiGlow;
+
+impl RendererConformance for ImGuiGlow {
+    fn create(imgui: &mut imgui::Context) -> Result<(Self, Self::Device), String> {
+        let gl = self::gl_context()?;
+        let renderer = Self::new(imgui, &gl).map_err(|err| err.to_string())?;
+        Ok((renderer, gl))
+    }
+}
+
+/// GL 3.3 core context of a hidden window, kept alive for the rest of the thread
+#[cfg(feature = "sdl2")]
+fn gl_context() -> Result<Rc<glow::Context>, String> {
+    use std::cell::RefCell;
+
+    thread_local! {
+        // the GL context is dropped before the window
+        static WINDOW: RefCell<Option<(sdl2::video::GLContext, sdl2::video::Window)>> =
+            RefCell::new(None);
+    }
+
+    WINDOW.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        if cell.is_none() {
+            let sdl = sdl2::init()?;
+            let vid = sdl.video()?;
+
+            let attr = vid.gl_attr();
+            attr.set_context_profile(sdl2::video::GLProfile::Core);
+            attr.set_context_version(3, 3);
+
+            let win = vid
+                .window("imgui-glow conformance", 1280, 720)
+                .opengl()
+                .hidden()
+                .build()
+                .map_err(|err| err.to_string())?;
+            let gl = win.gl_create_context()?;
+            *cell = Some((gl, win));
+        }
+
+        let (gl, win) = cell.as_ref().unwrap();
+        win.gl_make_current(gl)?;
+        let vid = win.subsystem();
+        Ok(Rc::new(unsafe {
+            glow::Context::from_loader_function(|s| vid.gl_get_proc_address(s) as *const _)
+        }))
+    })
+}
+
+#[cfg(not(feature = "sdl2"))]
+fn gl_context() -> Result<Rc<glow::Context>, String> {
+    Err("the `sdl2` feature is required to create a GL context".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[ignore = "requires a display and OpenGL 3.3"]
+    fn conformance() {
+        crate::helper::conformance::assert_conformance::<super::ImGuiGlow>();
+    }
+}
@@ -0,0 +1,215 @@
+//! Checked GL calls
+//!
+//! Preconditions are validated in debug builds only. Problems and GL errors are collected and
+//! reported once per frame. Redundant scissor and texture changes are skipped and counted.
+
+use {glow::HasContext, std::mem::size_of};
+
+/// State changes made and skipped by the draw loop in a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCounters {
+    pub draw_calls: u32,
+    pub scissor_changes: u32,
+    /// Scissors equal to the current one or covering the framebuffer while it's disabled
+    pub scissor_skipped: u32,
+    pub texture_binds: u32,
+    pub texture_binds_skipped: u32,
+}
+
+/// Scissor state set by [`GlDevice::scissor`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scissor {
+    Rect([i32; 4]),
+    /// The scissor test is disabled for draws covering the whole framebuffer
+    Disabled,
+}
+
+/// Thin wrapper of the GL calls made in the draw loop
+#[derive(Debug, Default)]
+pub struct GlDevice {
+    issues: Vec<String>,
+    /// Issues of the last frame
+    last_frame: Vec<String>,
+    counters: StateCounters,
+    last_counters: StateCounters,
+    /// `None` if unknown
+    scissor: Option<Scissor>,
+    /// Texture bound to `TEXTURE0`. `None` if unknown
+    texture: Option<glow::Texture>,
+}
+
+impl GlDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Diagnostics collected in the last frame
+    pub fn last_frame(&self) -> &[String] {
+        &self.last_frame
+    }
+
+    /// State changes of the last frame
+    pub fn last_counters(&self) -> StateCounters {
+        self.last_counters
+    }
+
+    /// Forgets the cached states, e.g. after they're set outside of the device
+    pub fn invalidate_state(&mut self) {
+        self.scissor = None;
+        self.texture = None;
+    }
+
+    fn issue(&mut self, msg: String) {
+        // don't flood
+        if self.issues.len() < 16 {
+            self.issues.push(msg);
+        }
+    }
+
+    /// Sets the scissor rectangle unless it's already set. With `full` (the draw covers the
+    /// framebuffer), the scissor test is disabled instead so that runs of such draws change nothing
+    pub unsafe fn scissor(&mut self, gl: &glow::Context, rect: [i32; 4], full: bool) {
+        let next = if full {
+            Scissor::Disabled
+        } else {
+            Scissor::Rect(rect)
+        };
+        if self.scissor == Some(next) {
+            self.counters.scissor_skipped += 1;
+            return;
+        }
+
+        let [x, y, w, h] = match next {
+            Scissor::Rect(rect) => rect,
+            Scissor::Disabled => {
+                gl.disable(glow::SCISSOR_TEST);
+                self.scissor = Some(next);
+                self.counters.scissor_changes += 1;
+                return;
+            }
+        };
+
+        if cfg!(debug_assertions) {
+            let mut vp = [0; 4];
+            gl.get_parameter_i32_slice(glow::VIEWPORT, &mut vp);
+            if !self::is_in_viewport([x, y, w, h], vp) {
+                self.issue(format!(
+                    "scissor [{}, {}, {}, {}] is out of viewport {:?}",
+                    x, y, w, h, vp
+                ));
+            }
+        }
+
+        // the test is disabled by a full-framebuffer draw, or unknown
+        if !matches!(self.scissor, Some(Scissor::Rect(_))) {
+            gl.enable(glow::SCISSOR_TEST);
+        }
+        gl.scissor(x, y, w, h);
+        self.scissor = Some(next);
+        self.counters.scissor_changes += 1;
+    }
+
+    /// Enables the scissor test again if a full-framebuffer draw disabled it, leaving the states
+    /// as [`super::Resources::bind`] sets them
+    pub unsafe fn end_pass(&mut self, gl: &glow::Context) {
+        if self.scissor == Some(Scissor::Disabled) {
+            gl.enable(glow::SCISSOR_TEST);
+        }
+        self.invalidate_state();
+    }
+
+    /// Binds the texture and draws indexed triangles from the bound buffers
+    pub unsafe fn draw_indexed(
+        &mut self,
+        gl: &glow::Context,
+        tex: glow::Texture,
+        n_elems: i32,
+        idx_offset: i32,
+        vtx_offset: i32,
+    ) {
+        if cfg!(debug_assertions) {
+            if gl.get_parameter_i32(glow::VERTEX_ARRAY_BINDING) == 0 {
+                self.issue("draw call without VAO".to_string());
+            }
+            if gl.get_parameter_i32(glow::CURRENT_PROGRAM) == 0 {
+                self.issue("draw call without shader program".to_string());
+            }
+        }
+
+        if self.texture == Some(tex) {
+            self.counters.texture_binds_skipped += 1;
+        } else {
+            gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+            self.texture = Some(tex);
+            self.counters.texture_binds += 1;
+        }
+        self.counters.draw_calls += 1;
+        gl.draw_elements_base_vertex(
+            // mode
+            glow::TRIANGLES,
+            // count
+            n_elems,
+            if size_of::<imgui::DrawIdx>() == 2 {
+                glow::UNSIGNED_SHORT
+            } else {
+                glow::UNSIGNED_INT
+            },
+            idx_offset * size_of::<imgui::DrawIdx>() as i32,
+            vtx_offset,
+        );
+    }
+
+    /// Collects GL errors. Returns true if the context is lost (`GL_CONTEXT_LOST` of the
+    /// robustness extension)
+    pub unsafe fn poll_errors(&mut self, gl: &glow::Context) -> bool {
+        // bounded in case the context is lost without robustness
+        for _ in 0..16 {
+            let err = gl.get_error();
+            if err == glow::NO_ERROR {
+                break;
+            }
+            if err == glow::CONTEXT_LOST {
+                return true;
+            }
+            self.issue(format!("GL error 0x{:X}", err));
+        }
+        false
+    }
+
+    /// Collects GL errors and reports the issues of this frame. Returns true if the context is lost
+    pub unsafe fn end_frame(&mut self, gl: &glow::Context, context: &str) -> bool {
+        let lost = self.poll_errors(gl);
+
+        // report only when something changed
+        if self.issues != self.last_frame {
+            for issue in &self.issues {
+                log::warn!("{}: {}", context, issue);
+            }
+        }
+
+        self.last_frame = std::mem::take(&mut self.issues);
+        self.last_counters = std::mem::take(&mut self.counters);
+        lost
+    }
+}
+
+/// If the scissor rectangle `[x, y, w, h]` is in the viewport. `GL_VIEWPORT` is `[x, y, w, h]` too
+fn is_in_viewport(rect: [i32; 4], vp: [i32; 4]) -> bool {
+    let [x, y, w, h] = rect;
+    w >= 0 && h >= 0 && x >= vp[0] && y >= vp[1] && x + w <= vp[0] + vp[2] && y + h <= vp[1] + vp[3]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_in_viewport;
+
+    #[test]
+    fn scissor_in_offset_viewport() {
+        let vp = [100, 50, 640, 480];
+        assert!(is_in_viewport([100, 50, 640, 480], vp));
+        assert!(is_in_viewport([700, 500, 40, 30], vp));
+        assert!(!is_in_viewport([700, 500, 41, 30], vp));
+        assert!(!is_in_viewport([99, 50, 10, 10], vp));
+        assert!(!is_in_viewport([100, 50, -1, 10], vp));
+    }
+}
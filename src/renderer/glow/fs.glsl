@@ -1,12 +1,65 @@
 #version 330 core
+// `DUAL_SOURCE` is defined for `TextBlending::PerChannel`
 
 uniform sampler2D tex;
+uniform sampler2DArray tex_array;
+uniform mat4 color_matrix;
+// 0: standard, 1: luminance, 2: per channel. See `TextBlending`
+uniform int text_blending;
+uniform float text_gamma;
+// set while the font atlas is bound
+uniform bool font_atlas;
+// user textures have premultiplied alpha and so has the output (non dual-source)
+uniform bool premultiplied;
+// the framebuffer encodes sRGB (`GL_FRAMEBUFFER_SRGB`), so output linear colors
+uniform bool linear_output;
 
 in vec4 fs_color;
 in vec2 fs_uv;
+flat in float fs_layer;
 
+#ifdef DUAL_SOURCE
+layout(location = 0, index = 0) out vec4 out_color;
+// blend factors per channel
+layout(location = 0, index = 1) out vec4 out_factor;
+#else
 out vec4 out_color;
+#endif
 
 void main() {
-    out_color = texture(tex, fs_uv) * fs_color;
+    vec4 texel = fs_layer > 0.5
+        ? texture(tex_array, vec3(fs_uv, fs_layer - 1.0))
+        : texture(tex, fs_uv);
+    if (premultiplied && (!font_atlas || fs_layer > 0.5) && texel.a > 0.0) {
+        // straight alpha for the color math below. The font atlas is always straight
+        texel.rgb /= texel.a;
+    }
+    vec4 color = color_matrix * (texel * fs_color);
+
+    vec3 coverage = vec3(color.a);
+    if (font_atlas && text_blending != 0 && fs_layer < 0.5) {
+        // bright text gets more coverage and dark text less. The white pixel (coverage 1) of
+        // shapes is not affected
+        vec3 rgb = clamp(color.rgb, 0.0, 1.0);
+        if (text_blending == 1) {
+            rgb = vec3(dot(rgb, vec3(0.2126, 0.7152, 0.0722)));
+        }
+        vec3 e = mix(vec3(text_gamma), vec3(1.0 / text_gamma), rgb);
+        coverage = pow(vec3(texel.a), e) * fs_color.a;
+    }
+
+    if (linear_output) {
+        // UI colors are authored in sRGB
+        color.rgb = pow(max(color.rgb, 0.0), vec3(2.2));
+    }
+
+#ifdef DUAL_SOURCE
+    // dst = out_color + dst * (1 - out_factor)
+    out_color = vec4(color.rgb * coverage, color.a);
+    out_factor = vec4(coverage, color.a);
+#else
+    out_color = premultiplied
+        ? vec4(color.rgb * coverage.g, coverage.g)
+        : vec4(color.rgb, coverage.g);
+#endif
 }
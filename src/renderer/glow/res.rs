@@ -4,9 +4,11 @@ GPU resources
 
 use anyhow::*;
 use glow::HasContext;
-use std::{any::TypeId, marker::PhantomData, mem::size_of};
 
-/// Max number of quadliterals
+use crate::helper::{as_bytes, Plain, TextBlending, VertexLayout};
+use std::{marker::PhantomData, mem::size_of};
+
+/// Default initial capacity of the buffers in quadliterals. Stream buffers grow on demand
 pub const N_QUADS: usize = 2048;
 
 const VS_SRC: &'static str = include_str!("vs.glsl");
@@ -41,10 +43,22 @@ unsafe fn gen_shader_program(gl: &glow::Context, sources: &[(u32, &str)]) -> glo
     program
 }
 
-unsafe fn alloc_buffer(gl: &glow::Context, type_: u32, capacity: usize) -> Result<glow::Buffer> {
+/// Names a GL object for graphics debuggers such as RenderDoc. No-op without `KHR_debug`
+pub unsafe fn set_object_label(gl: &glow::Context, identifier: u32, name: u32, label: &str) {
+    if gl.supports_debug() {
+        gl.object_label(identifier, name, Some(label));
+    }
+}
+
+unsafe fn alloc_buffer(
+    gl: &glow::Context,
+    type_: u32,
+    capacity: usize,
+    usage: u32,
+) -> Result<glow::Buffer> {
     let buf = gl.create_buffer().map_err(Error::msg)?;
     gl.bind_buffer(type_, Some(buf));
-    gl.buffer_data_size(type_, capacity as i32, glow::STREAM_DRAW);
+    gl.buffer_data_size(type_, capacity as i32, usage);
     gl.bind_buffer(type_, None);
     Ok(buf)
 }
@@ -55,61 +69,146 @@ struct Buffer<T> {
     id: glow::Buffer,
     len_bytes: i32,
     capacity_bytes: i32,
+    usage: u32,
     _marker: PhantomData<T>,
 }
 
-impl<T> Buffer<T> {
-    pub fn new(gl: &glow::Context, type_: u32, len: usize) -> Result<Self> {
+impl<T: Plain> Buffer<T> {
+    pub fn new(gl: &glow::Context, type_: u32, len: usize, usage: u32) -> Result<Self> {
         assert!(type_ == glow::ARRAY_BUFFER || type_ == glow::ELEMENT_ARRAY_BUFFER);
-        let capacity_bytes = size_of::<T>() * len;
+        let capacity_bytes = size_of::<T>() * len.max(1);
         assert!(capacity_bytes < i32::MAX as usize);
 
-        let id = unsafe { self::alloc_buffer(gl, type_, capacity_bytes)? };
+        let id = unsafe { self::alloc_buffer(gl, type_, capacity_bytes, usage)? };
 
         Ok(Self {
             type_,
             id,
             len_bytes: 0,
             capacity_bytes: capacity_bytes as i32,
+            usage,
             _marker: PhantomData,
         })
     }
 
+    pub fn capacity(&self) -> usize {
+        self.capacity_bytes as usize / size_of::<T>()
+    }
+
     pub fn reset_offset(&mut self) {
         self.len_bytes = 0;
     }
 
-    pub fn append(&mut self, gl: &glow::Context, data: &[T]) {
+    pub fn has_room(&self, len: usize) -> bool {
+        self.len_bytes as usize + size_of::<T>() * len <= self.capacity_bytes as usize
+    }
+
+    /// Appends data to the bound buffer and returns the element offset of it
+    pub fn append(&mut self, gl: &glow::Context, data: &[T]) -> usize {
         let len_bytes = size_of::<T>() * data.len();
         let new_len_bytes = self.len_bytes + len_bytes as i32;
         assert!(new_len_bytes <= self.capacity_bytes);
+        let offset = self.len_bytes as usize / size_of::<T>();
         unsafe {
-            let bytes: &[u8] = std::slice::from_raw_parts(data.as_ptr() as *const _, len_bytes);
             // FIXME:
-            gl.buffer_sub_data_u8_slice(self.type_, self.len_bytes, bytes);
+            gl.buffer_sub_data_u8_slice(self.type_, self.len_bytes, as_bytes(data));
         }
         self.len_bytes = new_len_bytes;
+        offset
+    }
+
+    /// Writes data at the element `offset` of the bound buffer, e.g. parallel to another buffer.
+    /// Returns false if it doesn't fit
+    pub fn write_at(&mut self, gl: &glow::Context, offset: usize, data: &[T]) -> bool {
+        let start = size_of::<T>() * offset;
+        let end = start + size_of::<T>() * data.len();
+        if end > self.capacity_bytes as usize {
+            return false;
+        }
+        unsafe {
+            gl.buffer_sub_data_u8_slice(self.type_, start as i32, as_bytes(data));
+        }
+        self.len_bytes = self.len_bytes.max(end as i32);
+        true
+    }
+
+    /// Replaces the storage of the buffer with a new one (orphaning), doubling the capacity until
+    /// `len` more elements than the current content fit. Draw calls already issued keep reading the
+    /// old storage. Binds the buffer and resets the offset
+    pub unsafe fn orphan(&mut self, gl: &glow::Context, len: usize) -> Result<()> {
+        let needed = self.len_bytes as usize + size_of::<T>() * len;
+        let mut capacity = self.capacity_bytes as usize;
+        while capacity < needed {
+            capacity *= 2;
+        }
+        ensure!(
+            capacity < i32::MAX as usize,
+            "ImGuiGlow: buffer of {} bytes is too large",
+            capacity
+        );
+
+        if capacity != self.capacity_bytes as usize {
+            log::debug!("ImGuiGlow: grow buffer {:?} to {} bytes", self.id, capacity);
+        }
+
+        gl.bind_buffer(self.type_, Some(self.id));
+        gl.buffer_data_size(self.type_, capacity as i32, self.usage);
+        self.capacity_bytes = capacity as i32;
+        self.len_bytes = 0;
+        Ok(())
     }
 }
 
+/// Set of vertex/index buffers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferKind {
+    /// Rewritten every frame
+    Stream,
+    /// Keeps cached draw lists across frames
+    Static,
+}
+
 /// GPU resources
 pub struct Resources {
     // pipeline
     vao: glow::VertexArray,
+    /// VAOs of other (shared) contexts, keyed by the context address. VAOs are not shared
+    window_vaos: Vec<(usize, glow::VertexArray)>,
+    /// VAO bound by [`Self::bind`]
+    active_vao: glow::VertexArray,
+    /// Program in use: `standard_program` or `dual_program`
     program: glow::Program,
+    standard_program: glow::Program,
+    /// Variant with dual-source blending, compiled on first use
+    dual_program: Option<glow::Program>,
+    /// If dual-source blending (`GL_ARB_blend_func_extended`, core since GL 3.3) is available
+    dual_supported: bool,
     // GPU buffers and texture slot
     vbuf: Buffer<imgui::DrawVert>,
     ibuf: Buffer<imgui::DrawIdx>,
+    static_vbuf: Buffer<imgui::DrawVert>,
+    static_ibuf: Buffer<imgui::DrawIdx>,
+    /// Thumbnail array layers per vertex, written at the offsets of the vertices in the stream
+    /// vertex buffer. Always as large as it
+    layers: Buffer<f32>,
+    /// Buffers bound by the last [`Self::bind_buffers`]
+    bound: BufferKind,
+    /// If the layer attribute is enabled for the bound buffers
+    layers_bound: bool,
     // TODO:
     // vbuf_cpu: Vec<imgui::DrawVert>,
     // ibuf_cpu: Vec<imgui::DrawVert>,
     /// We won't free this texture on drop; basically a weak reference
     tex: Option<glow::Texture>,
+    /// Textures have premultiplied alpha and so has the output
+    premultiplied: bool,
+    /// `GL_FRAMEBUFFER_SRGB` is enabled while bound
+    srgb: bool,
 }
 
 impl Resources {
-    /// Allocates GPU resources
-    pub fn new(gl: &glow::Context) -> Result<Self> {
+    /// Allocates GPU resources with the initial capacity of the buffers
+    pub fn new(gl: &glow::Context, n_vertices: usize, n_indices: usize) -> Result<Self> {
         unsafe {
             let vao = gl
                 .create_vertex_array()
@@ -123,33 +222,68 @@ impl Resources {
                 ],
             );
 
-            let vbuf = Buffer::new(
-                gl,
-                glow::ARRAY_BUFFER,
-                4 * N_QUADS * size_of::<imgui::DrawVert>(),
-            )?;
-
-            let ibuf = Buffer::new(
-                gl,
-                glow::ELEMENT_ARRAY_BUFFER,
-                6 * N_QUADS * size_of::<imgui::DrawIdx>(),
-            )?;
+            let vbuf = Buffer::new(gl, glow::ARRAY_BUFFER, n_vertices, glow::STREAM_DRAW)?;
+            let ibuf = Buffer::new(gl, glow::ELEMENT_ARRAY_BUFFER, n_indices, glow::STREAM_DRAW)?;
+
+            // fixed size; draw lists that don't fit are streamed
+            let static_vbuf = Buffer::new(gl, glow::ARRAY_BUFFER, n_vertices, glow::STATIC_DRAW)?;
+            let static_ibuf =
+                Buffer::new(gl, glow::ELEMENT_ARRAY_BUFFER, n_indices, glow::STATIC_DRAW)?;
+
+            // one layer per vertex of the stream vertex buffer
+            let layers = Buffer::new(gl, glow::ARRAY_BUFFER, n_vertices, glow::STREAM_DRAW)?;
+
+            self::set_object_label(gl, glow::VERTEX_ARRAY, vao, "imgui-glow vao");
+            self::set_object_label(gl, glow::PROGRAM, program, "imgui-glow program");
+            for (buf, label) in &[
+                (vbuf.id, "imgui-glow vertex buffer"),
+                (ibuf.id, "imgui-glow index buffer"),
+                (static_vbuf.id, "imgui-glow static vertex buffer"),
+                (static_ibuf.id, "imgui-glow static index buffer"),
+                (layers.id, "imgui-glow layer buffer"),
+            ] {
+                self::set_object_label(gl, glow::BUFFER, *buf, label);
+            }
 
             Ok(Self {
                 vao,
+                window_vaos: Vec::new(),
+                active_vao: vao,
                 program,
+                standard_program: program,
+                dual_program: None,
+                dual_supported: gl.get_parameter_i32(glow::MAX_DUAL_SOURCE_DRAW_BUFFERS) >= 1,
                 vbuf,
                 ibuf,
+                static_vbuf,
+                static_ibuf,
+                layers,
+                bound: BufferKind::Stream,
+                layers_bound: false,
                 tex: None,
+                premultiplied: false,
+                srgb: false,
             })
         }
     }
 
     pub unsafe fn free(&mut self, gl: &glow::Context) {
-        gl.delete_program(self.program);
+        gl.delete_program(self.standard_program);
+        if let Some(program) = self.dual_program.take() {
+            gl.delete_program(program);
+        }
         gl.delete_vertex_array(self.vao);
+        if !self.window_vaos.is_empty() {
+            log::debug!(
+                "ImGuiGlow: {} VAOs of window contexts are left to their contexts",
+                self.window_vaos.len()
+            );
+        }
         gl.delete_buffer(self.vbuf.id);
         gl.delete_buffer(self.ibuf.id);
+        gl.delete_buffer(self.static_vbuf.id);
+        gl.delete_buffer(self.static_ibuf.id);
+        gl.delete_buffer(self.layers.id);
     }
 }
 
@@ -157,33 +291,248 @@ impl Resources {
     pub fn reset_buf_offsets(&mut self) {
         self.vbuf.reset_offset();
         self.ibuf.reset_offset();
+        self.layers.reset_offset();
+    }
+
+    /// Binds [`Self::bind`] to the VAO of the window context, creating it on first use. `None`
+    /// selects the context the resources were created with
+    pub unsafe fn select_context(&mut self, gl: Option<&glow::Context>) -> Result<()> {
+        let gl = match gl {
+            Some(gl) => gl,
+            None => {
+                self.active_vao = self.vao;
+                return Ok(());
+            }
+        };
+
+        let key = gl as *const _ as usize;
+        let vao = match self.window_vaos.iter().find(|(k, _)| *k == key) {
+            Some((_, vao)) => *vao,
+            None => {
+                let vao = gl.create_vertex_array().map_err(Error::msg)?;
+                self.window_vaos.push((key, vao));
+                vao
+            }
+        };
+        self.active_vao = vao;
+        Ok(())
+    }
+
+    /// Deletes the VAO of the window context. The context has to be current
+    pub unsafe fn release_context(&mut self, gl: &glow::Context) {
+        let key = gl as *const _ as usize;
+        if let Some(i) = self.window_vaos.iter().position(|(k, _)| *k == key) {
+            let (_, vao) = self.window_vaos.remove(i);
+            gl.delete_vertex_array(vao);
+        }
+    }
+
+    /// If the shader program is visible, i.e., the current context shares objects with the context
+    /// the resources were created with
+    pub unsafe fn is_visible(&self, gl: &glow::Context) -> bool {
+        let visible = gl.get_program_link_status(self.program);
+        // clear `GL_INVALID_VALUE` of unknown names (bounded in case the context is lost)
+        for _ in 0..16 {
+            if gl.get_error() == glow::NO_ERROR {
+                break;
+            }
+        }
+        visible
     }
 
     pub fn set_texture(&mut self, tex: glow::Texture) {
         self.tex = Some(tex);
     }
 
-    pub fn append_vbuf(&mut self, gl: &glow::Context, vbuf: &[imgui::DrawVert]) {
-        self.vbuf.append(gl, vbuf);
+    /// Forgets the cached draw lists
+    pub fn clear_static(&mut self) {
+        self.static_vbuf.reset_offset();
+        self.static_ibuf.reset_offset();
+    }
+
+    /// Appends a draw list to the bound buffers of `kind`, returning the vertex and index offsets
+    /// of it. Returns `None` if there's no room in the static buffers; stream buffers grow
+    pub fn append_buffers(
+        &mut self,
+        gl: &glow::Context,
+        kind: BufferKind,
+        vtx: &[imgui::DrawVert],
+        idx: &[imgui::DrawIdx],
+    ) -> Result<Option<(usize, usize)>> {
+        let (vbuf, ibuf) = match kind {
+            BufferKind::Stream => (&mut self.vbuf, &mut self.ibuf),
+            BufferKind::Static => (&mut self.static_vbuf, &mut self.static_ibuf),
+        };
+
+        if !vbuf.has_room(vtx.len()) || !ibuf.has_room(idx.len()) {
+            if kind == BufferKind::Static {
+                return Ok(None);
+            }
+            unsafe {
+                self.orphan_stream(gl, vtx.len(), idx.len())?;
+            }
+            return Ok(Some((self.vbuf.append(gl, vtx), self.ibuf.append(gl, idx))));
+        }
+
+        Ok(Some((vbuf.append(gl, vtx), ibuf.append(gl, idx))))
+    }
+
+    /// Orphans the stream buffers so that `n_vertices` and `n_indices` more fit. The layer buffer
+    /// is orphaned with the vertex buffer so that it stays as large. Leaves the stream
+    /// buffers bound
+    unsafe fn orphan_stream(
+        &mut self,
+        gl: &glow::Context,
+        n_vertices: usize,
+        n_indices: usize,
+    ) -> Result<()> {
+        self.vbuf.orphan(gl, n_vertices)?;
+        self.ibuf.orphan(gl, n_indices)?;
+        self.layers.reset_offset();
+        self.layers.orphan(gl, self.vbuf.capacity())?;
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbuf.id));
+        Ok(())
+    }
+
+    /// Writes per-vertex thumbnail layers of the draw list at `vtx_base` of the stream vertex
+    /// buffer and enables the layer attribute. Returns false if the stream buffers are not bound
+    pub unsafe fn upload_layers(
+        &mut self,
+        gl: &glow::Context,
+        vtx_base: usize,
+        layers: &[f32],
+    ) -> bool {
+        if self.bound != BufferKind::Stream {
+            return false;
+        }
+
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.layers.id));
+        let written = self.layers.write_at(gl, vtx_base, layers);
+        if written {
+            self.bind_layers(gl);
+        } else {
+            gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbuf.id));
+        }
+        written
     }
 
-    pub fn append_ibuf(&mut self, gl: &glow::Context, ibuf: &[imgui::DrawIdx]) {
-        self.ibuf.append(gl, ibuf);
+    /// Enables the thumbnail layer attribute. Leaves the stream vertex buffer bound
+    unsafe fn bind_layers(&mut self, gl: &glow::Context) {
+        self.layers_bound = true;
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.layers.id));
+
+        let index = 3;
+        gl.enable_vertex_attrib_array(index);
+        gl.vertex_attrib_pointer_f32(index, 1, glow::FLOAT, false, size_of::<f32>() as i32, 0);
+
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbuf.id));
     }
 }
 
 impl Resources {
-    pub unsafe fn set_uniforms(&self, gl: &glow::Context, mat: [f32; 16]) {
+    pub unsafe fn set_uniforms(&self, gl: &glow::Context, mat: [f32; 16], opacity: f32) {
         let location = gl
             // we must not add '\0' here -- glow does it
             .get_uniform_location(self.program, "transform")
             .expect("Unable to locate transform uniform");
         gl.uniform_matrix_4_f32_slice(Some(&location), false, &mat);
+
+        let location = gl
+            .get_uniform_location(self.program, "opacity")
+            .expect("Unable to locate opacity uniform");
+        gl.uniform_1_f32(Some(&location), opacity);
+    }
+
+    /// Sets the color transformation of [`crate::helper::ColorMode`]. The program has to be in use
+    pub unsafe fn set_color_matrix(&self, gl: &glow::Context, mat: [f32; 16]) {
+        let location = gl
+            .get_uniform_location(self.program, "color_matrix")
+            .expect("Unable to locate color_matrix uniform");
+        gl.uniform_matrix_4_f32_slice(Some(&location), false, &mat);
+    }
+
+    pub fn supports_dual_source(&self) -> bool {
+        self.dual_supported
+    }
+
+    pub fn is_dual_source(&self) -> bool {
+        Some(self.program) == self.dual_program
+    }
+
+    /// Switches to the program with or without dual-source blending. Applied on [`Self::bind`]
+    pub unsafe fn set_dual_source(&mut self, gl: &glow::Context, enable: bool) {
+        if !enable || !self.dual_supported {
+            self.program = self.standard_program;
+            return;
+        }
+
+        let program = match self.dual_program {
+            Some(program) => program,
+            None => {
+                let fs = FS_SRC.replacen(
+                    "#version 330 core\n",
+                    "#version 330 core\n#define DUAL_SOURCE\n",
+                    1,
+                );
+                let program = self::gen_shader_program(
+                    gl,
+                    &[(glow::VERTEX_SHADER, VS_SRC), (glow::FRAGMENT_SHADER, &fs)],
+                );
+                self::set_object_label(
+                    gl,
+                    glow::PROGRAM,
+                    program,
+                    "imgui-glow dual-source program",
+                );
+                self.dual_program = Some(program);
+                program
+            }
+        };
+        self.program = program;
+    }
+
+    /// Sets the uniforms of [`TextBlending`]. The program has to be in use
+    pub unsafe fn set_text_blending(&self, gl: &glow::Context, blending: TextBlending) {
+        if let Some(location) = gl.get_uniform_location(self.program, "text_blending") {
+            gl.uniform_1_i32(Some(&location), blending.shader_mode());
+        }
+        if let Some(location) = gl.get_uniform_location(self.program, "text_gamma") {
+            gl.uniform_1_f32(Some(&location), blending.gamma());
+        }
+        self.set_font_atlas(gl, false);
+    }
+
+    /// Tells the shader if the font atlas is bound, for [`TextBlending`]
+    pub unsafe fn set_font_atlas(&self, gl: &glow::Context, bound: bool) {
+        if let Some(location) = gl.get_uniform_location(self.program, "font_atlas") {
+            gl.uniform_1_i32(Some(&location), bound as i32);
+        }
+    }
+
+    /// Output blending and encoding. Applied on [`Self::bind`] and [`Self::set_output_uniforms`]
+    pub fn set_output(&mut self, premultiplied: bool, srgb: bool) {
+        self.premultiplied = premultiplied;
+        self.srgb = srgb;
+    }
+
+    /// Sets the uniforms of [`Self::set_output`]. The program has to be in use
+    pub unsafe fn set_output_uniforms(&self, gl: &glow::Context) {
+        if let Some(location) = gl.get_uniform_location(self.program, "premultiplied") {
+            gl.uniform_1_i32(Some(&location), self.premultiplied as i32);
+        }
+        if let Some(location) = gl.get_uniform_location(self.program, "linear_output") {
+            gl.uniform_1_i32(Some(&location), self.srgb as i32);
+        }
+    }
+
+    /// If the shader needs [`Self::set_font_atlas`] on every draw call
+    pub fn tracks_font_atlas(&self, text_blending: bool) -> bool {
+        text_blending || self.premultiplied
     }
 
     pub unsafe fn bind(&self, gl: &glow::Context) {
         // NOTE: The order is important.. bind buffers first and then setup VAO!
-        gl.bind_vertex_array(Some(self.vao));
+        gl.bind_vertex_array(Some(self.active_vao));
         gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.vbuf.id));
         gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(self.ibuf.id));
         // TODO: disable dangling attributes
@@ -192,10 +541,31 @@ impl Resources {
         gl.use_program(Some(self.program));
         gl.bind_texture(glow::TEXTURE_2D, self.tex);
 
+        // `sampler2D` and `sampler2DArray` must not share a texture unit
+        let location = gl
+            .get_uniform_location(self.program, "tex_array")
+            .expect("Unable to locate tex_array uniform");
+        gl.uniform_1_i32(Some(&location), 1);
+
         // use alpha blending. use scissor test.
         gl.enable(glow::BLEND);
-        gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+        if self.is_dual_source() {
+            // colors are premultiplied per channel by the shader
+            gl.blend_func_separate(
+                glow::ONE,
+                glow::ONE_MINUS_SRC1_COLOR,
+                glow::SRC_ALPHA,
+                glow::ONE_MINUS_SRC_ALPHA,
+            );
+        } else if self.premultiplied {
+            gl.blend_func(glow::ONE, glow::ONE_MINUS_SRC_ALPHA);
+        } else {
+            gl.blend_func(glow::SRC_ALPHA, glow::ONE_MINUS_SRC_ALPHA);
+        }
         gl.enable(glow::SCISSOR_TEST);
+        if self.srgb {
+            gl.enable(glow::FRAMEBUFFER_SRGB);
+        }
 
         // TODO: not needed to set states?
         gl.disable(glow::DEPTH_TEST);
@@ -203,7 +573,35 @@ impl Resources {
         gl.disable(glow::STENCIL_TEST);
     }
 
-    pub unsafe fn unbind(gl: &glow::Context) {
+    /// Binds the vertex/index buffers of `kind` to the VAO
+    pub unsafe fn bind_buffers(&mut self, gl: &glow::Context, kind: BufferKind) {
+        self.bound = kind;
+        self.layers_bound = false;
+        let (vbuf, ibuf) = match kind {
+            BufferKind::Stream => (self.vbuf.id, self.ibuf.id),
+            BufferKind::Static => (self.static_vbuf.id, self.static_ibuf.id),
+        };
+        gl.bind_buffer(glow::ARRAY_BUFFER, Some(vbuf));
+        gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, Some(ibuf));
+        self::set_vertex_attributes(gl);
+        // enabled again by `upload_layers` if needed
+        gl.disable_vertex_attrib_array(3);
+    }
+
+    /// Binds the buffers of the last [`Self::bind_buffers`] again, e.g. after a draw callback
+    pub unsafe fn rebind_buffers(&mut self, gl: &glow::Context) {
+        let layers_bound = self.layers_bound;
+        self.bind_buffers(gl, self.bound);
+        if layers_bound {
+            self.bind_layers(gl);
+        }
+    }
+
+    pub unsafe fn unbind(&self, gl: &glow::Context) {
+        if self.srgb {
+            gl.disable(glow::FRAMEBUFFER_SRGB);
+        }
+        gl.disable_vertex_attrib_array(3);
         gl.bind_vertex_array(None);
         gl.use_program(None);
         // TODO: disable attributes?
@@ -211,26 +609,11 @@ impl Resources {
         gl.bind_buffer(glow::ARRAY_BUFFER, None);
         gl.bind_buffer(glow::ELEMENT_ARRAY_BUFFER, None);
     }
-
-    pub unsafe fn draw(&self, gl: &glow::Context, n_elems: i32, idx_offset: i32, vtx_offset: i32) {
-        gl.draw_elements_base_vertex(
-            // mode
-            glow::TRIANGLES,
-            // count
-            n_elems,
-            if std::any::TypeId::of::<imgui::DrawIdx>() == std::any::TypeId::of::<u16>() {
-                glow::UNSIGNED_SHORT
-            } else {
-                glow::UNSIGNED_INT
-            },
-            idx_offset * size_of::<imgui::DrawIdx>() as i32,
-            vtx_offset,
-        );
-    }
 }
 
 pub unsafe fn set_vertex_attributes(gl: &glow::Context) {
-    let stride = size_of::<imgui::DrawVert>() as i32;
+    let layout = VertexLayout::draw_vert();
+    let stride = layout.stride as i32;
 
     // pos: [f32: 2]
     let index = 0;
@@ -247,9 +630,8 @@ pub unsafe fn set_vertex_attributes(gl: &glow::Context) {
         // stride: size of vertex
         stride,
         // offset: byte offset of this component in a vertex
-        0,
+        layout.pos as i32,
     );
-    gl.enable_vertex_attrib_array(index);
 
     // uv: [f32: 2]
     let index = 1;
@@ -266,10 +648,10 @@ pub unsafe fn set_vertex_attributes(gl: &glow::Context) {
         // stride
         stride,
         // offset
-        2 * size_of::<f32>() as i32,
+        layout.uv as i32,
     );
 
-    // color: [u8: 2]
+    // color: [u8: 4]
     let index = 2;
     gl.enable_vertex_attrib_array(index);
     gl.vertex_attrib_pointer_f32(
@@ -283,7 +665,7 @@ pub unsafe fn set_vertex_attributes(gl: &glow::Context) {
         // stride
         stride,
         // offset
-        4 * size_of::<f32>() as i32,
+        layout.col as i32,
     );
 
     // TODO:
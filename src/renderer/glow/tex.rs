@@ -1,8 +1,12 @@
 //! Texture
 
+use std::rc::Rc;
+
 use anyhow::*;
 use glow::HasContext;
 
+use crate::FontSampling;
+
 unsafe fn gen_texture(gl: &glow::Context, pixels: &[u8], w: u32, h: u32) -> Result<glow::Texture> {
     let tex = gl.create_texture().map_err(Error::msg)?;
 
@@ -39,27 +43,32 @@ unsafe fn gen_texture(gl: &glow::Context, pixels: &[u8], w: u32, h: u32) -> Resu
     Ok(tex)
 }
 
-#[derive(Debug, Clone)]
+/// Deletes the texture on drop with the context it was created with
 pub struct TextureDrop {
-    gl: *mut glow::Context,
+    gl: Rc<glow::Context>,
     id: glow::Texture,
 }
 
+impl std::fmt::Debug for TextureDrop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TextureDrop").field("id", &self.id).finish()
+    }
+}
+
 impl Drop for TextureDrop {
     fn drop(&mut self) {
         unsafe {
-            let gl = &mut *self.gl;
-            gl.delete_texture(self.id);
+            self.gl.delete_texture(self.id);
         }
     }
 }
 
 impl TextureDrop {
-    pub fn new(gl: &glow::Context, pixels: &[u8], w: u32, h: u32) -> Result<Self> {
+    pub fn new(gl: &Rc<glow::Context>, pixels: &[u8], w: u32, h: u32) -> Result<Self> {
         let tex = unsafe { self::gen_texture(gl, pixels, w, h)? };
 
         Ok(Self {
-            gl: gl as *const _ as *mut _,
+            gl: Rc::clone(gl),
             id: tex,
         })
     }
@@ -69,18 +78,48 @@ impl TextureDrop {
     }
 }
 
-#[derive(Debug, Clone)]
+unsafe fn set_filter(gl: &glow::Context, tex: glow::Texture, filter: u32) {
+    gl.bind_texture(glow::TEXTURE_2D, Some(tex));
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MIN_FILTER, filter as i32);
+    gl.tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_MAG_FILTER, filter as i32);
+    gl.bind_texture(glow::TEXTURE_2D, None);
+}
+
+/// Not `Clone`; a clone would delete the texture twice
+#[derive(Debug)]
 pub struct Texture {
     own: TextureDrop,
+    sampling: FontSampling,
 }
 
 impl Texture {
-    pub fn new(gl: &glow::Context, pixels: &[u8], w: u32, h: u32) -> Result<Self> {
+    pub fn new(gl: &Rc<glow::Context>, pixels: &[u8], w: u32, h: u32) -> Result<Self> {
         let own = TextureDrop::new(gl, pixels, w, h)?;
-        Ok(Self { own })
+        Ok(Self {
+            own,
+            sampling: FontSampling::Linear,
+        })
     }
 
     pub fn id(&self) -> glow::Texture {
         self.own.id
     }
+
+    /// Updates min/mag filters only when the sampling is changed
+    pub fn set_sampling(&mut self, gl: &glow::Context, sampling: FontSampling) {
+        if self.sampling == sampling {
+            return;
+        }
+
+        let filter = match sampling {
+            FontSampling::Linear => glow::LINEAR,
+            FontSampling::Nearest => glow::NEAREST,
+        };
+
+        unsafe {
+            self::set_filter(gl, self.own.id, filter);
+        }
+
+        self.sampling = sampling;
+    }
 }
@@ -0,0 +1,79 @@
+//! GPU timing with `GL_TIME_ELAPSED` queries
+
+use {glow::HasContext, std::time::Duration};
+
+/// Number of queries in flight. Results are read a few frames later so that we don't stall
+const N_QUERIES: usize = 4;
+
+pub struct GpuTimer {
+    queries: [glow::Query; N_QUERIES],
+    pending: [bool; N_QUERIES],
+    frame: usize,
+    last: Option<Duration>,
+}
+
+impl GpuTimer {
+    pub unsafe fn new(gl: &glow::Context) -> Result<Self, String> {
+        let mut queries = [gl.create_query()?; N_QUERIES];
+        for q in queries.iter_mut().skip(1) {
+            *q = gl.create_query()?;
+        }
+
+        Ok(Self {
+            queries,
+            pending: [false; N_QUERIES],
+            frame: 0,
+            last: None,
+        })
+    }
+
+    pub unsafe fn free(&self, gl: &glow::Context) {
+        for q in self.queries.iter() {
+            gl.delete_query(*q);
+        }
+    }
+
+    /// GPU time of the latest finished measurement
+    pub fn last(&self) -> Option<Duration> {
+        self.last
+    }
+
+    pub unsafe fn begin(&mut self, gl: &glow::Context) {
+        self.poll(gl);
+
+        let i = self.frame % N_QUERIES;
+        if self.pending[i] {
+            // the result is too late; wait for it
+            let ns = gl.get_query_parameter_u32(self.queries[i], glow::QUERY_RESULT);
+            self.last = Some(Duration::from_nanos(ns as u64));
+            self.pending[i] = false;
+        }
+
+        gl.begin_query(glow::TIME_ELAPSED, self.queries[i]);
+    }
+
+    pub unsafe fn end(&mut self, gl: &glow::Context) {
+        gl.end_query(glow::TIME_ELAPSED);
+        self.pending[self.frame % N_QUERIES] = true;
+        self.frame += 1;
+    }
+
+    /// Reads available results, oldest first
+    unsafe fn poll(&mut self, gl: &glow::Context) {
+        for n in 0..N_QUERIES {
+            let i = (self.frame + n) % N_QUERIES;
+            if !self.pending[i] {
+                continue;
+            }
+
+            let q = self.queries[i];
+            if gl.get_query_parameter_u32(q, glow::QUERY_RESULT_AVAILABLE) == 0 {
+                break;
+            }
+
+            let ns = gl.get_query_parameter_u32(q, glow::QUERY_RESULT);
+            self.last = Some(Duration::from_nanos(ns as u64));
+            self.pending[i] = false;
+        }
+    }
+}
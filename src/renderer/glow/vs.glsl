@@ -1,16 +1,21 @@
 #version 330 core
 
 uniform mat4 transform;
+uniform float opacity;
 
 layout(location=0) in vec2 vs_pos;
 layout(location=1) in vec2 vs_uv;
 layout(location=2) in vec4 vs_color;
+// thumbnail array layer + 1. 0 (the default of a disabled attribute) is the bound 2D texture
+layout(location=3) in float vs_layer;
 
 out vec4 fs_color;
 out vec2 fs_uv;
+flat out float fs_layer;
 
 void main() {
     gl_Position = transform * vec4(vs_pos, 0.0, 1.0);
-    fs_color = vs_color;
+    fs_color = vec4(vs_color.rgb, vs_color.a * opacity);
     fs_uv = vs_uv;
+    fs_layer = vs_layer;
 }
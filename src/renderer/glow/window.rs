@@ -0,0 +1,93 @@
+//! Rendering to other windows with GL contexts sharing objects with the renderer's context
+//!
+//! Textures, buffers and shader programs are shared between the contexts, but VAOs are not; a VAO is
+//! created per window context on first use.
+
+use {glow::HasContext, std::rc::Rc};
+
+use crate::{dpi::PhysicalSize, BackendError, Renderer};
+
+use super::ImGuiGlow;
+
+/// Render target of [`ImGuiGlow::render_to_window`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowTarget {
+    /// `None` for the default framebuffer of the window
+    pub framebuffer: Option<glow::Framebuffer>,
+    /// Size of the framebuffer in physical pixels
+    pub size: PhysicalSize,
+}
+
+impl ImGuiGlow {
+    /// Renders draw data of a window (e.g. a viewport) with the window's GL context, which has to be
+    /// current and share objects with the context the renderer was created with
+    ///
+    /// Projection and scissor rectangles come from the `display_pos`, `display_size` and
+    /// `framebuffer_scale` of the draw data, so pass the draw data built for the window.
+    pub fn render_to_window(
+        &mut self,
+        gl: &mut Rc<glow::Context>,
+        target: &WindowTarget,
+        draw_data: &imgui::DrawData,
+    ) -> std::result::Result<(), BackendError> {
+        let main = Rc::ptr_eq(gl, &self.gl);
+        let objs = self.objs.as_mut().ok_or_else(|| {
+            BackendError::Other("ImGuiGlow: device objects are invalidated".to_string())
+        })?;
+
+        unsafe {
+            if !main && cfg!(debug_assertions) && !objs.res.is_visible(gl) {
+                return Err(BackendError::Other(
+                    "ImGuiGlow: renderer objects are not visible; is the window's context current and sharing with the renderer's?"
+                        .to_string(),
+                ));
+            }
+
+            objs.res
+                .select_context(if main { None } else { Some(&**gl) })
+                .map_err(|e| BackendError::Other(e.to_string()))?;
+
+            let [w, h] = target.size.0;
+            let fb = [
+                draw_data.display_size[0] * draw_data.framebuffer_scale[0],
+                draw_data.display_size[1] * draw_data.framebuffer_scale[1],
+            ];
+            if cfg!(debug_assertions) && (fb[0] as u32 != w || fb[1] as u32 != h) {
+                log::warn!(
+                    "ImGuiGlow: draw data of {:?} rendered to a window of {}x{}",
+                    fb,
+                    w,
+                    h
+                );
+            }
+
+            gl.bind_framebuffer(glow::FRAMEBUFFER, target.framebuffer);
+            gl.viewport(0, 0, w as i32, h as i32);
+        }
+
+        // the window target wins over the pass placement
+        let pass_targets = self.pass_targets.take();
+        let result = Renderer::render(self, draw_data, gl);
+        self.pass_targets = pass_targets;
+
+        unsafe {
+            gl.bind_framebuffer(glow::FRAMEBUFFER, None);
+            if let Some(objs) = self.objs.as_mut() {
+                // never fails for the renderer's context
+                let _ = objs.res.select_context(None);
+            }
+        }
+
+        result
+    }
+
+    /// Deletes the per-window objects. Call it with the window's context current before destroying
+    /// the context
+    pub fn release_window(&mut self, gl: &glow::Context) {
+        if let Some(objs) = self.objs.as_mut() {
+            unsafe {
+                objs.res.release_context(gl);
+            }
+        }
+    }
+}
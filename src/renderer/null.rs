@@ -0,0 +1,148 @@
+/*!
+Renderer that submits nothing but records [`DrawStats`]
+*/
+
+use crate::{
+    helper::{DrawStats, TextureInfo, TextureRegistry},
+    FontAtlasInfo, FontSampling, RegisterTexture, Renderer,
+};
+
+pub const FONT_TEXTUER_ID: usize = usize::MAX;
+
+/// Renderer with no device. Useful for headless runs such as generating fixtures
+#[derive(Debug)]
+pub struct NullRenderer {
+    stats: DrawStats,
+    /// Registered textures. Their sizes are kept in the entries
+    textures: TextureRegistry<()>,
+    font_size: [u32; 2],
+    font_generation: u64,
+}
+
+impl NullRenderer {
+    pub fn new(imgui: &mut imgui::Context) -> Self {
+        imgui.set_renderer_name(Some(crate::backend_name("imgui-null-renderer", &[])));
+
+        // the font atlas has to be built anyways
+        let mut fonts = imgui.fonts();
+        let font_size = {
+            let atlas = fonts.build_rgba32_texture();
+            [atlas.width, atlas.height]
+        };
+        fonts.tex_id = imgui::TextureId::from(FONT_TEXTUER_ID);
+
+        Self {
+            stats: DrawStats::default(),
+            textures: TextureRegistry::new(),
+            font_size,
+            font_generation: 1,
+        }
+    }
+
+    /// Statistics of the last rendered frame
+    pub fn stats(&self) -> &DrawStats {
+        &self.stats
+    }
+
+    pub fn textures(&self) -> &TextureRegistry<()> {
+        &self.textures
+    }
+}
+
+impl Renderer for NullRenderer {
+    type Device = ();
+    type Error = std::convert::Infallible;
+    const HAS_VTX_OFFSET: bool = true;
+    const MAX_INDEX_SIZE: usize = 4;
+
+    fn render(
+        &mut self,
+        draw_data: &imgui::DrawData,
+        _device: &mut Self::Device,
+    ) -> std::result::Result<(), Self::Error> {
+        self.textures.flush_removed();
+        self.stats = DrawStats::new(draw_data);
+        Ok(())
+    }
+
+    fn font_atlas_info(&self) -> Option<FontAtlasInfo> {
+        Some(FontAtlasInfo {
+            tex_id: imgui::TextureId::from(FONT_TEXTUER_ID),
+            size: self.font_size,
+            generation: self.font_generation,
+        })
+    }
+
+    fn set_font_sampling(&mut self, _sampling: FontSampling) {}
+
+    fn set_ui_opacity(&mut self, _opacity: f32) {}
+
+    fn invalidate_device_objects(&mut self, _device: &mut Self::Device) {}
+
+    fn create_device_objects(
+        &mut self,
+        imgui: &mut imgui::Context,
+        _device: &mut Self::Device,
+    ) -> std::result::Result<(), Self::Error> {
+        let mut fonts = imgui.fonts();
+        let atlas = fonts.build_rgba32_texture();
+        self.font_size = [atlas.width, atlas.height];
+        self.font_generation += 1;
+        Ok(())
+    }
+
+    fn replace_font_atlas(
+        &mut self,
+        _device: &mut Self::Device,
+        _pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> std::result::Result<bool, Self::Error> {
+        self.font_size = [w, h];
+        self.font_generation += 1;
+        Ok(true)
+    }
+}
+
+impl RegisterTexture for NullRenderer {
+    fn register_rgba8(
+        &mut self,
+        _device: &mut Self::Device,
+        _pixels: &[u8],
+        w: u32,
+        h: u32,
+        _retain: bool,
+    ) -> std::result::Result<imgui::TextureId, Self::Error> {
+        Ok(self.textures.insert((), w, h, None))
+    }
+
+    fn remove_textures<I>(&mut self, ids: I) -> usize
+    where
+        I: IntoIterator<Item = imgui::TextureId>,
+    {
+        self.textures.remove_many(ids)
+    }
+
+    fn set_texture_label(&mut self, _device: &mut Self::Device, id: imgui::TextureId, label: &str) {
+        self.textures.set_label(id, label);
+    }
+
+    fn texture_infos(&self) -> Vec<TextureInfo<'_>> {
+        self.textures.textures_iter().collect()
+    }
+}
+
+#[cfg(feature = "test-engine")]
+impl crate::helper::conformance::RendererConformance for NullRenderer {
+    fn create(imgui: &mut imgui::Context) -> Result<(Self, Self::Device), String> {
+        Ok((Self::new(imgui), ()))
+    }
+}
+
+#[cfg(all(test, feature = "test-engine"))]
+mod tests {
+    #[test]
+    fn conformance() {
+        crate::helper::conformance::assert_conformance::<super::NullRenderer>();
+    }
+}
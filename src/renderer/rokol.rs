@@ -4,7 +4,6 @@
 
 use {
     anyhow::*,
-    imgui::BackendFlags,
     rokol::{
         ffi::gfx as rfg,
         gfx::{self as rg, BakedResource},
@@ -13,26 +12,42 @@ use {
 };
 
 use crate::{
-    helper::{DrawParams, DrawParamsIterator},
-    Renderer,
+    helper::{
+        as_bytes, ColorMode, DrawItem, DrawParams, DrawParamsIterator, FlipY, ReloadBus,
+        ReloadEvent, TextureEntry, TextureInfo, TextureParams, TextureRegistry, TextureSource,
+        VertexLayout,
+    },
+    FontSampling, RegisterTexture, Renderer,
 };
 
+mod shader;
+pub use self::shader::{ShaderError, ShaderSource, SHADER_NAME};
+
+#[cfg(feature = "test-engine")]
+mod conformance;
+
 /// `mplus-1p-regular.ttf`
 pub const JP_FONT: &[u8] = include_bytes!("../../assets/mplus-1p-regular.ttf");
 
-/// Number of quadliterals
+/// Default number of quadliterals. See [`RokolConfig`]
 pub const N_QUADS: usize = 8192;
 
 pub const FONT_TEXTUER_ID: usize = usize::MAX;
 
 /// Size of a vertex in bytes
-pub const VERT_SIZE: usize = 20;
+pub const VERT_SIZE: usize = std::mem::size_of::<imgui::DrawVert>();
 
 // TODO: extend and use this error
 #[derive(Debug, Error)]
 pub enum ImGuiRendererError {
     #[error("bad texture id")]
     BadTexture(imgui::TextureId),
+    #[error("texture is not dynamic and can't be updated")]
+    ImmutableTexture(imgui::TextureId),
+    #[error("pixel data size mismatch: expected {expected} bytes, given {given} bytes")]
+    PixelSizeMismatch { expected: usize, given: usize },
+    #[error(transparent)]
+    Shader(#[from] ShaderError),
 }
 
 /// RAII texture object on `rokol`
@@ -41,6 +56,8 @@ pub struct Texture2d {
     pub img: rg::Image,
     pub w: u32,
     pub h: u32,
+    /// If the image was created with [`rg::ResourceUsage::Dynamic`]
+    pub is_dynamic: bool,
 }
 
 impl Drop for Texture2d {
@@ -49,6 +66,79 @@ impl Drop for Texture2d {
     }
 }
 
+impl Texture2d {
+    /// Creates an RGBA8 image
+    ///
+    /// Dynamic images are uploaded with `rg::update_image` since `sokol` doesn't accept initial data
+    /// for them. Note that they can be updated only once per frame.
+    pub fn new(pixels: &[u8], w: u32, h: u32, filter: rg::Filter, is_dynamic: bool) -> Self {
+        let usage = if is_dynamic {
+            rg::ResourceUsage::Dynamic
+        } else {
+            rg::ResourceUsage::Immutable
+        };
+
+        let img = rg::Image::create(&{
+            let mut desc = rg::ImageDesc {
+                type_: rg::ImageType::Dim2.to_ffi(),
+                usage: usage.to_ffi(),
+                width: w as i32,
+                height: h as i32,
+                min_filter: filter.to_ffi(),
+                mag_filter: filter.to_ffi(),
+                ..Default::default()
+            };
+            if !is_dynamic {
+                desc.data.subimage[0][0] = pixels.into();
+            }
+            desc
+        });
+
+        let tex = Self {
+            img,
+            w,
+            h,
+            is_dynamic,
+        };
+
+        if is_dynamic {
+            tex.upload(pixels);
+        }
+
+        tex
+    }
+
+    fn upload(&self, pixels: &[u8]) {
+        let mut data = rg::ImageData::default();
+        data.subimage[0][0] = pixels.into();
+        rg::update_image(self.img, &data);
+    }
+
+    /// Overwrites the whole image. Only dynamic images can be updated. `tex_id` is the ID the image
+    /// is registered with, for errors
+    pub fn update(
+        &self,
+        tex_id: imgui::TextureId,
+        pixels: &[u8],
+    ) -> Result<(), ImGuiRendererError> {
+        let expected = 4 * (self.w * self.h) as usize;
+        if pixels.len() != expected {
+            return Err(ImGuiRendererError::PixelSizeMismatch {
+                expected,
+                given: pixels.len(),
+            });
+        }
+
+        // `sokol` would report it as a validation error, but we prefer typed errors
+        if !self.is_dynamic {
+            return Err(ImGuiRendererError::ImmutableTexture(tex_id));
+        }
+
+        self.upload(pixels);
+        Ok(())
+    }
+}
+
 /// RAII shader object on `rokol`
 #[derive(Debug)]
 pub struct Shader {
@@ -91,10 +181,15 @@ impl Shader {
 /// }
 /// ```
 fn layout() -> rg::LayoutDesc {
+    let layout = VertexLayout::draw_vert();
     let mut desc = rg::LayoutDesc::default();
+    desc.buffers[0].stride = layout.stride as i32;
     desc.attrs[0].format = rg::VertexFormat::Float2.to_ffi();
+    desc.attrs[0].offset = layout.pos as i32;
     desc.attrs[1].format = rg::VertexFormat::Float2.to_ffi();
+    desc.attrs[1].offset = layout.uv as i32;
     desc.attrs[2].format = rg::VertexFormat::UByte4N.to_ffi();
+    desc.attrs[2].offset = layout.col as i32;
     desc
 }
 
@@ -125,6 +220,96 @@ macro_rules! ub {
     }};
 }
 
+/// Configuration of [`ImGuiRokolGfx`], accepted by [`ImGuiRokolGfx::with_config`]
+///
+/// `sokol_gfx` doesn't control sRGB writes of the default pass, so there's no sRGB option.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RokolConfig {
+    /// Capacity of the vertex buffer
+    pub n_vertices: usize,
+    /// Capacity of the index buffer
+    pub n_indices: usize,
+    /// Initial sampling of the font atlas. See [`Renderer::set_font_sampling`]
+    pub font_sampling: FontSampling,
+    /// Filter of user textures. `rokol` images can't change their filter after creation
+    pub texture_filter: FontSampling,
+    /// User textures have premultiplied alpha (see [`crate::helper::premultiply_rgba8`]) and the
+    /// UI is blended with `ONE, ONE_MINUS_SRC_ALPHA`. The font atlas is premultiplied on upload
+    pub premultiplied_alpha: bool,
+    pub shader_source: ShaderSource,
+}
+
+impl Default for RokolConfig {
+    fn default() -> Self {
+        Self {
+            n_vertices: 4 * N_QUADS,
+            n_indices: 6 * N_QUADS,
+            font_sampling: FontSampling::default(),
+            texture_filter: FontSampling::Linear,
+            premultiplied_alpha: false,
+            shader_source: ShaderSource::default(),
+        }
+    }
+}
+
+impl RokolConfig {
+    pub fn buffer_capacity(mut self, n_vertices: usize, n_indices: usize) -> Self {
+        self.n_vertices = n_vertices;
+        self.n_indices = n_indices;
+        self
+    }
+
+    pub fn font_sampling(mut self, sampling: FontSampling) -> Self {
+        self.font_sampling = sampling;
+        self
+    }
+
+    pub fn texture_filter(mut self, filter: FontSampling) -> Self {
+        self.texture_filter = filter;
+        self
+    }
+
+    pub fn premultiplied_alpha(mut self, premultiplied: bool) -> Self {
+        self.premultiplied_alpha = premultiplied;
+        self
+    }
+
+    pub fn shader_source(mut self, source: ShaderSource) -> Self {
+        self.shader_source = source;
+        self
+    }
+
+    /// Font atlas pixels as uploaded
+    fn font_pixels<'a>(&self, pixels: &'a [u8]) -> std::borrow::Cow<'a, [u8]> {
+        if self.premultiplied_alpha {
+            let mut pixels = pixels.to_vec();
+            crate::helper::premultiply_rgba8(&mut pixels);
+            pixels.into()
+        } else {
+            pixels.into()
+        }
+    }
+}
+
+fn filter(sampling: FontSampling) -> rg::Filter {
+    match sampling {
+        FontSampling::Linear => rg::Filter::Linear,
+        FontSampling::Nearest => rg::Filter::Nearest,
+    }
+}
+
+/// Re-creates a user texture as it was registered
+fn restore_texture(pixels: &[u8], entry: &TextureEntry) -> Texture2d {
+    let params = entry.params;
+    Texture2d::new(
+        pixels,
+        entry.w,
+        entry.h,
+        self::filter(params.filter),
+        params.is_dynamic,
+    )
+}
+
 const ALPHA_BLEND: rg::BlendState = rg::BlendState {
     enabled: true,
     src_factor_rgb: rfg::sg_blend_factor::SG_BLENDFACTOR_SRC_ALPHA,
@@ -135,17 +320,24 @@ const ALPHA_BLEND: rg::BlendState = rg::BlendState {
     op_alpha: rfg::sg_blend_op::_SG_BLENDOP_DEFAULT,
 };
 
-const VS: &'static str = concat!(include_str!("rokol/texture.vs"), '\0');
-const FS: &'static str = concat!(include_str!("rokol/texture.fs"), '\0');
+const PREMULTIPLIED_BLEND: rg::BlendState = rg::BlendState {
+    src_factor_rgb: rfg::sg_blend_factor::SG_BLENDFACTOR_ONE,
+    ..ALPHA_BLEND
+};
 
-fn create_shader() -> Shader {
+fn create_shader(source: &ShaderSource, premultiplied: bool) -> Result<Shader, ShaderError> {
     log::trace!("creating imgui-rokol-gfx shader...");
 
+    let (vs, fs) = source.load()?;
     let shd = rg::Shader::create(&{
-        let mut desc = unsafe { rokol::gfx::shader_desc(VS, FS) };
-        // let mut desc = unsafe { rokol::gfx::shader_desc(&vs, &fs) };
+        // SAFETY: `shader_desc` stores pointers to the sources, which are nul-terminated by
+        // `ShaderSource::load` and outlive `Shader::create`
+        let mut desc = unsafe { rokol::gfx::shader_desc(&vs, &fs) };
         desc.fs.images[0] = img_type!("tex", rg::ImageType::Dim2);
+        desc.fs.uniform_blocks[0] = ub!("color_matrix", rg::UniformType::Mat4, [f32; 16]);
         desc.vs.uniform_blocks[0] = ub!("transform", rg::UniformType::Mat4, [f32; 16]);
+        // `x`: opacity, `y`: 1.0 if vertex colors are premultiplied by the shader
+        desc.vs.uniform_blocks[1] = ub!("opacity", rg::UniformType::Float4, [f32; 4]);
         desc
     });
 
@@ -157,19 +349,23 @@ fn create_shader() -> Shader {
             cull_mode: rg::CullMode::None.to_ffi(),
             ..Default::default()
         };
-        desc.colors[0].blend = ALPHA_BLEND;
+        desc.colors[0].blend = if premultiplied {
+            PREMULTIPLIED_BLEND
+        } else {
+            ALPHA_BLEND
+        };
         desc
     });
 
-    Shader::new(shd, pip)
+    Ok(Shader::new(shd, pip))
 }
 
-fn create_bindings() -> rg::Bindings {
+fn create_bindings(n_vertices: usize, n_indices: usize) -> rg::Bindings {
     rg::Bindings {
         vertex_buffers: {
             let mut xs = [Default::default(); 8];
             xs[0] = rg::Buffer::create(&rg::vbuf_desc_dyn(
-                VERT_SIZE * N_QUADS * 4,
+                VERT_SIZE * n_vertices,
                 rg::ResourceUsage::Stream,
                 "",
             ));
@@ -177,7 +373,7 @@ fn create_bindings() -> rg::Bindings {
         },
         index_buffer: rg::Buffer::create(&rg::ibuf_desc_dyn(
             // NOTE: ImGUI uses 16 bits index
-            2 * N_QUADS * 6,
+            2 * n_indices,
             rg::ResourceUsage::Stream,
             "",
         )),
@@ -185,77 +381,272 @@ fn create_bindings() -> rg::Bindings {
     }
 }
 
+/// Closure that returns the size of the render target in pixels
+pub struct TargetSizeProvider(Box<dyn Fn() -> [u32; 2]>);
+
+impl std::fmt::Debug for TargetSizeProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TargetSizeProvider(..)")
+    }
+}
+
+/// GPU objects released on [`Renderer::invalidate_device_objects`]
 #[derive(Debug)]
-pub struct ImGuiRokolGfx {
-    textures: imgui::Textures<Texture2d>,
+struct DeviceObjects {
     font_texture: Texture2d,
+    /// Copy of the font texture with nearest filter. `rokol` images can't change their filter
+    font_texture_nearest: Texture2d,
     shd: Shader,
     binds: rg::Bindings,
+    /// Byte offset of the current draw list in the vertex buffer. `vtx_offset` of draw calls is
+    /// added to it
+    list_vtx_offset: i32,
 }
 
-impl ImGuiRokolGfx {
-    pub fn new(imgui: &mut imgui::Context) -> Result<Self, ImGuiRendererError> {
-        imgui.set_renderer_name(Some(format!(
-            "imgui-rokol-renderer {}",
-            env!("CARGO_PKG_VERSION")
-        )));
-
-        imgui
-            .io_mut()
-            .backend_flags
-            .insert(BackendFlags::RENDERER_HAS_VTX_OFFSET);
-
-        let font_texture = Self::load_font_texture(imgui.fonts())?;
-        let shd = self::create_shader();
-        let mut binds = self::create_bindings();
+impl Drop for DeviceObjects {
+    fn drop(&mut self) {
+        rg::Buffer::destroy(self.binds.vertex_buffers[0]);
+        rg::Buffer::destroy(self.binds.index_buffer);
+    }
+}
+
+impl DeviceObjects {
+    fn new(
+        fonts: imgui::FontAtlasRefMut,
+        config: &RokolConfig,
+    ) -> Result<Self, ImGuiRendererError> {
+        let shd = self::create_shader(&config.shader_source, config.premultiplied_alpha)?;
+        let (font_texture, font_texture_nearest) = ImGuiRokolGfx::load_font_texture(fonts, config)?;
+        let mut binds = self::create_bindings(config.n_vertices, config.n_indices);
         binds.fs_images[0] = font_texture.img;
 
         Ok(Self {
-            textures: imgui::Textures::new(),
             font_texture,
+            font_texture_nearest,
             shd,
             binds,
+            list_vtx_offset: 0,
         })
     }
+}
+
+#[derive(Debug)]
+pub struct ImGuiRokolGfx {
+    config: RokolConfig,
+    textures: TextureRegistry<Texture2d>,
+    font_sampling: FontSampling,
+    opacity: f32,
+    color_mode: ColorMode,
+    flip_y: FlipY,
+    /// `None` while the device objects are invalidated
+    objs: Option<DeviceObjects>,
+    /// Incremented every time the font texture is created or updated
+    font_generation: u64,
+    reload_bus: Option<ReloadBus>,
+    /// Pass action of the UI pass. Defaults to `LOAD` (preserve the framebuffer)
+    pass_action: rg::PassAction,
+    /// Falls back to `surface_size` and then the framebuffer size of the draw data if `None`
+    target_size: Option<TargetSizeProvider>,
+    /// Given by [`Renderer::on_resize`]
+    surface_size: Option<[u32; 2]>,
+    /// Set on [`DrawItem::ResetState`]; uniforms are applied again on the next draw call
+    reset_uniforms: bool,
+    /// Index buffer of the current draw list. The first draw call of a list can be culled, so new
+    /// lists are detected by their buffers rather than `idx_offset == 0`
+    list_idx: *const imgui::DrawIdx,
+}
+
+impl ImGuiRokolGfx {
+    pub fn new(imgui: &mut imgui::Context) -> Result<Self, ImGuiRendererError> {
+        Self::with_config(imgui, RokolConfig::default())
+    }
+
+    /// Creates the renderer reading the UI shader from `shader_source`
+    pub fn with_shader_source(
+        imgui: &mut imgui::Context,
+        shader_source: ShaderSource,
+    ) -> Result<Self, ImGuiRendererError> {
+        Self::with_config(imgui, RokolConfig::default().shader_source(shader_source))
+    }
 
-    /// Create font texture with ID `FONT_TEXTURE_ID`
+    pub fn with_config(
+        imgui: &mut imgui::Context,
+        config: RokolConfig,
+    ) -> Result<Self, ImGuiRendererError> {
+        imgui.set_renderer_name(Some(crate::backend_name("imgui-rokol-renderer", &[])));
+
+        let objs = DeviceObjects::new(imgui.fonts(), &config)?;
+
+        Ok(Self {
+            font_sampling: config.font_sampling,
+            config,
+            textures: TextureRegistry::new(),
+            opacity: 1.0,
+            color_mode: ColorMode::default(),
+            flip_y: FlipY::default(),
+            objs: Some(objs),
+            font_generation: 1,
+            reload_bus: None,
+            pass_action: rg::PassAction::LOAD,
+            target_size: None,
+            surface_size: None,
+            reset_uniforms: false,
+            list_idx: std::ptr::null(),
+        })
+    }
+
+    /// Create font textures (linear and nearest) with ID `FONT_TEXTURE_ID`
     fn load_font_texture(
         mut fonts: imgui::FontAtlasRefMut,
-    ) -> Result<Texture2d, ImGuiRendererError> {
-        let tex = {
+        config: &RokolConfig,
+    ) -> Result<(Texture2d, Texture2d), ImGuiRendererError> {
+        let texs = {
             let atlas_texture = fonts.build_rgba32_texture();
-            let (pixels, w, h) = (
-                atlas_texture.data,
-                atlas_texture.width,
-                atlas_texture.height,
-            );
-
-            let img = rg::Image::create(&{
-                let mut desc = rg::ImageDesc {
-                    type_: rg::ImageType::Dim2.to_ffi(),
-                    // FIXME: Is immutable OK?
-                    usage: rg::ResourceUsage::Immutable.to_ffi(),
-                    width: w as i32,
-                    height: h as i32,
-                    ..Default::default()
-                };
-                desc.data.subimage[0][0] = pixels.as_ref().into();
-                desc
-            });
-
-            Texture2d { img, w, h }
+            let pixels = config.font_pixels(atlas_texture.data);
+            let (w, h) = (atlas_texture.width, atlas_texture.height);
+
+            // dynamic so that the atlas can be rebuilt at runtime (e.g. on DPI change)
+            (
+                Texture2d::new(&pixels, w, h, rg::Filter::Linear, true),
+                Texture2d::new(&pixels, w, h, rg::Filter::Nearest, true),
+            )
         };
 
         // NOTE: we have to set the ID *AFTER* creating the font atlas texture
         fonts.tex_id = imgui::TextureId::from(FONT_TEXTUER_ID);
 
-        Ok(tex)
+        Ok(texs)
+    }
+
+    /// Sets the pass action of the UI pass, e.g. `rg::PassAction::clear(color)`
+    pub fn set_pass_action(&mut self, pass_action: rg::PassAction) {
+        self.pass_action = pass_action;
+    }
+
+    /// Sets the closure that returns the render target size in pixels. If not set, the size given
+    /// by [`Renderer::on_resize`] or the framebuffer size of the draw data is used
+    pub fn set_target_size_provider(&mut self, provider: impl Fn() -> [u32; 2] + 'static) {
+        self.target_size = Some(TargetSizeProvider(Box::new(provider)));
+    }
+
+    /// Grayscale or color blindness simulation/compensation of the whole UI pass
+    pub fn set_color_mode(&mut self, mode: ColorMode) {
+        self.color_mode = mode;
+    }
+
+    /// Flips the UI vertically, e.g. for engines with bottom-left origin UI space
+    pub fn set_flip_y(&mut self, flip_y: FlipY) {
+        self.flip_y = flip_y;
+    }
+
+    /// Textures (and the shader as [`SHADER_NAME`]) announced on the bus are re-created at the
+    /// beginning of the next render
+    pub fn set_reload_bus(&mut self, bus: Option<ReloadBus>) {
+        self.reload_bus = bus;
+    }
+
+    fn handle_reloads(&mut self) {
+        if let Some(bus) = self.reload_bus.as_ref() {
+            let rest = bus
+                .reload_texture_entries::<_, std::convert::Infallible>(
+                    &mut self.textures,
+                    |pixels, entry| Ok(self::restore_texture(pixels, entry)),
+                )
+                .unwrap_or_default();
+            for ev in rest {
+                match ev {
+                    ReloadEvent::Shader(ref name) if name == SHADER_NAME => self.reload_shader(),
+                    ev => log::debug!("ImGuiRokolGfx: unhandled reload event {:?}", ev),
+                }
+            }
+        }
+    }
+
+    /// Keeps the current shader if the new one fails to load
+    fn reload_shader(&mut self) {
+        let shd = match self::create_shader(
+            &self.config.shader_source,
+            self.config.premultiplied_alpha,
+        ) {
+            Ok(shd) => shd,
+            Err(err) => {
+                log::error!("ImGuiRokolGfx: failed to reload shader: {}", err);
+                return;
+            }
+        };
+
+        if let Some(objs) = self.objs.as_mut() {
+            objs.shd = shd;
+        }
+    }
+
+    /// Uploads an RGBA8 texture. If `retain` is true, a CPU copy of the pixels is kept so that the
+    /// texture can be restored after device loss
+    pub fn register_texture(
+        &mut self,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        is_dynamic: bool,
+        retain: bool,
+    ) -> imgui::TextureId {
+        let params = TextureParams {
+            filter: self.config.texture_filter,
+            is_dynamic,
+        };
+        let tex = Texture2d::new(pixels, w, h, self::filter(params.filter), is_dynamic);
+        let source = if retain {
+            Some(TextureSource::Pixels(pixels.to_vec()))
+        } else {
+            None
+        };
+        let id = self.textures.insert(tex, w, h, source);
+        self.textures.set_params(id, params);
+        id
+    }
+
+    pub fn textures(&self) -> &TextureRegistry<Texture2d> {
+        &self.textures
+    }
+
+    pub fn textures_mut(&mut self) -> &mut TextureRegistry<Texture2d> {
+        &mut self.textures
+    }
+
+    /// Overwrites the pixels of a dynamic texture (including the font texture). Each texture can be
+    /// updated only once per frame
+    pub fn update_texture(
+        &mut self,
+        tex_id: imgui::TextureId,
+        pixels: &[u8],
+    ) -> Result<(), ImGuiRendererError> {
+        if tex_id.id() == FONT_TEXTUER_ID {
+            let objs = self
+                .objs
+                .as_ref()
+                .ok_or_else(|| ImGuiRendererError::BadTexture(tex_id))?;
+            let pixels = self.config.font_pixels(pixels);
+            objs.font_texture.update(tex_id, &pixels)?;
+            objs.font_texture_nearest.update(tex_id, &pixels)?;
+            self.font_generation += 1;
+            return Ok(());
+        }
+
+        let tex = self
+            .textures
+            .get(tex_id)
+            .ok_or_else(|| ImGuiRendererError::BadTexture(tex_id))?;
+        tex.update(tex_id, pixels)
     }
 
     fn lookup_texture(&self, tex_id: imgui::TextureId) -> Option<&Texture2d> {
         if tex_id.id() == FONT_TEXTUER_ID {
             // we didn't store the font texture in `textures`
-            Some(&self.font_texture)
+            let objs = self.objs.as_ref()?;
+            Some(match self.font_sampling {
+                FontSampling::Linear => &objs.font_texture,
+                FontSampling::Nearest => &objs.font_texture_nearest,
+            })
         } else if let Some(texture) = self.textures.get(tex_id) {
             Some(texture)
         } else {
@@ -264,33 +655,158 @@ impl ImGuiRokolGfx {
     }
 }
 
+impl RegisterTexture for ImGuiRokolGfx {
+    fn register_rgba8(
+        &mut self,
+        _device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> std::result::Result<imgui::TextureId, Self::Error> {
+        Ok(self.register_texture(pixels, w, h, false, retain))
+    }
+
+    fn remove_textures<I>(&mut self, ids: I) -> usize
+    where
+        I: IntoIterator<Item = imgui::TextureId>,
+    {
+        self.textures.remove_many(ids)
+    }
+
+    fn set_texture_label(&mut self, _device: &mut Self::Device, id: imgui::TextureId, label: &str) {
+        // sokol labels are given on creation only
+        self.textures.set_label(id, label);
+    }
+
+    fn texture_infos(&self) -> Vec<TextureInfo<'_>> {
+        self.textures.textures_iter().collect()
+    }
+}
+
 impl Renderer for ImGuiRokolGfx {
     type Device = ();
     type Error = anyhow::Error;
+    const HAS_VTX_OFFSET: bool = true;
+    const SUPPORTS_TEXTURE_UPDATE: bool = true;
 
     fn render(
         &mut self,
         draw_data: &imgui::DrawData,
         _device: &mut Self::Device,
     ) -> std::result::Result<(), Self::Error> {
-        self.pre_render();
-        for params in DrawParamsIterator::new(draw_data) {
-            self.draw(&params)?;
+        ensure!(
+            self.objs.is_some(),
+            "ImGuiRokolGfx: device objects are invalidated"
+        );
+
+        self.textures.flush_removed();
+        self.handle_reloads();
+        self.pre_render(draw_data);
+        self.list_idx = std::ptr::null();
+        for item in DrawParamsIterator::new(draw_data) {
+            match item {
+                DrawItem::Draw(params) => self.draw(&params)?,
+                DrawItem::ResetState => {
+                    // applying the pipeline invalidates the uniforms
+                    self.objs_mut().shd.apply_pip();
+                    self.reset_uniforms = true;
+                }
+                DrawItem::Callback(callback) => self.run_callback(&mut (), &callback)?,
+            }
         }
         self.post_render();
 
         Ok(())
     }
+
+    fn font_atlas_info(&self) -> Option<crate::FontAtlasInfo> {
+        self.objs.as_ref().map(|objs| crate::FontAtlasInfo {
+            tex_id: imgui::TextureId::from(FONT_TEXTUER_ID),
+            size: [objs.font_texture.w, objs.font_texture.h],
+            generation: self.font_generation,
+        })
+    }
+
+    fn on_resize(&mut self, width: u32, height: u32) {
+        self.surface_size = Some([width, height]);
+    }
+
+    fn set_font_sampling(&mut self, sampling: FontSampling) {
+        self.font_sampling = sampling;
+    }
+
+    fn set_ui_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    fn invalidate_device_objects(&mut self, _device: &mut Self::Device) {
+        self.textures.invalidate();
+        self.objs = None;
+    }
+
+    fn create_device_objects(
+        &mut self,
+        imgui: &mut imgui::Context,
+        _device: &mut Self::Device,
+    ) -> std::result::Result<(), Self::Error> {
+        self.objs = None;
+        self.objs = Some(DeviceObjects::new(imgui.fonts(), &self.config)?);
+        self.font_generation += 1;
+
+        self.textures
+            .restore_entries::<std::convert::Infallible>(|pixels, entry| {
+                Ok(self::restore_texture(pixels, entry))
+            })
+            .ok();
+        Ok(())
+    }
+
+    fn replace_font_atlas(
+        &mut self,
+        _device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> std::result::Result<bool, Self::Error> {
+        let objs = match self.objs.as_mut() {
+            Some(objs) => objs,
+            None => return Ok(false),
+        };
+
+        // the size may change, so re-create rather than `update_texture`
+        let pixels = self.config.font_pixels(pixels);
+        objs.font_texture = Texture2d::new(&pixels, w, h, rg::Filter::Linear, true);
+        objs.font_texture_nearest = Texture2d::new(&pixels, w, h, rg::Filter::Nearest, true);
+        self.font_generation += 1;
+        Ok(true)
+    }
 }
 
 impl ImGuiRokolGfx {
-    fn pre_render(&mut self) {
-        self.binds.vertex_buffer_offsets[0] = 0;
-        self.binds.index_buffer_offset = 0;
+    /// Panics if the device objects are invalidated. It's checked on `render`
+    fn objs_mut(&mut self) -> &mut DeviceObjects {
+        self.objs
+            .as_mut()
+            .expect("ImGuiRokolGfx: device objects are invalidated")
+    }
 
-        // FIXME: the hard-coded backbuffer size
-        rg::begin_default_pass(&rg::PassAction::LOAD, 1280, 720);
-        self.shd.apply_pip();
+    fn pre_render(&mut self, draw_data: &imgui::DrawData) {
+        let objs = self.objs_mut();
+        objs.binds.vertex_buffer_offsets[0] = 0;
+        objs.binds.index_buffer_offset = 0;
+
+        let [w, h] = match (self.target_size.as_ref(), self.surface_size) {
+            (Some(provider), _) => (provider.0)(),
+            (None, Some(size)) => size,
+            (None, None) => [
+                (draw_data.display_size[0] * draw_data.framebuffer_scale[0]) as u32,
+                (draw_data.display_size[1] * draw_data.framebuffer_scale[1]) as u32,
+            ],
+        };
+
+        rg::begin_default_pass(&self.pass_action, w, h);
+        self.objs_mut().shd.apply_pip();
     }
 
     fn post_render(&mut self) {
@@ -303,72 +819,55 @@ impl ImGuiRokolGfx {
     ) -> std::result::Result<(), <Self as Renderer>::Error> {
         log::trace!("draw: {}, {}", params.vtx_offset, params.idx_offset);
 
+        let img = self
+            .lookup_texture(params.tex_id)
+            .ok_or_else(|| anyhow!("Bad texture id: {:?}", params.tex_id))?
+            .img;
+        let opacity = self.opacity;
+        let premultiplied = self.config.premultiplied_alpha;
+        let color_matrix = self.color_mode.matrix4_gl();
+        let flip_y = self.flip_y;
+        let reset_uniforms = std::mem::take(&mut self.reset_uniforms);
+        let new_list = params.idx_buffer.as_ptr() != self.list_idx;
+        self.list_idx = params.idx_buffer.as_ptr();
+        let objs = self.objs_mut();
+
         // on new `DrawList`
-        if params.idx_offset == 0 {
+        if new_list {
             // FIXME: don't use `append_buffer`. use batched CPU buffer
             // 1. append buffers
-            unsafe {
-                self.binds.vertex_buffer_offsets[0] = rg::append_buffer(
-                    self.binds.vertex_buffers[0],
-                    std::slice::from_raw_parts(
-                        params.vtx_buffer.as_ptr() as *const u8,
-                        std::mem::size_of::<imgui::DrawVert>() * params.vtx_buffer.len(),
-                    ),
-                );
-
-                // FIXME:
-                self.binds.index_buffer_offset = rg::append_buffer(
-                    // rg::append_buffer(
-                    self.binds.index_buffer,
-                    std::slice::from_raw_parts(
-                        params.idx_buffer.as_ptr() as *const u8,
-                        std::mem::size_of::<imgui::DrawIdx>() * params.idx_buffer.len(),
-                    ),
-                );
-            }
+            objs.list_vtx_offset =
+                rg::append_buffer(objs.binds.vertex_buffers[0], as_bytes(params.vtx_buffer));
+            objs.binds.index_buffer_offset =
+                rg::append_buffer(objs.binds.index_buffer, as_bytes(params.idx_buffer));
+        }
 
+        if new_list || reset_uniforms {
             // 2. set orthographic projection matrix
-            let mat = crate::helper::ortho_mat_gl(
-                // left, right
-                params.display.left(),
-                params.display.right(),
-                // bottom, top (flipped so that y axis goes down)
-                params.display.top(),
-                params.display.bottom(),
-                // near, far
-                0.0,
-                1.0,
-            );
-
-            let bytes = unsafe {
-                std::slice::from_raw_parts(
-                    mat.as_ptr() as *const _,
-                    std::mem::size_of::<[f32; 16]>(),
-                )
-            };
-            self.shd.set_vs_uniform(0, bytes);
+            let mat = flip_y.projection(&params.display);
+            objs.shd.set_vs_uniform(0, as_bytes(&mat));
+
+            let opacity = [opacity, premultiplied as u32 as f32, 0.0, 0.0];
+            objs.shd.set_vs_uniform(1, as_bytes(&opacity));
+
+            objs.shd.set_fs_uniform(0, as_bytes(&color_matrix));
         }
 
         // 1. scissor
-        rg::scissor_f(
-            params.scissor.left(),
-            // NOTE: the y axis goes up, so we're swappping it
-            // FIXME: `params.scissor.top` and `params.scissor.bottom` is somehow swapped
-            params.display.height() - f32::max(params.scissor.top(), params.scissor.bottom()),
-            params.scissor.width(),
-            params.scissor.height(),
-        );
+        // `rg::scissor_f` takes bottom-left origin coordinates. The rectangle is clamped to the
+        // framebuffer and never empty
+        let [x, y, w, h] = flip_y.scissor(params, true);
+        rg::scissor_f(x as f32, y as f32, w as f32, h as f32);
 
         // 2. set texture
-        let tex = self
-            .lookup_texture(params.tex_id)
-            .ok_or_else(|| anyhow!("Bad texture id: {:?}", params.tex_id))?;
-        self.binds.fs_images[0] = tex.img;
+        objs.binds.fs_images[0] = img;
 
         // 3. draw
-        rg::apply_bindings(&self.binds);
-        rg::draw(0, params.n_elems as u32, 1);
-        // rg::draw(params.idx_offset as u32, params.n_elems as u32, 1);
+        // indices are relative to `vtx_offset` (more than 64K vertices per list)
+        objs.binds.vertex_buffer_offsets[0] =
+            objs.list_vtx_offset + (params.vtx_offset * VERT_SIZE) as i32;
+        rg::apply_bindings(&objs.binds);
+        rg::draw(params.idx_offset as u32, params.n_elems as u32, 1);
 
         Ok(())
     }
@@ -0,0 +1,63 @@
+//! [`RendererConformance`] of [`ImGuiRokolGfx`] on a hidden SDL2 window
+
+use rokol::gfx as rg;
+
+use crate::helper::conformance::RendererConformance;
+
+use super::ImGuiRokolGfx;
+
+impl RendererConformance for ImGuiRokolGfx {
+    fn create(imgui: &mut imgui::Context) -> Result<(Self, Self::Device), String> {
+        self::setup()?;
+        let renderer = Self::new(imgui).map_err(|err| err.to_string())?;
+        Ok((renderer, ()))
+    }
+
+    fn end_frame(&mut self, _device: &mut Self::Device) -> Result<(), String> {
+        rg::commit();
+        Ok(())
+    }
+}
+
+/// Sets up `rokol::gfx` on a hidden window once. The window is kept alive for the rest of the
+/// thread
+#[cfg(feature = "sdl2-rokol-gl")]
+fn setup() -> Result<(), String> {
+    use std::{any::Any, cell::RefCell};
+
+    thread_local! {
+        static HANDLES: RefCell<Option<Box<dyn Any>>> = RefCell::new(None);
+    }
+
+    HANDLES.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        if cell.is_none() {
+            let handles = rokol::glue::sdl::Init {
+                title: "imgui-rokol conformance".to_string(),
+                w: 1280,
+                h: 720,
+                use_high_dpi: false,
+                settings: Default::default(),
+            }
+            .init(|window_builder| {
+                window_builder.hidden();
+            })?;
+            *cell = Some(Box::new(handles));
+        }
+        Ok(())
+    })
+}
+
+#[cfg(not(feature = "sdl2-rokol-gl"))]
+fn setup() -> Result<(), String> {
+    Err("the `sdl2-rokol-gl` feature is required to set up `rokol::gfx`".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[ignore = "requires a display and OpenGL 3.3"]
+    fn conformance() {
+        crate::helper::conformance::assert_conformance::<super::ImGuiRokolGfx>();
+    }
+}
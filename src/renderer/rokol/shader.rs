@@ -0,0 +1,83 @@
+//! Shader sources of [`super::ImGuiRokolGfx`]
+
+#[cfg(feature = "dev-shaders")]
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+/// Name of the UI shader in [`crate::helper::ReloadEvent::Shader`]
+pub const SHADER_NAME: &str = "imgui";
+
+const VS: &str = include_str!("texture.vs");
+const FS: &str = include_str!("texture.fs");
+
+#[derive(Debug, Error)]
+pub enum ShaderError {
+    #[cfg(feature = "dev-shaders")]
+    #[error("failed to read shader source `{}`", path.display())]
+    Read {
+        path: PathBuf,
+        #[source]
+        err: std::io::Error,
+    },
+    #[error("{0} shader source contains a nul byte")]
+    Nul(&'static str),
+}
+
+/// Where the GLSL sources of the UI shader come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderSource {
+    /// Sources compiled into the binary
+    Embedded,
+    /// Sources read at runtime. Announce [`SHADER_NAME`] on the reload bus to re-read them
+    #[cfg(feature = "dev-shaders")]
+    Files { vs: PathBuf, fs: PathBuf },
+}
+
+impl Default for ShaderSource {
+    fn default() -> Self {
+        Self::Embedded
+    }
+}
+
+impl ShaderSource {
+    /// Files of the embedded sources in this crate, for editing them in place
+    #[cfg(feature = "dev-shaders")]
+    pub fn crate_files() -> Self {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src/renderer/rokol");
+        Self::Files {
+            vs: dir.join("texture.vs"),
+            fs: dir.join("texture.fs"),
+        }
+    }
+
+    /// Nul-terminated vertex and fragment shader sources
+    pub fn load(&self) -> Result<(String, String), ShaderError> {
+        let (vs, fs) = match self {
+            Self::Embedded => (VS.to_string(), FS.to_string()),
+            #[cfg(feature = "dev-shaders")]
+            Self::Files { vs, fs } => (self::read(vs)?, self::read(fs)?),
+        };
+
+        Ok((
+            self::terminate(vs, "vertex")?,
+            self::terminate(fs, "fragment")?,
+        ))
+    }
+}
+
+#[cfg(feature = "dev-shaders")]
+fn read(path: &Path) -> Result<String, ShaderError> {
+    std::fs::read_to_string(path).map_err(|err| ShaderError::Read {
+        path: path.to_path_buf(),
+        err,
+    })
+}
+
+fn terminate(mut src: String, stage: &'static str) -> Result<String, ShaderError> {
+    if src.contains('\0') {
+        return Err(ShaderError::Nul(stage));
+    }
+    src.push('\0');
+    Ok(src)
+}
@@ -1,6 +1,7 @@
 #version 330
 
 uniform sampler2D tex;
+uniform mat4 color_matrix;
 
 in vec4 fs_color;
 in vec2 fs_uv;
@@ -8,5 +9,5 @@ in vec2 fs_uv;
 out vec4 out_color;
 
 void main() {
-    out_color = texture(tex, fs_uv) * fs_color;
+    out_color = color_matrix * (texture(tex, fs_uv) * fs_color);
 }
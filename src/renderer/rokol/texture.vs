@@ -1,6 +1,8 @@
 #version 330
 
 uniform mat4 transform;
+// x: opacity, y: 1.0 if the output is premultiplied alpha
+uniform vec4 opacity;
 
 layout(location=0) in vec2 vs_pos;
 layout(location=1) in vec2 vs_uv;
@@ -11,6 +13,7 @@ out vec2 fs_uv;
 
 void main() {
     gl_Position = transform * vec4(vs_pos, 0.0, 1.0);
-    fs_color = vs_color;
+    float alpha = vs_color.a * opacity.x;
+    fs_color = vec4(mix(vs_color.rgb, vs_color.rgb * alpha, opacity.y), alpha);
     fs_uv = vs_uv;
 }
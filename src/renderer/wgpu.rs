@@ -0,0 +1,772 @@
+/*!
+`wgpu` integration for `imgui-rs`
+
+The UI is drawn on top of [`WgpuDevice::target`] (load, not clear). sRGB targets are supported:
+vertex colors are converted to linear in the fragment shader and user textures are uploaded as
+sRGB textures.
+*/
+
+use std::{borrow::Cow, num::NonZeroU32};
+
+use thiserror::Error;
+
+use crate::{
+    helper::{
+        as_bytes, DrawItem, DrawParams, DrawParamsIterator, TextureInfo, TextureRegistry,
+        TextureSource, VertexLayout,
+    },
+    FontSampling, RegisterTexture, Renderer,
+};
+
+#[cfg(feature = "test-engine")]
+mod conformance;
+
+pub const FONT_TEXTUER_ID: usize = usize::MAX;
+
+/// Size of a vertex in bytes
+pub const VERT_SIZE: usize = std::mem::size_of::<imgui::DrawVert>();
+
+const SHADER: &str = include_str!("wgpu/shader.wgsl");
+
+/// Size of the uniform block in bytes: scale, translate and opacity
+const UNIFORMS_SIZE: u64 = 32;
+
+#[derive(Debug, Error)]
+pub enum WgpuRendererError {
+    #[error("bad texture id")]
+    BadTexture(imgui::TextureId),
+    #[error("no render target is set")]
+    NoTarget,
+    #[error("device objects are invalidated")]
+    Invalidated,
+    #[error("pixel data size mismatch: expected {expected} bytes, given {given} bytes")]
+    PixelSizeMismatch { expected: usize, given: usize },
+}
+
+/// Rendering context of [`ImGuiWgpu`]
+#[derive(Debug)]
+pub struct WgpuDevice {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    /// View rendered to on the next render, e.g. of the current swap chain frame. It must have the
+    /// format given to [`ImGuiWgpu::new`]
+    pub target: Option<wgpu::TextureView>,
+}
+
+impl WgpuDevice {
+    pub fn new(device: wgpu::Device, queue: wgpu::Queue) -> Self {
+        Self {
+            device,
+            queue,
+            target: None,
+        }
+    }
+}
+
+/// GPU texture bound with a sampler
+#[derive(Debug)]
+pub struct Texture {
+    pub texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+fn is_srgb(format: wgpu::TextureFormat) -> bool {
+    matches!(
+        format,
+        wgpu::TextureFormat::Rgba8UnormSrgb
+            | wgpu::TextureFormat::Bgra8UnormSrgb
+            | wgpu::TextureFormat::Bc1RgbaUnormSrgb
+            | wgpu::TextureFormat::Bc2RgbaUnormSrgb
+            | wgpu::TextureFormat::Bc3RgbaUnormSrgb
+            | wgpu::TextureFormat::Bc7RgbaUnormSrgb
+            | wgpu::TextureFormat::Etc2RgbUnormSrgb
+            | wgpu::TextureFormat::Etc2RgbA1UnormSrgb
+    )
+}
+
+/// GPU objects released on [`Renderer::invalidate_device_objects`]
+#[derive(Debug)]
+struct DeviceObjects {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    uniform_bind_group: wgpu::BindGroup,
+    texture_layout: wgpu::BindGroupLayout,
+    linear: wgpu::Sampler,
+    nearest: wgpu::Sampler,
+    font_texture: wgpu::Texture,
+    /// Font texture bound with the linear and nearest samplers
+    font_bind_groups: [wgpu::BindGroup; 2],
+    font_size: [u32; 2],
+    vbuf: wgpu::Buffer,
+    ibuf: wgpu::Buffer,
+    /// Sizes of the vertex and index buffers in bytes. They grow on demand
+    vbuf_size: u64,
+    ibuf_size: u64,
+}
+
+impl DeviceObjects {
+    fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        mut fonts: imgui::FontAtlasRefMut,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("imgui-wgpu shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(SHADER)),
+            flags: wgpu::ShaderFlags::all(),
+        });
+
+        let uniform_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("imgui-wgpu uniform layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStage::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let texture_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("imgui-wgpu texture layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStage::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler {
+                        filtering: true,
+                        comparison: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("imgui-wgpu pipeline layout"),
+            bind_group_layouts: &[&uniform_layout, &texture_layout],
+            push_constant_ranges: &[],
+        });
+
+        let vertex_layout = VertexLayout::draw_vert();
+        let attributes = [
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x2,
+                offset: vertex_layout.pos as u64,
+                shader_location: 0,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x2,
+                offset: vertex_layout.uv as u64,
+                shader_location: 1,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Unorm8x4,
+                offset: vertex_layout.col as u64,
+                shader_location: 2,
+            },
+        ];
+
+        // premultiplied alpha is not used; alpha is accumulated like other backends
+        let blend = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::SrcAlpha,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            },
+        };
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("imgui-wgpu pipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: vertex_layout.stride as u64,
+                    step_mode: wgpu::InputStepMode::Vertex,
+                    attributes: &attributes,
+                }],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: if self::is_srgb(format) {
+                    "fs_main_srgb"
+                } else {
+                    "fs_main"
+                },
+                targets: &[wgpu::ColorTargetState {
+                    format,
+                    blend: Some(blend),
+                    write_mask: wgpu::ColorWrite::ALL,
+                }],
+            }),
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("imgui-wgpu uniforms"),
+            size: UNIFORMS_SIZE,
+            usage: wgpu::BufferUsage::UNIFORM | wgpu::BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("imgui-wgpu uniforms"),
+            layout: &uniform_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let sampler = |filter| {
+            device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("imgui-wgpu sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: filter,
+                min_filter: filter,
+                mipmap_filter: filter,
+                ..Default::default()
+            })
+        };
+        let linear = sampler(wgpu::FilterMode::Linear);
+        let nearest = sampler(wgpu::FilterMode::Nearest);
+
+        let (font_texture, font_size) = {
+            let atlas = fonts.build_rgba32_texture();
+            let tex =
+                self::create_texture(device, queue, atlas.data, atlas.width, atlas.height, format);
+            (tex, [atlas.width, atlas.height])
+        };
+        // NOTE: we have to set the ID *AFTER* creating the font atlas texture
+        fonts.tex_id = imgui::TextureId::from(FONT_TEXTUER_ID);
+
+        let font_bind_groups = [
+            self::bind_texture(device, &texture_layout, &font_texture, &linear),
+            self::bind_texture(device, &texture_layout, &font_texture, &nearest),
+        ];
+
+        let (vbuf_size, ibuf_size) = (1 << 16, 1 << 16);
+        let vbuf = self::create_buffer(device, vbuf_size, wgpu::BufferUsage::VERTEX);
+        let ibuf = self::create_buffer(device, ibuf_size, wgpu::BufferUsage::INDEX);
+
+        Self {
+            pipeline,
+            uniform_buffer,
+            uniform_bind_group,
+            texture_layout,
+            linear,
+            nearest,
+            font_texture,
+            font_bind_groups,
+            font_size,
+            vbuf,
+            ibuf,
+            vbuf_size,
+            ibuf_size,
+        }
+    }
+
+    /// Sets the pipeline, the uniforms, the buffers and the viewport
+    fn set_render_state<'a>(&'a self, pass: &mut wgpu::RenderPass<'a>, fb_size: [f32; 2]) {
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.uniform_bind_group, &[]);
+        pass.set_vertex_buffer(0, self.vbuf.slice(..));
+        pass.set_index_buffer(
+            self.ibuf.slice(..),
+            if std::mem::size_of::<imgui::DrawIdx>() == 2 {
+                wgpu::IndexFormat::Uint16
+            } else {
+                wgpu::IndexFormat::Uint32
+            },
+        );
+        pass.set_viewport(0.0, 0.0, fb_size[0], fb_size[1], 0.0, 1.0);
+    }
+
+    fn register(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        format: wgpu::TextureFormat,
+    ) -> Texture {
+        let texture = self::create_texture(device, queue, pixels, w, h, format);
+        let bind_group = self::bind_texture(device, &self.texture_layout, &texture, &self.linear);
+        Texture {
+            texture,
+            bind_group,
+        }
+    }
+
+    /// Grows the vertex and index buffers to hold the draw data
+    fn reserve(&mut self, device: &wgpu::Device, vbuf_size: u64, ibuf_size: u64) {
+        if vbuf_size > self.vbuf_size {
+            self.vbuf_size = vbuf_size.next_power_of_two();
+            self.vbuf = self::create_buffer(device, self.vbuf_size, wgpu::BufferUsage::VERTEX);
+        }
+        if ibuf_size > self.ibuf_size {
+            self.ibuf_size = ibuf_size.next_power_of_two();
+            self.ibuf = self::create_buffer(device, self.ibuf_size, wgpu::BufferUsage::INDEX);
+        }
+    }
+}
+
+fn create_buffer(device: &wgpu::Device, size: u64, usage: wgpu::BufferUsage) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("imgui-wgpu buffer"),
+        size,
+        usage: usage | wgpu::BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Creates an RGBA8 texture. It's an sRGB texture if the target is
+fn create_texture(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    pixels: &[u8],
+    w: u32,
+    h: u32,
+    target_format: wgpu::TextureFormat,
+) -> wgpu::Texture {
+    let size = wgpu::Extent3d {
+        width: w,
+        height: h,
+        depth_or_array_layers: 1,
+    };
+
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("imgui-wgpu texture"),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: if self::is_srgb(target_format) {
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        } else {
+            wgpu::TextureFormat::Rgba8Unorm
+        },
+        usage: wgpu::TextureUsage::SAMPLED | wgpu::TextureUsage::COPY_DST,
+    });
+
+    queue.write_texture(
+        wgpu::ImageCopyTexture {
+            texture: &texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d::ZERO,
+        },
+        pixels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: NonZeroU32::new(4 * w),
+            rows_per_image: NonZeroU32::new(h),
+        },
+        size,
+    );
+
+    texture
+}
+
+fn bind_texture(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    texture: &wgpu::Texture,
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("imgui-wgpu texture"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    })
+}
+
+/// `wgpu` ImGUI renderer
+#[derive(Debug)]
+pub struct ImGuiWgpu {
+    textures: TextureRegistry<Texture>,
+    font_sampling: FontSampling,
+    opacity: f32,
+    /// Format of the render target
+    format: wgpu::TextureFormat,
+    /// `None` while the device objects are invalidated
+    objs: Option<DeviceObjects>,
+    /// Incremented every time the font texture is created
+    font_generation: u64,
+}
+
+impl ImGuiWgpu {
+    /// `format` is the format of the render targets
+    pub fn new(
+        imgui: &mut imgui::Context,
+        device: &WgpuDevice,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        imgui.set_renderer_name(Some(crate::backend_name("imgui-wgpu-renderer", &[])));
+
+        let objs = DeviceObjects::new(&device.device, &device.queue, imgui.fonts(), format);
+
+        Self {
+            textures: TextureRegistry::new(),
+            font_sampling: FontSampling::default(),
+            opacity: 1.0,
+            format,
+            objs: Some(objs),
+            font_generation: 1,
+        }
+    }
+
+    /// Uploads an RGBA8 texture. If `retain` is true, a CPU copy of the pixels is kept so that the
+    /// texture can be restored after device loss
+    pub fn register_texture(
+        &mut self,
+        device: &WgpuDevice,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> Result<imgui::TextureId, WgpuRendererError> {
+        let expected = (w as usize) * (h as usize) * 4;
+        if pixels.len() != expected {
+            return Err(WgpuRendererError::PixelSizeMismatch {
+                expected,
+                given: pixels.len(),
+            });
+        }
+
+        let objs = self.objs.as_ref().ok_or(WgpuRendererError::Invalidated)?;
+        let tex = objs.register(&device.device, &device.queue, pixels, w, h, self.format);
+        let source = if retain {
+            Some(TextureSource::Pixels(pixels.to_vec()))
+        } else {
+            None
+        };
+        Ok(self.textures.insert(tex, w, h, source))
+    }
+
+    pub fn textures(&self) -> &TextureRegistry<Texture> {
+        &self.textures
+    }
+
+    pub fn textures_mut(&mut self) -> &mut TextureRegistry<Texture> {
+        &mut self.textures
+    }
+
+    fn bind_group(&self, tex_id: imgui::TextureId) -> Option<&wgpu::BindGroup> {
+        if tex_id.id() == FONT_TEXTUER_ID {
+            let objs = self.objs.as_ref()?;
+            return Some(match self.font_sampling {
+                FontSampling::Linear => &objs.font_bind_groups[0],
+                FontSampling::Nearest => &objs.font_bind_groups[1],
+            });
+        }
+
+        self.textures.get(tex_id).map(|tex| &tex.bind_group)
+    }
+
+    /// Uploads every draw list. Returns the base vertex and base index of each list, keyed with
+    /// the address of its index buffer
+    fn upload(
+        &mut self,
+        device: &WgpuDevice,
+        draw_data: &imgui::DrawData,
+    ) -> Vec<(*const imgui::DrawIdx, usize, usize)> {
+        let mut vtx = Vec::<u8>::new();
+        let mut idx = Vec::<u8>::new();
+        let mut bases = Vec::with_capacity(draw_data.draw_lists_count());
+
+        for list in draw_data.draw_lists() {
+            bases.push((
+                list.idx_buffer().as_ptr(),
+                vtx.len() / VERT_SIZE,
+                idx.len() / std::mem::size_of::<imgui::DrawIdx>(),
+            ));
+            vtx.extend_from_slice(as_bytes(list.vtx_buffer()));
+            idx.extend_from_slice(as_bytes(list.idx_buffer()));
+        }
+
+        // writes have to be multiples of 4 bytes
+        while idx.len() % 4 != 0 {
+            idx.push(0);
+        }
+
+        let objs = self.objs.as_mut().unwrap();
+        objs.reserve(&device.device, vtx.len() as u64, idx.len() as u64);
+        if !vtx.is_empty() {
+            device.queue.write_buffer(&objs.vbuf, 0, &vtx);
+        }
+        if !idx.is_empty() {
+            device.queue.write_buffer(&objs.ibuf, 0, &idx);
+        }
+
+        bases
+    }
+
+    fn write_uniforms(&self, device: &WgpuDevice, draw_data: &imgui::DrawData) {
+        let [x, y] = draw_data.display_pos;
+        let [w, h] = draw_data.display_size;
+        let scale = [2.0 / w, -2.0 / h];
+        let translate = [-1.0 - x * scale[0], 1.0 - y * scale[1]];
+        let uniforms: [f32; 8] = [
+            scale[0],
+            scale[1],
+            translate[0],
+            translate[1],
+            self.opacity,
+            0.0,
+            0.0,
+            0.0,
+        ];
+
+        let objs = self.objs.as_ref().unwrap();
+        device
+            .queue
+            .write_buffer(&objs.uniform_buffer, 0, as_bytes(&uniforms));
+    }
+
+    fn draw<'r>(
+        &'r self,
+        pass: &mut wgpu::RenderPass<'r>,
+        params: &DrawParams,
+        base: (usize, usize),
+    ) -> Result<(), WgpuRendererError> {
+        let bind_group = self
+            .bind_group(params.tex_id)
+            .ok_or(WgpuRendererError::BadTexture(params.tex_id))?;
+
+        let [x1, y1, x2, y2] = params.clip_rect;
+        pass.set_scissor_rect(x1 as u32, y1 as u32, (x2 - x1) as u32, (y2 - y1) as u32);
+        pass.set_bind_group(1, bind_group, &[]);
+
+        let (vtx_base, idx_base) = base;
+        let start = (idx_base + params.idx_offset) as u32;
+        pass.draw_indexed(
+            start..start + params.n_elems as u32,
+            (vtx_base + params.vtx_offset) as i32,
+            0..1,
+        );
+
+        Ok(())
+    }
+}
+
+impl RegisterTexture for ImGuiWgpu {
+    fn register_rgba8(
+        &mut self,
+        device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+        retain: bool,
+    ) -> Result<imgui::TextureId, Self::Error> {
+        self.register_texture(device, pixels, w, h, retain)
+    }
+
+    fn remove_textures<I>(&mut self, ids: I) -> usize
+    where
+        I: IntoIterator<Item = imgui::TextureId>,
+    {
+        self.textures.remove_many(ids)
+    }
+
+    fn set_texture_label(&mut self, _device: &mut Self::Device, id: imgui::TextureId, label: &str) {
+        // wgpu labels are given on creation only
+        self.textures.set_label(id, label);
+    }
+
+    fn texture_infos(&self) -> Vec<TextureInfo<'_>> {
+        self.textures.textures_iter().collect()
+    }
+}
+
+impl Renderer for ImGuiWgpu {
+    type Device = WgpuDevice;
+    type Error = WgpuRendererError;
+    const HAS_VTX_OFFSET: bool = true;
+    const SUPPORTS_SRGB: bool = true;
+    const MAX_INDEX_SIZE: usize = 4;
+
+    fn font_atlas_info(&self) -> Option<crate::FontAtlasInfo> {
+        self.objs.as_ref().map(|objs| crate::FontAtlasInfo {
+            tex_id: imgui::TextureId::from(FONT_TEXTUER_ID),
+            size: objs.font_size,
+            generation: self.font_generation,
+        })
+    }
+
+    fn render(
+        &mut self,
+        draw_data: &imgui::DrawData,
+        device: &mut Self::Device,
+    ) -> Result<(), Self::Error> {
+        if self.objs.is_none() {
+            return Err(WgpuRendererError::Invalidated);
+        }
+        let target = device.target.as_ref().ok_or(WgpuRendererError::NoTarget)?;
+
+        self.textures.flush_removed();
+
+        let fb_size = [
+            draw_data.display_size[0] * draw_data.framebuffer_scale[0],
+            draw_data.display_size[1] * draw_data.framebuffer_scale[1],
+        ];
+        if fb_size[0] <= 0.0 || fb_size[1] <= 0.0 || draw_data.total_idx_count == 0 {
+            return Ok(());
+        }
+
+        let bases = self.upload(device, draw_data);
+        self.write_uniforms(device, draw_data);
+
+        let mut encoder = device
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("imgui-wgpu"),
+            });
+
+        {
+            let objs = self.objs.as_ref().unwrap();
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("imgui-wgpu"),
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: target,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+            });
+
+            objs.set_render_state(&mut pass, fb_size);
+
+            // draw lists are yielded in order
+            let mut cursor = 0;
+            for item in DrawParamsIterator::new(draw_data) {
+                let params = match item {
+                    DrawItem::Draw(params) => params,
+                    // the scissor is set on every draw call
+                    DrawItem::ResetState => {
+                        objs.set_render_state(&mut pass, fb_size);
+                        continue;
+                    }
+                    // callbacks can't record into the UI pass, so `run_callback` is bypassed
+                    DrawItem::Callback(callback) => {
+                        callback.invoke();
+                        continue;
+                    }
+                };
+                let ptr = params.idx_buffer.as_ptr();
+                while bases[cursor].0 != ptr {
+                    cursor += 1;
+                }
+                let (_, vtx_base, idx_base) = bases[cursor];
+                self.draw(&mut pass, &params, (vtx_base, idx_base))?;
+            }
+        }
+
+        device.queue.submit(std::iter::once(encoder.finish()));
+        Ok(())
+    }
+
+    fn set_font_sampling(&mut self, sampling: FontSampling) {
+        self.font_sampling = sampling;
+    }
+
+    fn set_ui_opacity(&mut self, opacity: f32) {
+        self.opacity = opacity;
+    }
+
+    fn invalidate_device_objects(&mut self, _device: &mut Self::Device) {
+        self.textures.invalidate();
+        self.objs = None;
+    }
+
+    fn create_device_objects(
+        &mut self,
+        imgui: &mut imgui::Context,
+        device: &mut Self::Device,
+    ) -> Result<(), Self::Error> {
+        self.objs = None;
+        let objs = DeviceObjects::new(&device.device, &device.queue, imgui.fonts(), self.format);
+        self.font_generation += 1;
+
+        let format = self.format;
+        self.textures
+            .restore::<std::convert::Infallible>(|pixels, w, h| {
+                Ok(objs.register(&device.device, &device.queue, pixels, w, h, format))
+            })
+            .ok();
+        self.objs = Some(objs);
+
+        Ok(())
+    }
+
+    fn replace_font_atlas(
+        &mut self,
+        device: &mut Self::Device,
+        pixels: &[u8],
+        w: u32,
+        h: u32,
+    ) -> Result<bool, Self::Error> {
+        let format = self.format;
+        let objs = match self.objs.as_mut() {
+            Some(objs) => objs,
+            None => return Ok(false),
+        };
+
+        let texture = self::create_texture(&device.device, &device.queue, pixels, w, h, format);
+        objs.font_bind_groups = [
+            self::bind_texture(&device.device, &objs.texture_layout, &texture, &objs.linear),
+            self::bind_texture(
+                &device.device,
+                &objs.texture_layout,
+                &texture,
+                &objs.nearest,
+            ),
+        ];
+        objs.font_texture = texture;
+        objs.font_size = [w, h];
+        self.font_generation += 1;
+        Ok(true)
+    }
+}
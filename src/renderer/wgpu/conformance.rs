@@ -0,0 +1,89 @@
+//! [`RendererConformance`] of [`ImGuiWgpu`] rendering to an offscreen texture
+
+use std::{
+    future::Future,
+    task::{Context, Poll, RawWaker, RawWakerVTable, Waker},
+};
+
+use crate::helper::conformance::RendererConformance;
+
+use super::{ImGuiWgpu, WgpuDevice};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+impl RendererConformance for ImGuiWgpu {
+    fn create(imgui: &mut imgui::Context) -> Result<(Self, Self::Device), String> {
+        let instance = wgpu::Instance::new(wgpu::BackendBit::PRIMARY);
+        let adapter = self::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::default(),
+            compatible_surface: None,
+        }))
+        .ok_or_else(|| "no wgpu adapter is available".to_string())?;
+        let (device, queue) = self::block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("imgui-wgpu conformance"),
+                features: wgpu::Features::empty(),
+                limits: wgpu::Limits::default(),
+            },
+            None,
+        ))
+        .map_err(|err| err.to_string())?;
+
+        // the view keeps the texture alive
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("imgui-wgpu conformance target"),
+            size: wgpu::Extent3d {
+                width: 1280,
+                height: 720,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: FORMAT,
+            usage: wgpu::TextureUsage::RENDER_ATTACHMENT,
+        });
+
+        let mut device = WgpuDevice::new(device, queue);
+        device.target = Some(target.create_view(&wgpu::TextureViewDescriptor::default()));
+        let renderer = Self::new(imgui, &device, FORMAT);
+        Ok((renderer, device))
+    }
+
+    fn end_frame(&mut self, device: &mut Self::Device) -> Result<(), String> {
+        device.device.poll(wgpu::Maintain::Wait);
+        Ok(())
+    }
+}
+
+/// Polls the future until it's ready. Adapter and device requests of native backends resolve
+/// immediately, so no real executor is needed
+fn block_on<F: Future>(future: F) -> F::Output {
+    fn raw_waker() -> RawWaker {
+        fn clone(_: *const ()) -> RawWaker {
+            raw_waker()
+        }
+        fn noop(_: *const ()) {}
+        static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+        RawWaker::new(std::ptr::null(), &VTABLE)
+    }
+
+    let waker = unsafe { Waker::from_raw(raw_waker()) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = Box::pin(future);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    #[ignore = "requires a GPU adapter"]
+    fn conformance() {
+        crate::helper::conformance::assert_conformance::<super::ImGuiWgpu>();
+    }
+}
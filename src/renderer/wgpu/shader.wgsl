@@ -0,0 +1,52 @@
+[[block]]
+struct Uniforms {
+    scale: vec2<f32>;
+    translate: vec2<f32>;
+    // only `x` is used
+    opacity: vec4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: Uniforms;
+
+[[group(1), binding(0)]]
+var tex: texture_2d<f32>;
+[[group(1), binding(1)]]
+var tex_sampler: sampler;
+
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+    [[location(1)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(
+    [[location(0)]] pos: vec2<f32>,
+    [[location(1)]] uv: vec2<f32>,
+    [[location(2)]] color: vec4<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.position = vec4<f32>(pos * uniforms.scale + uniforms.translate, 0.0, 1.0);
+    out.uv = uv;
+    out.color = vec4<f32>(color.rgb, color.a * uniforms.opacity.x);
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(v: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return v.color * textureSample(tex, tex_sampler, v.uv);
+}
+
+// ImGUI colors are sRGB; sRGB targets expect linear colors
+fn srgb_to_linear(c: vec3<f32>) -> vec3<f32> {
+    let lo = c / 12.92;
+    let hi = pow((c + vec3<f32>(0.055, 0.055, 0.055)) / 1.055, vec3<f32>(2.4, 2.4, 2.4));
+    return select(hi, lo, c <= vec3<f32>(0.04045, 0.04045, 0.04045));
+}
+
+[[stage(fragment)]]
+fn fs_main_srgb(v: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let color = vec4<f32>(srgb_to_linear(v.color.rgb), v.color.a);
+    return color * textureSample(tex, tex_sampler, v.uv);
+}